@@ -0,0 +1,78 @@
+#![no_std] // 禁用标准库，适用于嵌入式环境
+#![no_main] // 禁用标准main入口，使用自定义入口点
+
+//! 录制MPU6050原始数据
+//!
+//! 以100Hz读取加速度计和陀螺仪的16位原始值，按`tests/data/`中测试数据的CSV格式输出，
+//! 量程与固件默认值相同（±4g、±500°/s）。将输出保存为文件并在开头补充已知结果
+//! （如`# roll: 30`、`# steps: 60`），即可替换或新增测试数据。
+//!
+//! 运行：`cargo run --release --example record_imu > tests/data/xxx.csv`
+
+use defmt::println; // 嵌入式友好日志框架
+use embassy_executor::Spawner; // Embassy异步任务调度器
+use embassy_stm32::i2c; // STM32 I2C驱动
+use embassy_stm32::time::Hertz; // 频率单位
+use embassy_time::{Duration, Ticker, Timer};
+use {defmt_rtt as _, panic_probe as _}; // 日志和panic处理
+
+/// MPU6050的I2C地址
+const ADDRESS: u8 = 0x68;
+
+/// 采样周期（10ms，即100Hz）
+const SAMPLE_PERIOD: Duration = Duration::from_millis(10);
+
+/// 初始化时依次写入的寄存器和值
+const SETUP: [(u8, u8); 5] = [
+    (0x6B, 0x01), // PWR_MGMT_1：退出睡眠，使用X轴陀螺仪PLL时钟
+    (0x1A, 0x02), // CONFIG：DLPF模式2，与固件默认值相同
+    (0x19, 0x09), // SMPLRT_DIV：1kHz / (1 + 9) = 100Hz
+    (0x1B, 0x08), // GYRO_CONFIG：±500°/s
+    (0x1C, 0x08), // ACCEL_CONFIG：±4g
+];
+
+/// 加速度计X轴高字节寄存器，随后依次为加速度计、温度和陀螺仪共14字节
+const ACCEL_XOUT_H: u8 = 0x3B;
+
+/// 主入口函数
+///
+/// 初始化MPU6050后持续输出原始读数
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_stm32::init(Default::default());
+
+    // 配置I2C2接口（PB10: SCL, PB11: SDA），400kHz
+    let mut i2c =
+        i2c::I2c::new_blocking(p.I2C2, p.PB10, p.PB11, Hertz(400_000), Default::default());
+
+    // 复位后等待寄存器恢复默认值
+    i2c.blocking_write(ADDRESS, &[0x6B, 0x80]).unwrap();
+    Timer::after_millis(100).await;
+    for (register, value) in SETUP {
+        i2c.blocking_write(ADDRESS, &[register, value]).unwrap();
+    }
+
+    println!("# rate: 100");
+    println!("# accel_range: 4");
+    println!("# gyro_range: 500");
+    println!("ax,ay,az,gx,gy,gz");
+
+    let mut ticker = Ticker::every(SAMPLE_PERIOD);
+    let mut buf = [0u8; 14];
+    loop {
+        i2c.blocking_write_read(ADDRESS, &[ACCEL_XOUT_H], &mut buf)
+            .unwrap();
+        let value = |index: usize| i16::from_be_bytes([buf[index], buf[index + 1]]);
+        // 跳过第6、7字节的温度
+        println!(
+            "{},{},{},{},{},{}",
+            value(0),
+            value(2),
+            value(4),
+            value(8),
+            value(10),
+            value(12)
+        );
+        ticker.next().await;
+    }
+}
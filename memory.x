@@ -1,6 +1,9 @@
 /* Linker script for the STM32F103C8T6 */
 MEMORY
 {
-  FLASH : ORIGIN = 0x08000000, LENGTH = 64K
+  /* The last 4 pages (4K) of flash are kept out of FLASH for the settings
+     store, see `hardware::config::SETTINGS_OFFSET` */
+  FLASH : ORIGIN = 0x08000000, LENGTH = 60K
+  SETTINGS : ORIGIN = 0x0800F000, LENGTH = 4K
   RAM : ORIGIN = 0x20000000, LENGTH = 20K
 }
@@ -0,0 +1,767 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+/// 闹钟响铃的最长时间，超时后自动停止
+pub const RING_TIMEOUT: Duration = Duration::minutes(1);
+
+/// 两次轮询之间允许的最大间隔
+///
+/// 超过该间隔（或时间倒退）视为时间被重新设置，跳过期间的闹钟，
+/// 避免调整时间时一次性触发大量闹钟。
+const MAX_POLL_GAP: Duration = Duration::minutes(1);
+
+/// 贪睡时长范围（分钟）
+const SNOOZE_MINUTES_MIN: u8 = 1;
+const SNOOZE_MINUTES_MAX: u8 = 30;
+
+/// 星期掩码
+///
+/// 第0位表示周一，第6位表示周日。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    /// 不限星期（每天）
+    pub const NONE: Weekdays = Weekdays(0);
+    /// 每天
+    pub const EVERY_DAY: Weekdays = Weekdays(0b111_1111);
+    /// 工作日（周一至周五）
+    pub const WORKDAYS: Weekdays = Weekdays(0b001_1111);
+    /// 周末（周六、周日）
+    pub const WEEKEND: Weekdays = Weekdays(0b110_0000);
+
+    /// 从位掩码创建，忽略第7位
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::EVERY_DAY.0)
+    }
+
+    /// 获取位掩码
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// 是否未选择任何星期
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 是否包含指定星期
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & Self::bit(day) != 0
+    }
+
+    /// 切换指定星期的选中状态
+    pub fn toggle(&mut self, day: Weekday) {
+        self.0 ^= Self::bit(day);
+    }
+
+    /// 是否在指定星期触发（未选择任何星期时每天触发）
+    pub fn matches(self, day: Weekday) -> bool {
+        self.is_empty() || self.contains(day)
+    }
+
+    fn bit(day: Weekday) -> u8 {
+        1 << day.num_days_from_monday()
+    }
+}
+
+/// 闹钟重复方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// 单次：触发后自动关闭
+    Once,
+    /// 每周按星期掩码重复
+    Weekly,
+}
+
+/// 单个闹钟配置
+///
+/// 时间按本地时间（`NaiveDateTime`，不考虑夏令时）计算。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Alarm {
+    /// 触发时间（时:分）
+    pub time: NaiveTime,
+    /// 触发的星期，空掩码表示每天
+    pub weekdays: Weekdays,
+    /// 重复方式
+    pub repeat: Repeat,
+    /// 贪睡时长（分钟）
+    pub snooze_minutes: u8,
+    /// 是否启用
+    pub enabled: bool,
+}
+
+impl Alarm {
+    /// 创建每天重复、贪睡5分钟的闹钟
+    pub fn new(time: NaiveTime) -> Self {
+        Self {
+            time,
+            weekdays: Weekdays::EVERY_DAY,
+            repeat: Repeat::Weekly,
+            snooze_minutes: 5,
+            enabled: true,
+        }
+    }
+
+    /// 计算严格晚于`after`的下一次触发时间（不考虑启用状态）
+    ///
+    /// # 返回值
+    /// 下一次触发时间；超出日期范围时返回`None`
+    pub fn next_fire(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut date = after.date();
+
+        // 最多向后查找一周（当天时间已过时需要第8天）
+        for _ in 0..8 {
+            let candidate = date.and_time(self.time);
+            if candidate > after && self.weekdays.matches(date.weekday()) {
+                return Some(candidate);
+            }
+            date = date.succ_opt()?;
+        }
+
+        None
+    }
+
+    /// 贪睡时长
+    pub fn snooze(&self) -> Duration {
+        Duration::minutes(self.snooze_minutes as i64)
+    }
+}
+
+/// 闹钟槽位：配置加运行状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    alarm: Alarm,
+    /// 贪睡后的下一次响铃时间
+    snoozed_until: Option<NaiveDateTime>,
+}
+
+impl Slot {
+    /// 严格晚于`after`的下一次响铃时间（含贪睡）
+    fn next_due(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let snoozed = self.snoozed_until.filter(|&t| t > after);
+        let scheduled = match self.alarm.enabled {
+            true => self.alarm.next_fire(after),
+            false => None,
+        };
+
+        match (snoozed, scheduled) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// 一组闹钟序号（位掩码，第`i`位表示第`i`个闹钟）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DueAlarms(u32);
+
+impl DueAlarms {
+    /// 可表示的最大闹钟数量
+    pub const CAPACITY: usize = u32::BITS as usize;
+
+    /// 是否为空
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// 是否包含指定闹钟
+    pub fn contains(self, index: usize) -> bool {
+        index < Self::CAPACITY && self.0 & (1 << index) != 0
+    }
+
+    /// 序号最小的闹钟
+    pub fn first(self) -> Option<usize> {
+        self.iter().next()
+    }
+
+    /// 按序号从小到大遍历
+    pub fn iter(self) -> impl Iterator<Item = usize> {
+        (0..Self::CAPACITY).filter(move |&index| self.contains(index))
+    }
+
+    fn insert(&mut self, index: usize) {
+        self.0 |= 1 << index;
+    }
+
+    fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// 正在响铃的闹钟
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Ringing {
+    alarms: DueAlarms,
+    since: NaiveDateTime,
+}
+
+/// 闹钟管理器
+///
+/// 管理`N`个闹钟，由万年历时间驱动，通过周期调用[`AlarmManager::poll`]检测触发。
+/// 同时到期的闹钟一起响铃，贪睡和关闭对正在响铃的全部闹钟生效。
+///
+/// # 泛型参数
+/// - `N`: 闹钟数量，不超过[`DueAlarms::CAPACITY`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlarmManager<const N: usize> {
+    slots: [Slot; N],
+    ringing: Option<Ringing>,
+    last_poll: Option<NaiveDateTime>,
+}
+
+impl<const N: usize> AlarmManager<N> {
+    /// 创建管理器，所有闹钟默认为7:00且处于关闭状态
+    pub fn new() -> Self {
+        const { assert!(N <= DueAlarms::CAPACITY) };
+        let alarm = Alarm {
+            enabled: false,
+            ..Alarm::new(NaiveTime::from_hms_opt(7, 0, 0).unwrap())
+        };
+
+        Self {
+            slots: [Slot {
+                alarm,
+                snoozed_until: None,
+            }; N],
+            ringing: None,
+            last_poll: None,
+        }
+    }
+
+    /// 闹钟数量
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// 是否没有闹钟槽位
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// 获取指定闹钟
+    pub fn alarm(&self, index: usize) -> &Alarm {
+        &self.slots[index].alarm
+    }
+
+    /// 替换指定闹钟的配置，同时取消其贪睡状态
+    pub fn set_alarm(&mut self, index: usize, alarm: Alarm) {
+        self.slots[index] = Slot {
+            alarm,
+            snoozed_until: None,
+        };
+    }
+
+    /// 正在响铃的闹钟中序号最小的一个
+    pub fn ringing(&self) -> Option<usize> {
+        self.ringing_alarms().first()
+    }
+
+    /// 正在响铃的全部闹钟
+    pub fn ringing_alarms(&self) -> DueAlarms {
+        self.ringing.map(|r| r.alarms).unwrap_or_default()
+    }
+
+    /// 所有闹钟中最早的下一次响铃
+    ///
+    /// # 返回值
+    /// `(闹钟序号, 响铃时间)`，没有待响铃的闹钟时返回`None`
+    pub fn next_due(&self, now: NaiveDateTime) -> Option<(usize, NaiveDateTime)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.next_due(now).map(|t| (i, t)))
+            .min_by_key(|&(_, t)| t)
+    }
+
+    /// 以当前时间轮询闹钟
+    ///
+    /// 检测上次轮询到`now`之间（左开右闭）到期的全部闹钟，与仍在响铃的闹钟一起响铃。
+    /// 单次闹钟触发后自动关闭，响铃超过[`RING_TIMEOUT`]自动停止。
+    ///
+    /// # 返回值
+    /// 本次新触发的闹钟，没有时为空
+    pub fn poll(&mut self, now: NaiveDateTime) -> DueAlarms {
+        if let Some(ringing) = self.ringing
+            && now - ringing.since >= RING_TIMEOUT
+        {
+            self.ringing = None;
+        }
+
+        let mut due = DueAlarms::default();
+        let Some(last) = self.last_poll.replace(now) else {
+            return due;
+        };
+        if now <= last || now - last > MAX_POLL_GAP {
+            return due;
+        }
+
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            let Some(time) = slot.next_due(last).filter(|&time| time <= now) else {
+                continue;
+            };
+            if slot.snoozed_until != Some(time) && slot.alarm.repeat == Repeat::Once {
+                slot.alarm.enabled = false;
+            }
+            slot.snoozed_until = None;
+            due.insert(index);
+        }
+
+        if !due.is_empty() {
+            self.ringing = Some(Ringing {
+                alarms: self.ringing_alarms().union(due),
+                since: now,
+            });
+        }
+        due
+    }
+
+    /// 贪睡：停止响铃，正在响铃的每个闹钟在各自的贪睡时长后再次响铃
+    pub fn snooze(&mut self, now: NaiveDateTime) {
+        if let Some(ringing) = self.ringing.take() {
+            for index in ringing.alarms.iter() {
+                let slot = &mut self.slots[index];
+                slot.snoozed_until = now.checked_add_signed(slot.alarm.snooze());
+            }
+        }
+    }
+
+    /// 关闭响铃
+    pub fn dismiss(&mut self) {
+        self.ringing = None;
+    }
+}
+
+impl<const N: usize> Default for AlarmManager<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 闹钟编辑界面中可编辑的字段
+///
+/// 按键依次切换：开关 → 时 → 分 → 周一 … 周日 → 重复方式 → 贪睡时长 → 退出编辑
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlarmField {
+    Enabled,
+    Hour,
+    Minute,
+    Weekday(Weekday),
+    Repeat,
+    Snooze,
+}
+
+impl AlarmField {
+    /// 下一个字段，最后一个字段之后返回`None`（退出编辑）
+    pub fn next(self) -> Option<AlarmField> {
+        match self {
+            AlarmField::Enabled => Some(AlarmField::Hour),
+            AlarmField::Hour => Some(AlarmField::Minute),
+            AlarmField::Minute => Some(AlarmField::Weekday(Weekday::Mon)),
+            AlarmField::Weekday(Weekday::Sun) => Some(AlarmField::Repeat),
+            AlarmField::Weekday(day) => Some(AlarmField::Weekday(day.succ())),
+            AlarmField::Repeat => Some(AlarmField::Snooze),
+            AlarmField::Snooze => None,
+        }
+    }
+
+    /// 按字段调整闹钟
+    ///
+    /// - 开关、星期、重复方式：任意方向旋转均切换状态
+    /// - 时/分：在0-23、0-59之间循环
+    /// - 贪睡时长：在1-30分钟之间调整，不循环
+    pub fn adjust(self, alarm: &mut Alarm, delta: i32) {
+        if delta == 0 {
+            return;
+        }
+
+        let toggle = delta % 2 != 0;
+        match self {
+            AlarmField::Enabled if toggle => alarm.enabled = !alarm.enabled,
+            AlarmField::Hour => {
+                let hour = (alarm.time.hour() as i32 + delta).rem_euclid(24) as u32;
+                alarm.time = alarm.time.with_hour(hour).unwrap_or(alarm.time);
+            }
+            AlarmField::Minute => {
+                let minute = (alarm.time.minute() as i32 + delta).rem_euclid(60) as u32;
+                alarm.time = alarm.time.with_minute(minute).unwrap_or(alarm.time);
+            }
+            AlarmField::Weekday(day) if toggle => alarm.weekdays.toggle(day),
+            AlarmField::Repeat if toggle => {
+                alarm.repeat = match alarm.repeat {
+                    Repeat::Once => Repeat::Weekly,
+                    Repeat::Weekly => Repeat::Once,
+                }
+            }
+            AlarmField::Snooze => {
+                alarm.snooze_minutes = (alarm.snooze_minutes as i32 + delta)
+                    .clamp(SNOOZE_MINUTES_MIN as i32, SNOOZE_MINUTES_MAX as i32)
+                    as u8;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 闹钟编辑器状态机
+///
+/// 浏览状态下编码器选择闹钟，按键进入编辑；
+/// 编辑状态下按键切换字段，编码器调整字段值。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AlarmEditor {
+    /// 当前选中的闹钟序号
+    selected: usize,
+    /// 正在编辑的字段，`None`表示浏览状态
+    editing: Option<AlarmField>,
+}
+
+impl AlarmEditor {
+    /// 当前选中的闹钟序号
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// 正在编辑的字段
+    pub fn editing(&self) -> Option<AlarmField> {
+        self.editing
+    }
+
+    /// 选中指定闹钟并回到浏览状态
+    pub fn select(&mut self, index: usize) {
+        self.selected = index;
+        self.editing = None;
+    }
+
+    /// 处理按键事件：进入编辑或切换到下一个字段
+    pub fn on_button(&mut self) {
+        self.editing = match self.editing {
+            None => Some(AlarmField::Enabled),
+            Some(field) => field.next(),
+        };
+    }
+
+    /// 处理编码器事件
+    ///
+    /// # 返回值
+    /// 浏览状态下向前越过第一个闹钟时返回`false`，表示应退出闹钟界面；其余情况返回`true`
+    pub fn on_encoder<const N: usize>(&mut self, alarms: &mut AlarmManager<N>, delta: i32) -> bool {
+        match self.editing {
+            None => {
+                let selected = self.selected as i32 + delta;
+                if selected < 0 {
+                    return false;
+                }
+                self.selected = (selected as usize).min(N.saturating_sub(1));
+            }
+            Some(field) => {
+                let mut alarm = *alarms.alarm(self.selected);
+                field.adjust(&mut alarm, delta);
+                alarms.set_alarm(self.selected, alarm);
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    fn alarm(h: u32, m: u32, weekdays: Weekdays, repeat: Repeat) -> Alarm {
+        Alarm {
+            weekdays,
+            repeat,
+            ..Alarm::new(NaiveTime::from_hms_opt(h, m, 0).unwrap())
+        }
+    }
+
+    #[test]
+    fn next_fire_later_same_day() {
+        // 2025-07-21 是周一
+        let a = alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly);
+        assert_eq!(
+            a.next_fire(at(2025, 7, 21, 6, 0, 0)),
+            Some(at(2025, 7, 21, 7, 30, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_is_strictly_after() {
+        let a = alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly);
+        assert_eq!(
+            a.next_fire(at(2025, 7, 21, 7, 30, 0)),
+            Some(at(2025, 7, 22, 7, 30, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_skips_weekend_for_workdays() {
+        let a = alarm(7, 30, Weekdays::WORKDAYS, Repeat::Weekly);
+        // 周五闹钟已过 → 下周一
+        assert_eq!(
+            a.next_fire(at(2025, 7, 25, 8, 0, 0)),
+            Some(at(2025, 7, 28, 7, 30, 0))
+        );
+        // 周六、周日 → 下周一
+        assert_eq!(
+            a.next_fire(at(2025, 7, 26, 6, 0, 0)),
+            Some(at(2025, 7, 28, 7, 30, 0))
+        );
+        assert_eq!(
+            a.next_fire(at(2025, 7, 27, 23, 59, 59)),
+            Some(at(2025, 7, 28, 7, 30, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_single_weekday_waits_a_full_week() {
+        let mut days = Weekdays::NONE;
+        days.toggle(Weekday::Wed);
+        let a = alarm(9, 0, days, Repeat::Weekly);
+        // 周三闹钟刚过 → 下周三
+        assert_eq!(
+            a.next_fire(at(2025, 7, 23, 9, 0, 1)),
+            Some(at(2025, 7, 30, 9, 0, 0))
+        );
+        // 周四 → 下周三
+        assert_eq!(
+            a.next_fire(at(2025, 7, 24, 0, 0, 0)),
+            Some(at(2025, 7, 30, 9, 0, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_weekend_across_year_and_leap_day() {
+        let a = alarm(10, 0, Weekdays::WEEKEND, Repeat::Weekly);
+        // 2025-12-31 周三 → 2026-01-03 周六
+        assert_eq!(
+            a.next_fire(at(2025, 12, 31, 12, 0, 0)),
+            Some(at(2026, 1, 3, 10, 0, 0))
+        );
+        // 2024-02-28 周三 → 2024-03-02 周六（经过2月29日）
+        assert_eq!(
+            a.next_fire(at(2024, 2, 28, 12, 0, 0)),
+            Some(at(2024, 3, 2, 10, 0, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_empty_mask_means_every_day() {
+        let a = alarm(0, 0, Weekdays::NONE, Repeat::Once);
+        assert_eq!(
+            a.next_fire(at(2025, 7, 26, 0, 0, 0)),
+            Some(at(2025, 7, 27, 0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn next_fire_for_every_weekday_combination() {
+        let start = at(2025, 7, 21, 12, 0, 0); // 周一
+        for bits in 1..=0b111_1111u8 {
+            let a = alarm(8, 0, Weekdays::from_bits(bits), Repeat::Weekly);
+            let fire = a.next_fire(start).unwrap();
+
+            assert!(fire > start);
+            assert!(fire - start <= Duration::days(7));
+            assert_eq!(fire.time(), a.time);
+            assert!(a.weekdays.contains(fire.weekday()));
+
+            // 中间不应存在更早的匹配日
+            let mut day = start.date().succ_opt().unwrap();
+            while day < fire.date() {
+                assert!(!a.weekdays.contains(day.weekday()));
+                day = day.succ_opt().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn manager_fires_when_crossing_alarm_time() {
+        let mut alarms = AlarmManager::<2>::new();
+        alarms.set_alarm(1, alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly));
+
+        assert!(alarms.poll(at(2025, 7, 21, 7, 29, 58)).is_empty());
+        assert!(alarms.poll(at(2025, 7, 21, 7, 29, 59)).is_empty());
+        assert_eq!(alarms.poll(at(2025, 7, 21, 7, 30, 0)).first(), Some(1));
+        assert_eq!(alarms.ringing(), Some(1));
+        assert!(alarms.poll(at(2025, 7, 21, 7, 30, 1)).is_empty());
+        assert!(alarms.alarm(1).enabled);
+    }
+
+    #[test]
+    fn manager_ignores_disabled_alarms() {
+        let mut alarms = AlarmManager::<1>::new();
+        alarms.poll(at(2025, 7, 21, 6, 59, 59));
+        assert!(alarms.poll(at(2025, 7, 21, 7, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn one_shot_alarm_disables_after_firing() {
+        let mut alarms = AlarmManager::<1>::new();
+        alarms.set_alarm(0, alarm(7, 30, Weekdays::NONE, Repeat::Once));
+
+        alarms.poll(at(2025, 7, 21, 7, 29, 59));
+        assert_eq!(alarms.poll(at(2025, 7, 21, 7, 30, 0)).first(), Some(0));
+        assert!(!alarms.alarm(0).enabled);
+        assert_eq!(alarms.next_due(at(2025, 7, 21, 7, 30, 0)), None);
+    }
+
+    #[test]
+    fn snooze_rings_again_after_snooze_duration() {
+        let mut alarms = AlarmManager::<1>::new();
+        alarms.set_alarm(0, alarm(7, 30, Weekdays::NONE, Repeat::Once));
+
+        alarms.poll(at(2025, 7, 21, 7, 29, 59));
+        alarms.poll(at(2025, 7, 21, 7, 30, 0));
+        alarms.snooze(at(2025, 7, 21, 7, 30, 10));
+        assert_eq!(alarms.ringing(), None);
+        assert_eq!(
+            alarms.next_due(at(2025, 7, 21, 7, 30, 10)),
+            Some((0, at(2025, 7, 21, 7, 35, 10)))
+        );
+
+        assert!(alarms.poll(at(2025, 7, 21, 7, 35, 9)).is_empty());
+        assert_eq!(alarms.poll(at(2025, 7, 21, 7, 35, 10)).first(), Some(0));
+
+        alarms.dismiss();
+        assert_eq!(alarms.ringing(), None);
+        assert_eq!(alarms.next_due(at(2025, 7, 21, 7, 35, 10)), None);
+    }
+
+    #[test]
+    fn ringing_times_out() {
+        let mut alarms = AlarmManager::<1>::new();
+        alarms.set_alarm(0, alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly));
+
+        alarms.poll(at(2025, 7, 21, 7, 29, 59));
+        alarms.poll(at(2025, 7, 21, 7, 30, 0));
+        alarms.poll(at(2025, 7, 21, 7, 30, 59));
+        assert_eq!(alarms.ringing(), Some(0));
+        alarms.poll(at(2025, 7, 21, 7, 31, 0));
+        assert_eq!(alarms.ringing(), None);
+    }
+
+    #[test]
+    fn time_jump_skips_alarms() {
+        let mut alarms = AlarmManager::<1>::new();
+        alarms.set_alarm(0, alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly));
+
+        alarms.poll(at(2025, 7, 21, 6, 0, 0));
+        assert!(alarms.poll(at(2025, 7, 21, 8, 0, 0)).is_empty());
+        assert!(alarms.poll(at(2025, 7, 21, 7, 0, 0)).is_empty());
+    }
+
+    #[test]
+    fn alarms_due_together_all_ring() {
+        let mut alarms = AlarmManager::<3>::new();
+        alarms.set_alarm(0, alarm(7, 30, Weekdays::EVERY_DAY, Repeat::Weekly));
+        alarms.set_alarm(2, alarm(7, 30, Weekdays::NONE, Repeat::Once));
+
+        alarms.poll(at(2025, 7, 21, 7, 29, 59));
+        let due = alarms.poll(at(2025, 7, 21, 7, 30, 0));
+        assert_eq!(due.iter().collect::<heapless::Vec<_, 3>>(), [0, 2]);
+        assert_eq!(alarms.ringing_alarms(), due);
+        assert!(!alarms.alarm(2).enabled);
+
+        // 贪睡对两个闹钟都生效
+        alarms.snooze(at(2025, 7, 21, 7, 30, 10));
+        assert!(alarms.ringing_alarms().is_empty());
+        assert!(alarms.poll(at(2025, 7, 21, 7, 35, 9)).is_empty());
+        let due = alarms.poll(at(2025, 7, 21, 7, 35, 10));
+        assert_eq!(due.iter().collect::<heapless::Vec<_, 3>>(), [0, 2]);
+    }
+
+    #[test]
+    fn next_due_picks_earliest_alarm() {
+        let mut alarms = AlarmManager::<3>::new();
+        alarms.set_alarm(0, alarm(9, 0, Weekdays::EVERY_DAY, Repeat::Weekly));
+        alarms.set_alarm(2, alarm(6, 0, Weekdays::WEEKEND, Repeat::Weekly));
+
+        // 周五 → 周五 9:00 早于周六 6:00
+        assert_eq!(
+            alarms.next_due(at(2025, 7, 25, 8, 0, 0)),
+            Some((0, at(2025, 7, 25, 9, 0, 0)))
+        );
+        // 周五晚上 → 周六 6:00 早于周六 9:00
+        assert_eq!(
+            alarms.next_due(at(2025, 7, 25, 22, 0, 0)),
+            Some((2, at(2025, 7, 26, 6, 0, 0)))
+        );
+    }
+
+    #[test]
+    fn field_adjustments() {
+        let mut a = alarm(23, 59, Weekdays::NONE, Repeat::Once);
+        AlarmField::Hour.adjust(&mut a, 1);
+        AlarmField::Minute.adjust(&mut a, 2);
+        assert_eq!(a.time, NaiveTime::from_hms_opt(0, 1, 0).unwrap());
+
+        AlarmField::Weekday(Weekday::Sat).adjust(&mut a, -1);
+        assert_eq!(a.weekdays, Weekdays::from_bits(0b010_0000));
+
+        AlarmField::Repeat.adjust(&mut a, 1);
+        assert_eq!(a.repeat, Repeat::Weekly);
+
+        AlarmField::Enabled.adjust(&mut a, 2);
+        assert!(a.enabled);
+        AlarmField::Enabled.adjust(&mut a, 1);
+        assert!(!a.enabled);
+
+        AlarmField::Snooze.adjust(&mut a, 100);
+        assert_eq!(a.snooze_minutes, 30);
+        AlarmField::Snooze.adjust(&mut a, -100);
+        assert_eq!(a.snooze_minutes, 1);
+    }
+
+    #[test]
+    fn editor_walks_all_fields() {
+        let mut editor = AlarmEditor::default();
+        let mut count = 0;
+        editor.on_button();
+        while editor.editing().is_some() {
+            count += 1;
+            editor.on_button();
+        }
+        // 开关、时、分、7个星期、重复、贪睡
+        assert_eq!(count, 12);
+    }
+
+    #[test]
+    fn editor_browses_and_exits() {
+        let mut alarms = AlarmManager::<3>::new();
+        let mut editor = AlarmEditor::default();
+
+        assert!(editor.on_encoder(&mut alarms, 5));
+        assert_eq!(editor.selected(), 2);
+        assert!(editor.on_encoder(&mut alarms, -2));
+        assert_eq!(editor.selected(), 0);
+        assert!(!editor.on_encoder(&mut alarms, -1));
+    }
+
+    #[test]
+    fn editor_edits_selected_alarm() {
+        let mut alarms = AlarmManager::<2>::new();
+        let mut editor = AlarmEditor::default();
+
+        editor.on_encoder(&mut alarms, 1);
+        editor.on_button(); // Enabled
+        editor.on_encoder(&mut alarms, 1);
+        editor.on_button(); // Hour
+        editor.on_encoder(&mut alarms, -1);
+
+        assert!(alarms.alarm(1).enabled);
+        assert_eq!(
+            alarms.alarm(1).time,
+            NaiveTime::from_hms_opt(6, 0, 0).unwrap()
+        );
+        assert!(!alarms.alarm(0).enabled);
+    }
+}
@@ -0,0 +1,664 @@
+use super::alarm::{Alarm, AlarmEditor, AlarmManager, DueAlarms};
+use super::clock::{ClockModel, HourFormat};
+use super::month_view::MonthView;
+use super::timer::{Countdown, CountdownState, Stopwatch};
+use super::timezone::{PRESETS, TimeZone};
+use chrono::NaiveDateTime;
+use embassy_time::{Duration, Instant};
+
+/// 闹钟数量
+pub const ALARM_COUNT: usize = 4;
+
+/// 秒表保留的计圈记录数
+pub const LAP_COUNT: usize = 3;
+
+/// 倒计时默认设定时长
+pub const DEFAULT_COUNTDOWN: Duration = Duration::from_secs(5 * 60);
+
+/// 屏幕亮度档位数
+pub const BRIGHTNESS_LEVELS: u8 = 5;
+
+/// 默认屏幕亮度档位
+pub const DEFAULT_BRIGHTNESS: u8 = 2;
+
+/// 应用模式，长按按键循环切换
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// 万年历（时钟、农历、月历、闹钟和时区界面）
+    Calendar,
+    /// 秒表
+    Stopwatch,
+    /// 倒计时
+    Timer,
+}
+
+impl Mode {
+    /// 循环切换的下一个模式
+    pub fn next(self) -> Self {
+        match self {
+            Mode::Calendar => Mode::Stopwatch,
+            Mode::Stopwatch => Mode::Timer,
+            Mode::Timer => Mode::Calendar,
+        }
+    }
+}
+
+/// 万年历界面
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Screen {
+    /// 日期时间界面
+    Clock,
+    /// 农历界面
+    Lunar,
+    /// 月历界面
+    Month,
+    /// 闹钟设置界面
+    Alarm,
+    /// 时区设置界面
+    TimeZone,
+}
+
+/// 万年历应用状态机
+///
+/// 组合时钟、闹钟、时区、秒表、倒计时和界面切换逻辑，统一处理按键和编码器输入：
+/// - 闹钟响铃时：按键或长按关闭响铃，旋转编码器贪睡
+/// - 长按：在万年历、秒表和倒计时模式之间循环切换；设置状态下忽略长按，
+///   避免丢弃未提交的修改
+///
+/// 万年历模式下各界面的操作：
+/// - 时钟界面：按键进入/切换时间设置字段；正常走时状态下向后旋转编码器进入农历界面
+/// - 农历界面：按键进入月历界面；向前旋转返回时钟界面，向后旋转进入闹钟界面
+/// - 月历界面：旋转编码器逐月翻页，按键返回农历界面
+/// - 闹钟界面：见[`AlarmEditor`]，在第一个闹钟处向前旋转返回农历界面，
+///   在最后一个闹钟处向后旋转进入时区界面
+/// - 时区界面：按键进入/退出选择状态，选择状态下旋转编码器切换时区预设；
+///   否则向前旋转返回闹钟界面
+///
+/// 秒表模式：按键开始/暂停；运行中旋转编码器计圈，暂停时向前旋转清零。
+///
+/// 倒计时模式：按键开始/暂停，时间到后按键或旋转编码器停止提醒；
+/// 未启动时旋转编码器调整设定时长，暂停时向前旋转复位。
+/// 倒计时在其他模式下也会继续运行，到时后自动切换到倒计时模式。
+///
+/// 时间源始终保存UTC时间，显示、设置和闹钟均使用转换后的当地时间；
+/// 秒表和倒计时使用调用方传入的单调时钟时刻。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalendarApp {
+    /// 当地时间
+    clock: ClockModel,
+    /// 最近一次同步的UTC时间
+    utc: NaiveDateTime,
+    timezone: TimeZone,
+    /// 当前时区对应的预设序号，自定义时区为`None`
+    timezone_preset: Option<usize>,
+    /// 是否处于时区选择状态
+    timezone_editing: bool,
+    alarms: AlarmManager<ALARM_COUNT>,
+    alarm_editor: AlarmEditor,
+    month_view: MonthView,
+    screen: Screen,
+    mode: Mode,
+    stopwatch: Stopwatch<LAP_COUNT>,
+    countdown: Countdown,
+    hour_format: HourFormat,
+    /// 屏幕亮度档位（0为最暗）
+    brightness: u8,
+}
+
+impl CalendarApp {
+    /// 以指定的UTC初始时间创建应用，使用第一个时区预设，显示时钟界面
+    pub fn new(utc: NaiveDateTime) -> Self {
+        let timezone = TimeZone::parse(PRESETS[0].rule).unwrap_or_else(|_| TimeZone::utc());
+        let local = timezone.to_local(utc);
+        Self {
+            clock: ClockModel::new(local),
+            utc,
+            timezone,
+            timezone_preset: Some(0),
+            timezone_editing: false,
+            alarms: AlarmManager::new(),
+            alarm_editor: AlarmEditor::default(),
+            month_view: MonthView::new(local.date()),
+            screen: Screen::Clock,
+            mode: Mode::Calendar,
+            stopwatch: Stopwatch::new(),
+            countdown: Countdown::new(DEFAULT_COUNTDOWN),
+            hour_format: HourFormat::H24,
+            brightness: DEFAULT_BRIGHTNESS,
+        }
+    }
+
+    /// 时钟状态（当地时间）
+    pub fn clock(&self) -> &ClockModel {
+        &self.clock
+    }
+
+    /// 闹钟列表
+    pub fn alarms(&self) -> &AlarmManager<ALARM_COUNT> {
+        &self.alarms
+    }
+
+    /// 闹钟编辑器状态
+    pub fn alarm_editor(&self) -> &AlarmEditor {
+        &self.alarm_editor
+    }
+
+    /// 月历视图状态
+    pub fn month_view(&self) -> &MonthView {
+        &self.month_view
+    }
+
+    /// 当前界面
+    pub fn screen(&self) -> Screen {
+        self.screen
+    }
+
+    /// 当前模式
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// 秒表状态
+    pub fn stopwatch(&self) -> &Stopwatch<LAP_COUNT> {
+        &self.stopwatch
+    }
+
+    /// 倒计时状态
+    pub fn countdown(&self) -> &Countdown {
+        &self.countdown
+    }
+
+    /// 最近一次同步的UTC时间
+    pub fn utc(&self) -> NaiveDateTime {
+        self.utc
+    }
+
+    /// 当前时区
+    pub fn timezone(&self) -> &TimeZone {
+        &self.timezone
+    }
+
+    /// 当前时区对应的预设序号，自定义时区为`None`
+    pub fn timezone_preset(&self) -> Option<usize> {
+        self.timezone_preset
+    }
+
+    /// 是否处于时区选择状态
+    pub fn timezone_editing(&self) -> bool {
+        self.timezone_editing
+    }
+
+    /// 小时显示格式
+    pub fn hour_format(&self) -> HourFormat {
+        self.hour_format
+    }
+
+    /// 屏幕亮度档位（0为最暗，最大为`BRIGHTNESS_LEVELS - 1`）
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// 是否处于时间、闹钟或时区设置状态
+    pub fn is_editing(&self) -> bool {
+        self.clock.editing().is_some()
+            || self.alarm_editor.editing().is_some()
+            || self.timezone_editing
+    }
+
+    /// 设置小时显示格式
+    pub fn set_hour_format(&mut self, format: HourFormat) {
+        self.hour_format = format;
+    }
+
+    /// 设置屏幕亮度档位，超出范围时取最亮档
+    pub fn set_brightness(&mut self, level: u8) {
+        self.brightness = level.min(BRIGHTNESS_LEVELS - 1);
+    }
+
+    /// 替换指定闹钟的配置（如从闪存恢复设置）
+    pub fn set_alarm(&mut self, index: usize, alarm: Alarm) {
+        self.alarms.set_alarm(index, alarm);
+    }
+
+    /// 设置时区并立即刷新当地时间
+    ///
+    /// # 参数
+    /// - `timezone`: 新时区
+    /// - `preset`: 对应的预设序号，自定义时区传入`None`
+    pub fn set_timezone(&mut self, timezone: TimeZone, preset: Option<usize>) {
+        self.timezone = timezone;
+        self.timezone_preset = preset;
+        if self.clock.editing().is_none() {
+            self.clock.set_now(timezone.to_local(self.utc));
+        }
+    }
+
+    /// 直接设置UTC时间并立即刷新当地时间（如通过串口校时）
+    ///
+    /// 时间设置模式下同时替换正在编辑的时间。
+    pub fn set_utc(&mut self, utc: NaiveDateTime) {
+        self.utc = utc;
+        self.clock.set_now(self.timezone.to_local(utc));
+    }
+
+    /// 切换到相对当前预设偏移`delta`个的时区预设（循环）
+    fn step_timezone_preset(&mut self, delta: i32) {
+        let current = self.timezone_preset.unwrap_or(0) as i32;
+        let index = (current + delta).rem_euclid(PRESETS.len() as i32) as usize;
+        if let Ok(timezone) = TimeZone::parse(PRESETS[index].rule) {
+            self.set_timezone(timezone, Some(index));
+        }
+    }
+
+    /// 处理按键（短按）事件
+    ///
+    /// # 参数
+    /// - `now`: 按下按键的单调时钟时刻，用于秒表和倒计时
+    ///
+    /// # 返回值
+    /// 退出时间设置模式时返回编辑完成的UTC时间，调用方应将其写入时间源
+    pub fn on_button(&mut self, now: Instant) -> Option<NaiveDateTime> {
+        if self.alarms.ringing().is_some() {
+            self.alarms.dismiss();
+            return None;
+        }
+
+        match self.mode {
+            Mode::Calendar => return self.on_calendar_button(),
+            Mode::Stopwatch => self.stopwatch.toggle(now),
+            Mode::Timer if self.countdown.is_expired() => self.countdown.reset(),
+            Mode::Timer => self.countdown.toggle(now),
+        }
+
+        None
+    }
+
+    /// 处理长按事件
+    pub fn on_long_press(&mut self) {
+        if self.alarms.ringing().is_some() {
+            self.alarms.dismiss();
+            return;
+        }
+        if self.countdown.is_expired() {
+            self.countdown.reset();
+            return;
+        }
+
+        if !self.is_editing() {
+            self.mode = self.mode.next();
+        }
+    }
+
+    /// 处理编码器事件
+    ///
+    /// # 参数
+    /// - `delta`: 编码器相对变化量
+    /// - `now`: 事件发生的单调时钟时刻，用于秒表计圈
+    pub fn on_encoder(&mut self, delta: i32, now: Instant) {
+        if self.alarms.ringing().is_some() {
+            self.alarms.snooze(self.clock.now());
+            return;
+        }
+
+        match self.mode {
+            Mode::Calendar => self.on_calendar_encoder(delta),
+            Mode::Stopwatch if self.stopwatch.is_running() => {
+                if delta != 0 {
+                    self.stopwatch.lap(now);
+                }
+            }
+            Mode::Stopwatch => {
+                if delta < 0 {
+                    self.stopwatch.reset();
+                }
+            }
+            Mode::Timer => match self.countdown.state() {
+                CountdownState::Idle => self.countdown.adjust(delta),
+                CountdownState::Paused if delta < 0 => self.countdown.reset(),
+                CountdownState::Expired if delta != 0 => self.countdown.reset(),
+                _ => {}
+            },
+        }
+    }
+
+    /// 万年历模式下的按键处理
+    fn on_calendar_button(&mut self) -> Option<NaiveDateTime> {
+        match self.screen {
+            Screen::Clock => {
+                let local = self.clock.on_button()?;
+                self.utc = self.timezone.to_utc(local);
+                return Some(self.utc);
+            }
+            Screen::Lunar => {
+                self.month_view = MonthView::new(self.clock.now().date());
+                self.screen = Screen::Month;
+            }
+            Screen::Month => self.screen = Screen::Lunar,
+            Screen::Alarm => self.alarm_editor.on_button(),
+            Screen::TimeZone => self.timezone_editing = !self.timezone_editing,
+        }
+
+        None
+    }
+
+    /// 万年历模式下的编码器处理
+    fn on_calendar_encoder(&mut self, delta: i32) {
+        match self.screen {
+            Screen::Clock if self.clock.editing().is_some() => self.clock.on_encoder(delta),
+            Screen::Clock => {
+                if delta > 0 {
+                    self.screen = Screen::Lunar;
+                }
+            }
+            Screen::Lunar => {
+                if delta > 0 {
+                    self.alarm_editor.select(0);
+                    self.screen = Screen::Alarm;
+                } else if delta < 0 {
+                    self.screen = Screen::Clock;
+                }
+            }
+            Screen::Month => self.month_view.scroll(delta),
+            Screen::Alarm => {
+                let browsing = self.alarm_editor.editing().is_none();
+                if browsing
+                    && delta > 0
+                    && self.alarm_editor.selected() as i32 + delta >= ALARM_COUNT as i32
+                {
+                    self.timezone_editing = false;
+                    self.screen = Screen::TimeZone;
+                } else if !self.alarm_editor.on_encoder(&mut self.alarms, delta) {
+                    self.screen = Screen::Lunar;
+                }
+            }
+            Screen::TimeZone if self.timezone_editing => self.step_timezone_preset(delta),
+            Screen::TimeZone => {
+                if delta < 0 {
+                    self.alarm_editor.select(ALARM_COUNT - 1);
+                    self.screen = Screen::Alarm;
+                }
+            }
+        }
+    }
+
+    /// 与时间源同步并检测闹钟
+    ///
+    /// # 参数
+    /// - `utc`: 时间源读取到的UTC时间
+    ///
+    /// # 返回值
+    /// 本次新触发的闹钟
+    pub fn sync(&mut self, utc: NaiveDateTime) -> DueAlarms {
+        self.utc = utc;
+        self.clock.sync(self.timezone.to_local(utc));
+        self.alarms.poll(self.clock.now())
+    }
+
+    /// 检测倒计时是否到时，到时后切换到倒计时模式
+    ///
+    /// # 参数
+    /// - `now`: 当前单调时钟时刻
+    ///
+    /// # 返回值
+    /// 倒计时是否刚刚到时
+    pub fn tick(&mut self, now: Instant) -> bool {
+        let expired = self.countdown.poll(now);
+        if expired {
+            self.mode = Mode::Timer;
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, NaiveDate, NaiveTime};
+
+    const T0: Instant = Instant::MIN;
+
+    fn at(h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, 21)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    /// 使用UTC时区的应用，当地时间与UTC时间相同
+    fn utc_app(now: NaiveDateTime) -> CalendarApp {
+        let mut app = CalendarApp::new(now);
+        app.set_timezone(TimeZone::utc(), None);
+        app
+    }
+
+    #[test]
+    fn encoder_switches_between_clock_and_alarm_screens() {
+        let mut app = utc_app(at(12, 0, 0));
+        app.on_encoder(-1, T0);
+        assert_eq!(app.screen(), Screen::Clock);
+
+        app.on_encoder(1, T0);
+        assert_eq!(app.screen(), Screen::Lunar);
+        app.on_encoder(1, T0);
+        assert_eq!(app.screen(), Screen::Alarm);
+        assert_eq!(app.alarm_editor().selected(), 0);
+
+        app.on_encoder(2, T0);
+        assert_eq!(app.alarm_editor().selected(), 2);
+        app.on_encoder(-3, T0);
+        assert_eq!(app.screen(), Screen::Lunar);
+        app.on_encoder(-1, T0);
+        assert_eq!(app.screen(), Screen::Clock);
+    }
+
+    #[test]
+    fn button_toggles_month_view_from_lunar_screen() {
+        let mut app = utc_app(at(12, 0, 0));
+        app.on_encoder(1, T0);
+        assert_eq!(app.on_button(T0), None);
+        assert_eq!(app.screen(), Screen::Month);
+        assert_eq!(app.clock().editing(), None);
+        assert_eq!(
+            (app.month_view().year(), app.month_view().month()),
+            (2025, 7)
+        );
+
+        app.on_encoder(6, T0);
+        assert_eq!(
+            (app.month_view().year(), app.month_view().month()),
+            (2026, 1)
+        );
+        app.on_encoder(-13, T0);
+        assert_eq!(
+            (app.month_view().year(), app.month_view().month()),
+            (2024, 12)
+        );
+        assert_eq!(app.screen(), Screen::Month);
+
+        assert_eq!(app.on_button(T0), None);
+        assert_eq!(app.screen(), Screen::Lunar);
+
+        // 再次进入时回到当前月份
+        app.on_button(T0);
+        assert_eq!(
+            (app.month_view().year(), app.month_view().month()),
+            (2025, 7)
+        );
+    }
+
+    #[test]
+    fn encoder_edits_time_instead_of_switching_screens() {
+        let mut app = utc_app(at(12, 0, 0));
+        app.on_button(T0); // Year
+        app.on_encoder(1, T0);
+        assert_eq!(app.screen(), Screen::Clock);
+        assert_eq!(app.clock().now(), at(12, 0, 0).with_year(2026).unwrap());
+    }
+
+    #[test]
+    fn button_dismisses_and_encoder_snoozes_ringing_alarm() {
+        let mut app = utc_app(at(7, 29, 59));
+        app.alarms
+            .set_alarm(0, Alarm::new(NaiveTime::from_hms_opt(7, 30, 0).unwrap()));
+
+        app.sync(at(7, 29, 59));
+        assert_eq!(app.sync(at(7, 30, 0)).first(), Some(0));
+        app.on_encoder(1, T0);
+        assert_eq!(app.alarms().ringing(), None);
+        assert_eq!(app.screen(), Screen::Clock);
+
+        let mut fired = None;
+        for second in 1..=300 {
+            fired = fired.or(app
+                .sync(at(7, 30, 0) + chrono::Duration::seconds(second))
+                .first());
+        }
+        assert_eq!(fired, Some(0));
+        assert_eq!(app.on_button(T0), None);
+        assert_eq!(app.alarms().ringing(), None);
+        assert_eq!(app.clock().editing(), None);
+    }
+
+    #[test]
+    fn alarm_screen_leads_to_timezone_screen() {
+        let mut app = CalendarApp::new(at(12, 0, 0));
+        app.on_encoder(1, T0);
+        app.on_encoder(1, T0);
+        app.on_encoder(ALARM_COUNT as i32 - 1, T0);
+        assert_eq!(app.screen(), Screen::Alarm);
+        assert_eq!(app.alarm_editor().selected(), ALARM_COUNT - 1);
+
+        app.on_encoder(1, T0);
+        assert_eq!(app.screen(), Screen::TimeZone);
+        app.on_encoder(1, T0);
+        assert_eq!(app.screen(), Screen::TimeZone);
+
+        app.on_encoder(-1, T0);
+        assert_eq!(app.screen(), Screen::Alarm);
+        assert_eq!(app.alarm_editor().selected(), ALARM_COUNT - 1);
+    }
+
+    #[test]
+    fn timezone_screen_selects_presets() {
+        let mut app = CalendarApp::new(at(10, 0, 0));
+        assert_eq!(app.timezone_preset(), Some(0));
+        assert_eq!(app.clock().now(), at(18, 0, 0));
+
+        app.on_encoder(1, T0);
+        app.on_encoder(1, T0);
+        app.on_encoder(ALARM_COUNT as i32, T0);
+        assert_eq!(app.screen(), Screen::TimeZone);
+
+        app.on_button(T0);
+        assert!(app.timezone_editing());
+        app.on_encoder(1, T0);
+        assert_eq!(app.timezone_preset(), Some(1));
+        assert_eq!(app.clock().now(), at(10, 0, 0));
+
+        app.on_encoder(-2, T0);
+        assert_eq!(app.timezone_preset(), Some(PRESETS.len() - 1));
+        assert_eq!(app.screen(), Screen::TimeZone);
+
+        app.on_button(T0);
+        assert!(!app.timezone_editing());
+        assert_eq!(app.utc(), at(10, 0, 0));
+    }
+
+    #[test]
+    fn edited_local_time_is_committed_as_utc() {
+        let mut app = CalendarApp::new(at(10, 0, 0));
+        for _ in 0..4 {
+            app.on_button(T0); // Year → Hour
+        }
+        app.on_encoder(1, T0);
+        assert_eq!(app.clock().now(), at(19, 0, 0));
+
+        app.on_button(T0); // Minute
+        app.on_button(T0); // Second
+        assert_eq!(app.on_button(T0), Some(at(11, 0, 0)));
+        assert_eq!(app.utc(), at(11, 0, 0));
+    }
+
+    #[test]
+    fn alarms_fire_at_local_time() {
+        let mut app = CalendarApp::new(at(23, 29, 59));
+        app.alarms
+            .set_alarm(0, Alarm::new(NaiveTime::from_hms_opt(7, 30, 0).unwrap()));
+
+        // UTC 23:30 即北京时间次日 07:30
+        assert!(app.sync(at(23, 29, 59)).is_empty());
+        assert_eq!(app.sync(at(23, 30, 0)).first(), Some(0));
+    }
+
+    #[test]
+    fn long_press_cycles_modes() {
+        let mut app = utc_app(at(12, 0, 0));
+        assert_eq!(app.mode(), Mode::Calendar);
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Stopwatch);
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Timer);
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Calendar);
+
+        // 设置时间时忽略长按
+        app.on_button(T0);
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Calendar);
+        assert!(app.clock().editing().is_some());
+    }
+
+    #[test]
+    fn stopwatch_mode_starts_laps_and_resets() {
+        let mut app = utc_app(at(12, 0, 0));
+        app.on_long_press();
+
+        app.on_button(Instant::from_secs(10));
+        app.on_encoder(0, Instant::from_secs(11));
+        app.on_encoder(1, Instant::from_secs(12));
+        app.on_encoder(-1, Instant::from_secs(15));
+        app.on_button(Instant::from_secs(16));
+        assert_eq!(
+            app.stopwatch().elapsed(Instant::from_secs(60)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(app.stopwatch().lap_count(), 2);
+        assert_eq!(app.screen(), Screen::Clock);
+
+        app.on_encoder(1, Instant::from_secs(17));
+        assert_eq!(app.stopwatch().lap_count(), 2);
+        app.on_encoder(-1, Instant::from_secs(18));
+        assert_eq!(app.stopwatch().lap_count(), 0);
+        assert!(!app.stopwatch().is_running());
+    }
+
+    #[test]
+    fn countdown_expiry_switches_to_timer_mode() {
+        let mut app = utc_app(at(12, 0, 0));
+        app.on_long_press();
+        app.on_long_press();
+        assert_eq!(app.countdown().preset(), DEFAULT_COUNTDOWN);
+        app.on_encoder(-9, T0); // 5:00 → 1:00 → 0:55
+        assert_eq!(app.countdown().preset(), Duration::from_secs(55));
+
+        app.on_button(Instant::from_secs(100));
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Calendar);
+        app.on_encoder(1, Instant::from_secs(120));
+        assert_eq!(app.screen(), Screen::Lunar);
+
+        assert!(!app.tick(Instant::from_secs(154)));
+        assert!(app.tick(Instant::from_secs(155)));
+        assert_eq!(app.mode(), Mode::Timer);
+        assert!(app.countdown().is_expired());
+
+        app.on_button(Instant::from_secs(156));
+        assert_eq!(app.countdown().state(), CountdownState::Idle);
+        assert_eq!(app.countdown().preset(), Duration::from_secs(55));
+
+        // 回到万年历时保持原界面
+        app.on_long_press();
+        assert_eq!(app.mode(), Mode::Calendar);
+        assert_eq!(app.screen(), Screen::Lunar);
+    }
+}
@@ -0,0 +1,181 @@
+use embedded_graphics::{
+    geometry::Size,
+    image::ImageRaw,
+    mono_font::{DecorationDimensions, MonoFont, mapping::StrGlyphMapping},
+};
+
+/// 字形宽度和高度（像素）
+const GLYPH_SIZE: u32 = 12;
+
+/// 字库包含的字符，顺序与[`GLYPHS`]一致，第一个字符（空格）同时作为缺字替代
+const GLYPH_CHARS: &str = " 一二三四五六七八九十正冬腊月闰初廿甲乙丙丁戊己庚辛壬癸子丑寅卯辰巳午未申酉戌亥鼠牛虎兔龙蛇马羊猴鸡狗猪小寒大立春雨水惊蛰分清明谷夏满芒种至暑秋处白露降雪年霜";
+
+/// 12x12点阵字形，每行一个`u16`，最高位为最左侧像素
+///
+/// 字形绘制在左上角11x11区域内，右侧和底部各留一像素作为字间距和行间距。
+#[rustfmt::skip]
+const GLYPHS: [[u16; GLYPH_SIZE as usize]; 79] = [
+    [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // 空格
+    [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0xffe0, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // 一
+    [0x0000, 0x0000, 0x7fc0, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0xffe0, 0x0000, 0x0000, 0x0000], // 二
+    [0x0000, 0x7fc0, 0x0000, 0x0000, 0x0000, 0x3f80, 0x0000, 0x0000, 0x0000, 0xffe0, 0x0000, 0x0000], // 三
+    [0x0000, 0xffe0, 0x8a20, 0x8a20, 0x8a20, 0x9220, 0x91e0, 0xa020, 0xc020, 0xffe0, 0x8020, 0x0000], // 四
+    [0x0000, 0xffc0, 0x0800, 0x0800, 0x0800, 0x7f80, 0x0880, 0x1080, 0x1080, 0x1080, 0xffe0, 0x0000], // 五
+    [0x0800, 0x0400, 0x0000, 0xffe0, 0x0000, 0x1100, 0x1080, 0x2040, 0x2020, 0x4020, 0x8000, 0x0000], // 六
+    [0x1000, 0x1000, 0x1040, 0x1780, 0x3e00, 0xf000, 0x1000, 0x1000, 0x1020, 0x1020, 0x0fe0, 0x0000], // 七
+    [0x0000, 0x1100, 0x1100, 0x1100, 0x1100, 0x1080, 0x2080, 0x2040, 0x4040, 0x8020, 0x0000, 0x0000], // 八
+    [0x1000, 0x1000, 0xfe00, 0x1200, 0x1200, 0x1200, 0x2200, 0x2200, 0x4220, 0x8220, 0x01e0, 0x0000], // 九
+    [0x0400, 0x0400, 0x0400, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 十
+    [0x0000, 0xffe0, 0x0400, 0x0400, 0x2400, 0x27c0, 0x2400, 0x2400, 0x2400, 0x2400, 0xffe0, 0x0000], // 正
+    [0x1000, 0x3f80, 0x4100, 0xa200, 0x0c00, 0x3300, 0xc0c0, 0x0c00, 0x0200, 0x1800, 0x0600, 0x0000], // 冬
+    [0xe880, 0xa880, 0xbfe0, 0xe880, 0xa880, 0xbfe0, 0xe000, 0xafc0, 0xa840, 0xafc0, 0xcfc0, 0x0000], // 腊
+    [0x3fc0, 0x2040, 0x2040, 0x3fc0, 0x2040, 0x2040, 0x3fc0, 0x2040, 0x4040, 0x4040, 0x81c0, 0x0000], // 月
+    [0x4000, 0x2fc0, 0x8040, 0xbe40, 0x8840, 0x8840, 0x9c40, 0x8840, 0x8840, 0xbe40, 0x80c0, 0x0000], // 闰
+    [0x4000, 0x2fe0, 0xe120, 0x2120, 0x5120, 0xd120, 0x4220, 0x4220, 0x4420, 0x4820, 0x50c0, 0x0000], // 初
+    [0x2080, 0x2080, 0x2080, 0xffe0, 0x2080, 0x2080, 0x2080, 0x2080, 0x3f80, 0x0000, 0x0000, 0x0000], // 廿
+    [0x7fc0, 0x4440, 0x4440, 0x7fc0, 0x4440, 0x4440, 0x7fc0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 甲
+    [0x0000, 0x7f80, 0x0100, 0x0200, 0x0400, 0x0800, 0x1000, 0x2000, 0x4020, 0x4020, 0x3fe0, 0x0000], // 乙
+    [0xffe0, 0x0400, 0x7fc0, 0x4440, 0x4440, 0x4a40, 0x5140, 0x60c0, 0x4040, 0x4040, 0x40c0, 0x0000], // 丙
+    [0x0000, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x1c00, 0x0000], // 丁
+    [0x0100, 0x0140, 0x7fe0, 0x4100, 0x4120, 0x40a0, 0x40c0, 0x4040, 0x40a0, 0x8120, 0x8220, 0x0000], // 戊
+    [0x7f80, 0x0040, 0x0040, 0x0040, 0x7fc0, 0x4000, 0x4000, 0x4000, 0x4020, 0x4020, 0x3fe0, 0x0000], // 己
+    [0x0400, 0x7fe0, 0x4400, 0x5f80, 0x4480, 0x7fc0, 0x4480, 0x5f80, 0x4600, 0x4900, 0x90c0, 0x0000], // 庚
+    [0x0400, 0x7fc0, 0x1100, 0x0a00, 0xffe0, 0x0400, 0x7fc0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 辛
+    [0x01c0, 0x7c00, 0x0400, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x7fc0, 0x0000], // 壬
+    [0x5140, 0xf0c0, 0x2900, 0x4680, 0x9960, 0x3f80, 0x0400, 0xffe0, 0x0a00, 0x3180, 0xc060, 0x0000], // 癸
+    [0x7f80, 0x0100, 0x0200, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x1c00, 0x0000], // 子
+    [0x7f80, 0x0880, 0x0880, 0x0880, 0x3fc0, 0x0880, 0x0880, 0x1080, 0x1080, 0x2080, 0xffe0, 0x0000], // 丑
+    [0x0400, 0xffe0, 0x8020, 0x3f80, 0x2480, 0x3f80, 0x2480, 0x3f80, 0x0000, 0x3180, 0xc060, 0x0000], // 寅
+    [0x23c0, 0x6240, 0xa240, 0xa240, 0xa240, 0xa240, 0xb2c0, 0xa200, 0xa200, 0x6200, 0x2200, 0x0000], // 卯
+    [0x7fe0, 0x4000, 0x5fc0, 0x4000, 0x7fe0, 0x4840, 0x4880, 0x4900, 0x4880, 0x8a40, 0x8c20, 0x0000], // 辰
+    [0x7f80, 0x4080, 0x4080, 0x4080, 0x7f80, 0x4000, 0x4000, 0x4000, 0x4020, 0x4020, 0x3fe0, 0x0000], // 巳
+    [0x2000, 0x7fe0, 0x4400, 0x8400, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 午
+    [0x0400, 0x0400, 0x7fc0, 0x0400, 0xffe0, 0x0e00, 0x1500, 0x2480, 0x4440, 0x8420, 0x0400, 0x0000], // 未
+    [0x0400, 0x7fc0, 0x4440, 0x4440, 0x7fc0, 0x4440, 0x4440, 0x7fc0, 0x0400, 0x0400, 0x0400, 0x0000], // 申
+    [0xffe0, 0x1100, 0x7fc0, 0x5140, 0x5140, 0x60c0, 0x4040, 0x7fc0, 0x4040, 0x4040, 0x7fc0, 0x0000], // 酉
+    [0x0140, 0x0120, 0x7fe0, 0x4100, 0x4120, 0x7f40, 0x40c0, 0x4080, 0x4140, 0x8220, 0x8400, 0x0000], // 戌
+    [0x0400, 0xffe0, 0x0800, 0x1100, 0x7e00, 0x0480, 0x1900, 0x6200, 0x0d00, 0x3080, 0xc060, 0x0000], // 亥
+    [0x3f80, 0x2480, 0x3f80, 0x2480, 0x3f80, 0x5500, 0x5540, 0x5b40, 0x7b40, 0xd4c0, 0x8000, 0x0000], // 鼠
+    [0x2400, 0x2400, 0x7fc0, 0x8400, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 牛
+    [0x0400, 0x0780, 0x7fc0, 0x4400, 0x5f80, 0x4000, 0x4780, 0x4480, 0x4480, 0x88a0, 0x9060, 0x0000], // 虎
+    [0x1000, 0x3e00, 0x4400, 0xbf80, 0x2480, 0x3f80, 0x0a00, 0x1200, 0x22a0, 0x4240, 0x83c0, 0x0000], // 兔
+    [0x0100, 0x0480, 0xffe0, 0x0400, 0x0c40, 0x0a80, 0x1300, 0x1200, 0x2220, 0x4220, 0x81e0, 0x0000], // 龙
+    [0x2080, 0x27e0, 0xfa20, 0xaa00, 0xaa40, 0xfa80, 0x2300, 0x2a00, 0xea20, 0x8a20, 0x03e0, 0x0000], // 蛇
+    [0x7f00, 0x0100, 0x1100, 0x1100, 0x1100, 0x1fc0, 0x0040, 0x0040, 0xffc0, 0x0040, 0x00c0, 0x0000], // 马
+    [0x2100, 0x1200, 0x7fc0, 0x0400, 0x7fc0, 0x0400, 0x0400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0000], // 羊
+    [0x9200, 0x65e0, 0x2500, 0x6de0, 0xa400, 0x25e0, 0x2440, 0x25e0, 0x24a0, 0x2520, 0xc400, 0x0000], // 猴
+    [0x0200, 0xf7c0, 0x1440, 0x9540, 0x5440, 0x27c0, 0x5040, 0x97c0, 0x0040, 0x0040, 0x00c0, 0x0000], // 鸡
+    [0x9000, 0x67e0, 0x4820, 0x5020, 0xaf20, 0x2920, 0x2920, 0x2f20, 0x2020, 0x2020, 0xc0c0, 0x0000], // 狗
+    [0x9100, 0x67c0, 0x4140, 0x5fe0, 0xa200, 0x27c0, 0x2a40, 0x23c0, 0x2240, 0x23c0, 0xc000, 0x0000], // 猪
+    [0x0400, 0x0400, 0x0400, 0x2480, 0x2440, 0x4440, 0x4420, 0x8420, 0x0400, 0x0400, 0x1c00, 0x0000], // 小
+    [0x0400, 0xffe0, 0xa0a0, 0x7fc0, 0x1100, 0x7fc0, 0x1100, 0xffe0, 0x1100, 0x2480, 0x4320, 0x0000], // 寒
+    [0x0400, 0x0400, 0x0400, 0xffe0, 0x0400, 0x0a00, 0x0a00, 0x1100, 0x2080, 0x4040, 0x8020, 0x0000], // 大
+    [0x0400, 0x0400, 0xffe0, 0x0000, 0x2080, 0x2080, 0x1100, 0x1100, 0x0a00, 0x0000, 0xffe0, 0x0000], // 立
+    [0x0400, 0x7fc0, 0x0400, 0x3f80, 0xffe0, 0x1100, 0x3f80, 0x60c0, 0xbfa0, 0x2080, 0x3f80, 0x0000], // 春
+    [0xffe0, 0x0400, 0x7fc0, 0x4440, 0x5540, 0x4cc0, 0x4440, 0x5540, 0x4cc0, 0x4440, 0x40c0, 0x0000], // 雨
+    [0x0400, 0x0400, 0x0440, 0xf480, 0x1500, 0x1600, 0x2600, 0x2500, 0x4480, 0x8460, 0x1c00, 0x0000], // 水
+    [0x4100, 0x5fe0, 0xc000, 0x6fc0, 0x4840, 0x4fc0, 0x4080, 0x4a80, 0x52a0, 0x4200, 0x4600, 0x0000], // 惊
+    [0x4400, 0xf700, 0x4d00, 0x6540, 0xcac0, 0x0400, 0x7fc0, 0x4440, 0x7fc0, 0x0480, 0xffe0, 0x0000], // 蛰
+    [0x0a00, 0x1100, 0x2080, 0x4040, 0xbfa0, 0x1080, 0x1080, 0x1080, 0x2080, 0x4080, 0x8380, 0x0000], // 分
+    [0x8200, 0x5fc0, 0x0200, 0x9fc0, 0x4200, 0x3fe0, 0x2840, 0x4fc0, 0x4840, 0x8fc0, 0x88c0, 0x0000], // 清
+    [0x07e0, 0xf420, 0x9420, 0x97e0, 0xf420, 0x9420, 0x97e0, 0xf420, 0x0420, 0x0820, 0x10e0, 0x0000], // 明
+    [0x1100, 0x2080, 0x4a40, 0x1100, 0x2080, 0x4040, 0xbfa0, 0x2080, 0x2080, 0x2080, 0x3f80, 0x0000], // 谷
+    [0xffe0, 0x0400, 0x3f80, 0x2080, 0x3f80, 0x2080, 0x3f80, 0x1f00, 0x2200, 0x0c00, 0x71e0, 0x0000], // 夏
+    [0x8880, 0x5fc0, 0x0880, 0xbfe0, 0x4200, 0x1fc0, 0x1540, 0x5540, 0x5b40, 0x9040, 0x90c0, 0x0000], // 满
+    [0x2080, 0xffe0, 0x2080, 0x0400, 0xffe0, 0x2000, 0x2000, 0x2000, 0x2000, 0x2020, 0x1fe0, 0x0000], // 芒
+    [0x2100, 0xe100, 0x4fe0, 0xe920, 0x4920, 0xefe0, 0xe100, 0xa100, 0x4100, 0x4100, 0x4100, 0x0000], // 种
+    [0xffe0, 0x0800, 0x1100, 0x2080, 0x7fc0, 0x0400, 0x0400, 0x3f80, 0x0400, 0x0400, 0xffe0, 0x0000], // 至
+    [0x3f80, 0x2080, 0x3f80, 0x2080, 0x3f80, 0x1f40, 0x0500, 0xffe0, 0x1fc0, 0x3040, 0x5fc0, 0x0000], // 暑
+    [0x2080, 0xe080, 0x4aa0, 0xf4a0, 0x4480, 0xe080, 0xe140, 0xa140, 0x4220, 0x4420, 0x4800, 0x0000], // 秋
+    [0x4100, 0x4100, 0x7900, 0x9180, 0x9140, 0x6120, 0x6100, 0x9100, 0x8900, 0x8400, 0x83e0, 0x0000], // 处
+    [0x0400, 0x0800, 0x7fc0, 0x4040, 0x4040, 0x7fc0, 0x4040, 0x4040, 0x4040, 0x7fc0, 0x0000, 0x0000], // 白
+    [0xffe0, 0xa4a0, 0x9520, 0xa4a0, 0xe780, 0xa900, 0xe600, 0x4980, 0x6f80, 0x4880, 0xef80, 0x0000], // 露
+    [0xe400, 0xaf80, 0xd100, 0xa600, 0xa9c0, 0xe400, 0x8f80, 0x9400, 0x9fc0, 0x8400, 0x8400, 0x0000], // 降
+    [0x7fc0, 0x0400, 0xffe0, 0xa4a0, 0x9520, 0x0000, 0x7f80, 0x0080, 0x3f80, 0x0080, 0x7f80, 0x0000], // 雪
+    [0x2000, 0x7fe0, 0x4400, 0xbf80, 0x2400, 0x2400, 0xffe0, 0x0400, 0x0400, 0x0400, 0x0400, 0x0000], // 年
+    [0xffe0, 0xa4a0, 0x9520, 0xa4a0, 0x47c0, 0xf440, 0x47c0, 0xe440, 0xa7c0, 0x4440, 0x47c0, 0x0000], // 霜
+];
+
+/// 将字形逐行展开为`ImageRaw`所需的字节序列（每行2字节，字形纵向排列）
+const fn glyph_data() -> [u8; GLYPHS.len() * GLYPH_SIZE as usize * 2] {
+    let mut data = [0; GLYPHS.len() * GLYPH_SIZE as usize * 2];
+    let mut glyph = 0;
+    while glyph < GLYPHS.len() {
+        let mut row = 0;
+        while row < GLYPH_SIZE as usize {
+            let [high, low] = GLYPHS[glyph][row].to_be_bytes();
+            let offset = (glyph * GLYPH_SIZE as usize + row) * 2;
+            data[offset] = high;
+            data[offset + 1] = low;
+            row += 1;
+        }
+        glyph += 1;
+    }
+    data
+}
+
+const GLYPH_DATA: [u8; GLYPHS.len() * GLYPH_SIZE as usize * 2] = glyph_data();
+
+/// 12x12中文点阵字体子集
+///
+/// 仅包含农历日期、天干地支、生肖和二十四节气所需的汉字，
+/// 可直接用于`embedded_graphics::mono_font::MonoTextStyle`。
+/// 字库中不存在的字符显示为空白。
+pub const FONT_CJK_12X12: MonoFont<'static> = MonoFont {
+    image: ImageRaw::new(&GLYPH_DATA, GLYPH_SIZE),
+    glyph_mapping: &StrGlyphMapping::new(GLYPH_CHARS, 0),
+    character_size: Size::new(GLYPH_SIZE, GLYPH_SIZE),
+    character_spacing: 0,
+    baseline: 10,
+    underline: DecorationDimensions::new(11, 1),
+    strikethrough: DecorationDimensions::default_strikethrough(11),
+};
+
+/// 字库是否包含指定字符
+pub fn contains(c: char) -> bool {
+    GLYPH_CHARS.contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::lunar::{
+        EARTHLY_BRANCHES, HEAVENLY_STEMS, LunarDate, SolarTerm, ZODIAC_ANIMALS,
+    };
+    use chrono::NaiveDate;
+
+    #[test]
+    fn glyph_table_matches_mapping() {
+        assert_eq!(GLYPH_CHARS.chars().count(), GLYPHS.len());
+        for (i, c) in GLYPH_CHARS.chars().enumerate() {
+            assert_eq!(GLYPH_CHARS.chars().position(|other| other == c), Some(i));
+        }
+        // 字形只占用左侧11列
+        assert!(GLYPHS.iter().flatten().all(|row| row & 0x001f == 0));
+    }
+
+    #[test]
+    fn covers_lunar_calendar_text() {
+        let mut texts = heapless::Vec::<&str, 128>::new();
+        texts.extend_from_slice(&HEAVENLY_STEMS).unwrap();
+        texts.extend_from_slice(&EARTHLY_BRANCHES).unwrap();
+        texts.extend_from_slice(&ZODIAC_ANIMALS).unwrap();
+        for term in SolarTerm::ALL {
+            texts.push(term.name()).unwrap();
+        }
+        texts.extend_from_slice(&["年", "闰"]).unwrap();
+
+        let mut date = NaiveDate::from_ymd_opt(2025, 1, 29).unwrap();
+        for _ in 0..385 {
+            let lunar = LunarDate::from_solar(date).unwrap();
+            for text in [lunar.month_name(), lunar.day_name()] {
+                assert!(text.chars().all(contains), "{text}");
+            }
+            date = date.succ_opt().unwrap();
+        }
+
+        for text in texts {
+            assert!(text.chars().all(contains), "{text}");
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// 时间设置模式下可编辑的字段
+///
+/// 按键依次切换：年 → 月 → 日 → 时 → 分 → 秒 → 退出设置
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditField {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl EditField {
+    /// 按切换顺序排列的全部字段
+    pub const ALL: [EditField; 6] = [
+        EditField::Year,
+        EditField::Month,
+        EditField::Day,
+        EditField::Hour,
+        EditField::Minute,
+        EditField::Second,
+    ];
+
+    /// 字段在切换顺序中的序号（从0开始）
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 下一个字段，最后一个字段之后返回`None`（退出设置模式）
+    pub fn next(self) -> Option<EditField> {
+        Self::ALL.get(self.index() + 1).copied()
+    }
+}
+
+/// 小时显示格式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HourFormat {
+    /// 24小时制（0-23）
+    #[default]
+    H24,
+    /// 12小时制（1-12，附上午/下午标记）
+    H12,
+}
+
+impl HourFormat {
+    /// 按显示格式换算小时
+    ///
+    /// # 返回值
+    /// 显示的小时数，12小时制时附带`"AM"`或`"PM"`
+    pub fn display_hour(self, hour: u32) -> (u32, Option<&'static str>) {
+        match self {
+            HourFormat::H24 => (hour, None),
+            HourFormat::H12 => {
+                let marker = if hour < 12 { "AM" } else { "PM" };
+                ((hour + 11) % 12 + 1, Some(marker))
+            }
+        }
+    }
+}
+
+/// 万年历时钟状态机
+///
+/// 与硬件无关的纯逻辑模型，负责维护当前时间和时间设置状态。
+/// 按键、编码器和定时器事件通过对应方法输入，显示任务只需读取状态，
+/// 因此可以在PC上直接进行单元测试。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockModel {
+    /// 当前时间
+    now: NaiveDateTime,
+    /// 正在编辑的字段，`None`表示正常走时
+    editing: Option<EditField>,
+}
+
+impl ClockModel {
+    /// 以指定的初始时间创建时钟，处于正常走时状态
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self { now, editing: None }
+    }
+
+    /// 获取当前时间
+    pub fn now(&self) -> NaiveDateTime {
+        self.now
+    }
+
+    /// 获取正在编辑的字段
+    pub fn editing(&self) -> Option<EditField> {
+        self.editing
+    }
+
+    /// 直接设置当前时间（不改变编辑状态）
+    pub fn set_now(&mut self, now: NaiveDateTime) {
+        self.now = now;
+    }
+
+    /// 处理按键事件：切换到下一个编辑字段
+    ///
+    /// 正常走时 → 年 → 月 → 日 → 时 → 分 → 秒 → 正常走时
+    ///
+    /// # 返回值
+    /// 退出设置模式时返回编辑完成的时间，调用方应将其写入时间源；否则返回`None`
+    pub fn on_button(&mut self) -> Option<NaiveDateTime> {
+        self.editing = match self.editing {
+            None => Some(EditField::Year),
+            Some(field) => field.next(),
+        };
+
+        match self.editing {
+            None => Some(self.now),
+            Some(_) => None,
+        }
+    }
+
+    /// 处理编码器事件：调整当前编辑字段
+    ///
+    /// 非设置模式下忽略编码器输入。各字段的调整规则见[`adjust_field`]。
+    ///
+    /// # 参数
+    /// - `delta`: 编码器的相对变化量（正数增加，负数减少）
+    pub fn on_encoder(&mut self, delta: i32) {
+        if let Some(field) = self.editing {
+            self.now = adjust_field(self.now, field, delta);
+        }
+    }
+
+    /// 与外部时间源同步：正常走时状态下采用时间源的时间
+    ///
+    /// 设置模式下忽略，保留正在编辑的时间。
+    pub fn sync(&mut self, now: NaiveDateTime) {
+        if self.editing.is_none() {
+            self.now = now;
+        }
+    }
+
+    /// 处理定时器事件：正常走时状态下推进时间
+    ///
+    /// 设置模式下时间保持不变，避免编辑过程中数值跳动。
+    ///
+    /// # 参数
+    /// - `elapsed`: 距上次调用经过的时间
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.editing.is_none() {
+            self.now = self.now.checked_add_signed(elapsed).unwrap_or(self.now);
+        }
+    }
+}
+
+/// 判断是否为闰年
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// 获取指定年月的天数
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// 在`[min, min + count)`范围内循环加减
+fn wrap(value: u32, delta: i32, min: u32, count: u32) -> u32 {
+    let offset = (value - min) as i64 + delta as i64;
+    offset.rem_euclid(count as i64) as u32 + min
+}
+
+/// 按字段调整时间
+///
+/// 每个字段独立调整，不会进位到其他字段：
+/// - 年：保持月、日不变，2月29日在非闰年钳位为2月28日；超出日期范围时保持不变
+/// - 月：在1-12之间循环，日超过当月天数时钳位为当月最后一天
+/// - 日：在当月天数范围内循环
+/// - 时/分/秒：分别在0-23、0-59、0-59之间循环
+///
+/// # 参数
+/// - `now`: 原始时间
+/// - `field`: 要调整的字段
+/// - `delta`: 调整量（正数增加，负数减少）
+///
+/// # 返回值
+/// 调整后的时间
+pub fn adjust_field(now: NaiveDateTime, field: EditField, delta: i32) -> NaiveDateTime {
+    let date = now.date();
+    let time = now.time();
+
+    let new_date = match field {
+        EditField::Year => date
+            .year()
+            .checked_add(delta)
+            .and_then(|year| clamped_date(year, date.month(), date.day())),
+        EditField::Month => clamped_date(date.year(), wrap(date.month(), delta, 1, 12), date.day()),
+        EditField::Day => {
+            let days = days_in_month(date.year(), date.month());
+            date.with_day(wrap(date.day(), delta, 1, days))
+        }
+        EditField::Hour | EditField::Minute | EditField::Second => Some(date),
+    };
+
+    let new_time = match field {
+        EditField::Hour => time.with_hour(wrap(time.hour(), delta, 0, 24)),
+        EditField::Minute => time.with_minute(wrap(time.minute(), delta, 0, 60)),
+        EditField::Second => time.with_second(wrap(time.second(), delta, 0, 60)),
+        EditField::Year | EditField::Month | EditField::Day => Some(time),
+    };
+
+    match (new_date, new_time) {
+        (Some(date), Some(time)) => date.and_time(time),
+        _ => now,
+    }
+}
+
+/// 构造日期，日超过当月天数时钳位为当月最后一天
+fn clamped_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(year, month, day.min(days_in_month(year, month)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    #[test]
+    fn starts_in_normal_mode() {
+        let clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        assert_eq!(clock.editing(), None);
+        assert_eq!(clock.now(), at(2025, 7, 20, 18, 0, 0));
+    }
+
+    #[test]
+    fn button_cycles_through_all_fields() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        for field in EditField::ALL {
+            assert_eq!(clock.on_button(), None);
+            assert_eq!(clock.editing(), Some(field));
+        }
+        assert_eq!(clock.on_button(), Some(at(2025, 7, 20, 18, 0, 0)));
+        assert_eq!(clock.editing(), None);
+    }
+
+    #[test]
+    fn leaving_edit_mode_returns_edited_time() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        clock.on_button(); // Year
+        clock.on_encoder(1);
+        for _ in 1..EditField::ALL.len() {
+            assert_eq!(clock.on_button(), None);
+        }
+        assert_eq!(clock.on_button(), Some(at(2026, 7, 20, 18, 0, 0)));
+    }
+
+    #[test]
+    fn sync_ignored_while_editing() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        clock.sync(at(2025, 7, 20, 18, 0, 5));
+        assert_eq!(clock.now(), at(2025, 7, 20, 18, 0, 5));
+
+        clock.on_button();
+        clock.sync(at(2025, 7, 20, 18, 0, 9));
+        assert_eq!(clock.now(), at(2025, 7, 20, 18, 0, 5));
+    }
+
+    #[test]
+    fn tick_advances_only_in_normal_mode() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        clock.tick(Duration::milliseconds(1500));
+        assert_eq!(
+            clock.now(),
+            at(2025, 7, 20, 18, 0, 1) + Duration::milliseconds(500)
+        );
+
+        let frozen = clock.now();
+        clock.on_button();
+        clock.tick(Duration::seconds(10));
+        assert_eq!(clock.now(), frozen);
+    }
+
+    #[test]
+    fn encoder_ignored_in_normal_mode() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+        clock.on_encoder(5);
+        assert_eq!(clock.now(), at(2025, 7, 20, 18, 0, 0));
+    }
+
+    #[test]
+    fn encoder_adjusts_selected_field() {
+        let mut clock = ClockModel::new(at(2025, 7, 20, 18, 0, 0));
+
+        clock.on_button(); // Year
+        clock.on_button(); // Month
+        clock.on_button(); // Day
+        clock.on_encoder(2);
+        assert_eq!(clock.now(), at(2025, 7, 22, 18, 0, 0));
+
+        clock.on_button(); // Hour
+        clock.on_encoder(-3);
+        assert_eq!(clock.now(), at(2025, 7, 22, 15, 0, 0));
+
+        clock.on_button(); // Minute
+        clock.on_encoder(15);
+        assert_eq!(clock.now(), at(2025, 7, 22, 15, 15, 0));
+
+        clock.on_button(); // Second
+        clock.on_encoder(-1);
+        assert_eq!(clock.now(), at(2025, 7, 22, 15, 15, 59));
+    }
+
+    #[test]
+    fn month_clamps_day() {
+        let jan31 = at(2025, 1, 31, 12, 0, 0);
+        assert_eq!(
+            adjust_field(jan31, EditField::Month, 1),
+            at(2025, 2, 28, 12, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(jan31, EditField::Month, 3),
+            at(2025, 4, 30, 12, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2024, 1, 31, 12, 0, 0), EditField::Month, 1),
+            at(2024, 2, 29, 12, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2025, 3, 31, 12, 0, 0), EditField::Month, -1),
+            at(2025, 2, 28, 12, 0, 0)
+        );
+    }
+
+    #[test]
+    fn month_wraps_without_changing_year() {
+        assert_eq!(
+            adjust_field(at(2025, 12, 15, 8, 0, 0), EditField::Month, 1),
+            at(2025, 1, 15, 8, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2025, 1, 15, 8, 0, 0), EditField::Month, -1),
+            at(2025, 12, 15, 8, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2025, 5, 15, 8, 0, 0), EditField::Month, 24),
+            at(2025, 5, 15, 8, 0, 0)
+        );
+    }
+
+    #[test]
+    fn year_keeps_month_and_day() {
+        assert_eq!(
+            adjust_field(at(2025, 7, 20, 18, 0, 0), EditField::Year, 1),
+            at(2026, 7, 20, 18, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2025, 7, 20, 18, 0, 0), EditField::Year, -25),
+            at(2000, 7, 20, 18, 0, 0)
+        );
+    }
+
+    #[test]
+    fn year_clamps_leap_day() {
+        let leap_day = at(2024, 2, 29, 6, 30, 0);
+        assert_eq!(
+            adjust_field(leap_day, EditField::Year, 1),
+            at(2025, 2, 28, 6, 30, 0)
+        );
+        assert_eq!(
+            adjust_field(leap_day, EditField::Year, -1),
+            at(2023, 2, 28, 6, 30, 0)
+        );
+        assert_eq!(
+            adjust_field(leap_day, EditField::Year, 4),
+            at(2028, 2, 29, 6, 30, 0)
+        );
+        assert_eq!(
+            adjust_field(leap_day, EditField::Year, 76),
+            at(2100, 2, 28, 6, 30, 0)
+        );
+        assert_eq!(
+            adjust_field(leap_day, EditField::Year, -24),
+            at(2000, 2, 29, 6, 30, 0)
+        );
+    }
+
+    #[test]
+    fn year_out_of_range_is_ignored() {
+        let now = at(2025, 7, 20, 18, 0, 0);
+        assert_eq!(adjust_field(now, EditField::Year, i32::MAX), now);
+        assert_eq!(adjust_field(now, EditField::Year, i32::MIN), now);
+    }
+
+    #[test]
+    fn day_wraps_within_month() {
+        assert_eq!(
+            adjust_field(at(2025, 1, 31, 0, 0, 0), EditField::Day, 1),
+            at(2025, 1, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2025, 2, 1, 0, 0, 0), EditField::Day, -1),
+            at(2025, 2, 28, 0, 0, 0)
+        );
+        assert_eq!(
+            adjust_field(at(2024, 2, 28, 0, 0, 0), EditField::Day, 1),
+            at(2024, 2, 29, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn time_fields_wrap_without_carry() {
+        let now = at(2025, 12, 31, 23, 59, 59);
+        assert_eq!(
+            adjust_field(now, EditField::Second, 1),
+            at(2025, 12, 31, 23, 59, 0)
+        );
+        assert_eq!(
+            adjust_field(now, EditField::Minute, 1),
+            at(2025, 12, 31, 23, 0, 59)
+        );
+        assert_eq!(
+            adjust_field(now, EditField::Hour, 1),
+            at(2025, 12, 31, 0, 59, 59)
+        );
+
+        let midnight = at(2025, 1, 1, 0, 0, 0);
+        assert_eq!(
+            adjust_field(midnight, EditField::Second, -1),
+            at(2025, 1, 1, 0, 0, 59)
+        );
+        assert_eq!(
+            adjust_field(midnight, EditField::Minute, -61),
+            at(2025, 1, 1, 0, 59, 0)
+        );
+        assert_eq!(
+            adjust_field(midnight, EditField::Hour, -25),
+            at(2025, 1, 1, 23, 0, 0)
+        );
+    }
+
+    #[test]
+    fn sub_second_part_is_preserved() {
+        let now = at(2025, 1, 31, 10, 0, 0) + Duration::milliseconds(250);
+        let adjusted = adjust_field(now, EditField::Month, 1);
+        assert_eq!(
+            adjusted,
+            at(2025, 2, 28, 10, 0, 0) + Duration::milliseconds(250)
+        );
+    }
+
+    #[test]
+    fn days_in_month_matches_chrono() {
+        for year in [1900, 2000, 2023, 2024, 2100] {
+            for month in 1..=12 {
+                let first = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+                let next = if month == 12 {
+                    NaiveDate::from_ymd_opt(year + 1, 1, 1).unwrap()
+                } else {
+                    NaiveDate::from_ymd_opt(year, month + 1, 1).unwrap()
+                };
+                assert_eq!(days_in_month(year, month) as i64, (next - first).num_days());
+            }
+        }
+    }
+
+    /// 遍历2023-2025年的每一天，检查各字段调整只影响对应字段
+    #[test]
+    fn every_day_adjusts_only_selected_field() {
+        let mut date = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+
+        while date < end {
+            let now = date.and_hms_opt(13, 45, 30).unwrap();
+
+            for delta in -25..=25 {
+                let m = adjust_field(now, EditField::Month, delta);
+                assert_eq!(m.year(), now.year());
+                assert_eq!(m.time(), now.time());
+                assert_eq!(m.day(), now.day().min(days_in_month(m.year(), m.month())));
+                assert_eq!(
+                    m.month0() as i32,
+                    (now.month0() as i32 + delta).rem_euclid(12)
+                );
+
+                let y = adjust_field(now, EditField::Year, delta);
+                assert_eq!(y.year(), now.year() + delta);
+                assert_eq!(y.month(), now.month());
+                assert_eq!(y.time(), now.time());
+                assert_eq!(y.day(), now.day().min(days_in_month(y.year(), y.month())));
+
+                let d = adjust_field(now, EditField::Day, delta);
+                assert_eq!((d.year(), d.month()), (now.year(), now.month()));
+                assert_eq!(d.time(), now.time());
+
+                for field in [EditField::Hour, EditField::Minute, EditField::Second] {
+                    assert_eq!(adjust_field(now, field, delta).date(), now.date());
+                }
+            }
+
+            date = date.succ_opt().unwrap();
+        }
+    }
+
+    #[test]
+    fn edit_field_index_matches_order() {
+        for (i, field) in EditField::ALL.iter().enumerate() {
+            assert_eq!(field.index(), i);
+        }
+        assert_eq!(EditField::Second.next(), None);
+    }
+
+    #[test]
+    fn twelve_hour_format_maps_midnight_and_noon_to_twelve() {
+        assert_eq!(HourFormat::H24.display_hour(0), (0, None));
+        assert_eq!(HourFormat::H24.display_hour(23), (23, None));
+        assert_eq!(HourFormat::H12.display_hour(0), (12, Some("AM")));
+        assert_eq!(HourFormat::H12.display_hour(11), (11, Some("AM")));
+        assert_eq!(HourFormat::H12.display_hour(12), (12, Some("PM")));
+        assert_eq!(HourFormat::H12.display_hour(23), (11, Some("PM")));
+    }
+}
@@ -0,0 +1,536 @@
+//! 串口命令
+//!
+//! 通过USB CDC-ACM串口以文本行收发命令，每条命令占一行（以`\r`或`\n`结尾）。
+//! 响应由零或多行数据加一行结果组成，结果行为`OK`或`ERR <原因>`，
+//! 上位机读到结果行即可判断响应结束。
+//!
+//! 支持的命令：
+//! - `time get`：读取当地时间，如`2025-07-20T18:00:00+08:00 CST`
+//! - `time set <时间>`：设置时间。`2025-07-20T18:00:00`按当前时区的当地时间解释，
+//!   带`Z`或UTC偏移（如`2025-07-20T10:00:00Z`、`2025-07-20T18:00:00+08:00`）时按绝对时间解释
+//! - `alarm list`：列出所有闹钟
+//! - `display get`：读取显示设置，如`24h brightness 2`
+//! - `display 12h`/`display 24h`：切换小时显示格式
+//! - `display brightness <0-4>`：设置屏幕亮度档位
+//! - `help`：列出支持的命令
+
+use super::alarm::{Alarm, Repeat};
+use super::calendar::{BRIGHTNESS_LEVELS, CalendarApp};
+use super::clock::HourFormat;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Timelike, Weekday};
+use core::fmt::{self, Write};
+use core::str;
+
+/// 一行命令的最大长度（字节）
+pub const LINE_CAPACITY: usize = 64;
+
+/// 帮助信息
+const HELP: &str = "time get\r\ntime set YYYY-MM-DDTHH:MM:SS[Z|+HH:MM]\r\nalarm list\r\n\
+display get\r\ndisplay 12h|24h\r\ndisplay brightness 0-4\r\nhelp\r\n";
+
+/// 命令
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// 列出支持的命令
+    Help,
+    /// 读取当地时间
+    TimeGet,
+    /// 按当地时间设置时间
+    TimeSetLocal(NaiveDateTime),
+    /// 按UTC时间设置时间
+    TimeSetUtc(NaiveDateTime),
+    /// 列出所有闹钟
+    AlarmList,
+    /// 读取显示设置
+    DisplayGet,
+    /// 设置小时显示格式
+    SetHourFormat(HourFormat),
+    /// 设置屏幕亮度档位
+    SetBrightness(u8),
+}
+
+/// 命令解析错误
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// 空行
+    Empty,
+    /// 未知命令
+    UnknownCommand,
+    /// 缺少参数
+    MissingArgument,
+    /// 时间格式错误或超出范围
+    InvalidDateTime,
+    /// 参数值无效或超出范围
+    InvalidValue,
+    /// 多余的参数
+    UnexpectedArgument,
+    /// 命令行超过[`LINE_CAPACITY`]
+    LineTooLong,
+    /// 命令行不是有效的UTF-8
+    InvalidEncoding,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseError::Empty => "empty command",
+            ParseError::UnknownCommand => "unknown command, try help",
+            ParseError::MissingArgument => "missing argument",
+            ParseError::InvalidDateTime => "invalid date/time",
+            ParseError::InvalidValue => "invalid value",
+            ParseError::UnexpectedArgument => "unexpected argument",
+            ParseError::LineTooLong => "line too long",
+            ParseError::InvalidEncoding => "invalid encoding",
+        })
+    }
+}
+
+impl Command {
+    /// 解析一行命令，命令字不区分大小写，各部分以空白分隔
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let mut words = line.split_whitespace();
+        let first = words.next().ok_or(ParseError::Empty)?;
+        let second = words.next();
+
+        let command = if first.eq_ignore_ascii_case("help") {
+            if second.is_some() {
+                return Err(ParseError::UnexpectedArgument);
+            }
+            Command::Help
+        } else if first.eq_ignore_ascii_case("time") {
+            match second {
+                Some(word) if word.eq_ignore_ascii_case("get") => Command::TimeGet,
+                Some(word) if word.eq_ignore_ascii_case("set") => {
+                    let argument = words.next().ok_or(ParseError::MissingArgument)?;
+                    Self::parse_time(argument)?
+                }
+                Some(_) => return Err(ParseError::UnknownCommand),
+                None => return Err(ParseError::MissingArgument),
+            }
+        } else if first.eq_ignore_ascii_case("alarm") {
+            match second {
+                Some(word) if word.eq_ignore_ascii_case("list") => Command::AlarmList,
+                Some(_) => return Err(ParseError::UnknownCommand),
+                None => return Err(ParseError::MissingArgument),
+            }
+        } else if first.eq_ignore_ascii_case("display") {
+            match second {
+                Some(word) if word.eq_ignore_ascii_case("get") => Command::DisplayGet,
+                Some(word) if word.eq_ignore_ascii_case("12h") => {
+                    Command::SetHourFormat(HourFormat::H12)
+                }
+                Some(word) if word.eq_ignore_ascii_case("24h") => {
+                    Command::SetHourFormat(HourFormat::H24)
+                }
+                Some(word) if word.eq_ignore_ascii_case("brightness") => {
+                    let argument = words.next().ok_or(ParseError::MissingArgument)?;
+                    match argument.parse() {
+                        Ok(level) if level < BRIGHTNESS_LEVELS => Command::SetBrightness(level),
+                        _ => return Err(ParseError::InvalidValue),
+                    }
+                }
+                Some(_) => return Err(ParseError::UnknownCommand),
+                None => return Err(ParseError::MissingArgument),
+            }
+        } else {
+            return Err(ParseError::UnknownCommand);
+        };
+
+        match words.next() {
+            Some(_) => Err(ParseError::UnexpectedArgument),
+            None => Ok(command),
+        }
+    }
+
+    /// 解析`time set`的时间参数
+    fn parse_time(argument: &str) -> Result<Self, ParseError> {
+        // 日期部分之后出现`Z`、`+`或`-`说明带有UTC偏移
+        let has_offset = argument
+            .get(10..)
+            .is_some_and(|time| time.contains(['Z', 'z', '+', '-']));
+
+        if has_offset {
+            DateTime::<FixedOffset>::parse_from_rfc3339(argument)
+                .map(|time| Command::TimeSetUtc(time.naive_utc()))
+        } else {
+            argument.parse().map(Command::TimeSetLocal)
+        }
+        .map_err(|_| ParseError::InvalidDateTime)
+    }
+
+    /// 在应用上执行命令
+    ///
+    /// # 返回值
+    /// 设置时间时返回新的UTC时间，调用方应将其写入时间源
+    pub fn execute(&self, app: &mut CalendarApp) -> Option<NaiveDateTime> {
+        let utc = match *self {
+            Command::TimeSetLocal(local) => app.timezone().to_utc(local),
+            Command::TimeSetUtc(utc) => utc,
+            Command::SetHourFormat(format) => {
+                app.set_hour_format(format);
+                return None;
+            }
+            Command::SetBrightness(level) => {
+                app.set_brightness(level);
+                return None;
+            }
+            Command::Help | Command::TimeGet | Command::AlarmList | Command::DisplayGet => {
+                return None;
+            }
+        };
+        app.set_utc(utc);
+        Some(utc)
+    }
+
+    /// 写出命令执行后的响应，以结果行`OK`结束
+    pub fn respond<W: Write>(&self, app: &CalendarApp, out: &mut W) -> fmt::Result {
+        match self {
+            Command::Help => out.write_str(HELP)?,
+            Command::TimeGet | Command::TimeSetLocal(_) | Command::TimeSetUtc(_) => {
+                write_time(app, out)?
+            }
+            Command::AlarmList => {
+                for index in 0..app.alarms().len() {
+                    write_alarm(index, app.alarms().alarm(index), out)?;
+                }
+            }
+            Command::DisplayGet | Command::SetHourFormat(_) | Command::SetBrightness(_) => {
+                let format = match app.hour_format() {
+                    HourFormat::H24 => "24h",
+                    HourFormat::H12 => "12h",
+                };
+                write!(out, "{format} brightness {}\r\n", app.brightness())?
+            }
+        }
+        out.write_str("OK\r\n")
+    }
+}
+
+/// 写出解析失败的响应
+pub fn respond_error<W: Write>(error: ParseError, out: &mut W) -> fmt::Result {
+    write!(out, "ERR {error}\r\n")
+}
+
+/// 写出当地时间、UTC偏移和时区缩写
+fn write_time<W: Write>(app: &CalendarApp, out: &mut W) -> fmt::Result {
+    let utc = app.utc();
+    let local = app.timezone().to_local(utc);
+    let offset = app.timezone().offset_at(utc);
+    let sign = if offset < 0 { '-' } else { '+' };
+    write!(
+        out,
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02} {}\r\n",
+        local.year(),
+        local.month(),
+        local.day(),
+        local.hour(),
+        local.minute(),
+        local.second(),
+        sign,
+        offset.unsigned_abs() / 3600,
+        offset.unsigned_abs() % 3600 / 60,
+        app.timezone().abbreviation_at(utc)
+    )
+}
+
+/// 写出一个闹钟，如`1 07:30 on weekly MTWTF-- snooze 5m`
+fn write_alarm<W: Write>(index: usize, alarm: &Alarm, out: &mut W) -> fmt::Result {
+    const DAYS: [(Weekday, char); 7] = [
+        (Weekday::Mon, 'M'),
+        (Weekday::Tue, 'T'),
+        (Weekday::Wed, 'W'),
+        (Weekday::Thu, 'T'),
+        (Weekday::Fri, 'F'),
+        (Weekday::Sat, 'S'),
+        (Weekday::Sun, 'S'),
+    ];
+
+    write!(
+        out,
+        "{} {:02}:{:02} {} {} ",
+        index + 1,
+        alarm.time.hour(),
+        alarm.time.minute(),
+        if alarm.enabled { "on" } else { "off" },
+        match alarm.repeat {
+            Repeat::Once => "once",
+            Repeat::Weekly => "weekly",
+        }
+    )?;
+    for (day, letter) in DAYS {
+        out.write_char(if alarm.weekdays.contains(day) {
+            letter
+        } else {
+            '-'
+        })?;
+    }
+    write!(out, " snooze {}m\r\n", alarm.snooze_minutes)
+}
+
+/// 命令行缓冲区
+///
+/// 逐字节接收串口数据并按行切分，支持退格删除，忽略空行。
+#[derive(Debug)]
+pub struct LineBuffer {
+    buf: [u8; LINE_CAPACITY],
+    len: usize,
+    /// 当前行是否已超长，超长部分被丢弃直到行尾
+    overflow: bool,
+}
+
+impl Default for LineBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineBuffer {
+    /// 创建空缓冲区
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; LINE_CAPACITY],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// 输入一个字节
+    ///
+    /// # 返回值
+    /// 收到行尾时返回完整的一行（不含行尾），超长或编码错误时返回对应错误；
+    /// 其余情况返回`None`
+    pub fn push(&mut self, byte: u8) -> Option<Result<&str, ParseError>> {
+        match byte {
+            b'\r' | b'\n' => {
+                let len = core::mem::take(&mut self.len);
+                if core::mem::take(&mut self.overflow) {
+                    return Some(Err(ParseError::LineTooLong));
+                }
+                if len == 0 {
+                    return None;
+                }
+                Some(str::from_utf8(&self.buf[..len]).map_err(|_| ParseError::InvalidEncoding))
+            }
+            // 退格（BS/DEL）
+            0x08 | 0x7f => {
+                self.len = self.len.saturating_sub(1);
+                None
+            }
+            _ if self.len < LINE_CAPACITY => {
+                self.buf[self.len] = byte;
+                self.len += 1;
+                None
+            }
+            _ => {
+                self.overflow = true;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::alarm::Weekdays;
+    use crate::app::timezone::TimeZone;
+    use chrono::{NaiveDate, NaiveTime};
+    use heapless::String;
+
+    fn at(h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, 20)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    fn respond(command: Command, app: &CalendarApp) -> String<256> {
+        let mut out = String::new();
+        command.respond(app, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Command::parse("time get"), Ok(Command::TimeGet));
+        assert_eq!(Command::parse("  TIME   Get "), Ok(Command::TimeGet));
+        assert_eq!(Command::parse("alarm list"), Ok(Command::AlarmList));
+        assert_eq!(Command::parse("help"), Ok(Command::Help));
+        assert_eq!(
+            Command::parse("time set 2025-07-20T18:00:00"),
+            Ok(Command::TimeSetLocal(at(18, 0, 0)))
+        );
+        assert_eq!(
+            Command::parse("time set 2025-07-20T10:00:00Z"),
+            Ok(Command::TimeSetUtc(at(10, 0, 0)))
+        );
+        assert_eq!(
+            Command::parse("time set 2025-07-20T18:00:00+08:00"),
+            Ok(Command::TimeSetUtc(at(10, 0, 0)))
+        );
+        assert_eq!(
+            Command::parse("time set 2025-07-20T05:30:00-04:30"),
+            Ok(Command::TimeSetUtc(at(10, 0, 0)))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_commands() {
+        assert_eq!(Command::parse(""), Err(ParseError::Empty));
+        assert_eq!(Command::parse("   "), Err(ParseError::Empty));
+        assert_eq!(Command::parse("date"), Err(ParseError::UnknownCommand));
+        assert_eq!(Command::parse("time"), Err(ParseError::MissingArgument));
+        assert_eq!(Command::parse("time now"), Err(ParseError::UnknownCommand));
+        assert_eq!(Command::parse("time set"), Err(ParseError::MissingArgument));
+        assert_eq!(Command::parse("alarm"), Err(ParseError::MissingArgument));
+        assert_eq!(Command::parse("alarm add"), Err(ParseError::UnknownCommand));
+        assert_eq!(
+            Command::parse("help me"),
+            Err(ParseError::UnexpectedArgument)
+        );
+        assert_eq!(
+            Command::parse("time get now"),
+            Err(ParseError::UnexpectedArgument)
+        );
+        for argument in [
+            "2025-07-20",
+            "2025-13-20T18:00:00",
+            "2025-02-29T18:00:00",
+            "2025-07-20T24:00:00",
+            "2025-07-20T18:00:00+25:00",
+            "18:00:00",
+        ] {
+            let mut line: String<64> = String::new();
+            write!(&mut line, "time set {argument}").unwrap();
+            assert_eq!(
+                Command::parse(&line),
+                Err(ParseError::InvalidDateTime),
+                "{argument}"
+            );
+        }
+    }
+
+    #[test]
+    fn time_set_updates_app_and_reports_utc() {
+        let mut app = CalendarApp::new(at(0, 0, 0));
+
+        // 默认时区为北京时间
+        let command = Command::parse("time set 2025-07-20T18:00:00").unwrap();
+        assert_eq!(command.execute(&mut app), Some(at(10, 0, 0)));
+        assert_eq!(app.utc(), at(10, 0, 0));
+        assert_eq!(app.clock().now(), at(18, 0, 0));
+        assert_eq!(
+            respond(command, &app),
+            "2025-07-20T18:00:00+08:00 CST\r\nOK\r\n"
+        );
+
+        let command = Command::parse("time set 2025-07-20T12:34:56Z").unwrap();
+        assert_eq!(command.execute(&mut app), Some(at(12, 34, 56)));
+        assert_eq!(app.clock().now(), at(20, 34, 56));
+    }
+
+    #[test]
+    fn time_get_reports_local_time_with_offset() {
+        let mut app = CalendarApp::new(at(10, 0, 0));
+        app.set_timezone(TimeZone::parse("EST5EDT,M3.2.0,M11.1.0").unwrap(), None);
+        assert_eq!(Command::TimeGet.execute(&mut app), None);
+        assert_eq!(
+            respond(Command::TimeGet, &app),
+            "2025-07-20T06:00:00-04:00 EDT\r\nOK\r\n"
+        );
+    }
+
+    #[test]
+    fn alarm_list_reports_every_alarm() {
+        let app = CalendarApp::new(at(10, 0, 0));
+        let out = respond(Command::AlarmList, &app);
+        assert_eq!(out.lines().count(), app.alarms().len() + 1);
+        assert_eq!(
+            out.lines().next(),
+            Some("1 07:00 off weekly MTWTFSS snooze 5m")
+        );
+        assert_eq!(out.lines().last(), Some("OK"));
+
+        let mut out: String<64> = String::new();
+        let alarm = Alarm {
+            weekdays: Weekdays::WORKDAYS,
+            repeat: Repeat::Once,
+            snooze_minutes: 10,
+            ..Alarm::new(NaiveTime::from_hms_opt(6, 45, 0).unwrap())
+        };
+        write_alarm(2, &alarm, &mut out).unwrap();
+        assert_eq!(out, "3 06:45 on once MTWTF-- snooze 10m\r\n");
+    }
+
+    #[test]
+    fn display_commands_update_preferences() {
+        let mut app = CalendarApp::new(at(10, 0, 0));
+        assert_eq!(
+            respond(Command::parse("display get").unwrap(), &app),
+            "24h brightness 2\r\nOK\r\n"
+        );
+
+        let command = Command::parse("Display 12H").unwrap();
+        assert_eq!(command, Command::SetHourFormat(HourFormat::H12));
+        assert_eq!(command.execute(&mut app), None);
+        let command = Command::parse("display brightness 4").unwrap();
+        assert_eq!(command.execute(&mut app), None);
+        assert_eq!(respond(command, &app), "12h brightness 4\r\nOK\r\n");
+
+        assert_eq!(
+            Command::parse("display brightness 5"),
+            Err(ParseError::InvalidValue)
+        );
+        assert_eq!(
+            Command::parse("display brightness -1"),
+            Err(ParseError::InvalidValue)
+        );
+        assert_eq!(
+            Command::parse("display brightness"),
+            Err(ParseError::MissingArgument)
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let mut out: String<64> = String::new();
+        respond_error(ParseError::UnknownCommand, &mut out).unwrap();
+        assert_eq!(out, "ERR unknown command, try help\r\n");
+    }
+
+    #[test]
+    fn line_buffer_splits_lines() {
+        let mut buffer = LineBuffer::new();
+        let mut lines: heapless::Vec<Result<String<64>, ParseError>, 8> = heapless::Vec::new();
+        for &byte in b"time get\r\n\r\nalarm  lisx\x08t\ntime\xff\n" {
+            if let Some(line) = buffer.push(byte) {
+                lines
+                    .push(line.map(|line| String::try_from(line).unwrap()))
+                    .unwrap();
+            }
+        }
+        assert_eq!(
+            lines,
+            [
+                Ok(String::try_from("time get").unwrap()),
+                Ok(String::try_from("alarm  list").unwrap()),
+                Err(ParseError::InvalidEncoding),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_buffer_rejects_long_lines() {
+        let mut buffer = LineBuffer::new();
+        for _ in 0..LINE_CAPACITY + 10 {
+            assert_eq!(buffer.push(b'a'), None);
+        }
+        assert_eq!(buffer.push(b'\n'), Some(Err(ParseError::LineTooLong)));
+
+        // 超长后下一行恢复正常
+        for &byte in b"help" {
+            assert_eq!(buffer.push(byte), None);
+        }
+        assert_eq!(buffer.push(b'\r'), Some(Ok("help")));
+    }
+}
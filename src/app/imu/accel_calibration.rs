@@ -0,0 +1,304 @@
+//! 加速度计六面校准
+//!
+//! 依次将电路板的六个面朝上静置，每个面朝上时加速度计应测得沿对应坐标轴的±1g。
+//! 以最小二乘法拟合线性模型
+//!
+//! `a = T·(r - b)`
+//!
+//! 其中`r`为未校准读数，`b`为各轴零偏，`T`同时包含各轴灵敏度误差和轴间不正交（交叉耦合）误差。
+//! 与只求平均值的零偏校准不同，六面校准不要求电路板水平放置，也能修正灵敏度误差。
+
+use super::units::Acceleration;
+use nalgebra::{Matrix3, Matrix4, Matrix4x3, Vector3, Vector4};
+
+/// 判断朝上面时，主轴读数的最小值（g）
+const FACE_MAJOR_MIN: f32 = 0.8;
+
+/// 判断朝上面时，其余两轴读数的最大值（g）
+const FACE_MINOR_MAX: f32 = 0.3;
+
+/// 电路板朝上的面，以指向上方的传感器坐标轴表示
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Face {
+    /// Z轴向上（元件面朝上，水平放置）
+    ZUp,
+    /// Z轴向下
+    ZDown,
+    /// Y轴向上
+    YUp,
+    /// Y轴向下
+    YDown,
+    /// X轴向上
+    XUp,
+    /// X轴向下
+    XDown,
+}
+
+impl Face {
+    /// 校准时的放置顺序
+    pub const ALL: [Self; 6] = [
+        Self::ZUp,
+        Self::ZDown,
+        Self::YUp,
+        Self::YDown,
+        Self::XUp,
+        Self::XDown,
+    ];
+
+    /// 该面朝上静置时加速度计的理想读数（g）
+    pub fn gravity(self) -> Vector3<f32> {
+        match self {
+            Self::ZUp => Vector3::z(),
+            Self::ZDown => -Vector3::z(),
+            Self::YUp => Vector3::y(),
+            Self::YDown => -Vector3::y(),
+            Self::XUp => Vector3::x(),
+            Self::XDown => -Vector3::x(),
+        }
+    }
+
+    /// 显示用名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ZUp => "Z+ UP",
+            Self::ZDown => "Z- UP",
+            Self::YUp => "Y+ UP",
+            Self::YDown => "Y- UP",
+            Self::XUp => "X+ UP",
+            Self::XDown => "X- UP",
+        }
+    }
+
+    /// 根据加速度读数判断朝上的面
+    ///
+    /// # 返回值
+    /// 读数不接近任何一个面（电路板倾斜）时返回`None`
+    pub fn detect(accel: &Acceleration) -> Option<Self> {
+        Self::ALL.into_iter().find(|face| {
+            let gravity = face.gravity();
+            let major = accel.as_g().dot(&gravity);
+            let minor = accel.as_g() - gravity * major;
+            major >= FACE_MAJOR_MIN && minor.amax() <= FACE_MINOR_MAX
+        })
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 加速度计校准参数，校准后的读数为`transform·(raw - bias)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccelCalibration {
+    /// 各轴零偏（g）
+    pub bias: Vector3<f32>,
+    /// 灵敏度和交叉耦合修正矩阵
+    pub transform: Matrix3<f32>,
+}
+
+impl Default for AccelCalibration {
+    fn default() -> Self {
+        Self::from_bias(Vector3::zeros())
+    }
+}
+
+impl AccelCalibration {
+    /// 只修正零偏
+    pub fn from_bias(bias: Vector3<f32>) -> Self {
+        Self {
+            bias,
+            transform: Matrix3::identity(),
+        }
+    }
+
+    /// 校准读数
+    pub fn apply(&self, raw: Acceleration) -> Acceleration {
+        Acceleration::from_g(self.transform * (raw.as_g() - self.bias))
+    }
+
+    /// 由读数和对应的理想读数拟合校准参数
+    ///
+    /// 对每个输出轴求解最小二乘问题 `ideal = A·raw + c`，再换算为`transform = A`、
+    /// `bias = -A⁻¹·c`。至少需要4个不共面的读数。
+    ///
+    /// # 参数
+    /// - `samples`: (未校准读数, 理想读数) 对，单位g
+    ///
+    /// # 返回值
+    /// 读数不足或共面时返回`None`
+    pub fn fit(samples: impl IntoIterator<Item = (Vector3<f32>, Vector3<f32>)>) -> Option<Self> {
+        // 正规方程 XᵀX·θ = XᵀY，X的每行为 [rawᵀ, 1]，Y的每行为 idealᵀ
+        let mut normal = Matrix4::zeros();
+        let mut rhs = Matrix4x3::zeros();
+        for (raw, ideal) in samples {
+            let row = Vector4::new(raw.x, raw.y, raw.z, 1.0);
+            normal += row * row.transpose();
+            rhs += row * ideal.transpose();
+        }
+
+        let solution = normal.cholesky()?.solve(&rhs);
+        let transform = solution.fixed_rows::<3>(0).transpose();
+        let offset = solution.row(3).transpose();
+        let bias = -(transform.try_inverse()? * offset);
+        Some(Self { bias, transform })
+    }
+}
+
+/// 六面校准失败的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationError {
+    /// 缺少该面的读数
+    MissingFace(Face),
+    /// 读数无法确定校准参数（如多个面的读数相同）
+    Singular,
+}
+
+/// 六面校准的读数收集
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SixFaceCalibration {
+    /// 各面朝上静置时的平均读数（未校准）
+    readings: [Option<Vector3<f32>>; 6],
+}
+
+impl SixFaceCalibration {
+    /// 创建空的校准过程
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录某个面朝上时的平均读数，重复记录时覆盖
+    pub fn record(&mut self, face: Face, mean: Acceleration) {
+        self.readings[face.index()] = Some(*mean.as_g());
+    }
+
+    /// 某个面的读数
+    pub fn reading(&self, face: Face) -> Option<&Vector3<f32>> {
+        self.readings[face.index()].as_ref()
+    }
+
+    /// 按放置顺序的下一个尚未记录的面，全部记录后返回`None`
+    pub fn next_face(&self) -> Option<Face> {
+        Face::ALL
+            .into_iter()
+            .find(|face| self.reading(*face).is_none())
+    }
+
+    /// 已记录的面数
+    pub fn recorded(&self) -> usize {
+        self.readings.iter().flatten().count()
+    }
+
+    /// 求解校准参数
+    pub fn solve(&self) -> Result<AccelCalibration, CalibrationError> {
+        if let Some(face) = self.next_face() {
+            return Err(CalibrationError::MissingFace(face));
+        }
+        AccelCalibration::fit(
+            Face::ALL
+                .into_iter()
+                .filter_map(|face| Some((*self.reading(face)?, face.gravity()))),
+        )
+        .ok_or(CalibrationError::Singular)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::RowVector3;
+
+    /// 模拟传感器的真实误差：零偏、灵敏度和交叉耦合
+    fn sensor() -> AccelCalibration {
+        AccelCalibration {
+            bias: Vector3::new(0.06, -0.025, 0.08),
+            transform: Matrix3::from_rows(&[
+                RowVector3::new(1.03, 0.012, -0.008),
+                RowVector3::new(-0.006, 0.97, 0.015),
+                RowVector3::new(0.01, -0.004, 1.05),
+            ]),
+        }
+    }
+
+    /// 理想读数对应的未校准读数，叠加确定的小噪声
+    fn raw(sensor: &AccelCalibration, ideal: Vector3<f32>, noise: f32) -> Acceleration {
+        let raw = sensor.transform.try_inverse().unwrap() * ideal + sensor.bias;
+        Acceleration::from_g(raw + Vector3::new(noise, -noise, noise * 0.5))
+    }
+
+    fn calibrate(sensor: &AccelCalibration) -> SixFaceCalibration {
+        let mut calibration = SixFaceCalibration::new();
+        for (index, face) in Face::ALL.into_iter().enumerate() {
+            let noise = if index % 2 == 0 { 0.001 } else { -0.001 };
+            calibration.record(face, raw(sensor, face.gravity(), noise));
+        }
+        calibration
+    }
+
+    #[test]
+    fn recovers_bias_scale_and_misalignment() {
+        let sensor = sensor();
+        let result = calibrate(&sensor).solve().unwrap();
+
+        assert!((result.bias - sensor.bias).amax() < 0.003, "{result:?}");
+        assert!((result.transform - sensor.transform).amax() < 0.003);
+
+        // 任意倾斜姿态下校准后的读数为1g
+        let tilted = Vector3::new(0.3, -0.5, 0.8).normalize();
+        let corrected = result.apply(raw(&sensor, tilted, 0.0));
+        assert!((corrected.as_g() - tilted).amax() < 0.003);
+    }
+
+    #[test]
+    fn exact_readings_fit_exactly() {
+        let sensor = sensor();
+        let samples = Face::ALL
+            .into_iter()
+            .map(|face| (*raw(&sensor, face.gravity(), 0.0).as_g(), face.gravity()));
+        let result = AccelCalibration::fit(samples).unwrap();
+        assert!((result.bias - sensor.bias).amax() < 1e-4);
+        assert!((result.transform - sensor.transform).amax() < 1e-4);
+    }
+
+    #[test]
+    fn requires_all_faces() {
+        let mut calibration = SixFaceCalibration::new();
+        assert_eq!(calibration.next_face(), Some(Face::ZUp));
+        assert_eq!(
+            calibration.solve(),
+            Err(CalibrationError::MissingFace(Face::ZUp))
+        );
+
+        let sensor = sensor();
+        for face in &Face::ALL[..5] {
+            calibration.record(*face, raw(&sensor, face.gravity(), 0.0));
+        }
+        assert_eq!(calibration.recorded(), 5);
+        assert_eq!(calibration.next_face(), Some(Face::XDown));
+        assert_eq!(
+            calibration.solve(),
+            Err(CalibrationError::MissingFace(Face::XDown))
+        );
+    }
+
+    #[test]
+    fn identical_readings_are_singular() {
+        let mut calibration = SixFaceCalibration::new();
+        for face in Face::ALL {
+            // 电路板始终水平放置
+            calibration.record(face, Acceleration::from_g(Vector3::z()));
+        }
+        assert_eq!(calibration.solve(), Err(CalibrationError::Singular));
+    }
+
+    #[test]
+    fn detects_face_up() {
+        let sensor = sensor();
+        for face in Face::ALL {
+            assert_eq!(Face::detect(&raw(&sensor, face.gravity(), 0.0)), Some(face));
+        }
+
+        // 倾斜45°时无法判断
+        let tilted = Acceleration::from_g(Vector3::new(0.0, 0.707, 0.707));
+        assert_eq!(Face::detect(&tilted), None);
+    }
+}
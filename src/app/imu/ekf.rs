@@ -0,0 +1,280 @@
+//! 扩展卡尔曼滤波（EKF）姿态估计
+//!
+//! 状态为姿态四元数和陀螺仪零偏。四元数以误差状态形式参与滤波（乘性EKF）：
+//! 协方差只描述3维姿态误差和3维零偏误差，避免四元数单位约束导致协方差奇异。
+//!
+//! - 预测：用减去零偏估计的陀螺仪角速度积分姿态
+//! - 修正：用加速度计测得的重力方向修正姿态和零偏
+//!
+//! 零偏沿重力方向的分量只有在传感器姿态变化后才可观测，
+//! 因此设备静止水平放置时航向零偏不会收敛，转动后才能学到。
+
+use super::filter::OrientationFilter;
+use ahrs::AhrsError;
+use nalgebra::{Matrix3, Matrix3x6, Matrix6, UnitQuaternion, Vector3, Vector6};
+
+/// 初始姿态误差方差（rad²），约40°标准差，从水平姿态出发也能收敛
+const INITIAL_ATTITUDE_VARIANCE: f32 = 0.5;
+
+/// 初始零偏误差方差（(rad/s)²），约5.7°/s标准差
+const INITIAL_BIAS_VARIANCE: f32 = 0.01;
+
+/// 滤波器噪声参数
+///
+/// 测量噪声越大越依赖陀螺仪积分，运动时受线加速度影响越小，但收敛越慢。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EkfNoise {
+    /// 陀螺仪噪声标准差（rad/s）
+    pub gyro: f32,
+    /// 陀螺仪零偏随机游走（rad/s/√s）
+    pub gyro_bias: f32,
+    /// 加速度计噪声标准差（g），包含运动产生的线加速度
+    pub accel: f32,
+}
+
+impl Default for EkfNoise {
+    fn default() -> Self {
+        Self {
+            gyro: 0.01,
+            gyro_bias: 0.001,
+            accel: 0.05,
+        }
+    }
+}
+
+/// 带陀螺仪零偏估计的扩展卡尔曼滤波器
+#[derive(Clone, Copy, Debug)]
+pub struct ExtendedKalmanFilter {
+    /// 采样周期（秒）
+    sample_period: f32,
+    /// 噪声参数
+    noise: EkfNoise,
+    /// 姿态估计
+    quat: UnitQuaternion<f32>,
+    /// 陀螺仪零偏估计（rad/s）
+    bias: Vector3<f32>,
+    /// 误差状态协方差：前3维为姿态误差（传感器坐标系，rad），后3维为零偏误差（rad/s）
+    covariance: Matrix6<f32>,
+}
+
+impl ExtendedKalmanFilter {
+    /// 创建滤波器，初始姿态为水平、零偏为零
+    ///
+    /// # 参数
+    /// - `sample_period`: 采样周期（秒）
+    /// - `noise`: 噪声参数
+    pub fn new(sample_period: f32, noise: EkfNoise) -> Self {
+        Self {
+            sample_period,
+            noise,
+            quat: UnitQuaternion::identity(),
+            bias: Vector3::zeros(),
+            covariance: Matrix6::from_diagonal(&Vector6::new(
+                INITIAL_ATTITUDE_VARIANCE,
+                INITIAL_ATTITUDE_VARIANCE,
+                INITIAL_ATTITUDE_VARIANCE,
+                INITIAL_BIAS_VARIANCE,
+                INITIAL_BIAS_VARIANCE,
+                INITIAL_BIAS_VARIANCE,
+            )),
+        }
+    }
+
+    /// 陀螺仪零偏估计（rad/s）
+    pub fn bias(&self) -> &Vector3<f32> {
+        &self.bias
+    }
+
+    /// 噪声参数
+    pub fn noise(&self) -> &EkfNoise {
+        &self.noise
+    }
+
+    /// 修改噪声参数，已有的估计和协方差保持不变
+    pub fn set_noise(&mut self, noise: EkfNoise) {
+        self.noise = noise;
+    }
+
+    /// 预测：积分扣除零偏后的角速度，传播误差协方差
+    fn predict(&mut self, gyro: &Vector3<f32>) {
+        let dt = self.sample_period;
+        let rotation = (gyro - self.bias) * dt;
+        self.quat *= UnitQuaternion::from_scaled_axis(rotation);
+
+        // 姿态误差随本次旋转转到新的传感器坐标系，零偏误差使姿态误差按 -dt 累积
+        let mut transition = Matrix6::identity();
+        transition.fixed_view_mut::<3, 3>(0, 0).copy_from(
+            UnitQuaternion::from_scaled_axis(-rotation)
+                .to_rotation_matrix()
+                .matrix(),
+        );
+        transition
+            .fixed_view_mut::<3, 3>(0, 3)
+            .copy_from(&(Matrix3::identity() * -dt));
+
+        let attitude_noise = self.noise.gyro * self.noise.gyro * dt * dt;
+        let bias_noise = self.noise.gyro_bias * self.noise.gyro_bias * dt;
+        let process_noise = Matrix6::from_diagonal(&Vector6::new(
+            attitude_noise,
+            attitude_noise,
+            attitude_noise,
+            bias_noise,
+            bias_noise,
+            bias_noise,
+        ));
+
+        self.covariance = transition * self.covariance * transition.transpose() + process_noise;
+    }
+
+    /// 修正：比较测得与估计的重力方向（单位向量），更新姿态和零偏
+    fn correct(&mut self, measured: &Vector3<f32>) {
+        let predicted = self.quat.inverse() * Vector3::z();
+
+        // 姿态误差δθ使估计的重力方向变为 predicted + predicted × δθ
+        let mut observation = Matrix3x6::zeros();
+        observation
+            .fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&predicted.cross_matrix());
+
+        let measurement_noise = Matrix3::from_diagonal_element(self.noise.accel * self.noise.accel);
+        let innovation_covariance =
+            observation * self.covariance * observation.transpose() + measurement_noise;
+        let Some(inverse) = innovation_covariance.try_inverse() else {
+            return;
+        };
+        let gain = self.covariance * observation.transpose() * inverse;
+
+        let correction = gain * (measured - predicted);
+        self.quat *= UnitQuaternion::from_scaled_axis(correction.fixed_rows::<3>(0).into_owned());
+        self.bias += correction.fixed_rows::<3>(3);
+
+        // Joseph形式，保持协方差对称正定
+        let residual = Matrix6::identity() - gain * observation;
+        self.covariance = residual * self.covariance * residual.transpose()
+            + gain * measurement_noise * gain.transpose();
+    }
+}
+
+impl OrientationFilter for ExtendedKalmanFilter {
+    fn update_imu(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        let measured = accel
+            .try_normalize(0.0)
+            .ok_or(AhrsError::AccelerometerNormZero)?;
+
+        self.predict(gyro);
+        self.correct(&measured);
+        Ok(&self.quat)
+    }
+
+    fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.quat
+    }
+
+    fn set_sample_period(&mut self, sample_period: f32) {
+        self.sample_period = sample_period;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::simulation::Simulator;
+    use core::f32::consts::FRAC_PI_4;
+
+    const SAMPLE_PERIOD: f32 = 0.01;
+
+    /// 注入的陀螺仪零偏（rad/s），约1-2°/s
+    const BIAS: Vector3<f32> = Vector3::new(0.02, -0.03, 0.015);
+
+    fn filter() -> ExtendedKalmanFilter {
+        ExtendedKalmanFilter::new(SAMPLE_PERIOD, EkfNoise::default())
+    }
+
+    fn run(
+        filter: &mut ExtendedKalmanFilter,
+        sim: &mut Simulator,
+        rate: Vector3<f32>,
+        samples: usize,
+    ) {
+        for _ in 0..samples {
+            let sample = sim.step(rate);
+            filter.update_imu(&sample.gyro, &sample.accel).unwrap();
+        }
+    }
+
+    /// 依次绕X、Y轴来回倾斜45°并停留，使各轴零偏都可观测
+    fn tilt_around(filter: &mut ExtendedKalmanFilter, sim: &mut Simulator, rounds: usize) {
+        for _ in 0..rounds {
+            for rate in [Vector3::x(), Vector3::y(), -Vector3::x(), -Vector3::y()] {
+                run(filter, sim, rate * FRAC_PI_4, 100);
+                run(filter, sim, Vector3::zeros(), 200);
+            }
+        }
+    }
+
+    #[test]
+    fn learns_injected_gyro_bias() {
+        let mut filter = filter();
+        let mut sim = Simulator::new(UnitQuaternion::identity());
+        sim.gyro_bias = BIAS;
+
+        tilt_around(&mut filter, &mut sim, 5);
+
+        let error = filter.bias() - BIAS;
+        assert!(error.amax() < 0.002, "{:?}", filter.bias());
+        assert!(sim.tilt_error(filter.quaternion()) < 1.0);
+    }
+
+    #[test]
+    fn learned_bias_stops_heading_drift() {
+        let mut filter = filter();
+        let mut sim = Simulator::new(UnitQuaternion::identity());
+        sim.gyro_bias = BIAS;
+        tilt_around(&mut filter, &mut sim, 5);
+
+        // 静止10秒：未补偿的Z轴零偏会使航向漂移约8.6°
+        let start = *filter.quaternion();
+        let truth = sim.attitude;
+        run(&mut filter, &mut sim, Vector3::zeros(), 1000);
+
+        let estimated = start.angle_to(filter.quaternion()).to_degrees();
+        let actual = truth.angle_to(&sim.attitude).to_degrees();
+        assert!(actual < 1e-3);
+        assert!(estimated < 0.5, "{estimated}°");
+    }
+
+    #[test]
+    fn converges_to_static_tilt_with_bias() {
+        let mut filter = filter();
+        let mut sim = Simulator::new(UnitQuaternion::from_euler_angles(
+            30f32.to_radians(),
+            -20f32.to_radians(),
+            0.0,
+        ));
+        sim.gyro_bias = BIAS;
+
+        run(&mut filter, &mut sim, Vector3::zeros(), 2000);
+        assert!(sim.tilt_error(filter.quaternion()) < 1.0);
+
+        // 垂直于重力方向的零偏分量在静止时即可观测
+        let up = sim.attitude.inverse() * Vector3::z();
+        let error = filter.bias() - BIAS;
+        let horizontal_error = error - up * up.dot(&error);
+        assert!(horizontal_error.amax() < 0.002, "{:?}", filter.bias());
+    }
+
+    #[test]
+    fn covariance_stays_symmetric() {
+        let mut filter = filter();
+        let mut sim = Simulator::new(UnitQuaternion::identity());
+        tilt_around(&mut filter, &mut sim, 1);
+
+        let asymmetry = filter.covariance - filter.covariance.transpose();
+        assert!(asymmetry.amax() < 1e-6);
+        assert!(filter.covariance.diagonal().iter().all(|&v| v > 0.0));
+    }
+}
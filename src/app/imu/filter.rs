@@ -0,0 +1,359 @@
+//! 姿态滤波器
+//!
+//! [`OrientationFilter`]统一了不同的陀螺仪/加速度计融合算法，
+//! 使姿态解算器可以在构造时选择滤波器，便于在同一硬件上比较效果：
+//! - [`Madgwick`]：梯度下降法，`beta`越大收敛越快、噪声越大
+//! - [`Mahony`]：比例积分（PI）反馈，积分项可补偿陀螺仪零偏
+//! - [`ComplementaryFilter`]：陀螺仪积分加按固定比例向加速度计方向修正，计算量最小
+//!
+//! 姿态四元数将传感器坐标系中的向量旋转到地球坐标系。只使用陀螺仪和加速度计时
+//! 航向角不可观测，只有横滚角和俯仰角会收敛；加入磁力计（[`OrientationFilter::update_marg`]）后
+//! 地球坐标系的X轴指向磁北，Z轴向上，航向角也会收敛。
+
+use ahrs::{Ahrs, AhrsError};
+use nalgebra::{UnitQuaternion, Vector3};
+
+pub use ahrs::{Madgwick, Mahony};
+
+/// 姿态滤波器
+pub trait OrientationFilter {
+    /// 融合一次采样，更新姿态
+    ///
+    /// # 参数
+    /// - `gyro`: 陀螺仪角速度（rad/s）
+    /// - `accel`: 加速度计读数（任意单位，只使用方向）
+    ///
+    /// # 返回值
+    /// 更新后的姿态；加速度为零向量时返回错误，姿态保持不变
+    fn update_imu(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError>;
+
+    /// 融合一次带磁力计读数的采样，更新包括航向在内的姿态
+    ///
+    /// 默认忽略磁力计读数，与[`update_imu`](Self::update_imu)相同。
+    ///
+    /// # 参数
+    /// - `gyro`: 陀螺仪角速度（rad/s）
+    /// - `accel`: 加速度计读数（任意单位，只使用方向）
+    /// - `mag`: 校准后的磁力计读数（任意单位，只使用方向）
+    ///
+    /// # 返回值
+    /// 更新后的姿态；加速度或磁场为零向量时返回错误，姿态保持不变
+    fn update_marg(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+        mag: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        let _ = mag;
+        self.update_imu(gyro, accel)
+    }
+
+    /// 当前姿态
+    fn quaternion(&self) -> &UnitQuaternion<f32>;
+
+    /// 修改采样周期（秒），之后的更新按新的周期积分
+    ///
+    /// 按传感器自身的采样时钟读取数据时，由解算器设置为传感器的实际采样周期。
+    fn set_sample_period(&mut self, sample_period: f32);
+}
+
+impl OrientationFilter for Madgwick<f32> {
+    fn update_imu(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        Ahrs::update_imu(self, gyro, accel)
+    }
+
+    fn update_marg(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+        mag: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        Ahrs::update(self, gyro, accel, mag)
+    }
+
+    fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.quat
+    }
+
+    fn set_sample_period(&mut self, sample_period: f32) {
+        *self.sample_period_mut() = sample_period;
+    }
+}
+
+impl OrientationFilter for Mahony<f32> {
+    fn update_imu(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        Ahrs::update_imu(self, gyro, accel)
+    }
+
+    fn update_marg(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+        mag: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        Ahrs::update(self, gyro, accel, mag)
+    }
+
+    fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.quat
+    }
+
+    fn set_sample_period(&mut self, sample_period: f32) {
+        *self.sample_period_mut() = sample_period;
+    }
+}
+
+/// 互补滤波器
+///
+/// 每次采样先积分陀螺仪角速度，再将估计的重力方向向加速度计测得的方向
+/// 旋转`gain`比例的夹角。有磁力计读数时，再绕竖直轴将磁场的水平分量向X轴
+/// 旋转`gain`比例的夹角，修正航向而不影响倾角。
+#[derive(Clone, Copy, Debug)]
+pub struct ComplementaryFilter {
+    /// 采样周期（秒）
+    sample_period: f32,
+    /// 每次采样的加速度计修正比例（0-1）
+    gain: f32,
+    quat: UnitQuaternion<f32>,
+}
+
+impl ComplementaryFilter {
+    /// 创建互补滤波器，初始姿态为水平
+    ///
+    /// # 参数
+    /// - `sample_period`: 采样周期（秒）
+    /// - `gain`: 每次采样的加速度计修正比例（0-1），时间常数约为`sample_period / gain`
+    pub fn new(sample_period: f32, gain: f32) -> Self {
+        Self {
+            sample_period,
+            gain: gain.clamp(0.0, 1.0),
+            quat: UnitQuaternion::identity(),
+        }
+    }
+}
+
+impl OrientationFilter for ComplementaryFilter {
+    fn update_imu(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        let measured = accel
+            .try_normalize(0.0)
+            .ok_or(AhrsError::AccelerometerNormZero)?;
+
+        // 陀螺仪积分（传感器坐标系中的旋转）
+        self.quat *= UnitQuaternion::from_scaled_axis(gyro * self.sample_period);
+
+        // 将估计的重力方向向测量方向修正：旋转后的姿态满足 q⁻¹·z 更接近测量值。
+        // 两者恰好相反时无法确定旋转轴，本次只做积分
+        let estimated = self.quat.inverse() * Vector3::z();
+        if let Some(correction) =
+            UnitQuaternion::scaled_rotation_between(&measured, &estimated, self.gain)
+        {
+            self.quat *= correction;
+        }
+
+        Ok(&self.quat)
+    }
+
+    fn update_marg(
+        &mut self,
+        gyro: &Vector3<f32>,
+        accel: &Vector3<f32>,
+        mag: &Vector3<f32>,
+    ) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        if mag.norm_squared() == 0.0 {
+            return Err(AhrsError::MagnetometerNormZero);
+        }
+        self.update_imu(gyro, accel)?;
+
+        // 地球坐标系中磁场的水平分量应指向X轴；磁场接近竖直或恰好指向-X时本次不修正航向
+        let field = self.quat * mag;
+        let horizontal = Vector3::new(field.x, field.y, 0.0);
+        if let Some(correction) =
+            UnitQuaternion::scaled_rotation_between(&horizontal, &Vector3::x(), self.gain)
+        {
+            self.quat = correction * self.quat;
+        }
+
+        Ok(&self.quat)
+    }
+
+    fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.quat
+    }
+
+    fn set_sample_period(&mut self, sample_period: f32) {
+        self.sample_period = sample_period;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::ekf::{EkfNoise, ExtendedKalmanFilter};
+    use crate::app::imu::recording::{Recording, synthetic};
+    use crate::app::imu::simulation::Simulator;
+    use core::f32::consts::FRAC_PI_2;
+
+    const SAMPLE_PERIOD: f32 = 0.01;
+
+    /// 放在斜面上静止20秒
+    ///
+    /// 由`tools/fixturegen/filters.py`的运动模型生成的模拟数据，不是传感器实测录制。
+    const TILT_REST: Recording = synthetic!("imu_tilt_rest.csv");
+
+    /// 水平静止后绕X轴转动90°并保持（模拟数据，来源同上）
+    const TURN_X: Recording = synthetic!("imu_turn_x.csv");
+
+    /// 以`rate`转动`samples`个采样周期，将读数送入滤波器
+    fn run(
+        filter: &mut dyn OrientationFilter,
+        sim: &mut Simulator,
+        rate: Vector3<f32>,
+        samples: usize,
+    ) {
+        for _ in 0..samples {
+            let sample = sim.step(rate);
+            filter.update_imu(&sample.gyro, &sample.accel).unwrap();
+        }
+    }
+
+    /// 将数据文件的`samples`个采样送入滤波器，陀螺仪读数减去开头静止段估计的零偏
+    fn replay(
+        filter: &mut dyn OrientationFilter,
+        recording: &Recording,
+        skip: usize,
+        samples: usize,
+    ) {
+        let offset = recording.gyro_offset();
+        for sample in recording.samples().skip(skip).take(samples) {
+            filter
+                .update_imu(&(sample.gyro - offset), &sample.accel)
+                .unwrap();
+        }
+    }
+
+    /// 对各滤波器分别运行同一测试，初始姿态均为水平
+    fn for_each_filter(test: fn(&str, &mut dyn OrientationFilter)) {
+        test("madgwick", &mut Madgwick::new(SAMPLE_PERIOD, 0.1));
+        test("mahony", &mut Mahony::new(SAMPLE_PERIOD, 1.0, 0.05));
+        test(
+            "complementary",
+            &mut ComplementaryFilter::new(SAMPLE_PERIOD, 0.02),
+        );
+        test(
+            "ekf",
+            &mut ExtendedKalmanFilter::new(SAMPLE_PERIOD, EkfNoise::default()),
+        );
+    }
+
+    #[test]
+    fn converges_to_static_tilt() {
+        for_each_filter(|name, filter| {
+            filter.set_sample_period(TILT_REST.sample_period());
+            assert!(TILT_REST.tilt_error(filter.quaternion()) > 30.0);
+
+            replay(filter, &TILT_REST, 0, usize::MAX);
+            let error = TILT_REST.tilt_error(filter.quaternion());
+            assert!(error < 1.0, "{name}: {error}°");
+        });
+    }
+
+    #[test]
+    fn tracks_rotation_from_gyro() {
+        for_each_filter(|name, filter| {
+            filter.set_sample_period(TURN_X.sample_period());
+
+            // 转动过程中加速度计只能缓慢修正，主要依靠陀螺仪
+            let settled = TURN_X.samples_in(TURN_X.value("settled"));
+            replay(filter, &TURN_X, 0, settled);
+            let error = TURN_X.tilt_error(filter.quaternion());
+            assert!(error < 5.0, "{name}: {error}°");
+
+            // 静止后收敛
+            replay(filter, &TURN_X, settled, usize::MAX);
+            let error = TURN_X.tilt_error(filter.quaternion());
+            assert!(error < 1.0, "{name}: {error}°");
+        });
+    }
+
+    #[test]
+    fn integrates_with_changed_sample_period() {
+        for_each_filter(|name, filter| {
+            // 实际采样周期为构造时的一半
+            filter.set_sample_period(SAMPLE_PERIOD / 2.0);
+            let mut sim = Simulator::new(UnitQuaternion::identity());
+            sim.sample_period = SAMPLE_PERIOD / 2.0;
+            run(filter, &mut sim, Vector3::x() * FRAC_PI_2, 200);
+            let error = sim.tilt_error(filter.quaternion());
+            assert!(error < 5.0, "{name}: {error}°");
+        });
+    }
+
+    #[test]
+    fn converges_to_magnetic_heading() {
+        let filters: [(&str, &mut dyn OrientationFilter); 3] = [
+            ("madgwick", &mut Madgwick::new(SAMPLE_PERIOD, 0.1)),
+            ("mahony", &mut Mahony::new(SAMPLE_PERIOD, 1.0, 0.05)),
+            (
+                "complementary",
+                &mut ComplementaryFilter::new(SAMPLE_PERIOD, 0.02),
+            ),
+        ];
+        for (name, filter) in filters {
+            // 航向60°且倾斜，滤波器从水平、航向0°出发
+            let mut sim = Simulator::new(UnitQuaternion::from_euler_angles(
+                20f32.to_radians(),
+                -10f32.to_radians(),
+                60f32.to_radians(),
+            ));
+            for _ in 0..6000 {
+                let sample = sim.step(Vector3::zeros());
+                filter
+                    .update_marg(&sample.gyro, &sample.accel, &sample.mag)
+                    .unwrap();
+            }
+            let error = sim.attitude.angle_to(filter.quaternion()).to_degrees();
+            assert!(error < 2.0, "{name}: {error}°");
+        }
+    }
+
+    #[test]
+    fn rejects_zero_magnetic_field() {
+        let mut filter = ComplementaryFilter::new(SAMPLE_PERIOD, 0.02);
+        assert!(matches!(
+            filter.update_marg(&Vector3::x(), &Vector3::z(), &Vector3::zeros()),
+            Err(AhrsError::MagnetometerNormZero)
+        ));
+        assert_eq!(*filter.quaternion(), UnitQuaternion::identity());
+    }
+
+    #[test]
+    fn rejects_zero_acceleration() {
+        for_each_filter(|name, filter| {
+            let before = *filter.quaternion();
+            assert!(
+                matches!(
+                    filter.update_imu(&Vector3::x(), &Vector3::zeros()),
+                    Err(AhrsError::AccelerometerNormZero)
+                ),
+                "{name}"
+            );
+            assert_eq!(*filter.quaternion(), before, "{name}");
+        });
+    }
+}
@@ -0,0 +1,746 @@
+//! 手势与运动事件检测
+//!
+//! 把IMU当作输入设备：从校准后的加速度和角速度中检测敲击、双击、摇晃、失重、
+//! 倾斜超过阈值和翻转。检测器逐个采样更新，检测到的事件先放入队列，
+//! 再由[`GestureDetector::publish`]发布到[`MotionEventChannel`]，可以有多个订阅者。
+//!
+//! 加速度经低通滤波得到重力（敲击刚开始时和失重时暂停更新），读数减去重力为动态加速度：
+//! - 敲击：动态加速度短暂超过阈值后迅速回落，之后静默时间内的余振属于同一次敲击；
+//!   双击窗口内再次敲击为双击，窗口结束仍无第二次敲击时报告单击
+//! - 摇晃：较强的动态加速度在窗口内多次反向；摇晃后的一个窗口内不报告敲击，
+//!   重力也暂停更新，以免来回的加速度被当作倾斜
+//! - 失重：加速度模长持续接近零，如跌落
+//! - 倾斜：重力与设备Z轴的夹角超过阈值；回落到阈值减去回差以下时报告恢复水平
+//! - 翻转：设备朝下（或朝上）并基本停止转动一段时间
+
+use super::mounting::Axis;
+use super::units::{Acceleration, AngularRate};
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::pubsub::{PubSubChannel, Subscriber};
+use heapless::Deque;
+use nalgebra::{ComplexField, RealField, Vector3};
+
+/// 发布通道中为每个订阅者保留的事件数
+pub const EVENT_CAPACITY: usize = 4;
+/// 发布通道的订阅者数
+pub const EVENT_SUBSCRIBERS: usize = 2;
+/// 发布通道的发布者数
+pub const EVENT_PUBLISHERS: usize = 1;
+
+/// 运动事件的发布通道
+pub type MotionEventChannel<M> =
+    PubSubChannel<M, MotionEvent, EVENT_CAPACITY, EVENT_SUBSCRIBERS, EVENT_PUBLISHERS>;
+
+/// 运动事件的订阅者
+pub type MotionEventSubscriber<'a, M> =
+    Subscriber<'a, M, MotionEvent, EVENT_CAPACITY, EVENT_SUBSCRIBERS, EVENT_PUBLISHERS>;
+
+/// 检测器中等待发布的事件数上限，超过时丢弃最早的事件
+const EVENT_QUEUE_LEN: usize = 8;
+
+/// 重力低通滤波的时间常数（秒）
+const GRAVITY_TIME_CONSTANT: f32 = 0.2;
+
+/// 动态加速度回落到敲击阈值的此比例以下时认为敲击结束
+const TAP_RELEASE_RATIO: f32 = 0.5;
+
+/// 判定朝上或朝下时重力与Z轴夹角的上限为45°，此为其余弦
+const FLIP_COS: f32 = core::f32::consts::FRAC_1_SQRT_2;
+
+/// 判定翻转完成时角速度模长的上限（rad/s）
+const FLIP_GYRO_MAX: f32 = 0.5;
+
+/// 重力模长低于此值（g）时方向不可信，不判断倾斜和翻转
+const MIN_GRAVITY: f32 = 0.5;
+
+/// 运动事件
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MotionEvent {
+    /// 单击，参数为敲击时设备被推动的方向，例如敲击顶面为`NegZ`
+    Tap(Axis),
+    /// 双击，参数为第一次敲击的方向
+    DoubleTap(Axis),
+    /// 摇晃
+    Shake,
+    /// 失重（跌落）
+    FreeFall,
+    /// 倾斜超过阈值
+    Tilted {
+        /// 此时重力与设备Z轴的夹角（rad）
+        angle: f32,
+    },
+    /// 恢复水平
+    Leveled,
+    /// 翻转
+    Flip {
+        /// 翻转后朝下（Z轴指向地面）为`true`，翻回朝上为`false`
+        face_down: bool,
+    },
+}
+
+impl MotionEvent {
+    /// 显示用名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Tap(_) => "TAP",
+            Self::DoubleTap(_) => "DOUBLE TAP",
+            Self::Shake => "SHAKE",
+            Self::FreeFall => "FREE FALL",
+            Self::Tilted { .. } => "TILT",
+            Self::Leveled => "LEVEL",
+            Self::Flip { face_down: true } => "FACE DOWN",
+            Self::Flip { face_down: false } => "FACE UP",
+        }
+    }
+}
+
+/// 事件检测的阈值
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// 敲击时动态加速度的阈值（g）
+    pub tap_threshold: f32,
+    /// 敲击持续时间的上限（秒），更长的是普通运动
+    pub tap_max_duration: f32,
+    /// 敲击结束后的静默时间（秒），期间的余振不算新的敲击
+    pub tap_quiet: f32,
+    /// 双击时两次敲击的最大间隔（秒），也是报告单击的延迟
+    pub double_tap_window: f32,
+    /// 摇晃时动态加速度的阈值（g）
+    pub shake_threshold: f32,
+    /// 判定为摇晃所需的反向次数
+    pub shake_reversals: u8,
+    /// 反向次数的统计窗口（秒）
+    pub shake_window: f32,
+    /// 失重时加速度模长的上限（g）
+    pub free_fall_threshold: f32,
+    /// 失重持续时间的下限（秒），0.1秒约对应5厘米的落差
+    pub free_fall_duration: f32,
+    /// 倾斜角阈值（rad）
+    pub tilt_threshold: f32,
+    /// 恢复水平的回差（rad）
+    pub tilt_hysteresis: f32,
+    /// 翻转后需保持不动的时间（秒）
+    pub flip_hold: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_threshold: 1.0,
+            tap_max_duration: 0.05,
+            tap_quiet: 0.1,
+            double_tap_window: 0.5,
+            shake_threshold: 1.0,
+            shake_reversals: 4,
+            shake_window: 1.0,
+            free_fall_threshold: 0.3,
+            free_fall_duration: 0.1,
+            tilt_threshold: 30f32.to_radians(),
+            tilt_hysteresis: 5f32.to_radians(),
+            flip_hold: 0.5,
+        }
+    }
+}
+
+/// 敲击检测的阶段
+#[derive(Clone, Copy, Debug, Default)]
+enum TapPhase {
+    /// 等待敲击
+    #[default]
+    Idle,
+    /// 动态加速度超过阈值
+    Spike {
+        /// 已持续的时间（秒）
+        duration: f32,
+        /// 模长最大的动态加速度
+        peak: Vector3<f32>,
+    },
+    /// 敲击结束后的静默
+    Quiet {
+        /// 剩余的静默时间（秒）
+        left: f32,
+    },
+}
+
+/// 敲击和双击检测
+#[derive(Clone, Copy, Debug, Default)]
+struct TapDetector {
+    /// 当前阶段
+    phase: TapPhase,
+    /// 等待第二次敲击的第一次敲击：方向和已过去的时间（秒）
+    pending: Option<(Axis, f32)>,
+}
+
+impl TapDetector {
+    /// 是否处于敲击刚开始的短时间内，此时读数不应计入重力
+    fn active(&self, config: &GestureConfig) -> bool {
+        match self.phase {
+            TapPhase::Spike { duration, .. } => duration <= config.tap_max_duration,
+            _ => false,
+        }
+    }
+
+    /// 放弃进行中的敲击和等待中的单击
+    fn cancel(&mut self) {
+        *self = Self::default();
+    }
+
+    fn update(
+        &mut self,
+        dynamic: &Vector3<f32>,
+        dt: f32,
+        config: &GestureConfig,
+    ) -> Option<MotionEvent> {
+        let mut event = None;
+        if let Some((axis, age)) = &mut self.pending {
+            *age += dt;
+            if *age > config.double_tap_window {
+                event = Some(MotionEvent::Tap(*axis));
+                self.pending = None;
+            }
+        }
+
+        let magnitude = dynamic.norm();
+        match &mut self.phase {
+            TapPhase::Idle => {
+                if magnitude > config.tap_threshold {
+                    self.phase = TapPhase::Spike {
+                        duration: dt,
+                        peak: *dynamic,
+                    };
+                }
+            }
+            TapPhase::Spike { duration, peak } => {
+                if magnitude > config.tap_threshold * TAP_RELEASE_RATIO {
+                    *duration += dt;
+                    if magnitude > peak.norm() {
+                        *peak = *dynamic;
+                    }
+                } else if *duration <= config.tap_max_duration {
+                    let axis = Axis::nearest(peak);
+                    self.phase = TapPhase::Quiet {
+                        left: config.tap_quiet,
+                    };
+                    event = event.or(match self.pending.take() {
+                        Some((first, _)) => Some(MotionEvent::DoubleTap(first)),
+                        None => {
+                            self.pending = Some((axis, 0.0));
+                            None
+                        }
+                    });
+                } else {
+                    // 持续较长的是普通运动，之前等待的单击也不再报告
+                    self.cancel();
+                }
+            }
+            TapPhase::Quiet { left } => {
+                *left -= dt;
+                if *left <= 0.0 {
+                    self.phase = TapPhase::Idle;
+                }
+            }
+        }
+        event
+    }
+}
+
+/// 摇晃检测
+#[derive(Clone, Copy, Debug, Default)]
+struct ShakeDetector {
+    /// 上一次较强的动态加速度
+    direction: Option<Vector3<f32>>,
+    /// 窗口内的反向次数
+    reversals: u8,
+    /// 第一次反向后经过的时间（秒）
+    elapsed: f32,
+}
+
+impl ShakeDetector {
+    fn update(
+        &mut self,
+        dynamic: &Vector3<f32>,
+        dt: f32,
+        config: &GestureConfig,
+    ) -> Option<MotionEvent> {
+        if self.reversals > 0 {
+            self.elapsed += dt;
+            if self.elapsed > config.shake_window {
+                self.reversals = 0;
+                self.elapsed = 0.0;
+            }
+        }
+
+        if dynamic.norm() <= config.shake_threshold {
+            return None;
+        }
+        if self
+            .direction
+            .is_some_and(|previous| previous.dot(dynamic) < 0.0)
+        {
+            self.reversals += 1;
+        }
+        self.direction = Some(*dynamic);
+        if self.reversals < config.shake_reversals {
+            return None;
+        }
+        *self = Self::default();
+        Some(MotionEvent::Shake)
+    }
+}
+
+/// 失重检测
+#[derive(Clone, Copy, Debug, Default)]
+struct FreeFallDetector {
+    /// 已持续失重的时间（秒）
+    duration: f32,
+    /// 本次失重是否已报告
+    reported: bool,
+}
+
+impl FreeFallDetector {
+    /// 是否正在失重
+    fn falling(&self) -> bool {
+        self.duration > 0.0
+    }
+
+    fn update(
+        &mut self,
+        accel: &Vector3<f32>,
+        dt: f32,
+        config: &GestureConfig,
+    ) -> Option<MotionEvent> {
+        if accel.norm() >= config.free_fall_threshold {
+            *self = Self::default();
+            return None;
+        }
+        self.duration += dt;
+        if self.reported || self.duration < config.free_fall_duration {
+            return None;
+        }
+        self.reported = true;
+        Some(MotionEvent::FreeFall)
+    }
+}
+
+/// 倾斜检测
+#[derive(Clone, Copy, Debug, Default)]
+struct TiltDetector {
+    /// 是否倾斜，尚未判断时为`None`
+    tilted: Option<bool>,
+}
+
+impl TiltDetector {
+    fn update(&mut self, gravity: &Vector3<f32>, config: &GestureConfig) -> Option<MotionEvent> {
+        let horizontal = ComplexField::sqrt(gravity.x * gravity.x + gravity.y * gravity.y);
+        let angle = RealField::atan2(horizontal, gravity.z);
+        let tilted = match self.tilted {
+            Some(false) | None if angle > config.tilt_threshold => true,
+            Some(true) | None if angle < config.tilt_threshold - config.tilt_hysteresis => false,
+            _ => return None,
+        };
+
+        // 第一次判断只记录状态，之后每次越过阈值报告一次
+        self.tilted.replace(tilted)?;
+        Some(if tilted {
+            MotionEvent::Tilted { angle }
+        } else {
+            MotionEvent::Leveled
+        })
+    }
+}
+
+/// 翻转检测
+#[derive(Clone, Copy, Debug, Default)]
+struct FlipDetector {
+    /// 是否朝下，尚未判断时为`None`
+    face_down: Option<bool>,
+    /// 以另一面朝向保持不动的时间（秒）
+    hold: f32,
+}
+
+impl FlipDetector {
+    fn update(
+        &mut self,
+        gravity: &Vector3<f32>,
+        gyro: &Vector3<f32>,
+        dt: f32,
+        config: &GestureConfig,
+    ) -> Option<MotionEvent> {
+        let z = gravity.z / gravity.norm();
+        let face_down = if z < -FLIP_COS {
+            true
+        } else if z > FLIP_COS {
+            false
+        } else {
+            self.hold = 0.0;
+            return None;
+        };
+        if self.face_down == Some(face_down) || gyro.norm() >= FLIP_GYRO_MAX {
+            self.hold = 0.0;
+            return None;
+        }
+
+        self.hold += dt;
+        if self.hold < config.flip_hold {
+            return None;
+        }
+        self.hold = 0.0;
+        // 第一次判断只记录朝向
+        self.face_down.replace(face_down)?;
+        Some(MotionEvent::Flip { face_down })
+    }
+}
+
+/// 运动事件检测器
+#[derive(Clone, Debug, Default)]
+pub struct GestureDetector {
+    /// 阈值
+    config: GestureConfig,
+    /// 低通滤波得到的重力（g），第一个采样前为`None`
+    gravity: Option<Vector3<f32>>,
+    /// 敲击检测
+    tap: TapDetector,
+    /// 摇晃检测
+    shake: ShakeDetector,
+    /// 失重检测
+    free_fall: FreeFallDetector,
+    /// 距离上次摇晃结束还剩的时间（秒）
+    shaking: f32,
+    /// 倾斜检测
+    tilt: TiltDetector,
+    /// 翻转检测
+    flip: FlipDetector,
+    /// 等待发布的事件
+    events: Deque<MotionEvent, EVENT_QUEUE_LEN>,
+}
+
+impl GestureDetector {
+    /// 以指定的阈值创建
+    pub fn new(config: GestureConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// 阈值
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// 加入一个采样
+    ///
+    /// # 参数
+    /// - `accel`: 校准后的加速度
+    /// - `gyro`: 校准后的角速度
+    /// - `dt`: 采样周期（秒）
+    pub fn update(&mut self, accel: &Acceleration, gyro: &AngularRate, dt: f32) {
+        let config = self.config;
+        let accel = *accel.as_g();
+        let gravity = *self.gravity.get_or_insert(accel);
+        let dynamic = accel - gravity;
+
+        let tap = self.tap.update(&dynamic, dt, &config);
+        let shake = self.shake.update(&dynamic, dt, &config);
+        if shake.is_some() {
+            self.shaking = config.shake_window;
+        }
+        let shaking = self.shaking > 0.0;
+        if shaking {
+            self.shaking -= dt;
+            self.tap.cancel();
+        }
+        let free_fall = self.free_fall.update(&accel, dt, &config);
+
+        if shaking || self.tap.active(&config) || self.free_fall.falling() {
+            self.push(tap.into_iter().chain(shake).chain(free_fall));
+            return;
+        }
+        let gravity = gravity + (accel - gravity) * (dt / GRAVITY_TIME_CONSTANT).min(1.0);
+        self.gravity = Some(gravity);
+
+        let (tilt, flip) = if gravity.norm() < MIN_GRAVITY {
+            (None, None)
+        } else {
+            let gyro = gyro.as_rad_per_s();
+            (
+                self.tilt.update(&gravity, &config),
+                self.flip.update(&gravity, gyro, dt, &config),
+            )
+        };
+        self.push([tap, shake, free_fall, tilt, flip].into_iter().flatten());
+    }
+
+    /// 取出最早的一个未发布的事件
+    pub fn next_event(&mut self) -> Option<MotionEvent> {
+        self.events.pop_front()
+    }
+
+    /// 将未发布的事件全部发布到通道
+    ///
+    /// 订阅者的队列已满时丢弃其中最早的事件，不会阻塞采样。
+    ///
+    /// # 返回值
+    /// 发布的事件数
+    pub fn publish<M: RawMutex>(&mut self, channel: &MotionEventChannel<M>) -> usize {
+        let publisher = channel.immediate_publisher();
+        let mut count = 0;
+        while let Some(event) = self.next_event() {
+            publisher.publish_immediate(event);
+            count += 1;
+        }
+        count
+    }
+
+    /// 放入待发布的事件，队列已满时丢弃最早的事件
+    fn push(&mut self, events: impl Iterator<Item = MotionEvent>) {
+        for event in events {
+            if self.events.is_full() {
+                self.events.pop_front();
+            }
+            let _ = self.events.push_back(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::recording::{Recording, synthetic};
+    use crate::app::imu::replay::{Replay, Trace};
+    use core::fmt::Write;
+    use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+    use nalgebra::UnitQuaternion;
+
+    /// 敲击时线加速度的波形（g），每个值持续一个采样：冲击、回弹和衰减的余振
+    const TAP_WAVEFORM: [f32; 6] = [2.0, 1.2, -0.8, 0.3, -0.1, 0.05];
+
+    /// 收集检测到的事件
+    struct Collector {
+        detector: GestureDetector,
+        events: heapless::Vec<MotionEvent, 16>,
+    }
+
+    impl Replay for Collector {
+        fn update(&mut self, accel: &Acceleration, gyro: &AngularRate, dt: f32) {
+            self.detector.update(accel, gyro, dt);
+            while let Some(event) = self.detector.next_event() {
+                self.events.push(event).unwrap();
+            }
+        }
+    }
+
+    /// 水平静置1秒开始的模拟记录
+    fn simulate(config: GestureConfig) -> Trace<Collector> {
+        let collector = Collector {
+            detector: GestureDetector::new(config),
+            events: heapless::Vec::new(),
+        };
+        Trace::new(UnitQuaternion::identity(), collector)
+    }
+
+    /// 敲击使设备沿`direction`（地球坐标系）被推动，之后静置0.2秒
+    fn tap(trace: &mut Trace<Collector>, direction: Vector3<f32>) {
+        for value in TAP_WAVEFORM {
+            trace.step(Vector3::zeros(), direction * value);
+        }
+        trace.still(0.2);
+    }
+
+    /// 各动作的模拟数据，说明中的`events`为应检测到的事件
+    ///
+    /// 由`tools/fixturegen/gestures.py`的运动模型生成，不是传感器实测录制，
+    /// 只能说明阈值与该模型一致；有实测录制后应替换。
+    const SYNTHETIC_TRACES: [Recording; 4] = [
+        synthetic!("gesture_tap.csv"),
+        synthetic!("gesture_shake.csv"),
+        synthetic!("gesture_free_fall.csv"),
+        synthetic!("gesture_flip.csv"),
+    ];
+
+    /// 事件的显示名称，敲击带上方向，如`TAP Z-`
+    fn describe(event: &MotionEvent) -> heapless::String<16> {
+        let mut text = heapless::String::new();
+        write!(text, "{}", event.label()).unwrap();
+        if let MotionEvent::Tap(axis) | MotionEvent::DoubleTap(axis) = event {
+            write!(text, " {}", axis.label()).unwrap();
+        }
+        text
+    }
+
+    #[test]
+    fn detects_gestures_in_synthetic_traces() {
+        for recording in &SYNTHETIC_TRACES {
+            let mut collector = Collector {
+                detector: GestureDetector::default(),
+                events: heapless::Vec::new(),
+            };
+            recording.replay(&mut collector);
+
+            let detected: heapless::Vec<_, 16> = collector.events.iter().map(describe).collect();
+            let expected = recording.meta("events").expect("recording has no events");
+            assert!(
+                detected
+                    .iter()
+                    .map(|name| name.as_str())
+                    .eq(expected.split(", ")),
+                "expected {expected}, detected {detected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_single_tap_after_double_tap_window() {
+        let mut trace = simulate(GestureConfig::default());
+        tap(&mut trace, -Vector3::z());
+        assert_eq!(trace.events, [] as [MotionEvent; 0]);
+        trace.still(1.0);
+        assert_eq!(trace.events, [MotionEvent::Tap(Axis::NegZ)]);
+
+        // 相隔较远的两次敲击是两次单击
+        trace.events.clear();
+        tap(&mut trace, Vector3::x());
+        trace.still(0.6);
+        tap(&mut trace, Vector3::x());
+        trace.still(1.0);
+        assert_eq!(
+            trace.events,
+            [MotionEvent::Tap(Axis::PosX), MotionEvent::Tap(Axis::PosX)]
+        );
+
+        // 提高阈值后轻敲不再触发
+        let mut trace = simulate(GestureConfig {
+            tap_threshold: 2.5,
+            ..GestureConfig::default()
+        });
+        tap(&mut trace, -Vector3::z());
+        trace.still(1.0);
+        assert_eq!(trace.events, [] as [MotionEvent; 0]);
+    }
+
+    #[test]
+    fn detects_double_tap() {
+        let mut trace = simulate(GestureConfig::default());
+        tap(&mut trace, -Vector3::z());
+        tap(&mut trace, -Vector3::z());
+        trace.still(1.0);
+        assert_eq!(trace.events, [MotionEvent::DoubleTap(Axis::NegZ)]);
+    }
+
+    #[test]
+    fn shaking_is_not_tapping() {
+        let mut trace = simulate(GestureConfig::default());
+        // 沿X轴以4Hz、±2g来回摇晃1.5秒
+        for i in 0..150 {
+            let phase = 2.0 * core::f32::consts::PI * 4.0 * i as f32 * 0.01;
+            trace.step(Vector3::zeros(), Vector3::x() * 2.0 * phase.sin());
+        }
+        trace.still(1.0);
+
+        assert!(!trace.events.is_empty());
+        assert!(
+            trace
+                .events
+                .iter()
+                .all(|event| *event == MotionEvent::Shake)
+        );
+
+        // 来回次数不足时不算摇晃
+        let mut trace = simulate(GestureConfig::default());
+        for i in 0..25 {
+            let phase = 2.0 * core::f32::consts::PI * 4.0 * i as f32 * 0.01;
+            trace.step(Vector3::zeros(), Vector3::x() * 2.0 * phase.sin());
+        }
+        trace.still(1.0);
+        assert!(!trace.events.contains(&MotionEvent::Shake));
+    }
+
+    #[test]
+    fn detects_free_fall() {
+        let mut trace = simulate(GestureConfig::default());
+        // 跌落0.3秒后被接住，以0.5g减速到停止
+        trace.hold(0.3, Vector3::zeros(), -Vector3::z());
+        trace.hold(0.6, Vector3::zeros(), Vector3::z() * 0.5);
+        trace.still(1.0);
+        assert_eq!(trace.events, [MotionEvent::FreeFall]);
+
+        // 失重时间短于设定的下限时不报告
+        let mut trace = simulate(GestureConfig {
+            free_fall_duration: 0.2,
+            ..GestureConfig::default()
+        });
+        trace.hold(0.15, Vector3::zeros(), -Vector3::z());
+        trace.hold(0.1, Vector3::zeros(), Vector3::z() * 0.5);
+        trace.still(1.0);
+        assert_eq!(trace.events, [] as [MotionEvent; 0]);
+    }
+
+    #[test]
+    fn reports_tilt_past_threshold_with_hysteresis() {
+        let mut trace = simulate(GestureConfig::default());
+        trace.rotate(Vector3::x(), 45f32.to_radians());
+        trace.still(1.0);
+        let [MotionEvent::Tilted { angle }] = trace.events[..] else {
+            panic!("{:?}", trace.events);
+        };
+        assert!(
+            (30.0..35.0).contains(&angle.to_degrees()),
+            "{}",
+            angle.to_degrees()
+        );
+
+        // 回到阈值附近不恢复，回到回差以下才恢复
+        trace.events.clear();
+        trace.rotate(Vector3::x(), -18f32.to_radians());
+        trace.still(1.0);
+        assert_eq!(trace.events, [] as [MotionEvent; 0]);
+        trace.rotate(Vector3::x(), -27f32.to_radians());
+        trace.still(1.0);
+        assert_eq!(trace.events, [MotionEvent::Leveled]);
+    }
+
+    #[test]
+    fn detects_flip_over_and_back() {
+        let mut trace = simulate(GestureConfig::default());
+        trace.rotate(Vector3::y(), core::f32::consts::PI);
+        trace.still(1.0);
+        assert!(matches!(trace.events[0], MotionEvent::Tilted { .. }));
+        assert_eq!(trace.events[1..], [MotionEvent::Flip { face_down: true }]);
+
+        trace.events.clear();
+        trace.rotate(Vector3::y(), -core::f32::consts::PI);
+        trace.still(1.0);
+        assert_eq!(
+            trace.events,
+            [MotionEvent::Leveled, MotionEvent::Flip { face_down: false }]
+        );
+    }
+
+    #[test]
+    fn publishes_to_every_subscriber() {
+        let channel = MotionEventChannel::<NoopRawMutex>::new();
+        let mut first = channel.subscriber().unwrap();
+        let mut second = channel.subscriber().unwrap();
+
+        let mut trace = simulate(GestureConfig::default());
+        // 回放时不取出事件，交给发布
+        for value in TAP_WAVEFORM {
+            let accel = Acceleration::from_g(Vector3::z() * (1.0 - value));
+            trace.detector.update(&accel, &AngularRate::zeros(), 0.01);
+        }
+        for _ in 0..100 {
+            let accel = Acceleration::from_g(Vector3::z());
+            trace.detector.update(&accel, &AngularRate::zeros(), 0.01);
+        }
+
+        assert_eq!(trace.detector.publish(&channel), 1);
+        for subscriber in [&mut first, &mut second] {
+            assert_eq!(
+                subscriber.try_next_message_pure(),
+                Some(MotionEvent::Tap(Axis::NegZ))
+            );
+            assert_eq!(subscriber.try_next_message_pure(), None);
+        }
+        assert_eq!(trace.detector.publish(&channel), 0);
+    }
+}
@@ -0,0 +1,207 @@
+//! 传感器故障处理
+//!
+//! 一次I2C无应答或一次错误的读数不应使整个系统停止。读取失败时先重试；
+//! 连续失败达到次数后恢复总线并重新初始化传感器；连续恢复多次仍然失败则判定传感器丢失，
+//! 此后由调用方定期尝试恢复，读取成功后回到正常状态。
+
+/// 重试策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 恢复总线前连续重试的次数
+    pub retries: u8,
+    /// 判定传感器丢失前连续恢复的次数
+    pub recoveries: u8,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            recoveries: 3,
+        }
+    }
+}
+
+/// 传感器状态
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SensorState {
+    /// 正常运行
+    #[default]
+    Running,
+    /// 正在恢复总线和传感器
+    Recovering,
+    /// 多次恢复失败，传感器丢失
+    Lost,
+}
+
+/// 出错后的下一步操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// 丢弃本次数据后重新读取
+    Retry,
+    /// 恢复总线并重新初始化传感器
+    Recover,
+    /// 传感器丢失，等待一段时间后再尝试恢复
+    Lost,
+}
+
+/// 累计的错误计数，用于显示
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ErrorCounters {
+    /// 错误次数
+    pub errors: u32,
+    /// 总线恢复次数
+    pub recoveries: u32,
+}
+
+/// 按重试策略跟踪传感器的错误
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SensorHealth {
+    /// 重试策略
+    policy: RetryPolicy,
+    /// 当前状态
+    state: SensorState,
+    /// 自上次恢复以来连续重试的次数
+    retries: u8,
+    /// 自上次成功以来连续恢复的次数
+    recoveries: u8,
+    /// 累计计数
+    counters: ErrorCounters,
+}
+
+impl SensorHealth {
+    /// 创建正常状态的跟踪器
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> SensorState {
+        self.state
+    }
+
+    /// 累计的错误计数
+    pub fn counters(&self) -> ErrorCounters {
+        self.counters
+    }
+
+    /// 读取成功，回到正常状态并清除连续失败的计数
+    pub fn record_success(&mut self) {
+        self.state = SensorState::Running;
+        self.retries = 0;
+        self.recoveries = 0;
+    }
+
+    /// 读取、初始化或恢复失败
+    ///
+    /// # 返回值
+    /// 按重试策略的下一步操作
+    pub fn record_error(&mut self) -> Action {
+        self.counters.errors = self.counters.errors.saturating_add(1);
+        if self.state == SensorState::Lost {
+            return self.recover(Action::Lost);
+        }
+
+        if self.retries < self.policy.retries {
+            self.retries += 1;
+            return Action::Retry;
+        }
+
+        if self.recoveries < self.policy.recoveries {
+            self.recoveries += 1;
+            self.state = SensorState::Recovering;
+            return self.recover(Action::Recover);
+        }
+
+        self.state = SensorState::Lost;
+        self.recover(Action::Lost)
+    }
+
+    /// 记录一次总线恢复，恢复后重新开始重试计数
+    fn recover(&mut self, action: Action) -> Action {
+        self.retries = 0;
+        self.counters.recoveries = self.counters.recoveries.saturating_add(1);
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 连续出错`N`次，返回每次的下一步操作
+    fn errors<const N: usize>(health: &mut SensorHealth) -> [Action; N] {
+        core::array::from_fn(|_| health.record_error())
+    }
+
+    #[test]
+    fn retries_before_recovering() {
+        let mut health = SensorHealth::new(RetryPolicy::default());
+        assert_eq!(
+            errors(&mut health),
+            [Action::Retry, Action::Retry, Action::Retry, Action::Recover]
+        );
+        assert_eq!(health.state(), SensorState::Recovering);
+        assert_eq!(
+            health.counters(),
+            ErrorCounters {
+                errors: 4,
+                recoveries: 1
+            }
+        );
+
+        // 恢复后重新开始重试
+        assert_eq!(health.record_error(), Action::Retry);
+    }
+
+    #[test]
+    fn success_clears_consecutive_failures() {
+        let mut health = SensorHealth::new(RetryPolicy::default());
+        for _ in 0..10 {
+            errors::<3>(&mut health);
+            health.record_success();
+        }
+        assert_eq!(health.state(), SensorState::Running);
+        assert_eq!(health.counters().errors, 30);
+        assert_eq!(health.counters().recoveries, 0);
+
+        errors::<4>(&mut health);
+        health.record_success();
+        assert_eq!(health.state(), SensorState::Running);
+        assert_eq!(health.counters().recoveries, 1);
+    }
+
+    #[test]
+    fn lost_after_repeated_recoveries() {
+        let policy = RetryPolicy {
+            retries: 1,
+            recoveries: 2,
+        };
+        let mut health = SensorHealth::new(policy);
+        assert_eq!(
+            errors(&mut health),
+            [
+                Action::Retry,
+                Action::Recover,
+                Action::Retry,
+                Action::Recover,
+                Action::Retry,
+                Action::Lost
+            ]
+        );
+        assert_eq!(health.state(), SensorState::Lost);
+
+        // 丢失后每次出错都等待后再恢复，不再重试
+        assert_eq!(health.record_error(), Action::Lost);
+        assert_eq!(health.counters().recoveries, 4);
+
+        // 恢复成功后回到正常状态，重新按策略处理
+        health.record_success();
+        assert_eq!(health.state(), SensorState::Running);
+        assert_eq!(health.record_error(), Action::Retry);
+        assert_eq!(health.record_error(), Action::Recover);
+    }
+}
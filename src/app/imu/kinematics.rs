@@ -0,0 +1,307 @@
+//! 线加速度、速度与位移估计
+//!
+//! 加速度计测得的是比力，即运动加速度与重力反作用之和。用姿态四元数将读数转到地球坐标系
+//! 后减去1g，得到地球坐标系中的线加速度；对其积分得到速度，再积分得到位移。
+//!
+//! 加速度计零偏和姿态误差的残余经两次积分后迅速累积，速度和位移只在几秒内可信。
+//! 设备静止时速度必然为零：检测到静止后将速度清零（零速修正，ZUPT），并以静止期间的
+//! 线加速度估计残余零偏，误差因此只在每段运动内累积。位移从每段运动开始时重新计算。
+//!
+//! 静止检测依据加速度模长接近1g且角速度很小。持续的匀加速运动中加速度模长变化不大，
+//! 可能被误判为静止，只适合手持、行走等加速度不断变化的运动。
+
+use super::units::{Acceleration, AngularRate, STANDARD_GRAVITY};
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// 静止期间估计残余零偏的时间常数（秒）
+const BIAS_TIME_CONSTANT: f32 = 1.0;
+
+/// 地球坐标系中的线加速度（m/s²），Z轴朝上
+///
+/// # 参数
+/// - `attitude`: 姿态，将设备坐标系中的向量转到地球坐标系
+/// - `accel`: 设备坐标系中的加速度计读数
+pub fn linear_acceleration(attitude: &UnitQuaternion<f32>, accel: &Acceleration) -> Vector3<f32> {
+    (attitude * accel.as_g() - Vector3::z()) * STANDARD_GRAVITY
+}
+
+/// 静止检测的阈值
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StillnessConfig {
+    /// 加速度模长与1g之差的上限（g）
+    pub accel_tolerance: f32,
+    /// 角速度模长的上限（rad/s）
+    pub gyro_threshold: f32,
+    /// 持续满足条件多久后判定为静止（秒）
+    pub hold_time: f32,
+}
+
+impl Default for StillnessConfig {
+    fn default() -> Self {
+        Self {
+            accel_tolerance: 0.05,
+            gyro_threshold: 0.1,
+            hold_time: 0.25,
+        }
+    }
+}
+
+/// 静止检测
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StillnessDetector {
+    /// 阈值
+    config: StillnessConfig,
+    /// 已持续满足静止条件的时间（秒）
+    still_time: f32,
+}
+
+impl StillnessDetector {
+    /// 创建静止检测
+    pub fn new(config: StillnessConfig) -> Self {
+        Self {
+            config,
+            still_time: 0.0,
+        }
+    }
+
+    /// 阈值
+    pub fn config(&self) -> &StillnessConfig {
+        &self.config
+    }
+
+    /// 加入一个采样
+    ///
+    /// # 参数
+    /// - `accel`: 加速度计读数
+    /// - `gyro`: 角速度
+    /// - `dt`: 采样周期（秒）
+    ///
+    /// # 返回值
+    /// 是否处于静止状态
+    pub fn update(&mut self, accel: &Acceleration, gyro: &AngularRate, dt: f32) -> bool {
+        let quiet = (accel.as_g().norm() - 1.0).abs() < self.config.accel_tolerance
+            && gyro.as_rad_per_s().norm() < self.config.gyro_threshold;
+        self.still_time = if quiet { self.still_time + dt } else { 0.0 };
+        self.is_still()
+    }
+
+    /// 是否处于静止状态
+    pub fn is_still(&self) -> bool {
+        self.still_time >= self.config.hold_time
+    }
+
+    /// 重新开始检测
+    pub fn reset(&mut self) {
+        self.still_time = 0.0;
+    }
+}
+
+/// 某一时刻的运动状态，各量均在地球坐标系中
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Motion {
+    /// 线加速度（m/s²），已减去静止时估计的残余零偏
+    pub linear_acceleration: Vector3<f32>,
+    /// 速度（m/s）
+    pub velocity: Vector3<f32>,
+    /// 本段运动的位移（m），静止后保留到下一段运动开始
+    pub displacement: Vector3<f32>,
+    /// 是否静止
+    pub still: bool,
+    /// 本段运动已持续的时间（秒），时间越长速度和位移越不可信
+    pub moving_time: f32,
+}
+
+/// 带零速修正的速度和位移积分
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kinematics {
+    /// 静止检测
+    stillness: StillnessDetector,
+    /// 静止期间估计的线加速度残余零偏（m/s²）
+    bias: Vector3<f32>,
+    /// 当前运动状态
+    motion: Motion,
+}
+
+impl Kinematics {
+    /// 以指定的静止检测阈值创建
+    pub fn new(config: StillnessConfig) -> Self {
+        Self {
+            stillness: StillnessDetector::new(config),
+            ..Self::default()
+        }
+    }
+
+    /// 当前运动状态
+    pub fn motion(&self) -> &Motion {
+        &self.motion
+    }
+
+    /// 静止检测阈值
+    pub fn stillness_config(&self) -> &StillnessConfig {
+        self.stillness.config()
+    }
+
+    /// 加入一个采样
+    ///
+    /// 静止时速度清零并更新残余零偏，运动时以梯形法积分速度和位移。
+    ///
+    /// # 参数
+    /// - `attitude`: 姿态滤波器输出的姿态
+    /// - `accel`: 设备坐标系中的加速度计读数
+    /// - `gyro`: 设备坐标系中的角速度
+    /// - `dt`: 采样周期（秒）
+    pub fn update(
+        &mut self,
+        attitude: &UnitQuaternion<f32>,
+        accel: &Acceleration,
+        gyro: &AngularRate,
+        dt: f32,
+    ) {
+        let linear = linear_acceleration(attitude, accel);
+        let was_still = self.motion.still;
+        let motion = &mut self.motion;
+        motion.still = self.stillness.update(accel, gyro, dt);
+
+        if motion.still {
+            self.bias += (linear - self.bias) * (dt / BIAS_TIME_CONSTANT).min(1.0);
+            motion.linear_acceleration = linear - self.bias;
+            motion.velocity = Vector3::zeros();
+            motion.moving_time = 0.0;
+            return;
+        }
+
+        if was_still {
+            motion.displacement = Vector3::zeros();
+        }
+        let previous = motion.velocity;
+        motion.linear_acceleration = linear - self.bias;
+        motion.velocity += motion.linear_acceleration * dt;
+        motion.displacement += (previous + motion.velocity) * (dt / 2.0);
+        motion.moving_time += dt;
+    }
+
+    /// 清零速度、位移和残余零偏，重新开始静止检测
+    pub fn reset(&mut self) {
+        let config = *self.stillness.config();
+        *self = Self::new(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.01;
+
+    /// 以真实姿态和地球坐标系中的线加速度（m/s²）生成加速度计读数
+    fn accel(attitude: &UnitQuaternion<f32>, linear: Vector3<f32>) -> Acceleration {
+        Acceleration::from_g(attitude.inverse() * (linear / STANDARD_GRAVITY + Vector3::z()))
+    }
+
+    /// 运行`seconds`秒
+    fn run(
+        kinematics: &mut Kinematics,
+        attitude: &UnitQuaternion<f32>,
+        linear: Vector3<f32>,
+        seconds: f32,
+    ) {
+        let accel = accel(attitude, linear);
+        for _ in 0..(seconds / DT).round() as usize {
+            kinematics.update(attitude, &accel, &AngularRate::zeros(), DT);
+        }
+    }
+
+    #[test]
+    fn removes_gravity_at_any_attitude() {
+        let attitude = UnitQuaternion::from_euler_angles(0.7, -0.4, 2.0);
+        let linear = Vector3::new(1.5, -0.5, 2.0);
+        let result = linear_acceleration(&attitude, &accel(&attitude, linear));
+        assert!((result - linear).amax() < 1e-4, "{result}");
+
+        let level = linear_acceleration(&attitude, &accel(&attitude, Vector3::zeros()));
+        assert!(level.amax() < 1e-4);
+    }
+
+    #[test]
+    fn detects_stillness_after_hold_time() {
+        let mut detector = StillnessDetector::new(StillnessConfig::default());
+        let level = Acceleration::from_g(Vector3::z());
+        let turning = AngularRate::from_rad_per_s(Vector3::new(0.0, 0.0, 0.5));
+
+        for _ in 0..24 {
+            assert!(!detector.update(&level, &AngularRate::zeros(), DT));
+        }
+        assert!(detector.update(&level, &AngularRate::zeros(), DT));
+
+        // 原地转动或晃动都不算静止
+        assert!(!detector.update(&level, &turning, DT));
+        let shaking = Acceleration::from_g(Vector3::new(0.3, 0.0, 1.0));
+        assert!(!detector.update(&shaking, &AngularRate::zeros(), DT));
+    }
+
+    #[test]
+    fn integrates_a_short_move() {
+        let attitude = UnitQuaternion::from_euler_angles(0.1, 0.2, 0.8);
+        let mut kinematics = Kinematics::default();
+        run(&mut kinematics, &attitude, Vector3::zeros(), 1.0);
+        assert!(kinematics.motion().still);
+
+        // 沿北以5m/s²加速0.4秒再减速0.4秒，移动0.8米
+        run(&mut kinematics, &attitude, Vector3::new(5.0, 0.0, 0.0), 0.4);
+        let motion = *kinematics.motion();
+        assert!(!motion.still);
+        assert!((motion.velocity - Vector3::new(2.0, 0.0, 0.0)).amax() < 1e-3);
+        assert!((motion.moving_time - 0.4).abs() < 1e-4);
+
+        run(
+            &mut kinematics,
+            &attitude,
+            Vector3::new(-5.0, 0.0, 0.0),
+            0.4,
+        );
+        run(&mut kinematics, &attitude, Vector3::zeros(), 1.0);
+        let motion = *kinematics.motion();
+        assert!(motion.still);
+        assert_eq!(motion.velocity, Vector3::zeros());
+        assert!(
+            (motion.displacement - Vector3::new(0.8, 0.0, 0.0)).amax() < 1e-2,
+            "{}",
+            motion.displacement
+        );
+
+        // 下一段运动的位移重新计算
+        run(&mut kinematics, &attitude, Vector3::new(0.0, 0.0, 5.0), 0.2);
+        assert!((kinematics.motion().displacement - Vector3::new(0.0, 0.0, 0.1)).amax() < 1e-3);
+
+        kinematics.reset();
+        assert_eq!(*kinematics.motion(), Motion::default());
+    }
+
+    #[test]
+    fn zero_velocity_update_removes_residual_bias() {
+        // 姿态误差等残余使静止时仍有0.2m/s²的线加速度
+        let attitude = UnitQuaternion::identity();
+        let bias = Vector3::new(0.2, -0.1, 0.0);
+        let mut kinematics = Kinematics::default();
+        run(&mut kinematics, &attitude, bias, 3.0);
+        assert!(kinematics.motion().linear_acceleration.amax() < 0.02);
+
+        run(
+            &mut kinematics,
+            &attitude,
+            Vector3::new(5.0, 0.0, 0.0) + bias,
+            0.4,
+        );
+        run(
+            &mut kinematics,
+            &attitude,
+            Vector3::new(-5.0, 0.0, 0.0) + bias,
+            0.4,
+        );
+        let displacement = kinematics.motion().displacement;
+        assert!(
+            (displacement - Vector3::new(0.8, 0.0, 0.0)).amax() < 1e-2,
+            "{displacement}"
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! 磁力计硬铁/软铁校准
+//!
+//! 理想磁力计在各种姿态下测得的地磁场位于以原点为中心的球面上。电路板上的磁性元件
+//! 和电流使读数整体偏移（硬铁误差），导磁材料和各轴灵敏度差异使球面变形为椭球面
+//! （软铁误差）。转动电路板使其尽量朝向各个方向，收集读数后拟合椭球面
+//!
+//! `xᵀAx + 2vᵀx = 1`
+//!
+//! 其中心即为硬铁偏移，再由椭球的主轴求出将椭球面映射回球面的软铁修正矩阵。
+//! 校准后的读数为`transform·(raw - offset)`，各方向的模长相同。
+
+use super::units::MagneticField;
+use nalgebra::{ComplexField, Matrix3, SMatrix, SVector, Vector3};
+
+/// 拟合所需的最小样本数
+pub const MIN_SAMPLES: usize = 50;
+
+/// 椭球最长与最短半轴之比的上限，超过时认为样本未覆盖足够的方向或受到干扰
+const MAX_AXIS_RATIO: f64 = 2.0;
+
+/// 磁力计校准参数，校准后的读数为`transform·(raw - offset)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MagCalibration {
+    /// 硬铁偏移（Gs）
+    pub offset: Vector3<f32>,
+    /// 软铁修正矩阵
+    pub transform: Matrix3<f32>,
+}
+
+impl Default for MagCalibration {
+    fn default() -> Self {
+        Self {
+            offset: Vector3::zeros(),
+            transform: Matrix3::identity(),
+        }
+    }
+}
+
+impl MagCalibration {
+    /// 校准读数
+    pub fn apply(&self, raw: MagneticField) -> MagneticField {
+        MagneticField::from_gauss(self.transform * (raw.as_gauss() - self.offset))
+    }
+}
+
+/// 椭球面的最小二乘拟合
+///
+/// 逐个加入转动过程中的读数，只累积正规方程，不保存样本。
+/// 读数的数值范围在1Gs左右，平方项和交叉项相差较大，因此以`f64`累积。
+#[derive(Clone, Copy, Debug)]
+pub struct EllipsoidFit {
+    /// 正规方程左侧 XᵀX，X的每行为 [x², y², z², 2xy, 2xz, 2yz, 2x, 2y, 2z]
+    normal: SMatrix<f64, 9, 9>,
+    /// 正规方程右侧 Xᵀ1
+    rhs: SVector<f64, 9>,
+    /// 样本数
+    count: usize,
+}
+
+impl Default for EllipsoidFit {
+    fn default() -> Self {
+        Self {
+            normal: SMatrix::zeros(),
+            rhs: SVector::zeros(),
+            count: 0,
+        }
+    }
+}
+
+impl EllipsoidFit {
+    /// 创建空的拟合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入一个未校准的读数
+    pub fn add(&mut self, raw: MagneticField) {
+        let m = raw.as_gauss().cast::<f64>();
+        let (x, y, z) = (m.x, m.y, m.z);
+        let row = SVector::<f64, 9>::from_column_slice(&[
+            x * x,
+            y * y,
+            z * z,
+            2.0 * x * y,
+            2.0 * x * z,
+            2.0 * y * z,
+            2.0 * x,
+            2.0 * y,
+            2.0 * z,
+        ]);
+        self.normal += row * row.transpose();
+        self.rhs += row;
+        self.count += 1;
+    }
+
+    /// 样本数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 求解校准参数
+    ///
+    /// 校准后的球面半径取椭球三个半轴的几何平均，使校准前后磁场强度基本不变。
+    ///
+    /// # 返回值
+    /// 样本少于[`MIN_SAMPLES`]、样本不构成椭球面或椭球过扁时返回`None`
+    pub fn solve(&self) -> Option<MagCalibration> {
+        if self.count < MIN_SAMPLES {
+            return None;
+        }
+
+        let p = self.normal.cholesky()?.solve(&self.rhs);
+        #[rustfmt::skip]
+        let a = Matrix3::new(
+            p[0], p[3], p[4],
+            p[3], p[1], p[5],
+            p[4], p[5], p[2],
+        );
+        let v = Vector3::new(p[6], p[7], p[8]);
+
+        // 以中心c平移后为 (x-c)ᵀA(x-c) = 1 + cᵀAc
+        let center = -(a.try_inverse()? * v);
+        let scale = 1.0 + center.dot(&(a * center));
+        if scale <= 0.0 {
+            return None;
+        }
+
+        // 特征值λ对应半轴长度1/√λ，全部为正时才是椭球面
+        let eigen = (a / scale).symmetric_eigen();
+        let (min, max) = (eigen.eigenvalues.min(), eigen.eigenvalues.max());
+        if min <= 0.0 || max / min > MAX_AXIS_RATIO * MAX_AXIS_RATIO {
+            return None;
+        }
+
+        let sqrt = eigen.eigenvalues.map(ComplexField::sqrt);
+        let radius = ComplexField::cbrt(sqrt.product()).recip();
+        let transform = eigen.eigenvectors
+            * Matrix3::from_diagonal(&(sqrt * radius))
+            * eigen.eigenvectors.transpose();
+
+        Some(MagCalibration {
+            offset: center.cast::<f32>(),
+            transform: transform.cast::<f32>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::RowVector3;
+
+    /// 模拟磁力计的真实误差：硬铁偏移和软铁变形
+    fn sensor() -> MagCalibration {
+        MagCalibration {
+            offset: Vector3::new(0.12, -0.08, 0.2),
+            transform: Matrix3::from_rows(&[
+                RowVector3::new(1.15, 0.05, -0.03),
+                RowVector3::new(0.05, 0.9, 0.04),
+                RowVector3::new(-0.03, 0.04, 1.05),
+            ]),
+        }
+    }
+
+    /// 球面上大致均匀分布的`count`个方向（斐波那契网格）
+    fn directions(count: usize) -> impl Iterator<Item = Vector3<f32>> {
+        let golden = core::f32::consts::PI * (3.0 - 5f32.sqrt());
+        (0..count).map(move |i| {
+            let z = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+            let r = (1.0 - z * z).sqrt();
+            let theta = golden * i as f32;
+            Vector3::new(r * theta.cos(), r * theta.sin(), z)
+        })
+    }
+
+    /// 强度0.5Gs的地磁场在各方向上的未校准读数
+    fn raw(sensor: &MagCalibration, direction: Vector3<f32>) -> MagneticField {
+        MagneticField::from_gauss(sensor.transform * direction * 0.5 + sensor.offset)
+    }
+
+    fn fit(sensor: &MagCalibration, count: usize) -> EllipsoidFit {
+        let mut fit = EllipsoidFit::new();
+        for direction in directions(count) {
+            fit.add(raw(sensor, direction));
+        }
+        fit
+    }
+
+    #[test]
+    fn recovers_hard_iron_offset() {
+        let sensor = sensor();
+        let result = fit(&sensor, 200).solve().unwrap();
+        assert!((result.offset - sensor.offset).amax() < 1e-3, "{result:?}");
+    }
+
+    #[test]
+    fn maps_ellipsoid_onto_sphere() {
+        let sensor = sensor();
+        let result = fit(&sensor, 200).solve().unwrap();
+
+        let norms = directions(37).map(|d| result.apply(raw(&sensor, d)).as_gauss().norm());
+        let (min, max) = norms.fold((f32::MAX, 0f32), |(min, max), n| (min.min(n), max.max(n)));
+        assert!(max - min < 1e-3, "{min}..{max}");
+
+        // 球面半径为半轴的几何平均，接近原磁场强度
+        assert!((min - 0.5).abs() < 0.05, "{min}");
+
+        // 软铁修正保持方向间的夹角
+        let a = result
+            .apply(raw(&sensor, Vector3::x()))
+            .as_gauss()
+            .normalize();
+        let b = result
+            .apply(raw(&sensor, Vector3::y()))
+            .as_gauss()
+            .normalize();
+        assert!(a.dot(&b).abs() < 1e-3);
+    }
+
+    #[test]
+    fn sphere_needs_no_correction() {
+        let result = fit(&MagCalibration::default(), 100).solve().unwrap();
+        assert!(result.offset.amax() < 1e-4);
+        assert!((result.transform - Matrix3::identity()).amax() < 1e-3);
+    }
+
+    #[test]
+    fn rejects_insufficient_samples() {
+        let sensor = sensor();
+        assert_eq!(fit(&sensor, MIN_SAMPLES - 1).solve(), None);
+
+        // 只在水平面内转动，读数集中在一个圆上
+        let mut flat = EllipsoidFit::new();
+        for direction in directions(200) {
+            let horizontal = Vector3::new(direction.x, direction.y, 0.0);
+            if let Some(horizontal) = horizontal.try_normalize(1e-3) {
+                flat.add(raw(&sensor, horizontal));
+            }
+        }
+        assert!(flat.count() >= MIN_SAMPLES);
+        assert_eq!(flat.solve(), None);
+    }
+}
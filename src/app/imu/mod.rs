@@ -0,0 +1,22 @@
+//! 姿态解算算法
+//!
+//! 与传感器驱动无关的姿态滤波等算法，可在主机端测试。
+
+pub mod accel_calibration;
+pub mod ekf;
+pub mod filter;
+pub mod gesture;
+pub mod health;
+pub mod kinematics;
+pub mod magnetometer;
+pub mod mounting;
+pub mod orientation;
+pub mod pedometer;
+#[cfg(test)]
+mod recording;
+#[cfg(test)]
+mod replay;
+#[cfg(test)]
+mod simulation;
+pub mod temperature;
+pub mod units;
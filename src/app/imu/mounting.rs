@@ -0,0 +1,221 @@
+//! 传感器安装方向
+//!
+//! 电路板在设备中不一定按设备的坐标轴安装，例如竖直贴在侧面或翻转安装。
+//! 安装旋转把传感器坐标系中的读数转到设备坐标系后再送入姿态滤波器，
+//! 滤波器得到的就是设备本身的姿态。
+//!
+//! 轴向对齐的安装方向由传感器X轴和Z轴在设备中的指向确定，共24种；
+//! 其他角度的安装可以直接给出四元数。
+
+use nalgebra::{Matrix3, Rotation3, UnitQuaternion, Vector3};
+
+/// 判断安装旋转是否为轴向对齐时允许的误差（方向余弦）
+const AXIS_TOLERANCE: f32 = 1e-4;
+
+/// 设备坐标轴的正负方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// X轴正方向
+    PosX,
+    /// X轴负方向
+    NegX,
+    /// Y轴正方向
+    PosY,
+    /// Y轴负方向
+    NegY,
+    /// Z轴正方向
+    PosZ,
+    /// Z轴负方向
+    NegZ,
+}
+
+impl Axis {
+    /// 全部方向
+    pub const ALL: [Self; 6] = [
+        Self::PosX,
+        Self::NegX,
+        Self::PosY,
+        Self::NegY,
+        Self::PosZ,
+        Self::NegZ,
+    ];
+
+    /// 单位向量
+    pub fn vector(self) -> Vector3<f32> {
+        match self {
+            Self::PosX => Vector3::x(),
+            Self::NegX => -Vector3::x(),
+            Self::PosY => Vector3::y(),
+            Self::NegY => -Vector3::y(),
+            Self::PosZ => Vector3::z(),
+            Self::NegZ => -Vector3::z(),
+        }
+    }
+
+    /// 显示用名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PosX => "X+",
+            Self::NegX => "X-",
+            Self::PosY => "Y+",
+            Self::NegY => "Y-",
+            Self::PosZ => "Z+",
+            Self::NegZ => "Z-",
+        }
+    }
+
+    /// 与向量`v`夹角最小的方向，即`v`绝对值最大的分量及其符号
+    pub fn nearest(v: &Vector3<f32>) -> Self {
+        let index = v.iamax();
+        let negative = v[index] < 0.0;
+        Self::ALL[index * 2 + negative as usize]
+    }
+
+    /// 与向量`v`方向一致的轴
+    fn find(v: &Vector3<f32>) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|axis| axis.vector().dot(v) > 1.0 - AXIS_TOLERANCE)
+    }
+}
+
+/// 安装旋转：将传感器坐标系中的向量转到设备坐标系，默认传感器与设备的坐标轴一致
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mounting {
+    /// 传感器坐标系到设备坐标系的旋转
+    rotation: UnitQuaternion<f32>,
+}
+
+impl Default for Mounting {
+    fn default() -> Self {
+        Self {
+            rotation: UnitQuaternion::identity(),
+        }
+    }
+}
+
+impl Mounting {
+    /// 任意角度的安装
+    pub fn from_quaternion(rotation: UnitQuaternion<f32>) -> Self {
+        Self { rotation }
+    }
+
+    /// 轴向对齐的安装
+    ///
+    /// # 参数
+    /// - `x`: 传感器X轴在设备中的指向
+    /// - `z`: 传感器Z轴在设备中的指向
+    ///
+    /// # 返回值
+    /// 两个方向不垂直时返回`None`
+    pub fn from_axes(x: Axis, z: Axis) -> Option<Self> {
+        let (x, z) = (x.vector(), z.vector());
+        if x.dot(&z) != 0.0 {
+            return None;
+        }
+        let matrix = Matrix3::from_columns(&[x, z.cross(&x), z]);
+        let rotation = Rotation3::from_matrix_unchecked(matrix);
+        Some(Self::from_quaternion(UnitQuaternion::from_rotation_matrix(
+            &rotation,
+        )))
+    }
+
+    /// 全部24种轴向对齐的安装
+    pub fn presets() -> [Self; 24] {
+        let mut presets = [Self::default(); 24];
+        let axes = Axis::ALL
+            .into_iter()
+            .flat_map(|x| Axis::ALL.into_iter().map(move |z| (x, z)));
+        let mounts = axes.filter_map(|(x, z)| Self::from_axes(x, z));
+        for (preset, mounting) in presets.iter_mut().zip(mounts) {
+            *preset = mounting;
+        }
+        presets
+    }
+
+    /// 传感器坐标系到设备坐标系的旋转
+    pub fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.rotation
+    }
+
+    /// 轴向对齐时传感器X轴和Z轴在设备中的指向，任意角度的安装返回`None`
+    pub fn axes(&self) -> Option<(Axis, Axis)> {
+        let x = Axis::find(&(self.rotation * Vector3::x()))?;
+        let z = Axis::find(&(self.rotation * Vector3::z()))?;
+        Some((x, z))
+    }
+
+    /// 将传感器坐标系中的向量转到设备坐标系
+    pub fn apply(&self, v: &Vector3<f32>) -> Vector3<f32> {
+        self.rotation * v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::filter::{Madgwick, OrientationFilter};
+    use crate::app::imu::simulation::Simulator;
+
+    #[test]
+    fn presets_cover_all_axis_aligned_rotations() {
+        let presets = Mounting::presets();
+        assert!(presets.contains(&Mounting::default()));
+        assert_eq!(Mounting::default().axes(), Some((Axis::PosX, Axis::PosZ)));
+
+        for (index, a) in presets.iter().enumerate() {
+            let (x, z) = a.axes().unwrap();
+            assert_eq!(Mounting::from_axes(x, z), Some(*a));
+            for b in &presets[index + 1..] {
+                assert!(a.quaternion().angle_to(b.quaternion()) > 1.0);
+            }
+        }
+
+        assert_eq!(Axis::nearest(&Vector3::new(0.2, -0.9, 0.4)), Axis::NegY);
+        assert_eq!(Axis::nearest(&Vector3::new(0.1, 0.0, 0.3)), Axis::PosZ);
+
+        assert_eq!(Mounting::from_axes(Axis::PosX, Axis::NegX), None);
+        assert_eq!(Mounting::from_axes(Axis::PosY, Axis::PosY), None);
+    }
+
+    #[test]
+    fn axis_aligned_mounting_maps_axes() {
+        // 电路板竖直安装：传感器X轴朝上，Z轴朝设备前方，Y轴随之朝右
+        let mounting = Mounting::from_axes(Axis::PosZ, Axis::PosX).unwrap();
+        assert!((mounting.apply(&Vector3::x()) - Vector3::z()).amax() < 1e-6);
+        assert!((mounting.apply(&Vector3::z()) - Vector3::x()).amax() < 1e-6);
+        assert!((mounting.apply(&Vector3::y()) + Vector3::y()).amax() < 1e-6);
+
+        let tilted = Mounting::from_quaternion(UnitQuaternion::from_euler_angles(0.1, 0.0, 0.0));
+        assert_eq!(tilted.axes(), None);
+    }
+
+    #[test]
+    fn rotated_sensor_gives_identical_attitude() {
+        let arbitrary =
+            Mounting::from_quaternion(UnitQuaternion::from_euler_angles(0.4, -1.1, 2.3));
+        let rates = [Vector3::new(0.5, 0.0, 0.0), Vector3::new(0.0, -0.3, 0.8)];
+
+        for mounting in Mounting::presets().into_iter().chain([arbitrary]) {
+            let mut sim = Simulator::new(UnitQuaternion::from_euler_angles(0.3, -0.2, 0.0));
+            let mut reference = Madgwick::new(0.01, 0.1);
+            let mut mounted = Madgwick::new(0.01, 0.1);
+            let to_sensor = mounting.quaternion().inverse();
+
+            for rate in rates {
+                for _ in 0..300 {
+                    // 设备坐标系中的读数，经安装旋转的逆变换得到传感器实际的读数
+                    let sample = sim.step(rate);
+                    reference.update_imu(&sample.gyro, &sample.accel).unwrap();
+                    let (gyro, accel) = (to_sensor * sample.gyro, to_sensor * sample.accel);
+                    mounted
+                        .update_imu(&mounting.apply(&gyro), &mounting.apply(&accel))
+                        .unwrap();
+                }
+            }
+
+            let error = reference.quaternion().angle_to(mounted.quaternion());
+            assert!(error.to_degrees() < 0.01, "{:?}", mounting.axes());
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! 姿态的各种表示
+//!
+//! 滤波器输出的四元数没有奇异点，但不直观。[`Orientation`]由四元数换算出旋转矩阵、
+//! 按指定转动顺序的欧拉角、轴角和倾斜补偿后的航向，显示时按需选择。
+//!
+//! 欧拉角在中间一次转动为±90°时出现万向节锁：第一次和第三次转动绕同一条轴，
+//! 只有两者的和（或差）确定，数值上则表现为接近±90°时另外两个角剧烈跳变。
+//! 换算时以旋转矩阵元素的`atan2`求角，接近锁定时把第三个角取为零，结果始终有限，
+//! 并且按同样的顺序合成后与原姿态一致。
+//!
+//! 地球坐标系为X轴指北（有磁力计时为磁北，否则为启动时的朝向）、Z轴向上，
+//! 四元数将机体坐标系中的向量转到地球坐标系。
+
+use core::f32::consts::TAU;
+use nalgebra::{ComplexField, RealField, Rotation3, Unit, UnitQuaternion, Vector3};
+
+/// 中间一次转动的余弦小于该值时按万向节锁处理（与±90°相差约0.006°）
+const GIMBAL_LOCK_COS: f32 = 1e-4;
+
+/// 欧拉角的转动顺序（绕机体坐标轴依次转动）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EulerSequence {
+    /// 先绕Z轴偏航，再绕Y轴俯仰，最后绕X轴滚转，俯仰±90°时锁定
+    #[default]
+    Zyx,
+    /// 先绕X轴，再绕Y轴，最后绕Z轴，绕Y轴±90°时锁定
+    Xyz,
+}
+
+impl EulerSequence {
+    /// 全部转动顺序
+    pub const ALL: [Self; 2] = [Self::Zyx, Self::Xyz];
+
+    /// 依次转动所绕的机体坐标轴
+    pub fn axes(self) -> [Unit<Vector3<f32>>; 3] {
+        match self {
+            Self::Zyx => [Vector3::z_axis(), Vector3::y_axis(), Vector3::x_axis()],
+            Self::Xyz => [Vector3::x_axis(), Vector3::y_axis(), Vector3::z_axis()],
+        }
+    }
+
+    /// 显示用名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Zyx => "ZYX",
+            Self::Xyz => "XYZ",
+        }
+    }
+}
+
+/// 欧拉角
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EulerAngles {
+    /// 转动顺序
+    pub sequence: EulerSequence,
+    /// 按转动顺序的三个角（rad）：第一、三个在±π内，第二个在±π/2内。
+    /// ZYX顺序依次为偏航、俯仰、滚转
+    pub angles: [f32; 3],
+    /// 是否处于万向节锁，此时第三个角取为零
+    pub gimbal_lock: bool,
+}
+
+impl EulerAngles {
+    /// 按转动顺序合成姿态
+    pub fn to_quaternion(&self) -> UnitQuaternion<f32> {
+        let [first, second, third] = self.sequence.axes();
+        let [a, b, c] = self.angles;
+        UnitQuaternion::from_axis_angle(&first, a)
+            * UnitQuaternion::from_axis_angle(&second, b)
+            * UnitQuaternion::from_axis_angle(&third, c)
+    }
+}
+
+/// 姿态
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orientation {
+    /// 姿态四元数
+    quat: UnitQuaternion<f32>,
+    /// 磁偏角（rad），磁北在真北以东为正
+    declination: f32,
+}
+
+impl Orientation {
+    /// 由滤波器输出的四元数创建，磁偏角为零
+    pub fn new(quat: UnitQuaternion<f32>) -> Self {
+        Self {
+            quat,
+            declination: 0.0,
+        }
+    }
+
+    /// 设置磁偏角（rad），只影响[`Self::heading`]
+    pub fn with_declination(self, declination: f32) -> Self {
+        Self {
+            declination,
+            ..self
+        }
+    }
+
+    /// 姿态四元数
+    pub fn quaternion(&self) -> &UnitQuaternion<f32> {
+        &self.quat
+    }
+
+    /// 旋转矩阵，各列为机体坐标轴在地球坐标系中的方向
+    pub fn rotation_matrix(&self) -> Rotation3<f32> {
+        self.quat.to_rotation_matrix()
+    }
+
+    /// 按转动顺序分解为欧拉角
+    pub fn euler(&self, sequence: EulerSequence) -> EulerAngles {
+        let m = self.rotation_matrix().into_inner();
+
+        // (中间角的正弦, 中间角的余弦, 第一个角, 第三个角, 锁定时的第一个角)
+        let (sin, cos, first, third, locked) = match sequence {
+            // R = Rz(a)·Ry(b)·Rx(c)，锁定时 R01 = -sin(a)、R11 = cos(a)
+            EulerSequence::Zyx => (
+                -m[(2, 0)],
+                ComplexField::sqrt(m[(0, 0)] * m[(0, 0)] + m[(1, 0)] * m[(1, 0)]),
+                (m[(1, 0)], m[(0, 0)]),
+                (m[(2, 1)], m[(2, 2)]),
+                (-m[(0, 1)], m[(1, 1)]),
+            ),
+            // R = Rx(a)·Ry(b)·Rz(c)，锁定时 R21 = sin(a)、R11 = cos(a)
+            EulerSequence::Xyz => (
+                m[(0, 2)],
+                ComplexField::sqrt(m[(0, 0)] * m[(0, 0)] + m[(0, 1)] * m[(0, 1)]),
+                (-m[(1, 2)], m[(2, 2)]),
+                (-m[(0, 1)], m[(0, 0)]),
+                (m[(2, 1)], m[(1, 1)]),
+            ),
+        };
+
+        let second = RealField::atan2(sin, cos);
+        let gimbal_lock = cos < GIMBAL_LOCK_COS;
+        let angles = if gimbal_lock {
+            [RealField::atan2(locked.0, locked.1), second, 0.0]
+        } else {
+            [
+                RealField::atan2(first.0, first.1),
+                second,
+                RealField::atan2(third.0, third.1),
+            ]
+        };
+        EulerAngles {
+            sequence,
+            angles,
+            gimbal_lock,
+        }
+    }
+
+    /// 轴角：绕单位轴转过的角度（rad，0-π），不转动时轴取Z轴
+    pub fn axis_angle(&self) -> (Unit<Vector3<f32>>, f32) {
+        self.quat.axis_angle().unwrap_or((Vector3::z_axis(), 0.0))
+    }
+
+    /// 倾斜角（rad，0-π）：机体Z轴偏离竖直向上的角度
+    pub fn tilt(&self) -> f32 {
+        let up = self.quat.inverse() * Vector3::z();
+        RealField::atan2(ComplexField::sqrt(up.x * up.x + up.y * up.y), up.z)
+    }
+
+    /// 航向角（rad，0-2π）：机体X轴的水平投影从真北顺时针转过的角度
+    ///
+    /// 取ZYX欧拉角的偏航角，已补偿滚转和俯仰，电路板倾斜时不变。
+    /// 机头接近竖直时按滚转角为零处理，即取机体Z轴（机头朝上时为背面）的水平朝向。
+    pub fn heading(&self) -> f32 {
+        let [yaw, _, _] = self.euler(EulerSequence::Zyx).angles;
+        let heading = (self.declination - yaw) % TAU;
+        if heading < 0.0 {
+            heading + TAU
+        } else {
+            heading
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::{FRAC_PI_2, PI};
+
+    /// 两个姿态之间的夹角（°）
+    fn angle_between(a: &UnitQuaternion<f32>, b: &UnitQuaternion<f32>) -> f32 {
+        a.angle_to(b).to_degrees()
+    }
+
+    /// 绕各轴的若干角度组合，含±90°附近的俯仰
+    fn attitudes() -> impl Iterator<Item = UnitQuaternion<f32>> {
+        let angles = [-170.0, -90.0, -45.0, 0.0, 30.0, 120.0, 180.0];
+        let pitches = [-90.0, -89.999, -89.9, -60.0, 0.0, 45.0, 89.9, 89.999, 90.0];
+        angles.into_iter().flat_map(move |roll: f32| {
+            pitches.into_iter().flat_map(move |pitch: f32| {
+                angles.into_iter().map(move |yaw: f32| {
+                    UnitQuaternion::from_euler_angles(
+                        roll.to_radians(),
+                        pitch.to_radians(),
+                        yaw.to_radians(),
+                    )
+                })
+            })
+        })
+    }
+
+    #[test]
+    fn euler_angles_round_trip_through_gimbal_lock() {
+        for sequence in EulerSequence::ALL {
+            // 四元数q和-q表示同一姿态
+            let negated = attitudes().map(|q| UnitQuaternion::new_unchecked(-q.into_inner()));
+            for quat in attitudes().chain(negated) {
+                let orientation = Orientation::new(quat);
+                let euler = orientation.euler(sequence);
+                assert!(euler.angles.iter().all(|a| a.is_finite()), "{euler:?}");
+                assert!(euler.angles[1].abs() <= FRAC_PI_2 + 1e-6);
+
+                let error = angle_between(&euler.to_quaternion(), &quat);
+                assert!(error < 0.01, "{sequence:?} {euler:?}: {error}°");
+            }
+        }
+    }
+
+    #[test]
+    fn zyx_matches_yaw_pitch_roll() {
+        let quat = UnitQuaternion::from_euler_angles(0.3, -0.5, 2.0);
+        let euler = Orientation::new(quat).euler(EulerSequence::Zyx);
+        let (roll, pitch, yaw) = quat.euler_angles();
+        assert!(!euler.gimbal_lock);
+        for (a, b) in euler.angles.iter().zip([yaw, pitch, roll]) {
+            assert!((a - b).abs() < 1e-5, "{euler:?}");
+        }
+    }
+
+    #[test]
+    fn gimbal_lock_keeps_combined_rotation() {
+        // 俯仰90°时偏航和滚转绕同一条轴，只有偏航减滚转确定
+        for (yaw, roll) in [(30.0f32, 0.0f32), (50.0, 20.0), (-100.0, 80.0)] {
+            let quat =
+                UnitQuaternion::from_euler_angles(roll.to_radians(), FRAC_PI_2, yaw.to_radians());
+            let euler = Orientation::new(quat).euler(EulerSequence::Zyx);
+            assert!(euler.gimbal_lock);
+            assert_eq!(euler.angles[2], 0.0);
+            let combined = euler.angles[0].to_degrees();
+            assert!((combined - (yaw - roll)).abs() < 0.01, "{euler:?}");
+        }
+
+        // 俯仰略小于90°时未锁定，各角可能跳变但合成的姿态不变
+        let quat = UnitQuaternion::from_euler_angles(0.2, 89.99f32.to_radians(), 0.5);
+        let euler = Orientation::new(quat).euler(EulerSequence::Zyx);
+        assert!(!euler.gimbal_lock);
+        assert!(angle_between(&euler.to_quaternion(), &quat) < 0.01);
+    }
+
+    #[test]
+    fn xyz_locks_at_right_angle_about_y() {
+        let quat = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 0.4)
+            * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -FRAC_PI_2)
+            * UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.1);
+        let euler = Orientation::new(quat).euler(EulerSequence::Xyz);
+        assert!(euler.gimbal_lock);
+        assert!((euler.angles[1] + FRAC_PI_2).abs() < 1e-3);
+        assert!(angle_between(&euler.to_quaternion(), &quat) < 0.01);
+    }
+
+    #[test]
+    fn heading_compensates_tilt() {
+        // 机头指向北偏东30°（绕Z轴转-30°），任意滚转和俯仰下航向不变
+        for (roll, pitch) in [(0.0f32, 0.0f32), (40.0, 20.0), (-60.0, -45.0), (10.0, 80.0)] {
+            let quat = UnitQuaternion::from_euler_angles(
+                roll.to_radians(),
+                pitch.to_radians(),
+                -30f32.to_radians(),
+            );
+            let heading = Orientation::new(quat).heading().to_degrees();
+            assert!((heading - 30.0).abs() < 0.01, "{roll}/{pitch}: {heading}°");
+        }
+
+        // 磁偏角西偏7°
+        let quat = UnitQuaternion::from_euler_angles(0.0, 0.0, -30f32.to_radians());
+        let heading = Orientation::new(quat)
+            .with_declination(-7f32.to_radians())
+            .heading()
+            .to_degrees();
+        assert!((heading - 23.0).abs() < 0.01, "{heading}°");
+    }
+
+    #[test]
+    fn heading_stays_continuous_up_to_vertical() {
+        // 机头从水平抬到竖直，航向保持不变，不在90°附近跳变
+        for pitch in [0.0f32, 60.0, 89.0, 89.9, 89.99, 89.999, 90.0] {
+            for (pitch, expected) in [(pitch, 30.0), (-pitch, 30.0)] {
+                let quat =
+                    UnitQuaternion::from_euler_angles(0.0, pitch.to_radians(), -30f32.to_radians());
+                let heading = Orientation::new(quat).heading().to_degrees();
+                assert!((heading - expected).abs() < 0.05, "{pitch}: {heading}°");
+            }
+        }
+
+        // 航向在0-360°内
+        let quat = UnitQuaternion::from_euler_angles(0.0, 0.0, 0.001);
+        let heading = Orientation::new(quat).heading();
+        assert!((0.0..TAU).contains(&heading));
+        assert!((heading - TAU).abs() < 0.01);
+    }
+
+    #[test]
+    fn axis_angle_and_tilt() {
+        let identity = Orientation::new(UnitQuaternion::identity());
+        assert_eq!(identity.axis_angle(), (Vector3::z_axis(), 0.0));
+        assert_eq!(identity.tilt(), 0.0);
+
+        // 四元数q和-q表示同一姿态，转角取0-π
+        let quat = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 1.2);
+        let negated = UnitQuaternion::new_unchecked(-quat.into_inner());
+        for orientation in [Orientation::new(quat), Orientation::new(negated)] {
+            let (axis, angle) = orientation.axis_angle();
+            assert!((axis.into_inner() - Vector3::y()).amax() < 1e-5);
+            assert!((angle - 1.2).abs() < 1e-5);
+            assert!((orientation.tilt() - 1.2).abs() < 1e-5);
+        }
+
+        // 倒置
+        let flipped = Orientation::new(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI));
+        let (_, angle) = flipped.axis_angle();
+        assert!((angle - PI).abs() < 1e-5);
+        assert!((flipped.tilt() - PI).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotation_matrix_columns_are_body_axes() {
+        let quat = UnitQuaternion::from_euler_angles(0.3, -0.5, 2.0);
+        let matrix = Orientation::new(quat).rotation_matrix();
+        for (column, axis) in [Vector3::x(), Vector3::y(), Vector3::z()]
+            .iter()
+            .enumerate()
+        {
+            let expected = quat * axis;
+            assert!((matrix.matrix().column(column) - expected).amax() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,587 @@
+//! 计步器
+//!
+//! 行走时每一步脚跟着地都使加速度出现一次冲击，加速度模长随步伐以1-3Hz周期变化。
+//! 模长与设备朝向无关，放在口袋里或拿在手上都可以计步。
+//!
+//! 模长减去1g后经带通滤波器去掉零偏（直流分量）和振动噪声，再检测波峰：
+//! 波峰须高于自适应阈值（本段行走平均峰高的一定比例，不低于最小峰高），与上一步相隔不少于
+//! 最短步间隔，且两个波峰之间信号须先降到负阈值以下（回差），避免一步中的多个小峰被重复计数。
+//! 连续出现若干个间隔正常的波峰后才确认开始行走并补记这些步，偶尔的磕碰、敲击不会计步。
+//!
+//! 步频由最近几步的平均间隔求得，距离为步数乘以步长。
+//!
+//! 带通滤波器按创建时给定的采样周期设计，截止频率须低于该采样率的一半。
+
+use super::units::Acceleration;
+use nalgebra::{ComplexField, RealField};
+
+/// 步长与身高之比，用于由身高估计步长
+const STEP_LENGTH_RATIO: f32 = 0.415;
+
+/// 更新平均峰高的平滑系数
+const PEAK_SMOOTHING: f32 = 0.5;
+
+/// 超过平均步间隔仍没有检测到下一步时，平均峰高衰减的时间常数（秒），
+/// 步伐变轻时阈值随之降低
+const PEAK_DECAY_TIME: f32 = 0.5;
+
+/// 更新平均步间隔的平滑系数
+const INTERVAL_SMOOTHING: f32 = 0.3;
+
+/// 回差：两个波峰之间信号须降到负的阈值乘以该比例以下
+const VALLEY_RATIO: f32 = 0.5;
+
+/// 计步参数
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PedometerConfig {
+    /// 带通滤波器的下截止频率（Hz）
+    pub low_frequency: f32,
+    /// 带通滤波器的上截止频率（Hz）
+    pub high_frequency: f32,
+    /// 最小峰高（g），阈值不低于该值
+    pub min_peak: f32,
+    /// 阈值与本段行走平均峰高之比
+    pub threshold_ratio: f32,
+    /// 最短步间隔（秒），即最高步频
+    pub min_step_interval: f32,
+    /// 最长步间隔（秒），超过时认为行走已停止
+    pub max_step_interval: f32,
+    /// 连续出现多少步后确认开始行走
+    pub confirm_steps: u32,
+    /// 步长（m）
+    pub step_length: f32,
+}
+
+impl Default for PedometerConfig {
+    fn default() -> Self {
+        Self {
+            low_frequency: 1.0,
+            high_frequency: 3.0,
+            min_peak: 0.05,
+            threshold_ratio: 0.5,
+            min_step_interval: 0.25,
+            max_step_interval: 2.0,
+            confirm_steps: 5,
+            step_length: 0.7,
+        }
+    }
+}
+
+impl PedometerConfig {
+    /// 以身高（m）估计步长，其余参数为默认值
+    pub fn for_height(height: f32) -> Self {
+        Self {
+            step_length: height * STEP_LENGTH_RATIO,
+            ..Self::default()
+        }
+    }
+}
+
+/// 计步参数无法用于给定采样周期的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// 采样周期不为正
+    InvalidPeriod,
+    /// 截止频率不为正，或下截止频率不低于上截止频率
+    InvalidBand,
+    /// 上截止频率不低于采样率的一半（奈奎斯特频率）
+    AboveNyquist,
+    /// 最短步间隔为负，或不短于最长步间隔
+    InvalidStepInterval,
+    /// 确认开始行走所需的步数为零
+    InvalidConfirmSteps,
+}
+
+impl ConfigError {
+    /// 显示用名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::InvalidPeriod => "PERIOD",
+            Self::InvalidBand => "BAND",
+            Self::AboveNyquist => "NYQUIST",
+            Self::InvalidStepInterval => "INTERVAL",
+            Self::InvalidConfirmSteps => "CONFIRM",
+        }
+    }
+}
+
+/// 计步结果
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Steps {
+    /// 累计步数
+    pub count: u32,
+    /// 步频（步/分钟），没有在行走时为零
+    pub cadence: f32,
+    /// 累计距离（m）
+    pub distance: f32,
+    /// 是否正在行走
+    pub walking: bool,
+}
+
+/// 二阶带通滤波器（峰值增益为1）
+#[derive(Clone, Copy, Debug, Default)]
+struct BandPass {
+    /// 分子系数，b1为零、b2为-b0
+    b0: f32,
+    /// 分母系数
+    a1: f32,
+    a2: f32,
+    /// 前两个输入
+    x: [f32; 2],
+    /// 前两个输出
+    y: [f32; 2],
+}
+
+impl BandPass {
+    /// 以中心频率为上下截止频率的几何平均设计滤波器
+    ///
+    /// # 参数
+    /// - `low`: 下截止频率（Hz）
+    /// - `high`: 上截止频率（Hz）
+    /// - `period`: 采样周期（秒）
+    fn new(low: f32, high: f32, period: f32) -> Self {
+        let center = ComplexField::sqrt(low * high);
+        let quality = center / (high - low);
+        let omega = f32::two_pi() * center * period;
+        let (sin, cos) = ComplexField::sin_cos(omega);
+        let alpha = sin / (2.0 * quality);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: alpha / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            ..Self::default()
+        }
+    }
+
+    /// 输入一个采样，返回滤波结果
+    fn update(&mut self, x: f32) -> f32 {
+        let y = self.b0 * (x - self.x[1]) - self.a1 * self.y[0] - self.a2 * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// 基于加速度模长的计步器
+#[derive(Clone, Copy, Debug)]
+pub struct Pedometer {
+    /// 计步参数
+    config: PedometerConfig,
+    /// 采样周期（秒）
+    period: f32,
+    /// 带通滤波器
+    filter: BandPass,
+    /// 上一个滤波结果
+    previous: f32,
+    /// 上一个采样时信号是否在上升
+    rising: bool,
+    /// 上一步之后信号是否已降到谷值阈值以下
+    armed: bool,
+    /// 本段行走的平均峰高（g），为零表示还没有波峰
+    peak_average: f32,
+    /// 距上一步的时间（秒）
+    since_step: f32,
+    /// 本段行走的平均步间隔（秒），为零表示还没有间隔
+    interval_average: f32,
+    /// 尚未确认行走时检测到的步数
+    pending: u32,
+    /// 是否正在行走
+    walking: bool,
+    /// 累计步数
+    count: u32,
+    /// 累计距离（m）
+    distance: f32,
+}
+
+impl Pedometer {
+    /// 以指定参数创建计步器
+    ///
+    /// # 参数
+    /// - `config`: 计步参数
+    /// - `period`: 采样周期（秒）
+    ///
+    /// # 返回值
+    /// - `Ok(Pedometer)`: 创建的计步器
+    /// - `Err(ConfigError)`: 截止频率不合理或超出该采样率能表示的范围，或步间隔、确认步数不合理
+    pub fn new(config: PedometerConfig, period: f32) -> Result<Self, ConfigError> {
+        if !period.is_finite() || period <= 0.0 {
+            return Err(ConfigError::InvalidPeriod);
+        }
+        let (low, high) = (config.low_frequency, config.high_frequency);
+        if !low.is_finite() || !high.is_finite() || low <= 0.0 || high <= low {
+            return Err(ConfigError::InvalidBand);
+        }
+        if high >= 0.5 / period {
+            return Err(ConfigError::AboveNyquist);
+        }
+        let (min, max) = (config.min_step_interval, config.max_step_interval);
+        if !min.is_finite() || !max.is_finite() || min < 0.0 || min >= max {
+            return Err(ConfigError::InvalidStepInterval);
+        }
+        if config.confirm_steps == 0 {
+            return Err(ConfigError::InvalidConfirmSteps);
+        }
+
+        Ok(Self::build(config, period))
+    }
+
+    /// 计步参数
+    pub fn config(&self) -> &PedometerConfig {
+        &self.config
+    }
+
+    /// 采样周期（秒）
+    pub fn period(&self) -> f32 {
+        self.period
+    }
+
+    /// 当前的计步结果
+    pub fn steps(&self) -> Steps {
+        let cadence = if self.walking && self.interval_average > 0.0 {
+            60.0 / self.interval_average
+        } else {
+            0.0
+        };
+        Steps {
+            count: self.count,
+            cadence,
+            distance: self.distance,
+            walking: self.walking,
+        }
+    }
+
+    /// 加入一个采样，采样间隔须为创建时给定的采样周期
+    ///
+    /// # 参数
+    /// - `accel`: 加速度计读数，坐标系不限
+    pub fn update(&mut self, accel: &Acceleration) {
+        let dt = self.period;
+        let signal = self.filter.update(accel.as_g().norm() - 1.0);
+
+        self.since_step += dt;
+        if self.since_step > self.interval_average {
+            self.peak_average *= 1.0 - (dt / PEAK_DECAY_TIME).min(1.0);
+        }
+        if self.since_step > self.config.max_step_interval {
+            self.stop();
+        }
+
+        let threshold = self.threshold();
+        if signal < -threshold * VALLEY_RATIO {
+            self.armed = true;
+        }
+
+        // 信号由升转降时上一个采样是波峰
+        let peak = self.rising && signal < self.previous;
+        if peak && self.armed && self.previous > threshold {
+            self.step(self.previous);
+        }
+        self.rising = signal > self.previous;
+        self.previous = signal;
+    }
+
+    /// 清零步数和距离，重新开始检测
+    pub fn reset(&mut self) {
+        *self = Self::build(self.config, self.period);
+    }
+
+    /// 以已检查过的参数创建计步器
+    fn build(config: PedometerConfig, period: f32) -> Self {
+        Self {
+            config,
+            period,
+            filter: BandPass::new(config.low_frequency, config.high_frequency, period),
+            previous: 0.0,
+            rising: false,
+            armed: true,
+            peak_average: 0.0,
+            since_step: f32::INFINITY,
+            interval_average: 0.0,
+            pending: 0,
+            walking: false,
+            count: 0,
+            distance: 0.0,
+        }
+    }
+
+    /// 当前的峰高阈值（g）
+    fn threshold(&self) -> f32 {
+        (self.peak_average * self.config.threshold_ratio).max(self.config.min_peak)
+    }
+
+    /// 检测到高于阈值的波峰
+    fn step(&mut self, height: f32) {
+        if self.since_step < self.config.min_step_interval {
+            return;
+        }
+
+        let interval = self.since_step;
+        self.since_step = 0.0;
+        self.armed = false;
+        self.peak_average = if self.peak_average > 0.0 {
+            self.peak_average + (height - self.peak_average) * PEAK_SMOOTHING
+        } else {
+            height
+        };
+
+        // 本段行走的第一步没有间隔
+        if interval.is_finite() {
+            self.interval_average = if self.interval_average > 0.0 {
+                self.interval_average + (interval - self.interval_average) * INTERVAL_SMOOTHING
+            } else {
+                interval
+            };
+        }
+
+        if self.walking {
+            self.add_steps(1);
+            return;
+        }
+        self.pending += 1;
+        if self.pending >= self.config.confirm_steps {
+            self.walking = true;
+            self.add_steps(self.pending);
+            self.pending = 0;
+        }
+    }
+
+    /// 计入`steps`步
+    fn add_steps(&mut self, steps: u32) {
+        self.count += steps;
+        self.distance += steps as f32 * self.config.step_length;
+    }
+
+    /// 行走停止：丢弃未确认的步，下一段行走重新估计峰高和步频
+    fn stop(&mut self) {
+        self.since_step = f32::INFINITY;
+        self.walking = false;
+        self.pending = 0;
+        self.peak_average = 0.0;
+        self.interval_average = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::recording::{Recording, synthetic};
+    use crate::app::imu::replay::{Replay, Trace};
+    use crate::app::imu::units::AngularRate;
+    use core::f32::consts::PI;
+    use nalgebra::{UnitQuaternion, Vector3};
+
+    impl Replay for Pedometer {
+        fn update(&mut self, accel: &Acceleration, _gyro: &AngularRate, dt: f32) {
+            assert_eq!(dt, self.period());
+            Pedometer::update(self, accel);
+        }
+    }
+
+    /// 设备以任意朝向放置、静止1秒开始的模拟记录
+    fn simulate(config: PedometerConfig) -> Trace<Pedometer> {
+        let attitude = UnitQuaternion::from_euler_angles(1.2, -0.4, 0.7);
+        Trace::new(attitude, Pedometer::new(config, 0.01).unwrap())
+    }
+
+    /// 以步频`cadence`（步/分钟）行走`steps`步，竖直加速度幅值约为`amplitude`（g）
+    ///
+    /// 每一步的竖直加速度为基波加上二次谐波（脚跟着地的冲击），同时有前后方向的加减速
+    /// 和以两步为周期的左右摆动，躯干随之轻微转动。
+    fn walk(trace: &mut Trace<Pedometer>, cadence: f32, steps: u32, amplitude: f32) {
+        let period = trace.sim.sample_period;
+        let seconds = steps as f32 * 60.0 / cadence;
+        for n in 1..=trace.samples(seconds) {
+            // 步态相位（步）
+            let phase = n as f32 * cadence / 60.0 * period;
+            let angle = 2.0 * PI * phase;
+            let vertical = angle.sin() + 0.4 * (2.0 * angle).sin();
+            let forward = 0.3 * (angle + 1.2).sin();
+            let lateral = 0.2 * (angle / 2.0).sin();
+            let linear = Vector3::new(forward, lateral, vertical) * amplitude;
+            let rate = Vector3::new(0.0, 0.0, 0.3 * (angle / 2.0).cos());
+            trace.step(rate, linear);
+        }
+    }
+
+    /// 行走、跑步和拿在手里不走动的模拟数据，说明中的`steps`为生成的步数
+    ///
+    /// 由`tools/fixturegen/steps.py`的运动模型生成，不是传感器实测录制，
+    /// 只能说明计步算法与该模型一致；有实测录制（人工数出步数）后应替换。
+    const SYNTHETIC_TRACES: [Recording; 3] = [
+        synthetic!("steps_walking.csv"),
+        synthetic!("steps_running.csv"),
+        synthetic!("steps_idle.csv"),
+    ];
+
+    #[test]
+    fn counts_steps_in_synthetic_traces() {
+        for recording in &SYNTHETIC_TRACES {
+            let period = recording.sample_period();
+            let mut pedometer = Pedometer::new(PedometerConfig::default(), period).unwrap();
+            recording.replay(&mut pedometer);
+            let expected = recording.value("steps") as u32;
+            let steps = pedometer.steps();
+            assert!(
+                steps.count.abs_diff(expected) <= 2,
+                "expected {expected}: {steps:?}"
+            );
+            // 模拟数据都以静止结束
+            assert!(!steps.walking, "{steps:?}");
+        }
+    }
+
+    #[test]
+    fn band_pass_keeps_walking_frequencies() {
+        // 稳态增益：中心频率附近接近1，直流和高频振动被抑制
+        let gain = |frequency: f32| {
+            let mut filter = BandPass::new(1.0, 3.0, 0.01);
+            let mut peak: f32 = 0.0;
+            for n in 0..1000 {
+                let y = filter.update((2.0 * PI * frequency * n as f32 * 0.01).sin() + 0.5);
+                if n >= 500 {
+                    peak = peak.max(y.abs());
+                }
+            }
+            peak
+        };
+        assert!(gain(1.7) > 0.95);
+        assert!(gain(1.0) > 0.65 && gain(3.0) > 0.65);
+        assert!(gain(0.05) < 0.1, "{}", gain(0.05));
+        assert!(gain(15.0) < 0.2, "{}", gain(15.0));
+    }
+
+    #[test]
+    fn counts_steps_at_different_cadences() {
+        for (cadence, amplitude) in [(80.0, 0.15), (110.0, 0.3), (130.0, 0.5), (170.0, 1.0)] {
+            let mut trace = simulate(PedometerConfig::default());
+            walk(&mut trace, cadence, 60, amplitude);
+            let steps = trace.steps();
+            assert!(steps.walking);
+            assert!(steps.count.abs_diff(60) <= 1, "{cadence}: {steps:?}");
+            assert!(
+                (steps.cadence - cadence).abs() < cadence * 0.03,
+                "{cadence}: {steps:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn adapts_to_changing_intensity() {
+        // 慢走后跑步再慢走，阈值随峰高变化，跑步的冲击不会掩盖之后较弱的步伐
+        let mut trace = simulate(PedometerConfig::default());
+        walk(&mut trace, 100.0, 30, 0.2);
+        walk(&mut trace, 160.0, 40, 1.2);
+        walk(&mut trace, 100.0, 30, 0.2);
+        let steps = trace.steps();
+        assert!(steps.count.abs_diff(100) <= 2, "{steps:?}");
+        assert!((steps.cadence - 100.0).abs() < 5.0, "{steps:?}");
+    }
+
+    #[test]
+    fn stops_counting_when_standing() {
+        let mut trace = simulate(PedometerConfig::default());
+        trace.still(10.0);
+        assert_eq!(trace.steps(), Steps::default());
+
+        walk(&mut trace, 120.0, 20, 0.3);
+        let count = trace.steps().count;
+        assert!(count.abs_diff(20) <= 1, "{count}");
+
+        // 停下后步频归零，步数保持
+        trace.still(3.0);
+        let steps = trace.steps();
+        assert!(!steps.walking);
+        assert_eq!(steps.cadence, 0.0);
+        assert_eq!(steps.count, count);
+    }
+
+    #[test]
+    fn ignores_bumps_and_short_shuffles() {
+        let mut trace = simulate(PedometerConfig::default());
+
+        // 相隔较远的几次磕碰
+        for _ in 0..5 {
+            for value in [1.5, -0.6, 0.2] {
+                trace.step(Vector3::zeros(), Vector3::new(0.0, 0.0, value));
+            }
+            trace.still(2.5);
+        }
+        assert_eq!(trace.steps().count, 0);
+
+        // 原地挪动三步后停下，不足以确认行走
+        walk(&mut trace, 100.0, 3, 0.3);
+        trace.still(3.0);
+        assert_eq!(trace.steps().count, 0);
+    }
+
+    #[test]
+    fn rejects_unusable_config() {
+        // 100Hz采样时上截止频率须低于50Hz，5Hz采样时须低于2.5Hz
+        let cases = [
+            (1.0, 3.0, 0.01, None),
+            (1.0, 49.0, 0.01, None),
+            (3.0, 1.0, 0.01, Some(ConfigError::InvalidBand)),
+            (2.0, 2.0, 0.01, Some(ConfigError::InvalidBand)),
+            (0.0, 3.0, 0.01, Some(ConfigError::InvalidBand)),
+            (1.0, f32::NAN, 0.01, Some(ConfigError::InvalidBand)),
+            (1.0, 50.0, 0.01, Some(ConfigError::AboveNyquist)),
+            (1.0, 3.0, 0.2, Some(ConfigError::AboveNyquist)),
+            (1.0, 3.0, 0.0, Some(ConfigError::InvalidPeriod)),
+            (1.0, 3.0, -0.01, Some(ConfigError::InvalidPeriod)),
+        ];
+        for (low_frequency, high_frequency, period, expected) in cases {
+            let config = PedometerConfig {
+                low_frequency,
+                high_frequency,
+                ..PedometerConfig::default()
+            };
+            assert_eq!(
+                Pedometer::new(config, period).err(),
+                expected,
+                "{low_frequency}-{high_frequency}Hz, {period}s"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_inconsistent_step_settings() {
+        let cases = [
+            (0.25, 2.0, 5, None),
+            (0.0, 2.0, 1, None),
+            (2.0, 2.0, 5, Some(ConfigError::InvalidStepInterval)),
+            (2.5, 2.0, 5, Some(ConfigError::InvalidStepInterval)),
+            (-0.1, 2.0, 5, Some(ConfigError::InvalidStepInterval)),
+            (0.25, f32::NAN, 5, Some(ConfigError::InvalidStepInterval)),
+            (0.25, 2.0, 0, Some(ConfigError::InvalidConfirmSteps)),
+        ];
+        for (min_step_interval, max_step_interval, confirm_steps, expected) in cases {
+            let config = PedometerConfig {
+                min_step_interval,
+                max_step_interval,
+                confirm_steps,
+                ..PedometerConfig::default()
+            };
+            assert_eq!(
+                Pedometer::new(config, 0.01).err(),
+                expected,
+                "{min_step_interval}-{max_step_interval}s, {confirm_steps} steps"
+            );
+        }
+    }
+
+    #[test]
+    fn estimates_distance_from_step_length() {
+        let config = PedometerConfig::for_height(1.75);
+        assert!((config.step_length - 0.726).abs() < 1e-3);
+
+        let mut trace = simulate(config);
+        walk(&mut trace, 110.0, 40, 0.3);
+        let steps = trace.steps();
+        assert!((steps.distance - steps.count as f32 * config.step_length).abs() < 1e-3);
+
+        trace.reset();
+        assert_eq!(trace.steps(), Steps::default());
+        assert_eq!(*trace.config(), config);
+    }
+}
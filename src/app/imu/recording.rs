@@ -0,0 +1,116 @@
+//! 录制的IMU数据
+//!
+//! 测试数据保存在`tests/data/`下的CSV文件中，每行依次为加速度计和陀螺仪的16位原始值
+//! `ax,ay,az,gx,gy,gz`，可用`examples/record_imu.rs`从电路板上录制。
+//! `tests/data/synthetic/`下是`tools/fixturegen/`按运动模型生成的同格式模拟数据，
+//! 说明中的`source`注明了生成脚本，在有实测录制之前代替录制数据。
+//! 以`#`开头的行为说明，其中`# 键: 值`形式的行给出采样率（`rate`，Hz）、
+//! 量程（`accel_range`，g；`gyro_range`，°/s）以及测试断言所用的已知结果。
+
+use super::simulation::Sample;
+use super::units::{AccelRange, Acceleration, AngularRate, GyroRange};
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// `tests/data/synthetic/`下的模拟数据文件`$file`
+macro_rules! synthetic {
+    ($file:literal) => {
+        $crate::app::imu::recording::Recording::new(include_str!(concat!(
+            "../../../tests/data/synthetic/",
+            $file
+        )))
+    };
+}
+pub(crate) use synthetic;
+
+/// 一段录制数据
+pub struct Recording {
+    text: &'static str,
+}
+
+impl Recording {
+    /// 由CSV文本创建，通常配合`include_str!`使用
+    pub const fn new(text: &'static str) -> Self {
+        Self { text }
+    }
+
+    /// 读取说明中`key`对应的值
+    pub fn meta(&self, key: &str) -> Option<&'static str> {
+        self.text
+            .lines()
+            .filter_map(|line| line.strip_prefix('#'))
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == key).then(|| value.trim())
+            })
+    }
+
+    /// 读取说明中`key`对应的数值
+    ///
+    /// # Panics
+    /// 缺少该项或不是数值时panic
+    pub fn value(&self, key: &str) -> f32 {
+        self.meta(key)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_else(|| panic!("recording has no numeric `{key}`"))
+    }
+
+    /// 采样周期（秒）
+    pub fn sample_period(&self) -> f32 {
+        1.0 / self.value("rate")
+    }
+
+    /// `seconds`秒对应的采样数
+    pub fn samples_in(&self, seconds: f32) -> usize {
+        (seconds * self.value("rate")).round() as usize
+    }
+
+    /// 按录制顺序换算为物理单位的读数，磁力计读数为零
+    pub fn samples(&self) -> impl Iterator<Item = Sample> + '_ {
+        let accel_range = AccelRange::ALL
+            .into_iter()
+            .find(|range| range.full_scale() == self.value("accel_range"))
+            .expect("unsupported accel_range");
+        let gyro_range = GyroRange::ALL
+            .into_iter()
+            .find(|range| range.full_scale() == self.value("gyro_range"))
+            .expect("unsupported gyro_range");
+
+        self.text
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            // 跳过列名
+            .skip(1)
+            .map(move |line| {
+                let mut fields = line
+                    .split(',')
+                    .map(|field| field.trim().parse::<i16>().expect("invalid sample"));
+                let mut next = || Vector3::from_fn(|_, _| fields.next().expect("missing field"));
+                let accel = Acceleration::from_raw(next(), accel_range);
+                let gyro = AngularRate::from_raw(next(), gyro_range);
+                Sample {
+                    gyro: *gyro.as_rad_per_s(),
+                    accel: *accel.as_g(),
+                    mag: Vector3::zeros(),
+                }
+            })
+    }
+
+    /// 开头静止的`still`秒内陀螺仪读数的平均值，作为零偏
+    pub fn gyro_offset(&self) -> Vector3<f32> {
+        let count = self.samples_in(self.value("still"));
+        let sum: Vector3<f32> = self.samples().take(count).map(|sample| sample.gyro).sum();
+        sum / count as f32
+    }
+
+    /// 估计姿态与说明中记录的最终姿态（`roll`、`pitch`，度）的倾角误差（度）
+    pub fn tilt_error(&self, estimate: &UnitQuaternion<f32>) -> f32 {
+        let expected = UnitQuaternion::from_euler_angles(
+            self.value("roll").to_radians(),
+            self.value("pitch").to_radians(),
+            0.0,
+        );
+        let up = expected.inverse() * Vector3::z();
+        let estimated_up = estimate.inverse() * Vector3::z();
+        up.angle(&estimated_up).to_degrees()
+    }
+}
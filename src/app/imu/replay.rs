@@ -0,0 +1,97 @@
+//! 回放IMU数据
+//!
+//! 事件检测类算法的测试共用：实现[`Replay`]的检测器既可以回放录制数据
+//! （[`Recording::replay`]），也可以回放按动作脚本模拟的记录（[`Trace`]）。
+
+use super::recording::Recording;
+use super::simulation::{Sample, Simulator};
+use super::units::{Acceleration, AngularRate};
+use core::ops::{Deref, DerefMut};
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// 逐个采样接收IMU读数的检测器
+pub trait Replay {
+    /// 加入一个采样，`dt`为采样周期（秒）
+    fn update(&mut self, accel: &Acceleration, gyro: &AngularRate, dt: f32);
+
+    /// 加入一个物理单位的读数
+    fn update_sample(&mut self, sample: &Sample, dt: f32) {
+        let accel = Acceleration::from_g(sample.accel);
+        let gyro = AngularRate::from_rad_per_s(sample.gyro);
+        self.update(&accel, &gyro, dt);
+    }
+}
+
+impl Recording {
+    /// 按录制顺序把全部读数交给`target`
+    pub fn replay(&self, target: &mut impl Replay) {
+        let period = self.sample_period();
+        for sample in self.samples() {
+            target.update_sample(&sample, period);
+        }
+    }
+}
+
+/// 按动作脚本生成模拟的传感器记录并回放给检测器
+///
+/// 通过`Deref`直接访问检测器。
+pub struct Trace<T> {
+    /// 生成读数的模拟器
+    pub sim: Simulator,
+    /// 接收读数的检测器
+    pub target: T,
+}
+
+impl<T: Replay> Trace<T> {
+    /// 以姿态`attitude`静止1秒开始的记录
+    pub fn new(attitude: UnitQuaternion<f32>, target: T) -> Self {
+        let mut trace = Self {
+            sim: Simulator::new(attitude),
+            target,
+        };
+        trace.still(1.0);
+        trace
+    }
+
+    /// 以角速度`rate`（rad/s）转动、以线加速度`linear`（g，地球坐标系）运动一个采样
+    pub fn step(&mut self, rate: Vector3<f32>, linear: Vector3<f32>) {
+        let sample = self.sim.step_accelerating(rate, linear);
+        self.target.update_sample(&sample, self.sim.sample_period);
+    }
+
+    /// 持续`seconds`秒
+    pub fn hold(&mut self, seconds: f32, rate: Vector3<f32>, linear: Vector3<f32>) {
+        for _ in 0..self.samples(seconds) {
+            self.step(rate, linear);
+        }
+    }
+
+    /// 静止`seconds`秒
+    pub fn still(&mut self, seconds: f32) {
+        self.hold(seconds, Vector3::zeros(), Vector3::zeros());
+    }
+
+    /// 绕设备坐标轴`axis`转过`angle`（rad），用时1秒
+    pub fn rotate(&mut self, axis: Vector3<f32>, angle: f32) {
+        self.hold(1.0, axis * angle, Vector3::zeros());
+    }
+
+    /// `seconds`秒对应的采样数
+    pub fn samples(&self, seconds: f32) -> usize {
+        (seconds / self.sim.sample_period).round() as usize
+    }
+}
+
+impl<T> Deref for Trace<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.target
+    }
+}
+
+impl<T> DerefMut for Trace<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.target
+    }
+}
@@ -0,0 +1,91 @@
+//! 模拟IMU数据
+//!
+//! 按给定角速度积分出真实姿态，并生成带零偏和噪声的陀螺仪、加速度计和磁力计读数，
+//! 用于录制数据（见[`super::recording`]）无法覆盖的测试，如磁力计读数和改变采样周期。
+
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// 一次采样
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    /// 陀螺仪读数（rad/s）
+    pub gyro: Vector3<f32>,
+    /// 加速度计读数（g）
+    pub accel: Vector3<f32>,
+    /// 磁力计读数（Gs）
+    pub mag: Vector3<f32>,
+}
+
+/// 传感器运动模拟器
+///
+/// 姿态四元数将传感器坐标系中的向量旋转到地球坐标系（X轴指向磁北，Z轴向上）。
+pub struct Simulator {
+    /// 真实姿态
+    pub attitude: UnitQuaternion<f32>,
+    /// 采样周期（秒）
+    pub sample_period: f32,
+    /// 陀螺仪零偏（rad/s）
+    pub gyro_bias: Vector3<f32>,
+    /// 陀螺仪噪声幅值（rad/s）
+    pub gyro_noise: f32,
+    /// 加速度计噪声幅值（g）
+    pub accel_noise: f32,
+    /// 地球坐标系中的地磁场（Gs）
+    pub magnetic_field: Vector3<f32>,
+    /// 磁力计噪声幅值（Gs）
+    pub mag_noise: f32,
+    /// 伪随机数状态
+    seed: u32,
+}
+
+impl Simulator {
+    /// 以指定姿态创建模拟器，默认100Hz采样、无零偏、带少量噪声，地磁场为北半球中纬度的典型值
+    pub fn new(attitude: UnitQuaternion<f32>) -> Self {
+        Self {
+            attitude,
+            sample_period: 0.01,
+            gyro_bias: Vector3::zeros(),
+            gyro_noise: 0.01,
+            accel_noise: 0.01,
+            magnetic_field: Vector3::new(0.3, 0.0, -0.4),
+            mag_noise: 0.005,
+            seed: 1,
+        }
+    }
+
+    /// 以传感器坐标系中的角速度`rate`（rad/s）转动一个采样周期，返回该周期的读数
+    pub fn step(&mut self, rate: Vector3<f32>) -> Sample {
+        self.step_accelerating(rate, Vector3::zeros())
+    }
+
+    /// 转动的同时以地球坐标系中的线加速度`linear`（g）运动一个采样周期，返回该周期的读数
+    ///
+    /// 线加速度为`-Z`时加速度计读数为零，即自由落体。
+    pub fn step_accelerating(&mut self, rate: Vector3<f32>, linear: Vector3<f32>) -> Sample {
+        self.attitude *= UnitQuaternion::from_scaled_axis(rate * self.sample_period);
+
+        let gyro = rate + self.gyro_bias + self.noise() * self.gyro_noise;
+        let specific_force = Vector3::z() + linear;
+        let accel = self.attitude.inverse() * specific_force + self.noise() * self.accel_noise;
+        let mag = self.attitude.inverse() * self.magnetic_field + self.noise() * self.mag_noise;
+        Sample { gyro, accel, mag }
+    }
+
+    /// 估计姿态与真实姿态的倾角误差（度），即两者“上”方向之间的夹角
+    pub fn tilt_error(&self, estimate: &UnitQuaternion<f32>) -> f32 {
+        let up = self.attitude.inverse() * Vector3::z();
+        let estimated_up = estimate.inverse() * Vector3::z();
+        up.angle(&estimated_up).to_degrees()
+    }
+
+    /// 各分量在[-1, 1]内均匀分布的伪随机向量
+    fn noise(&mut self) -> Vector3<f32> {
+        Vector3::from_fn(|_, _| {
+            self.seed = self
+                .seed
+                .wrapping_mul(1_664_525)
+                .wrapping_add(1_013_904_223);
+            (self.seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+        })
+    }
+}
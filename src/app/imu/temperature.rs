@@ -0,0 +1,224 @@
+//! 陀螺仪零偏温度补偿
+//!
+//! MPU6050的陀螺仪零偏随芯片温度变化，上电后芯片逐渐升温，启动时测得的零偏随之失效。
+//! 在预热校准过程中保持设备静止，记录不同温度下的零偏，拟合零偏关于温度的
+//! 一次或二次多项式；之后按当前温度与零偏校准时温度之间的差值补偿零偏变化。
+
+use super::units::AngularRate;
+use nalgebra::{Matrix2, Matrix2x3, Matrix3, Vector3};
+
+/// 拟合所需的最小温度跨度（℃），跨度太小时无法区分温度系数与噪声
+pub const MIN_TEMPERATURE_SPAN: f32 = 2.0;
+
+/// 由原始读数换算芯片温度（℃），取自MPU6050寄存器手册
+pub fn temperature_from_raw(raw: i16) -> f32 {
+    raw as f32 / 340.0 + 36.53
+}
+
+/// 拟合多项式的阶数
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DriftOrder {
+    /// 一次（线性）
+    Linear,
+    /// 二次
+    Quadratic,
+}
+
+/// 陀螺仪零偏温度模型
+///
+/// `bias(T) = c0 + c1·(T - T0) + c2·(T - T0)²`，线性模型的`c2`为零。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GyroDriftModel {
+    /// 参考温度T0（℃）
+    pub reference: f32,
+    /// 各阶系数（rad/s、rad/s/℃、rad/s/℃²）
+    pub coefficients: [Vector3<f32>; 3],
+}
+
+impl GyroDriftModel {
+    /// 指定温度下的零偏
+    pub fn bias(&self, temperature: f32) -> AngularRate {
+        let delta = temperature - self.reference;
+        let [c0, c1, c2] = self.coefficients;
+        AngularRate::from_rad_per_s(c0 + c1 * delta + c2 * delta * delta)
+    }
+
+    /// 温度从`from`变为`to`时零偏的变化量
+    pub fn drift(&self, from: f32, to: f32) -> AngularRate {
+        self.bias(to) - self.bias(from)
+    }
+}
+
+/// 零偏温度模型的最小二乘拟合
+///
+/// 逐个加入静止时的平均读数，只累积正规方程，不保存样本。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DriftFit {
+    /// 参考温度，取第一个样本的温度以减小舍入误差
+    reference: Option<f32>,
+    /// 正规方程左侧 XᵀX，X的每行为 [1, ΔT, ΔT²]
+    normal: Matrix3<f32>,
+    /// 正规方程右侧 XᵀY，Y的每行为零偏
+    rhs: Matrix3<f32>,
+    /// 样本数
+    count: usize,
+    /// 最低温度
+    min: f32,
+    /// 最高温度
+    max: f32,
+}
+
+impl DriftFit {
+    /// 创建空的拟合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入一个样本
+    ///
+    /// # 参数
+    /// - `temperature`: 芯片温度（℃）
+    /// - `gyro`: 静止时的陀螺仪平均读数，即该温度下的零偏
+    pub fn add(&mut self, temperature: f32, gyro: AngularRate) {
+        let reference = *self.reference.get_or_insert(temperature);
+        let delta = temperature - reference;
+        let row = Vector3::new(1.0, delta, delta * delta);
+        self.normal += row * row.transpose();
+        self.rhs += row * gyro.as_rad_per_s().transpose();
+
+        if self.count == 0 {
+            (self.min, self.max) = (temperature, temperature);
+        } else {
+            self.min = self.min.min(temperature);
+            self.max = self.max.max(temperature);
+        }
+        self.count += 1;
+    }
+
+    /// 样本数
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// 样本的温度跨度（℃）
+    pub fn temperature_span(&self) -> f32 {
+        self.max - self.min
+    }
+
+    /// 求解模型
+    ///
+    /// # 返回值
+    /// 温度跨度小于[`MIN_TEMPERATURE_SPAN`]或样本不足时返回`None`
+    pub fn solve(&self, order: DriftOrder) -> Option<GyroDriftModel> {
+        let reference = self.reference?;
+        if self.temperature_span() < MIN_TEMPERATURE_SPAN {
+            return None;
+        }
+
+        let coefficients = match order {
+            DriftOrder::Linear => {
+                let normal: Matrix2<f32> = self.normal.fixed_view::<2, 2>(0, 0).into_owned();
+                let rhs: Matrix2x3<f32> = self.rhs.fixed_rows::<2>(0).into_owned();
+                let solution = normal.cholesky()?.solve(&rhs);
+                [
+                    solution.row(0).transpose(),
+                    solution.row(1).transpose(),
+                    Vector3::zeros(),
+                ]
+            }
+            DriftOrder::Quadratic => {
+                let solution = self.normal.cholesky()?.solve(&self.rhs);
+                [
+                    solution.row(0).transpose(),
+                    solution.row(1).transpose(),
+                    solution.row(2).transpose(),
+                ]
+            }
+        };
+
+        Some(GyroDriftModel {
+            reference,
+            coefficients,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 模拟芯片：25℃零偏与一次、二次温度系数
+    fn truth() -> GyroDriftModel {
+        GyroDriftModel {
+            reference: 25.0,
+            coefficients: [
+                Vector3::new(0.02, -0.015, 0.005),
+                Vector3::new(0.0008, 0.0005, -0.0012),
+                Vector3::new(0.00002, -0.00001, 0.00003),
+            ],
+        }
+    }
+
+    /// 从25℃升温到`end`，每0.25℃记录一次，叠加交替的噪声
+    fn warm_up(model: &GyroDriftModel, end: f32) -> DriftFit {
+        let mut fit = DriftFit::new();
+        let mut temperature = 25.0;
+        let mut sign = 1.0;
+        while temperature <= end {
+            let noise = AngularRate::from_rad_per_s(Vector3::repeat(0.0002 * sign));
+            fit.add(temperature, model.bias(temperature) + noise);
+            temperature += 0.25;
+            sign = -sign;
+        }
+        fit
+    }
+
+    #[test]
+    fn converts_raw_temperature() {
+        assert!((temperature_from_raw(0) - 36.53).abs() < 1e-4);
+        assert!((temperature_from_raw(-3580) - 26.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn fits_quadratic_drift() {
+        let truth = truth();
+        let model = warm_up(&truth, 45.0).solve(DriftOrder::Quadratic).unwrap();
+
+        for temperature in [25.0, 32.5, 40.0, 45.0] {
+            let error = model.bias(temperature) - truth.bias(temperature);
+            assert!(error.as_rad_per_s().amax() < 2e-4, "{temperature}℃");
+        }
+        let drift = model.drift(25.0, 45.0) - truth.drift(25.0, 45.0);
+        assert!(drift.as_rad_per_s().amax() < 2e-4);
+    }
+
+    #[test]
+    fn fits_linear_drift() {
+        let truth = GyroDriftModel {
+            coefficients: [
+                truth().coefficients[0],
+                truth().coefficients[1],
+                Vector3::zeros(),
+            ],
+            ..truth()
+        };
+        let fit = warm_up(&truth, 35.0);
+        assert_eq!(fit.count(), 41);
+        assert_eq!(fit.temperature_span(), 10.0);
+
+        let model = fit.solve(DriftOrder::Linear).unwrap();
+        assert_eq!(model.coefficients[2], Vector3::zeros());
+        let slope = model.coefficients[1] - truth.coefficients[1];
+        assert!(slope.amax() < 5e-5, "{model:?}");
+    }
+
+    #[test]
+    fn rejects_narrow_temperature_span() {
+        assert_eq!(DriftFit::new().solve(DriftOrder::Linear), None);
+
+        let fit = warm_up(&truth(), 26.5);
+        assert!(fit.count() > 3);
+        assert_eq!(fit.solve(DriftOrder::Linear), None);
+        assert_eq!(fit.solve(DriftOrder::Quadratic), None);
+    }
+}
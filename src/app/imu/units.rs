@@ -0,0 +1,287 @@
+//! 物理量与传感器量程
+//!
+//! MPU6050输出16位有符号原始值，换算系数取决于当前量程。
+//! 读数在换算后以[`Acceleration`]（g）、[`AngularRate`]（rad/s）和[`MagneticField`]（Gs）保存，
+//! 校准零偏也使用物理单位，因此修改量程后无需重新校准。
+
+use core::ops::{Add, Div, Sub};
+use nalgebra::Vector3;
+
+/// 标准重力加速度（m/s²）
+pub const STANDARD_GRAVITY: f32 = 9.806_65;
+
+/// 陀螺仪量程
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GyroRange {
+    /// ±250°/s
+    D250,
+    /// ±500°/s
+    #[default]
+    D500,
+    /// ±1000°/s
+    D1000,
+    /// ±2000°/s
+    D2000,
+}
+
+impl GyroRange {
+    /// 所有量程
+    pub const ALL: [Self; 4] = [Self::D250, Self::D500, Self::D1000, Self::D2000];
+
+    /// 满量程（°/s）
+    pub const fn full_scale(self) -> f32 {
+        match self {
+            Self::D250 => 250.0,
+            Self::D500 => 500.0,
+            Self::D1000 => 1000.0,
+            Self::D2000 => 2000.0,
+        }
+    }
+
+    /// 灵敏度（LSB/(°/s)），取自数据手册
+    pub const fn sensitivity(self) -> f32 {
+        match self {
+            Self::D250 => 131.0,
+            Self::D500 => 65.5,
+            Self::D1000 => 32.8,
+            Self::D2000 => 16.4,
+        }
+    }
+}
+
+/// 加速度计量程
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccelRange {
+    /// ±2g
+    G2,
+    /// ±4g
+    #[default]
+    G4,
+    /// ±8g
+    G8,
+    /// ±16g
+    G16,
+}
+
+impl AccelRange {
+    /// 所有量程
+    pub const ALL: [Self; 4] = [Self::G2, Self::G4, Self::G8, Self::G16];
+
+    /// 满量程（g）
+    pub const fn full_scale(self) -> f32 {
+        match self {
+            Self::G2 => 2.0,
+            Self::G4 => 4.0,
+            Self::G8 => 8.0,
+            Self::G16 => 16.0,
+        }
+    }
+
+    /// 灵敏度（LSB/g），取自数据手册
+    pub const fn sensitivity(self) -> f32 {
+        match self {
+            Self::G2 => 16384.0,
+            Self::G4 => 8192.0,
+            Self::G8 => 4096.0,
+            Self::G16 => 2048.0,
+        }
+    }
+}
+
+/// 三轴加速度（g）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Acceleration(Vector3<f32>);
+
+impl Acceleration {
+    /// 零加速度
+    pub fn zeros() -> Self {
+        Self(Vector3::zeros())
+    }
+
+    /// 以g为单位创建
+    pub fn from_g(g: Vector3<f32>) -> Self {
+        Self(g)
+    }
+
+    /// 以m/s²为单位创建
+    pub fn from_m_per_s2(value: Vector3<f32>) -> Self {
+        Self(value / STANDARD_GRAVITY)
+    }
+
+    /// 按量程换算原始读数
+    pub fn from_raw(raw: Vector3<i16>, range: AccelRange) -> Self {
+        Self(raw.map(|v| v as f32) / range.sensitivity())
+    }
+
+    /// 以g为单位的值
+    pub fn as_g(&self) -> &Vector3<f32> {
+        &self.0
+    }
+
+    /// 以m/s²为单位的值
+    pub fn as_m_per_s2(&self) -> Vector3<f32> {
+        self.0 * STANDARD_GRAVITY
+    }
+}
+
+/// 三轴角速度（rad/s）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AngularRate(Vector3<f32>);
+
+impl AngularRate {
+    /// 零角速度
+    pub fn zeros() -> Self {
+        Self(Vector3::zeros())
+    }
+
+    /// 以rad/s为单位创建
+    pub fn from_rad_per_s(value: Vector3<f32>) -> Self {
+        Self(value)
+    }
+
+    /// 以°/s为单位创建
+    pub fn from_deg_per_s(value: Vector3<f32>) -> Self {
+        Self(value.map(f32::to_radians))
+    }
+
+    /// 按量程换算原始读数
+    pub fn from_raw(raw: Vector3<i16>, range: GyroRange) -> Self {
+        Self::from_deg_per_s(raw.map(|v| v as f32) / range.sensitivity())
+    }
+
+    /// 以rad/s为单位的值
+    pub fn as_rad_per_s(&self) -> &Vector3<f32> {
+        &self.0
+    }
+
+    /// 以°/s为单位的值
+    pub fn as_deg_per_s(&self) -> Vector3<f32> {
+        self.0.map(f32::to_degrees)
+    }
+}
+
+/// 三轴磁感应强度（Gs）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MagneticField(Vector3<f32>);
+
+impl MagneticField {
+    /// 零磁场
+    pub fn zeros() -> Self {
+        Self(Vector3::zeros())
+    }
+
+    /// 以Gs为单位创建
+    pub fn from_gauss(value: Vector3<f32>) -> Self {
+        Self(value)
+    }
+
+    /// 按磁力计灵敏度（LSB/Gs）换算原始读数
+    pub fn from_raw(raw: Vector3<i16>, sensitivity: f32) -> Self {
+        Self(raw.map(|v| v as f32) / sensitivity)
+    }
+
+    /// 以Gs为单位的值
+    pub fn as_gauss(&self) -> &Vector3<f32> {
+        &self.0
+    }
+
+    /// 以μT为单位的值
+    pub fn as_microtesla(&self) -> Vector3<f32> {
+        self.0 * 100.0
+    }
+}
+
+/// 为物理量实现加减和按标量除（用于求平均值）
+macro_rules! impl_vector_ops {
+    ($($ty:ident),*) => {$(
+        impl Add for $ty {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $ty {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl Div<f32> for $ty {
+            type Output = Self;
+
+            fn div(self, rhs: f32) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+    )*};
+}
+
+impl_vector_ops!(Acceleration, AngularRate, MagneticField);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f32::consts::FRAC_PI_2;
+
+    /// 传感器在指定量程下对物理量的原始输出（四舍五入并饱和）
+    fn raw(value: Vector3<f32>, sensitivity: f32) -> Vector3<i16> {
+        value.map(|v| {
+            let lsb = v * sensitivity;
+            lsb.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        })
+    }
+
+    #[test]
+    fn static_reading_is_one_g_in_every_range() {
+        let gravity = Vector3::new(0.0, 0.0, 1.0);
+        for range in AccelRange::ALL {
+            let accel = Acceleration::from_raw(raw(gravity, range.sensitivity()), range);
+            let resolution = 1.0 / range.sensitivity();
+            assert!((accel.as_g() - gravity).amax() <= resolution, "{range:?}");
+            assert!((accel.as_m_per_s2().z - STANDARD_GRAVITY).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn known_rotation_rate_in_every_range() {
+        // 绕Y轴90°/s
+        let rate = Vector3::new(0.0, 90.0, 0.0);
+        for range in GyroRange::ALL {
+            let gyro = AngularRate::from_raw(raw(rate, range.sensitivity()), range);
+            assert!(
+                (gyro.as_rad_per_s().y - FRAC_PI_2).abs() < 1e-3,
+                "{range:?}"
+            );
+            assert!((gyro.as_deg_per_s() - rate).amax() < 0.05, "{range:?}");
+        }
+    }
+
+    #[test]
+    fn full_scale_matches_raw_limit() {
+        for range in AccelRange::ALL {
+            let max = Acceleration::from_raw(Vector3::repeat(i16::MAX), range);
+            assert!((max.as_g().x - range.full_scale()).abs() / range.full_scale() < 1e-3);
+        }
+        for range in GyroRange::ALL {
+            let max = AngularRate::from_raw(Vector3::repeat(i16::MAX), range);
+            assert!((max.as_deg_per_s().x - range.full_scale()).abs() / range.full_scale() < 2e-3);
+        }
+    }
+
+    #[test]
+    fn offsets_are_range_independent() {
+        let offset = Acceleration::from_g(Vector3::new(0.05, -0.02, 0.0));
+        let reading = Vector3::new(0.05, -0.02, 1.0);
+        for range in AccelRange::ALL {
+            let accel = Acceleration::from_raw(raw(reading, range.sensitivity()), range) - offset;
+            assert!((accel.as_g() - Vector3::z()).amax() < 1e-3, "{range:?}");
+        }
+
+        let sum = (0..4).fold(Acceleration::zeros(), |sum, _| sum + offset);
+        assert_eq!(sum / 4.0, offset);
+    }
+}
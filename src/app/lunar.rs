@@ -0,0 +1,644 @@
+use chrono::{Datelike, NaiveDate};
+
+/// 支持的最早农历年份
+pub const MIN_YEAR: i32 = 1900;
+/// 支持的最晚农历年份
+pub const MAX_YEAR: i32 = 2100;
+
+/// 天干
+pub const HEAVENLY_STEMS: [&str; 10] = ["甲", "乙", "丙", "丁", "戊", "己", "庚", "辛", "壬", "癸"];
+/// 地支
+pub const EARTHLY_BRANCHES: [&str; 12] = [
+    "子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥",
+];
+/// 生肖，与地支一一对应
+pub const ZODIAC_ANIMALS: [&str; 12] = [
+    "鼠", "牛", "虎", "兔", "龙", "蛇", "马", "羊", "猴", "鸡", "狗", "猪",
+];
+
+/// 农历月份名称
+const MONTH_NAMES: [&str; 12] = [
+    "正月", "二月", "三月", "四月", "五月", "六月", "七月", "八月", "九月", "十月", "冬月", "腊月",
+];
+/// 农历日期名称
+const DAY_NAMES: [&str; 30] = [
+    "初一", "初二", "初三", "初四", "初五", "初六", "初七", "初八", "初九", "初十", //
+    "十一", "十二", "十三", "十四", "十五", "十六", "十七", "十八", "十九", "二十", //
+    "廿一", "廿二", "廿三", "廿四", "廿五", "廿六", "廿七", "廿八", "廿九", "三十",
+];
+
+/// 1900-2100年农历年份信息
+///
+/// 每年一个20位数值：
+/// - bit 0-3: 闰月月份，0表示无闰月
+/// - bit 4-15: 1-12月大小月，bit 15对应正月，1表示30天，0表示29天
+/// - bit 16: 闰月大小，1表示30天
+#[rustfmt::skip]
+const LUNAR_INFO: [u32; 201] = [
+    0x04bd8, 0x04ae0, 0x0a570, 0x054d5, 0x0d260, 0x0d950, 0x16554, 0x056a0, 0x09ad0, 0x055d2, // 1900-1909
+    0x04ae0, 0x0a5b6, 0x0a4d0, 0x0d250, 0x1d255, 0x0b540, 0x0d6a0, 0x0ada2, 0x095b0, 0x14977, // 1910-1919
+    0x04970, 0x0a4b0, 0x0b4b5, 0x06a50, 0x06d40, 0x1ab54, 0x02b60, 0x09570, 0x052f2, 0x04970, // 1920-1929
+    0x06566, 0x0d4a0, 0x0ea50, 0x16a95, 0x05ad0, 0x02b60, 0x186e3, 0x092e0, 0x1c8d7, 0x0c950, // 1930-1939
+    0x0d4a0, 0x1d8a6, 0x0b550, 0x056a0, 0x1a5b4, 0x025d0, 0x092d0, 0x0d2b2, 0x0a950, 0x0b557, // 1940-1949
+    0x06ca0, 0x0b550, 0x15355, 0x04da0, 0x0a5b0, 0x14573, 0x052b0, 0x0a9a8, 0x0e950, 0x06aa0, // 1950-1959
+    0x0aea6, 0x0ab50, 0x04b60, 0x0aae4, 0x0a570, 0x05260, 0x0f263, 0x0d950, 0x05b57, 0x056a0, // 1960-1969
+    0x096d0, 0x04dd5, 0x04ad0, 0x0a4d0, 0x0d4d4, 0x0d250, 0x0d558, 0x0b540, 0x0b6a0, 0x195a6, // 1970-1979
+    0x095b0, 0x049b0, 0x0a974, 0x0a4b0, 0x0b27a, 0x06a50, 0x06d40, 0x0af46, 0x0ab60, 0x09570, // 1980-1989
+    0x04af5, 0x04970, 0x064b0, 0x074a3, 0x0ea50, 0x06b58, 0x05ac0, 0x0ab60, 0x096d5, 0x092e0, // 1990-1999
+    0x0c960, 0x0d954, 0x0d4a0, 0x0da50, 0x07552, 0x056a0, 0x0abb7, 0x025d0, 0x092d0, 0x0cab5, // 2000-2009
+    0x0a950, 0x0b4a0, 0x0baa4, 0x0ad50, 0x055d9, 0x04ba0, 0x0a5b0, 0x15176, 0x052b0, 0x0a930, // 2010-2019
+    0x07954, 0x06aa0, 0x0ad50, 0x05b52, 0x04b60, 0x0a6e6, 0x0a4e0, 0x0d260, 0x0ea65, 0x0d530, // 2020-2029
+    0x05aa0, 0x076a3, 0x096d0, 0x04afb, 0x04ad0, 0x0a4d0, 0x1d0b6, 0x0d250, 0x0d520, 0x0dd45, // 2030-2039
+    0x0b5a0, 0x056d0, 0x055b2, 0x049b0, 0x0a577, 0x0a4b0, 0x0aa50, 0x1b255, 0x06d20, 0x0ada0, // 2040-2049
+    0x14b63, 0x09370, 0x049f8, 0x04970, 0x064b0, 0x168a6, 0x0ea50, 0x06b20, 0x1a6c4, 0x0aae0, // 2050-2059
+    0x092e0, 0x0d2e3, 0x0c960, 0x0d557, 0x0d4a0, 0x0da50, 0x05d55, 0x056a0, 0x0a6d0, 0x055d4, // 2060-2069
+    0x052d0, 0x0a9b8, 0x0a950, 0x0b4a0, 0x0b6a6, 0x0ad50, 0x055a0, 0x0aba4, 0x0a5b0, 0x052b0, // 2070-2079
+    0x0b273, 0x06930, 0x07337, 0x06aa0, 0x0ad50, 0x14b55, 0x04b60, 0x0a570, 0x054e4, 0x0d160, // 2080-2089
+    0x0e968, 0x0d520, 0x0daa0, 0x16aa6, 0x056d0, 0x04ae0, 0x0a9d4, 0x0a2d0, 0x0d150, 0x0f252, // 2090-2099
+    0x0d520, // 2100
+];
+
+/// 二十四节气在各自月份中的最早日期，从小寒开始
+const SOLAR_TERM_BASE_DAYS: [u8; 24] = [
+    4, 19, 3, 18, 4, 19, 4, 19, 4, 20, 4, 20, 6, 22, 6, 22, 6, 22, 7, 22, 6, 21, 6, 21,
+];
+
+/// 1900-2100年二十四节气日期（北京时间）
+///
+/// 每年一个48位数值，每个节气占2位，bit `2k..2k+2`为第`k`个节气（从小寒开始）
+/// 相对[`SOLAR_TERM_BASE_DAYS`]的偏移天数。
+#[rustfmt::skip]
+const SOLAR_TERM_INFO: [u64; 201] = [
+    0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaabaaa6a, 0xaaabbabbafaa, 0x5aa665a65aab, // 1900-1904
+    0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a, 0xaaabbabbafaa, 0x5aa665a65aab, 0x6aaaa6aa9a5a, // 1905-1909
+    0xaaaaaaaaaa6a, 0xaaabbabbafaa, 0x56a665a65aab, 0x6aa6a6aa9a56, 0xaaaaaaaa9a5a, // 1910-1914
+    0xaaabaabaaeaa, 0x569665a65aaa, 0x6aa6a6a69a56, 0x6aaaaaaa9a5a, 0xaaabaabaaeaa, // 1915-1919
+    0x569665a65aaa, 0x5aa6a6a65a56, 0x6aaaaaaa9a5a, 0xaaabaabaaa6a, 0x569665a65aaa, // 1920-1924
+    0x5aa6a6a65a56, 0x6aaaa6aa9a5a, 0xaaabaabaaa6a, 0x555665a65aaa, 0x5aa665a65a56, // 1925-1929
+    0x6aaaa6aa9a5a, 0xaaaaaabaaa6a, 0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a, // 1930-1934
+    0xaaaaaaaaaa6a, 0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a, // 1935-1939
+    0x555665665aaa, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0xaaaaaaaaaa6a, 0x555665655aaa, // 1940-1944
+    0x569665a65a56, 0x6aa6a6aa9a56, 0xaaaaaaaa9a5a, 0x5556556559aa, 0x569665a65a55, // 1945-1949
+    0x6aa6a6a65a56, 0x6aaaaaaa9a5a, 0x5556556559aa, 0x569665a65a55, 0x5aa6a6a65a56, // 1950-1954
+    0x6aaaa6aa9a5a, 0x5556556555aa, 0x569665a65a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a, // 1955-1959
+    0x55555565556a, 0x555665665a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555565556a, // 1960-1964
+    0x555665665a55, 0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555555556a, 0x555665665a55, // 1965-1969
+    0x5aa665a65a56, 0x6aaaa6aa9a5a, 0x55555555556a, 0x555665655a55, 0x5aa665a65a56, // 1970-1974
+    0x6aa6a6aa9a5a, 0x55555555456a, 0x555655655a55, 0x5a9665a65a56, 0x6aa6a6a69a56, // 1975-1979
+    0x55555555456a, 0x555655655a55, 0x569665a65a56, 0x6aa6a6a65a56, 0x55555155455a, // 1980-1984
+    0x555655655955, 0x569665a65a55, 0x5aa6a5a65a56, 0x15555155455a, 0x555555655555, // 1985-1989
+    0x569665665a55, 0x5aa665a65a56, 0x15555155455a, 0x555555655515, 0x555665665a55, // 1990-1994
+    0x5aa665a65a56, 0x15555155455a, 0x555555555515, 0x555665665a55, 0x5aa665a65a56, // 1995-1999
+    0x15555155455a, 0x555555555515, 0x555665665a55, 0x5aa665a65a56, 0x15555155455a, // 2000-2004
+    0x555555555515, 0x555655655a55, 0x5aa665a65a56, 0x15515155455a, 0x555555554515, // 2005-2009
+    0x555655655a55, 0x5a9665a65a56, 0x15515151455a, 0x555551554515, 0x555655655a55, // 2010-2014
+    0x569665a65a56, 0x155151510556, 0x555551554505, 0x555655655955, 0x569665665a55, // 2015-2019
+    0x155110510556, 0x155551554505, 0x555555655555, 0x569665665a55, 0x055110510556, // 2020-2024
+    0x155551554505, 0x555555555515, 0x555665665a55, 0x055110510556, 0x155551554505, // 2025-2029
+    0x555555555515, 0x555665665a55, 0x055110510556, 0x155551554505, 0x555555555515, // 2030-2034
+    0x555655655a55, 0x055110510556, 0x155551554505, 0x555555555515, 0x555655655a55, // 2035-2039
+    0x055110510556, 0x155151514505, 0x555555554515, 0x555655655a55, 0x054110510556, // 2040-2044
+    0x155151510505, 0x555551554515, 0x555655655a55, 0x014110110556, 0x155110510501, // 2045-2049
+    0x555551554505, 0x555555655555, 0x014110110555, 0x155110510501, 0x555551554505, // 2050-2054
+    0x555555555555, 0x014110110555, 0x055110510501, 0x155551554505, 0x555555555555, // 2055-2059
+    0x000110110555, 0x055110510501, 0x155551554505, 0x555555555515, 0x000110110555, // 2060-2064
+    0x055110510501, 0x155551554505, 0x555555555515, 0x000100100555, 0x055110510501, // 2065-2069
+    0x155151514505, 0x555555555515, 0x000100100555, 0x054110510501, 0x155151514505, // 2070-2074
+    0x555551554515, 0x000100100555, 0x054110510501, 0x155150510505, 0x555551554515, // 2075-2079
+    0x000100100555, 0x014110110501, 0x155110510505, 0x555551554505, 0x000000100055, // 2080-2084
+    0x014110110500, 0x155110510501, 0x555551554505, 0x000000000055, 0x014110110500, // 2085-2089
+    0x055110510501, 0x155551554505, 0x000000000055, 0x000110110500, 0x055110510501, // 2090-2094
+    0x155551554505, 0x000000000015, 0x000100110500, 0x055110510501, 0x155551554505, // 2095-2099
+    0x555555555515, // 2100
+];
+
+/// 根据年份信息计算农历年总天数
+const fn info_days(info: u32) -> u32 {
+    let mut days = 12 * 29;
+    let mut mask = 0x8000;
+    while mask > 0x8 {
+        if info & mask != 0 {
+            days += 1;
+        }
+        mask >>= 1;
+    }
+    if info & 0xf != 0 {
+        days += if info & 0x10000 != 0 { 30 } else { 29 };
+    }
+    days
+}
+
+/// 各农历年正月初一相对[`epoch`]的天数，最后一项为2100年除夕的次日
+const NEW_YEAR_OFFSETS: [u32; LUNAR_INFO.len() + 1] = {
+    let mut offsets = [0; LUNAR_INFO.len() + 1];
+    let mut i = 0;
+    while i < LUNAR_INFO.len() {
+        offsets[i + 1] = offsets[i] + info_days(LUNAR_INFO[i]);
+        i += 1;
+    }
+    offsets
+};
+
+/// 农历新年基准：1900-01-31为农历1900年正月初一
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(MIN_YEAR, 1, 31).unwrap()
+}
+
+fn year_info(year: i32) -> Option<u32> {
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return None;
+    }
+    Some(LUNAR_INFO[(year - MIN_YEAR) as usize])
+}
+
+/// 获取农历年份的闰月月份
+///
+/// # 返回值
+/// 无闰月或年份超出范围时返回`None`
+pub fn leap_month(year: i32) -> Option<u8> {
+    match year_info(year)? & 0xf {
+        0 => None,
+        month => Some(month as u8),
+    }
+}
+
+/// 获取农历月份的天数
+///
+/// # 参数
+/// - `year`: 农历年份
+/// - `month`: 农历月份（1-12）
+/// - `leap`: 是否为闰月
+///
+/// # 返回值
+/// 29或30；月份不存在时返回0
+pub fn days_in_month(year: i32, month: u8, leap: bool) -> u32 {
+    let Some(info) = year_info(year) else {
+        return 0;
+    };
+    if !(1..=12).contains(&month) {
+        return 0;
+    }
+
+    let big = if leap {
+        if leap_month(year) != Some(month) {
+            return 0;
+        }
+        info & 0x10000 != 0
+    } else {
+        info & (0x10000 >> month) != 0
+    };
+
+    if big { 30 } else { 29 }
+}
+
+/// 获取农历年份的总天数，年份超出范围时返回0
+pub fn days_in_year(year: i32) -> u32 {
+    year_info(year).map_or(0, info_days)
+}
+
+/// 农历日期
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LunarDate {
+    /// 农历年份
+    pub year: i32,
+    /// 农历月份（1-12）
+    pub month: u8,
+    /// 农历日（1-30）
+    pub day: u8,
+    /// 是否为闰月
+    pub leap: bool,
+}
+
+impl LunarDate {
+    /// 公历日期转换为农历日期
+    ///
+    /// # 返回值
+    /// 日期超出农历1900年正月初一至2100年除夕的范围时返回`None`
+    pub fn from_solar(date: NaiveDate) -> Option<Self> {
+        let offset = u32::try_from((date - epoch()).num_days()).ok()?;
+        if offset >= NEW_YEAR_OFFSETS[LUNAR_INFO.len()] {
+            return None;
+        }
+
+        // 农历新年总在公历1月21日至2月20日之间，所在农历年只可能是公历年份或前一年
+        let index = (date.year() - MIN_YEAR).clamp(0, LUNAR_INFO.len() as i32 - 1) as usize;
+        let index = if offset < NEW_YEAR_OFFSETS[index] {
+            index - 1
+        } else {
+            index
+        };
+        let year = MIN_YEAR + index as i32;
+        let mut offset = offset - NEW_YEAR_OFFSETS[index];
+
+        let leap = leap_month(year);
+        for month in 1..=12 {
+            let days = days_in_month(year, month, false);
+            if offset < days {
+                return Some(Self::new_unchecked(year, month, offset, false));
+            }
+            offset -= days;
+
+            if leap == Some(month) {
+                let days = days_in_month(year, month, true);
+                if offset < days {
+                    return Some(Self::new_unchecked(year, month, offset, true));
+                }
+                offset -= days;
+            }
+        }
+
+        None
+    }
+
+    fn new_unchecked(year: i32, month: u8, offset: u32, leap: bool) -> Self {
+        Self {
+            year,
+            month,
+            day: offset as u8 + 1,
+            leap,
+        }
+    }
+
+    /// 农历日期转换为公历日期
+    ///
+    /// # 返回值
+    /// 农历日期不存在或超出范围时返回`None`
+    pub fn to_solar(&self) -> Option<NaiveDate> {
+        if self.day == 0 || self.day as u32 > days_in_month(self.year, self.month, self.leap) {
+            return None;
+        }
+
+        let mut offset = NEW_YEAR_OFFSETS[(self.year - MIN_YEAR) as usize];
+        // 闰月排在同名的平月之后
+        let last_regular = if self.leap {
+            self.month
+        } else {
+            self.month - 1
+        };
+        for month in 1..=last_regular {
+            offset += days_in_month(self.year, month, false);
+        }
+        if let Some(leap) = leap_month(self.year)
+            && leap < self.month
+        {
+            offset += days_in_month(self.year, leap, true);
+        }
+        offset += self.day as u32 - 1;
+
+        epoch().checked_add_days(chrono::Days::new(offset as u64))
+    }
+
+    /// 年份天干
+    pub fn stem(&self) -> &'static str {
+        HEAVENLY_STEMS[(self.year - 4).rem_euclid(10) as usize]
+    }
+
+    /// 年份地支
+    pub fn branch(&self) -> &'static str {
+        EARTHLY_BRANCHES[(self.year - 4).rem_euclid(12) as usize]
+    }
+
+    /// 生肖
+    pub fn zodiac(&self) -> &'static str {
+        ZODIAC_ANIMALS[(self.year - 4).rem_euclid(12) as usize]
+    }
+
+    /// 月份名称（如“正月”“冬月”），闰月需另行加“闰”字
+    pub fn month_name(&self) -> &'static str {
+        MONTH_NAMES[(self.month as usize).clamp(1, 12) - 1]
+    }
+
+    /// 日期名称（如“初一”“廿三”）
+    pub fn day_name(&self) -> &'static str {
+        DAY_NAMES[(self.day as usize).clamp(1, 30) - 1]
+    }
+}
+
+/// 二十四节气
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolarTerm {
+    XiaoHan,
+    DaHan,
+    LiChun,
+    YuShui,
+    JingZhe,
+    ChunFen,
+    QingMing,
+    GuYu,
+    LiXia,
+    XiaoMan,
+    MangZhong,
+    XiaZhi,
+    XiaoShu,
+    DaShu,
+    LiQiu,
+    ChuShu,
+    BaiLu,
+    QiuFen,
+    HanLu,
+    ShuangJiang,
+    LiDong,
+    XiaoXue,
+    DaXue,
+    DongZhi,
+}
+
+impl SolarTerm {
+    /// 按公历年内顺序排列的全部节气
+    pub const ALL: [SolarTerm; 24] = [
+        SolarTerm::XiaoHan,
+        SolarTerm::DaHan,
+        SolarTerm::LiChun,
+        SolarTerm::YuShui,
+        SolarTerm::JingZhe,
+        SolarTerm::ChunFen,
+        SolarTerm::QingMing,
+        SolarTerm::GuYu,
+        SolarTerm::LiXia,
+        SolarTerm::XiaoMan,
+        SolarTerm::MangZhong,
+        SolarTerm::XiaZhi,
+        SolarTerm::XiaoShu,
+        SolarTerm::DaShu,
+        SolarTerm::LiQiu,
+        SolarTerm::ChuShu,
+        SolarTerm::BaiLu,
+        SolarTerm::QiuFen,
+        SolarTerm::HanLu,
+        SolarTerm::ShuangJiang,
+        SolarTerm::LiDong,
+        SolarTerm::XiaoXue,
+        SolarTerm::DaXue,
+        SolarTerm::DongZhi,
+    ];
+
+    const NAMES: [&str; 24] = [
+        "小寒", "大寒", "立春", "雨水", "惊蛰", "春分", "清明", "谷雨", "立夏", "小满", "芒种",
+        "夏至", "小暑", "大暑", "立秋", "处暑", "白露", "秋分", "寒露", "霜降", "立冬", "小雪",
+        "大雪", "冬至",
+    ];
+
+    /// 在[`Self::ALL`]中的序号
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// 节气名称
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self.index()]
+    }
+
+    /// 获取节气在指定公历年份中的日期（北京时间）
+    ///
+    /// # 返回值
+    /// 年份超出1900-2100范围时返回`None`
+    pub fn date(self, year: i32) -> Option<NaiveDate> {
+        if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+            return None;
+        }
+
+        let k = self.index();
+        let offset = (SOLAR_TERM_INFO[(year - MIN_YEAR) as usize] >> (2 * k)) & 0b11;
+        let day = SOLAR_TERM_BASE_DAYS[k] as u32 + offset as u32;
+        NaiveDate::from_ymd_opt(year, k as u32 / 2 + 1, day)
+    }
+
+    /// 获取指定日期当天的节气
+    pub fn on(date: NaiveDate) -> Option<Self> {
+        // 每月两个节气，只需检查当月
+        let first = (date.month0() * 2) as usize;
+        Self::ALL[first..first + 2]
+            .iter()
+            .copied()
+            .find(|term| term.date(date.year()) == Some(date))
+    }
+
+    /// 获取指定日期之后（不含当天）的下一个节气及其日期
+    pub fn next_after(date: NaiveDate) -> Option<(Self, NaiveDate)> {
+        [date.year(), date.year() + 1].into_iter().find_map(|year| {
+            Self::ALL
+                .iter()
+                .filter_map(|&term| Some((term, term.date(year)?)))
+                .find(|&(_, day)| day > date)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn lunar(year: i32, month: u8, day: u8, leap: bool) -> LunarDate {
+        LunarDate {
+            year,
+            month,
+            day,
+            leap,
+        }
+    }
+
+    #[test]
+    fn spring_festivals() {
+        let festivals = [
+            ymd(1900, 1, 31),
+            ymd(1949, 1, 29),
+            ymd(1976, 1, 31),
+            ymd(1990, 1, 27),
+            ymd(2000, 2, 5),
+            ymd(2008, 2, 7),
+            ymd(2020, 1, 25),
+            ymd(2023, 1, 22),
+            ymd(2024, 2, 10),
+            ymd(2025, 1, 29),
+            ymd(2026, 2, 17),
+        ];
+        for date in festivals {
+            let result = LunarDate::from_solar(date).unwrap();
+            assert_eq!(result, lunar(date.year(), 1, 1, false), "{date}");
+            assert_eq!(result.to_solar(), Some(date));
+
+            let eve = LunarDate::from_solar(date.pred_opt().unwrap());
+            if date.year() > MIN_YEAR {
+                let eve = eve.unwrap();
+                assert_eq!(
+                    (eve.year, eve.month, eve.leap),
+                    (date.year() - 1, 12, false)
+                );
+            } else {
+                assert_eq!(eve, None);
+            }
+        }
+    }
+
+    #[test]
+    fn known_dates() {
+        // 2025年闰六月
+        assert_eq!(leap_month(2025), Some(6));
+        assert_eq!(
+            LunarDate::from_solar(ymd(2025, 7, 20)),
+            Some(lunar(2025, 6, 26, false))
+        );
+        assert_eq!(
+            LunarDate::from_solar(ymd(2025, 7, 25)),
+            Some(lunar(2025, 6, 1, true))
+        );
+        // 中秋节
+        assert_eq!(
+            LunarDate::from_solar(ymd(2025, 10, 6)),
+            Some(lunar(2025, 8, 15, false))
+        );
+        assert_eq!(
+            LunarDate::from_solar(ymd(2024, 9, 17)),
+            Some(lunar(2024, 8, 15, false))
+        );
+        // 端午节
+        assert_eq!(
+            LunarDate::from_solar(ymd(2025, 5, 31)),
+            Some(lunar(2025, 5, 5, false))
+        );
+        // 2020年闰四月、2023年闰二月
+        assert_eq!(
+            LunarDate::from_solar(ymd(2020, 5, 23)),
+            Some(lunar(2020, 4, 1, true))
+        );
+        assert_eq!(
+            LunarDate::from_solar(ymd(2023, 3, 22)),
+            Some(lunar(2023, 2, 1, true))
+        );
+        // 2033年闰十一月
+        assert_eq!(leap_month(2033), Some(11));
+    }
+
+    #[test]
+    fn range_limits() {
+        assert_eq!(LunarDate::from_solar(ymd(1900, 1, 30)), None);
+        let last = LunarDate::from_solar(ymd(2101, 1, 28)).unwrap();
+        assert_eq!((last.year, last.month, last.leap), (2100, 12, false));
+        assert_eq!(LunarDate::from_solar(ymd(2101, 1, 29)), None);
+        assert_eq!(lunar(2101, 1, 1, false).to_solar(), None);
+        assert_eq!(lunar(2025, 7, 1, true).to_solar(), None);
+        assert_eq!(lunar(2025, 2, 30, false).to_solar(), None);
+    }
+
+    #[test]
+    fn round_trip_every_day() {
+        let mut date = epoch();
+        let mut previous = LunarDate::from_solar(date).unwrap();
+        while let Some(result) = LunarDate::from_solar(date) {
+            assert_eq!(result.to_solar(), Some(date));
+            if result.day != 1 {
+                assert_eq!(result.day, previous.day + 1);
+                assert_eq!((result.month, result.leap), (previous.month, previous.leap));
+            }
+            previous = result;
+            date = date.succ_opt().unwrap();
+        }
+        assert_eq!(date, ymd(2101, 1, 29));
+    }
+
+    #[test]
+    fn year_names() {
+        let date = LunarDate::from_solar(ymd(2025, 7, 20)).unwrap();
+        assert_eq!(
+            (date.stem(), date.branch(), date.zodiac()),
+            ("乙", "巳", "蛇")
+        );
+        assert_eq!(date.month_name(), "六月");
+        assert_eq!(date.day_name(), "廿六");
+
+        let date = LunarDate::from_solar(ymd(1984, 2, 2)).unwrap();
+        assert_eq!(
+            (date.stem(), date.branch(), date.zodiac()),
+            ("甲", "子", "鼠")
+        );
+
+        // 春节前仍属上一年
+        let date = LunarDate::from_solar(ymd(2024, 2, 9)).unwrap();
+        assert_eq!(
+            (date.stem(), date.branch(), date.zodiac()),
+            ("癸", "卯", "兔")
+        );
+        assert_eq!(date.month_name(), "腊月");
+        assert_eq!(date.day_name(), "三十");
+    }
+
+    #[test]
+    fn solar_terms_2025() {
+        let days = [
+            (1, 5),
+            (1, 20),
+            (2, 3),
+            (2, 18),
+            (3, 5),
+            (3, 20),
+            (4, 4),
+            (4, 20),
+            (5, 5),
+            (5, 21),
+            (6, 5),
+            (6, 21),
+            (7, 7),
+            (7, 22),
+            (8, 7),
+            (8, 23),
+            (9, 7),
+            (9, 23),
+            (10, 8),
+            (10, 23),
+            (11, 7),
+            (11, 22),
+            (12, 7),
+            (12, 21),
+        ];
+        for (term, (month, day)) in SolarTerm::ALL.into_iter().zip(days) {
+            assert_eq!(
+                term.date(2025),
+                Some(ymd(2025, month, day)),
+                "{}",
+                term.name()
+            );
+        }
+    }
+
+    #[test]
+    fn solar_terms_known_dates() {
+        assert_eq!(SolarTerm::LiChun.date(2024), Some(ymd(2024, 2, 4)));
+        assert_eq!(SolarTerm::DongZhi.date(2023), Some(ymd(2023, 12, 22)));
+        assert_eq!(SolarTerm::JingZhe.date(2014), Some(ymd(2014, 3, 6)));
+        assert_eq!(SolarTerm::QingMing.date(2008), Some(ymd(2008, 4, 4)));
+        assert_eq!(SolarTerm::XiaZhi.date(1900), Some(ymd(1900, 6, 22)));
+        assert_eq!(SolarTerm::DongZhi.date(2100), Some(ymd(2100, 12, 22)));
+        assert_eq!(SolarTerm::DongZhi.date(2101), None);
+    }
+
+    #[test]
+    fn solar_term_lookup() {
+        assert_eq!(SolarTerm::on(ymd(2025, 7, 22)), Some(SolarTerm::DaShu));
+        assert_eq!(SolarTerm::on(ymd(2025, 7, 20)), None);
+        assert_eq!(
+            SolarTerm::next_after(ymd(2025, 7, 20)),
+            Some((SolarTerm::DaShu, ymd(2025, 7, 22)))
+        );
+        assert_eq!(
+            SolarTerm::next_after(ymd(2025, 7, 22)),
+            Some((SolarTerm::LiQiu, ymd(2025, 8, 7)))
+        );
+        assert_eq!(
+            SolarTerm::next_after(ymd(2025, 12, 21)),
+            Some((SolarTerm::XiaoHan, ymd(2026, 1, 5)))
+        );
+        assert_eq!(SolarTerm::next_after(ymd(2100, 12, 31)), None);
+    }
+
+    #[test]
+    fn every_year_has_ordered_terms() {
+        for year in MIN_YEAR..=MAX_YEAR {
+            let dates = SolarTerm::ALL.map(|term| term.date(year).unwrap());
+            assert!(dates.windows(2).all(|pair| pair[0] < pair[1]), "{year}");
+        }
+    }
+}
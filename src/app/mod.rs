@@ -0,0 +1,10 @@
+pub mod alarm;
+pub mod calendar;
+pub mod cjk_font;
+pub mod clock;
+pub mod command;
+pub mod imu;
+pub mod lunar;
+pub mod month_view;
+pub mod timer;
+pub mod timezone;
@@ -0,0 +1,202 @@
+//! 月历视图
+//!
+//! 以周一为每周第一天，将一个月的日期排布为最多6行×7列的网格，
+//! 支持按月翻页并给出每行对应的ISO周数。
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+/// 网格最大行数
+pub const MAX_WEEKS: usize = 6;
+
+/// 每周天数（网格列数）
+pub const DAYS_PER_WEEK: usize = 7;
+
+/// 月历视图状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonthView {
+    /// 当前显示月份的第一天
+    first: NaiveDate,
+}
+
+impl MonthView {
+    /// 创建显示`date`所在月份的视图
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            first: date - Duration::days(date.day0() as i64),
+        }
+    }
+
+    /// 当前显示的年份
+    pub fn year(&self) -> i32 {
+        self.first.year()
+    }
+
+    /// 当前显示的月份（1-12）
+    pub fn month(&self) -> u32 {
+        self.first.month()
+    }
+
+    /// 当前显示月份的第一天
+    pub fn first_day(&self) -> NaiveDate {
+        self.first
+    }
+
+    /// 当前显示月份的天数
+    pub fn days(&self) -> u32 {
+        match self.first.checked_add_months(Months::new(1)) {
+            Some(next) => (next - self.first).num_days() as u32,
+            None => 31, // 仅chrono支持范围的最后一个月（12月）
+        }
+    }
+
+    /// 向后（`delta` > 0）或向前翻动`delta`个月，超出日期范围时保持不变
+    pub fn scroll(&mut self, delta: i32) {
+        let months = Months::new(delta.unsigned_abs());
+        let target = if delta >= 0 {
+            self.first.checked_add_months(months)
+        } else {
+            self.first.checked_sub_months(months)
+        };
+        if let Some(first) = target {
+            self.first = first;
+        }
+    }
+
+    /// 当前月份实际占用的行数（4-6）
+    pub fn weeks(&self) -> usize {
+        let cells = self.leading_blanks() + self.days() as usize;
+        cells.div_ceil(DAYS_PER_WEEK)
+    }
+
+    /// 网格中指定单元格的日期，不属于当前月份的单元格返回`None`
+    ///
+    /// # 参数
+    /// - `week`: 行号，从0开始
+    /// - `weekday`: 列号，0为周一
+    pub fn day_at(&self, week: usize, weekday: usize) -> Option<NaiveDate> {
+        if week >= MAX_WEEKS || weekday >= DAYS_PER_WEEK {
+            return None;
+        }
+        let date = self.row_monday(week)? + Duration::days(weekday as i64);
+        (date.month() == self.month() && date.year() == self.year()).then_some(date)
+    }
+
+    /// 指定行的ISO周数
+    pub fn week_number(&self, week: usize) -> Option<u32> {
+        if week >= self.weeks() {
+            return None;
+        }
+        // 行内任意一天都属于同一ISO周，取行内属于当月的第一天以避开范围边界
+        let day = (0..DAYS_PER_WEEK).find_map(|weekday| self.day_at(week, weekday))?;
+        Some(day.iso_week().week())
+    }
+
+    /// 日期在网格中的位置`(行, 列)`，不在当前月份时返回`None`
+    pub fn position(&self, date: NaiveDate) -> Option<(usize, usize)> {
+        if date.year() != self.year() || date.month() != self.month() {
+            return None;
+        }
+        let index = self.leading_blanks() + date.day0() as usize;
+        Some((index / DAYS_PER_WEEK, index % DAYS_PER_WEEK))
+    }
+
+    /// 第一行中位于1日之前的空白单元格数
+    fn leading_blanks(&self) -> usize {
+        self.first.weekday().num_days_from_monday() as usize
+    }
+
+    /// 指定行的周一（可能属于上个月）
+    fn row_monday(&self, week: usize) -> Option<NaiveDate> {
+        let offset = (week * DAYS_PER_WEEK) as i64 - self.leading_blanks() as i64;
+        self.first.checked_add_signed(Duration::days(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn lays_out_month_starting_on_monday() {
+        // 2025-07-01 为周二
+        let view = MonthView::new(date(2025, 7, 20));
+        assert_eq!((view.year(), view.month()), (2025, 7));
+        assert_eq!(view.first_day(), date(2025, 7, 1));
+        assert_eq!(view.days(), 31);
+        assert_eq!(view.weeks(), 5);
+
+        assert_eq!(view.day_at(0, 0), None);
+        assert_eq!(view.day_at(0, 1), Some(date(2025, 7, 1)));
+        assert_eq!(view.day_at(2, 6), Some(date(2025, 7, 20)));
+        assert_eq!(view.day_at(4, 3), Some(date(2025, 7, 31)));
+        assert_eq!(view.day_at(4, 4), None);
+        assert_eq!(view.day_at(5, 0), None);
+        assert_eq!(view.day_at(0, DAYS_PER_WEEK), None);
+    }
+
+    #[test]
+    fn row_count_depends_on_month_shape() {
+        // 2021-02-01 为周一且为平年，恰好4行
+        assert_eq!(MonthView::new(date(2021, 2, 1)).weeks(), 4);
+        // 2025-06-01 为周日，需要6行
+        let view = MonthView::new(date(2025, 6, 1));
+        assert_eq!(view.weeks(), 6);
+        assert_eq!(view.day_at(0, 6), Some(date(2025, 6, 1)));
+        assert_eq!(view.day_at(5, 0), Some(date(2025, 6, 30)));
+    }
+
+    #[test]
+    fn position_matches_day_at() {
+        for first in [date(2024, 2, 1), date(2025, 6, 1), date(2025, 12, 1)] {
+            let view = MonthView::new(first);
+            for day in 1..=view.days() {
+                let date = first.with_day(day).unwrap();
+                let (week, weekday) = view.position(date).unwrap();
+                assert_eq!(view.day_at(week, weekday), Some(date));
+            }
+            assert_eq!(view.position(first - Duration::days(1)), None);
+        }
+    }
+
+    #[test]
+    fn scrolls_across_year_boundaries() {
+        let mut view = MonthView::new(date(2025, 12, 31));
+        view.scroll(1);
+        assert_eq!(view.first_day(), date(2026, 1, 1));
+        view.scroll(-2);
+        assert_eq!(view.first_day(), date(2025, 11, 1));
+        view.scroll(-23);
+        assert_eq!(view.first_day(), date(2023, 12, 1));
+        view.scroll(0);
+        assert_eq!(view.first_day(), date(2023, 12, 1));
+    }
+
+    #[test]
+    fn leap_february_has_29_days() {
+        assert_eq!(MonthView::new(date(2024, 2, 10)).days(), 29);
+        assert_eq!(MonthView::new(date(2100, 2, 10)).days(), 28);
+        assert_eq!(MonthView::new(date(2000, 2, 10)).days(), 29);
+    }
+
+    #[test]
+    fn week_numbers_follow_iso_8601() {
+        // 2026-01-01 为周四，属于2026年第1周
+        let view = MonthView::new(date(2026, 1, 1));
+        assert_eq!(view.week_number(0), Some(1));
+        assert_eq!(view.week_number(4), Some(5));
+        assert_eq!(view.week_number(5), None);
+
+        // 2027-01-01 为周五，属于2026年第53周
+        let view = MonthView::new(date(2027, 1, 1));
+        assert_eq!(view.week_number(0), Some(53));
+        assert_eq!(view.week_number(1), Some(1));
+
+        // 2025-12-29 起的一周属于2026年第1周
+        let view = MonthView::new(date(2025, 12, 1));
+        assert_eq!(view.week_number(4), Some(1));
+    }
+}
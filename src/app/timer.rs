@@ -0,0 +1,417 @@
+//! 秒表与倒计时
+//!
+//! 计时均基于单调时钟`embassy_time::Instant`：只记录启动/暂停时刻和累计时长，
+//! 读取时由调用方传入当前时刻计算，不依赖固定周期的累加，长时间运行也不会产生累积误差。
+
+use embassy_time::{Duration, Instant};
+
+/// 倒计时结束后提醒的最长时间，超时后自动停止
+pub const RING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 倒计时可设置的最大时长（99:59）
+pub const MAX_COUNTDOWN: Duration = Duration::from_secs(99 * 60 + 59);
+
+/// 秒表
+///
+/// 保存最近`N`次计圈的单圈用时，更早的记录被覆盖，但圈数继续累加。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stopwatch<const N: usize> {
+    /// 运行中时为本次启动的时刻
+    started: Option<Instant>,
+    /// 本次启动之前累计的时长
+    accumulated: Duration,
+    /// 最近一次计圈时的总时长
+    last_split: Duration,
+    /// 单圈用时环形缓冲区
+    laps: [Duration; N],
+    /// 已记录的总圈数
+    lap_count: usize,
+}
+
+impl<const N: usize> Default for Stopwatch<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Stopwatch<N> {
+    /// 创建已清零、未运行的秒表
+    pub const fn new() -> Self {
+        Self {
+            started: None,
+            accumulated: Duration::from_ticks(0),
+            last_split: Duration::from_ticks(0),
+            laps: [Duration::from_ticks(0); N],
+            lap_count: 0,
+        }
+    }
+
+    /// 是否正在计时
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// 截至`now`的总时长
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        match self.started {
+            Some(started) => self.accumulated + now.saturating_duration_since(started),
+            None => self.accumulated,
+        }
+    }
+
+    /// 截至`now`的当前圈用时
+    pub fn current_lap(&self, now: Instant) -> Duration {
+        self.elapsed(now) - self.last_split
+    }
+
+    /// 开始或继续计时
+    pub fn start(&mut self, now: Instant) {
+        if self.started.is_none() {
+            self.started = Some(now);
+        }
+    }
+
+    /// 暂停计时
+    pub fn stop(&mut self, now: Instant) {
+        self.accumulated = self.elapsed(now);
+        self.started = None;
+    }
+
+    /// 在运行和暂停之间切换
+    pub fn toggle(&mut self, now: Instant) {
+        if self.is_running() {
+            self.stop(now);
+        } else {
+            self.start(now);
+        }
+    }
+
+    /// 停止并清零，同时清空计圈记录
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// 计圈，仅在运行中有效
+    ///
+    /// # 返回值
+    /// 刚结束的一圈用时
+    pub fn lap(&mut self, now: Instant) -> Option<Duration> {
+        if !self.is_running() {
+            return None;
+        }
+
+        let elapsed = self.elapsed(now);
+        let lap = elapsed - self.last_split;
+        self.last_split = elapsed;
+        if N > 0 {
+            self.laps[self.lap_count % N] = lap;
+        }
+        self.lap_count += 1;
+        Some(lap)
+    }
+
+    /// 已记录的总圈数
+    pub fn lap_count(&self) -> usize {
+        self.lap_count
+    }
+
+    /// 最近的计圈记录，从新到旧依次给出`(圈序号, 单圈用时)`，圈序号从1开始
+    pub fn recent_laps(&self) -> impl Iterator<Item = (usize, Duration)> + '_ {
+        let kept = self.lap_count.min(N);
+        (0..kept).map(move |i| {
+            let number = self.lap_count - i;
+            (number, self.laps[(number - 1) % N])
+        })
+    }
+}
+
+/// 倒计时状态
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountdownState {
+    /// 未启动，可调整设定时长
+    Idle,
+    /// 计时中
+    Running,
+    /// 已暂停
+    Paused,
+    /// 时间到，正在提醒
+    Expired,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Idle,
+    Running { deadline: Instant },
+    Paused { remaining: Duration },
+    Expired { since: Instant },
+}
+
+/// 倒计时器
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Countdown {
+    /// 设定时长
+    preset: Duration,
+    state: State,
+}
+
+impl Countdown {
+    /// 以指定设定时长创建倒计时器，超过[`MAX_COUNTDOWN`]的部分被截断
+    pub fn new(preset: Duration) -> Self {
+        Self {
+            preset: preset.min(MAX_COUNTDOWN),
+            state: State::Idle,
+        }
+    }
+
+    /// 设定时长
+    pub fn preset(&self) -> Duration {
+        self.preset
+    }
+
+    /// 当前状态
+    pub fn state(&self) -> CountdownState {
+        match self.state {
+            State::Idle => CountdownState::Idle,
+            State::Running { .. } => CountdownState::Running,
+            State::Paused { .. } => CountdownState::Paused,
+            State::Expired { .. } => CountdownState::Expired,
+        }
+    }
+
+    /// 是否时间到、正在提醒
+    pub fn is_expired(&self) -> bool {
+        matches!(self.state, State::Expired { .. })
+    }
+
+    /// 截至`now`的剩余时长
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.state {
+            State::Idle => self.preset,
+            State::Running { deadline } => deadline.saturating_duration_since(now),
+            State::Paused { remaining } => remaining,
+            State::Expired { .. } => Duration::from_ticks(0),
+        }
+    }
+
+    /// 调整设定时长，仅在未启动时有效
+    ///
+    /// 每一步的步长随时长增大：1分钟以内5秒，10分钟以内30秒，其余1分钟，
+    /// 结果限制在0到[`MAX_COUNTDOWN`]之间。
+    pub fn adjust(&mut self, steps: i32) {
+        if self.state != State::Idle {
+            return;
+        }
+
+        let mut secs = self.preset.as_secs();
+        for _ in 0..steps.unsigned_abs() {
+            secs = if steps > 0 {
+                secs + Self::step_for(secs)
+            } else {
+                secs.saturating_sub(Self::step_for(secs.saturating_sub(1)))
+            };
+        }
+        self.preset = Duration::from_secs(secs).min(MAX_COUNTDOWN);
+    }
+
+    /// 开始或继续倒计时，设定时长为0时不启动
+    pub fn start(&mut self, now: Instant) {
+        let remaining = match self.state {
+            State::Idle => self.preset,
+            State::Paused { remaining } => remaining,
+            State::Running { .. } | State::Expired { .. } => return,
+        };
+        if remaining.as_ticks() > 0 {
+            self.state = State::Running {
+                deadline: now + remaining,
+            };
+        }
+    }
+
+    /// 暂停倒计时
+    pub fn pause(&mut self, now: Instant) {
+        if let State::Running { .. } = self.state {
+            self.state = State::Paused {
+                remaining: self.remaining(now),
+            };
+        }
+    }
+
+    /// 在运行和暂停之间切换
+    pub fn toggle(&mut self, now: Instant) {
+        match self.state {
+            State::Running { .. } => self.pause(now),
+            _ => self.start(now),
+        }
+    }
+
+    /// 停止并恢复到设定时长，同时停止提醒
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+    }
+
+    /// 检查是否到时
+    ///
+    /// 到时后进入提醒状态，提醒超过[`RING_TIMEOUT`]后自动复位。
+    ///
+    /// # 返回值
+    /// 本次调用是否刚刚到时
+    pub fn poll(&mut self, now: Instant) -> bool {
+        match self.state {
+            State::Running { deadline } if now >= deadline => {
+                self.state = State::Expired { since: deadline };
+                true
+            }
+            State::Expired { since } if now.saturating_duration_since(since) >= RING_TIMEOUT => {
+                self.state = State::Idle;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// 设定时长为`secs`秒时向上调整的步长（秒）
+    fn step_for(secs: u64) -> u64 {
+        match secs {
+            0..60 => 5,
+            60..600 => 30,
+            _ => 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: u64) -> Instant {
+        Instant::from_millis(ms)
+    }
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn stopwatch_accumulates_across_pauses() {
+        let mut stopwatch = Stopwatch::<3>::new();
+        assert_eq!(stopwatch.elapsed(at(5_000)), ms(0));
+
+        stopwatch.start(at(1_000));
+        assert!(stopwatch.is_running());
+        assert_eq!(stopwatch.elapsed(at(3_500)), ms(2_500));
+
+        stopwatch.toggle(at(4_000));
+        assert!(!stopwatch.is_running());
+        assert_eq!(stopwatch.elapsed(at(60_000)), ms(3_000));
+
+        stopwatch.toggle(at(10_000));
+        assert_eq!(stopwatch.elapsed(at(12_250)), ms(5_250));
+
+        // 重复启动不会重置起点
+        stopwatch.start(at(12_000));
+        assert_eq!(stopwatch.elapsed(at(12_250)), ms(5_250));
+
+        stopwatch.reset();
+        assert_eq!(stopwatch, Stopwatch::new());
+    }
+
+    #[test]
+    fn stopwatch_records_recent_laps() {
+        let mut stopwatch = Stopwatch::<3>::new();
+        assert_eq!(stopwatch.lap(at(0)), None);
+
+        stopwatch.start(at(0));
+        assert_eq!(stopwatch.lap(at(1_000)), Some(ms(1_000)));
+        assert_eq!(stopwatch.lap(at(2_500)), Some(ms(1_500)));
+        stopwatch.stop(at(3_000));
+        assert_eq!(stopwatch.current_lap(at(9_000)), ms(500));
+        assert_eq!(stopwatch.lap(at(9_000)), None);
+
+        stopwatch.start(at(10_000));
+        assert_eq!(stopwatch.lap(at(10_750)), Some(ms(1_250)));
+        assert_eq!(stopwatch.lap(at(11_000)), Some(ms(250)));
+        assert_eq!(stopwatch.lap_count(), 4);
+
+        let mut laps = stopwatch.recent_laps();
+        assert_eq!(laps.next(), Some((4, ms(250))));
+        assert_eq!(laps.next(), Some((3, ms(1_250))));
+        assert_eq!(laps.next(), Some((2, ms(1_500))));
+        assert_eq!(laps.next(), None);
+    }
+
+    #[test]
+    fn countdown_runs_pauses_and_expires() {
+        let mut countdown = Countdown::new(Duration::from_secs(10));
+        assert_eq!(countdown.state(), CountdownState::Idle);
+        assert_eq!(countdown.remaining(at(0)), Duration::from_secs(10));
+
+        countdown.start(at(1_000));
+        assert_eq!(countdown.state(), CountdownState::Running);
+        assert_eq!(countdown.remaining(at(4_000)), Duration::from_secs(7));
+        assert!(!countdown.poll(at(4_000)));
+
+        countdown.toggle(at(5_000));
+        assert_eq!(countdown.state(), CountdownState::Paused);
+        assert_eq!(countdown.remaining(at(60_000)), Duration::from_secs(6));
+        assert!(!countdown.poll(at(60_000)));
+
+        countdown.toggle(at(100_000));
+        assert!(!countdown.poll(at(105_875)));
+        assert!(countdown.poll(at(106_000)));
+        assert!(countdown.is_expired());
+        assert_eq!(countdown.remaining(at(106_000)), ms(0));
+        assert!(!countdown.poll(at(106_500)));
+
+        countdown.reset();
+        assert_eq!(countdown.state(), CountdownState::Idle);
+        assert_eq!(countdown.remaining(at(0)), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn late_poll_still_expires_and_ring_times_out() {
+        let mut countdown = Countdown::new(Duration::from_secs(5));
+        countdown.start(at(0));
+        assert!(countdown.poll(at(30_000)));
+
+        // 提醒时长从到时时刻算起
+        assert!(!countdown.poll(at(64_875)));
+        assert!(countdown.is_expired());
+        assert!(!countdown.poll(at(65_000)));
+        assert_eq!(countdown.state(), CountdownState::Idle);
+    }
+
+    #[test]
+    fn zero_preset_does_not_start() {
+        let mut countdown = Countdown::new(Duration::from_secs(0));
+        countdown.start(at(0));
+        assert_eq!(countdown.state(), CountdownState::Idle);
+        assert!(!countdown.poll(at(1_000)));
+    }
+
+    #[test]
+    fn adjust_uses_coarser_steps_for_longer_durations() {
+        let mut countdown = Countdown::new(Duration::from_secs(50));
+        countdown.adjust(2);
+        assert_eq!(countdown.preset(), Duration::from_secs(60));
+        countdown.adjust(1);
+        assert_eq!(countdown.preset(), Duration::from_secs(90));
+        countdown.adjust(-2);
+        assert_eq!(countdown.preset(), Duration::from_secs(55));
+
+        let mut countdown = Countdown::new(Duration::from_secs(9 * 60 + 30));
+        countdown.adjust(2);
+        assert_eq!(countdown.preset(), Duration::from_secs(11 * 60));
+        countdown.adjust(-3);
+        assert_eq!(countdown.preset(), Duration::from_secs(9 * 60));
+
+        countdown.adjust(-1000);
+        assert_eq!(countdown.preset(), Duration::from_secs(0));
+        countdown.adjust(1000);
+        assert_eq!(countdown.preset(), MAX_COUNTDOWN);
+
+        // 运行中不可调整
+        countdown.start(at(0));
+        countdown.adjust(-1);
+        assert_eq!(countdown.preset(), MAX_COUNTDOWN);
+    }
+}
@@ -0,0 +1,828 @@
+use super::clock::{days_in_month, is_leap_year};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use core::fmt;
+
+/// 时区缩写的最大长度
+pub const ABBREVIATION_CAPACITY: usize = 7;
+
+/// 格式化后POSIX TZ规则字符串的最大长度
+///
+/// 最长形如`<UTC+530>-24:59:59<UTC+630>-24:59:59,M12.5.6/-167:59:59,M12.5.6/-167:59:59`。
+pub const RULE_CAPACITY: usize = 74;
+
+/// 未指定切换时刻时的默认值（02:00:00）
+const DEFAULT_TRANSITION_TIME: i32 = 2 * 3600;
+
+/// 时区预设
+pub struct TimeZonePreset {
+    /// 显示名称（不超过16个字符）
+    pub name: &'static str,
+    /// POSIX TZ规则字符串
+    pub rule: &'static str,
+}
+
+/// 可在界面中选择的时区预设
+pub const PRESETS: &[TimeZonePreset] = &[
+    TimeZonePreset {
+        name: "Beijing",
+        rule: "CST-8",
+    },
+    TimeZonePreset {
+        name: "UTC",
+        rule: "UTC0",
+    },
+    TimeZonePreset {
+        name: "London",
+        rule: "GMT0BST,M3.5.0/1,M10.5.0",
+    },
+    TimeZonePreset {
+        name: "Berlin",
+        rule: "CET-1CEST,M3.5.0,M10.5.0/3",
+    },
+    TimeZonePreset {
+        name: "Moscow",
+        rule: "MSK-3",
+    },
+    TimeZonePreset {
+        name: "Kolkata",
+        rule: "IST-5:30",
+    },
+    TimeZonePreset {
+        name: "Tokyo",
+        rule: "JST-9",
+    },
+    TimeZonePreset {
+        name: "Sydney",
+        rule: "AEST-10AEDT,M10.1.0,M4.1.0/3",
+    },
+    TimeZonePreset {
+        name: "Auckland",
+        rule: "NZST-12NZDT,M9.5.0,M4.1.0/3",
+    },
+    TimeZonePreset {
+        name: "New York",
+        rule: "EST5EDT,M3.2.0,M11.1.0",
+    },
+    TimeZonePreset {
+        name: "Chicago",
+        rule: "CST6CDT,M3.2.0,M11.1.0",
+    },
+    TimeZonePreset {
+        name: "Los Angeles",
+        rule: "PST8PDT,M3.2.0,M11.1.0",
+    },
+];
+
+/// 时区规则解析错误
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// 时区缩写缺失、过短或过长
+    InvalidName,
+    /// UTC偏移量格式错误或超出范围
+    InvalidOffset,
+    /// 夏令时切换日期格式错误或超出范围
+    InvalidRule,
+    /// 指定了夏令时但缺少切换规则
+    MissingRule,
+    /// 规则末尾存在多余字符
+    TrailingCharacters,
+}
+
+/// 时区缩写（如`CST`、`CEST`、`+0530`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Abbreviation {
+    bytes: [u8; ABBREVIATION_CAPACITY],
+    len: u8,
+}
+
+impl Abbreviation {
+    fn new(name: &str) -> Option<Self> {
+        if name.len() < 3 || name.len() > ABBREVIATION_CAPACITY {
+            return None;
+        }
+
+        let mut bytes = [0; ABBREVIATION_CAPACITY];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        Some(Self {
+            bytes,
+            len: name.len() as u8,
+        })
+    }
+
+    /// 获取缩写字符串
+    pub fn as_str(&self) -> &str {
+        // 构造时已保证内容来自合法的ASCII子串
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl fmt::Display for Abbreviation {
+    /// 按POSIX格式输出，非纯字母的缩写用尖括号括起
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.as_str();
+        match name.bytes().all(|b| b.is_ascii_alphabetic()) {
+            true => f.write_str(name),
+            false => write!(f, "<{name}>"),
+        }
+    }
+}
+
+/// 夏令时切换日期
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleDate {
+    /// `Mm.w.d`：第`m`月第`w`个星期`d`（`w = 5`表示最后一个，`d = 0`表示周日）
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+    /// `Jn`：一年中的第`n`天（1-365，不计2月29日）
+    Julian1(u16),
+    /// `n`：一年中的第`n`天（0-365，计2月29日）
+    Julian0(u16),
+}
+
+impl RuleDate {
+    /// 计算指定年份的切换日期
+    fn date(self, year: i32) -> Option<NaiveDate> {
+        match self {
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let month = month as u32;
+                let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                let first_weekday = first.weekday().num_days_from_sunday();
+                let mut day = 1 + (weekday as u32 + 7 - first_weekday) % 7 + (week as u32 - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+                NaiveDate::from_ymd_opt(year, month, day)
+            }
+            RuleDate::Julian1(n) => {
+                let skip_leap_day = is_leap_year(year) && n >= 60;
+                NaiveDate::from_yo_opt(year, n as u32 + skip_leap_day as u32)
+            }
+            RuleDate::Julian0(n) => NaiveDate::from_yo_opt(year, n as u32 + 1),
+        }
+    }
+}
+
+/// 夏令时切换时刻：日期加当地时间（可为负数或超过24小时）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub date: RuleDate,
+    /// 切换时刻，以切换前生效的当地时间计，单位秒
+    pub time: i32,
+}
+
+impl Transition {
+    /// 计算指定年份切换时刻对应的UTC时间
+    ///
+    /// # 参数
+    /// - `year`: 年份
+    /// - `offset`: 切换前生效的UTC偏移量（秒，东正西负）
+    fn utc(self, year: i32, offset: i32) -> Option<NaiveDateTime> {
+        let midnight = self.date.date(year)?.and_hms_opt(0, 0, 0)?;
+        midnight.checked_add_signed(Duration::seconds((self.time - offset) as i64))
+    }
+}
+
+impl fmt::Display for Transition {
+    /// 按POSIX格式输出，切换时刻为默认的02:00:00时省略
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.date {
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => write!(f, "M{month}.{week}.{weekday}")?,
+            RuleDate::Julian1(n) => write!(f, "J{n}")?,
+            RuleDate::Julian0(n) => write!(f, "{n}")?,
+        }
+        if self.time != DEFAULT_TRANSITION_TIME {
+            f.write_str("/")?;
+            write_offset(f, self.time)?;
+        }
+        Ok(())
+    }
+}
+
+/// 夏令时规则
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DstRule {
+    /// 夏令时缩写
+    pub name: Abbreviation,
+    /// 夏令时UTC偏移量（秒，东正西负）
+    pub offset: i32,
+    /// 进入夏令时的时刻（以标准时间计）
+    pub start: Transition,
+    /// 退出夏令时的时刻（以夏令时计）
+    pub end: Transition,
+}
+
+/// 时区
+///
+/// 由固定的标准时间偏移量和可选的夏令时规则组成，
+/// 使用POSIX TZ格式描述，例如`CST-8`、`CET-1CEST,M3.5.0,M10.5.0/3`。
+///
+/// 注意POSIX格式中偏移量的符号与常用写法相反：`CST-8`表示UTC+8。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeZone {
+    /// 标准时间缩写
+    pub name: Abbreviation,
+    /// 标准时间UTC偏移量（秒，东正西负）
+    pub offset: i32,
+    /// 夏令时规则
+    pub dst: Option<DstRule>,
+}
+
+impl TimeZone {
+    /// UTC时区
+    pub fn utc() -> Self {
+        Self {
+            name: Abbreviation::new("UTC").unwrap(),
+            offset: 0,
+            dst: None,
+        }
+    }
+
+    /// 解析POSIX TZ规则字符串
+    ///
+    /// 格式：`std offset [dst [offset] ,start[/time],end[/time]]`
+    /// - `std`/`dst`: 3-7个字母，或用尖括号括起的字母、数字和正负号（如`<+0530>`）
+    /// - `offset`: `[+|-]hh[:mm[:ss]]`，正数表示UTC以西
+    /// - `start`/`end`: `Mm.w.d`、`Jn`或`n`
+    /// - `time`: `[+|-]hh[:mm[:ss]]`，默认02:00:00
+    pub fn parse(rule: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser { rest: rule };
+
+        let name = parser.name()?;
+        let offset = -parser.offset(24).ok_or(ParseError::InvalidOffset)?;
+
+        let dst = if parser.rest.is_empty() {
+            None
+        } else {
+            let dst_name = parser.name()?;
+            let dst_offset = match parser.rest.is_empty() || parser.rest.starts_with(',') {
+                true => offset + 3600,
+                false => -parser.offset(24).ok_or(ParseError::InvalidOffset)?,
+            };
+
+            if !parser.eat(',') {
+                return Err(ParseError::MissingRule);
+            }
+            let start = parser.transition()?;
+            if !parser.eat(',') {
+                return Err(ParseError::MissingRule);
+            }
+            let end = parser.transition()?;
+
+            Some(DstRule {
+                name: dst_name,
+                offset: dst_offset,
+                start,
+                end,
+            })
+        };
+
+        if !parser.rest.is_empty() {
+            return Err(ParseError::TrailingCharacters);
+        }
+
+        Ok(Self { name, offset, dst })
+    }
+
+    /// 指定UTC时刻是否处于夏令时
+    pub fn is_dst(&self, utc: NaiveDateTime) -> bool {
+        let Some(dst) = self.dst else {
+            return false;
+        };
+
+        // 以当地标准时间所在年份计算当年的切换时刻
+        let year = (utc + Duration::seconds(self.offset as i64)).year();
+        let (Some(start), Some(end)) = (
+            dst.start.utc(year, self.offset),
+            dst.end.utc(year, dst.offset),
+        ) else {
+            return false;
+        };
+
+        if start < end {
+            // 北半球：夏令时位于年中
+            start <= utc && utc < end
+        } else {
+            // 南半球：夏令时跨越年初
+            utc < end || start <= utc
+        }
+    }
+
+    /// 指定UTC时刻的UTC偏移量（秒，东正西负）
+    pub fn offset_at(&self, utc: NaiveDateTime) -> i32 {
+        match self.dst {
+            Some(dst) if self.is_dst(utc) => dst.offset,
+            _ => self.offset,
+        }
+    }
+
+    /// 指定UTC时刻的时区缩写
+    pub fn abbreviation_at(&self, utc: NaiveDateTime) -> &str {
+        match &self.dst {
+            Some(dst) if self.is_dst(utc) => dst.name.as_str(),
+            _ => self.name.as_str(),
+        }
+    }
+
+    /// UTC时间转换为当地时间
+    pub fn to_local(&self, utc: NaiveDateTime) -> NaiveDateTime {
+        utc.checked_add_signed(Duration::seconds(self.offset_at(utc) as i64))
+            .unwrap_or(utc)
+    }
+
+    /// 当地时间转换为UTC时间
+    ///
+    /// 夏令时结束时重复的一小时按夏令时（先出现的时刻）解释；
+    /// 夏令时开始时跳过的一小时按标准时间解释。
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        let candidates = [self.dst.map(|dst| dst.offset), Some(self.offset)];
+
+        candidates
+            .into_iter()
+            .flatten()
+            .filter_map(|offset| local.checked_sub_signed(Duration::seconds(offset as i64)))
+            .find(|&utc| self.to_local(utc) == local)
+            .or_else(|| local.checked_sub_signed(Duration::seconds(self.offset as i64)))
+            .unwrap_or(local)
+    }
+}
+
+impl fmt::Display for TimeZone {
+    /// 输出POSIX TZ规则字符串，可由[`TimeZone::parse`]解析回相同的时区
+    ///
+    /// 省略可由默认值推出的部分，如夏令时偏移量为标准时间加1小时时不输出。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        write_offset(f, -self.offset)?;
+        if let Some(dst) = &self.dst {
+            write!(f, "{}", dst.name)?;
+            if dst.offset != self.offset + 3600 {
+                write_offset(f, -dst.offset)?;
+            }
+            write!(f, ",{},{}", dst.start, dst.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// 按`[-]hh[:mm[:ss]]`输出秒数，分和秒为零时省略
+fn write_offset(f: &mut fmt::Formatter<'_>, seconds: i32) -> fmt::Result {
+    if seconds < 0 {
+        f.write_str("-")?;
+    }
+    let seconds = seconds.unsigned_abs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    write!(f, "{hours}")?;
+    if minutes != 0 || seconds != 0 {
+        write!(f, ":{minutes:02}")?;
+    }
+    if seconds != 0 {
+        write!(f, ":{seconds:02}")?;
+    }
+    Ok(())
+}
+
+/// POSIX TZ规则解析器
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 取出满足条件的最长前缀
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let end = self.rest.find(|c| !f(c)).unwrap_or(self.rest.len());
+        let (head, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        head
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 3 {
+            return None;
+        }
+        digits.parse().ok()
+    }
+
+    fn name(&mut self) -> Result<Abbreviation, ParseError> {
+        let name = if self.eat('<') {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-');
+            if !self.eat('>') {
+                return Err(ParseError::InvalidName);
+            }
+            name
+        } else {
+            self.take_while(|c| c.is_ascii_alphabetic())
+        };
+
+        Abbreviation::new(name).ok_or(ParseError::InvalidName)
+    }
+
+    /// 解析`[+|-]hh[:mm[:ss]]`，返回秒数
+    fn offset(&mut self, max_hours: u32) -> Option<i32> {
+        let sign = if self.eat('-') {
+            -1
+        } else {
+            self.eat('+');
+            1
+        };
+
+        let hours = self.number()?;
+        let minutes = match self.eat(':') {
+            true => self.number()?,
+            false => 0,
+        };
+        let seconds = match self.eat(':') {
+            true => self.number()?,
+            false => 0,
+        };
+
+        if hours > max_hours || minutes > 59 || seconds > 59 {
+            return None;
+        }
+
+        Some(sign * (hours * 3600 + minutes * 60 + seconds) as i32)
+    }
+
+    fn transition(&mut self) -> Result<Transition, ParseError> {
+        let date = if self.eat('M') {
+            let month = self.number().ok_or(ParseError::InvalidRule)?;
+            let week = match self.eat('.') {
+                true => self.number().ok_or(ParseError::InvalidRule)?,
+                false => return Err(ParseError::InvalidRule),
+            };
+            let weekday = match self.eat('.') {
+                true => self.number().ok_or(ParseError::InvalidRule)?,
+                false => return Err(ParseError::InvalidRule),
+            };
+
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                return Err(ParseError::InvalidRule);
+            }
+
+            RuleDate::MonthWeekDay {
+                month: month as u8,
+                week: week as u8,
+                weekday: weekday as u8,
+            }
+        } else if self.eat('J') {
+            match self.number() {
+                Some(n @ 1..=365) => RuleDate::Julian1(n as u16),
+                _ => return Err(ParseError::InvalidRule),
+            }
+        } else {
+            match self.number() {
+                Some(n @ 0..=365) => RuleDate::Julian0(n as u16),
+                _ => return Err(ParseError::InvalidRule),
+            }
+        };
+
+        // 扩展格式允许切换时刻为-167到167小时
+        let time = match self.eat('/') {
+            true => self.offset(167).ok_or(ParseError::InvalidRule)?,
+            false => DEFAULT_TRANSITION_TIME,
+        };
+
+        Ok(Transition { date, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, mo, d)
+            .unwrap()
+            .and_hms_opt(h, mi, s)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_fixed_offsets() {
+        let tz = TimeZone::parse("CST-8").unwrap();
+        assert_eq!(tz.name.as_str(), "CST");
+        assert_eq!(tz.offset, 8 * 3600);
+        assert_eq!(tz.dst, None);
+
+        let tz = TimeZone::parse("IST-5:30").unwrap();
+        assert_eq!(tz.offset, 5 * 3600 + 30 * 60);
+
+        let tz = TimeZone::parse("<+0545>-5:45").unwrap();
+        assert_eq!(tz.name.as_str(), "+0545");
+        assert_eq!(tz.offset, 5 * 3600 + 45 * 60);
+
+        let tz = TimeZone::parse("<-03>3").unwrap();
+        assert_eq!(tz.offset, -3 * 3600);
+
+        let tz = TimeZone::parse("HST+10").unwrap();
+        assert_eq!(tz.offset, -10 * 3600);
+    }
+
+    #[test]
+    fn parses_dst_rule() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        assert_eq!(tz.offset, 3600);
+
+        let dst = tz.dst.unwrap();
+        assert_eq!(dst.name.as_str(), "CEST");
+        assert_eq!(dst.offset, 2 * 3600);
+        assert_eq!(
+            dst.start,
+            Transition {
+                date: RuleDate::MonthWeekDay {
+                    month: 3,
+                    week: 5,
+                    weekday: 0
+                },
+                time: 2 * 3600,
+            }
+        );
+        assert_eq!(dst.end.time, 3 * 3600);
+    }
+
+    #[test]
+    fn parses_explicit_dst_offset_and_julian_days() {
+        let tz = TimeZone::parse("XST3XDT2:30,J60/-1,300/26").unwrap();
+        let dst = tz.dst.unwrap();
+        assert_eq!(tz.offset, -3 * 3600);
+        assert_eq!(dst.offset, -(2 * 3600 + 30 * 60));
+        assert_eq!(dst.start.date, RuleDate::Julian1(60));
+        assert_eq!(dst.start.time, -3600);
+        assert_eq!(dst.end.date, RuleDate::Julian0(300));
+        assert_eq!(dst.end.time, 26 * 3600);
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert_eq!(TimeZone::parse(""), Err(ParseError::InvalidName));
+        assert_eq!(TimeZone::parse("AB-1"), Err(ParseError::InvalidName));
+        assert_eq!(TimeZone::parse("<+05-5"), Err(ParseError::InvalidName));
+        assert_eq!(TimeZone::parse("CET"), Err(ParseError::InvalidOffset));
+        assert_eq!(TimeZone::parse("CET-25"), Err(ParseError::InvalidOffset));
+        assert_eq!(TimeZone::parse("CET-1:60"), Err(ParseError::InvalidOffset));
+        assert_eq!(TimeZone::parse("CET-1CEST"), Err(ParseError::MissingRule));
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M3.5.0"),
+            Err(ParseError::MissingRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M13.5.0,M10.5.0"),
+            Err(ParseError::InvalidRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M3.6.0,M10.5.0"),
+            Err(ParseError::InvalidRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,J0,M10.5.0"),
+            Err(ParseError::InvalidRule)
+        );
+        assert_eq!(
+            TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3x"),
+            Err(ParseError::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn all_presets_parse() {
+        for preset in PRESETS {
+            assert!(TimeZone::parse(preset.rule).is_ok(), "{}", preset.name);
+            assert!(preset.name.len() <= 16);
+        }
+    }
+
+    #[test]
+    fn formats_rules_that_parse_back() {
+        use core::fmt::Write;
+
+        fn format(tz: &TimeZone) -> heapless::String<RULE_CAPACITY> {
+            let mut out = heapless::String::new();
+            write!(out, "{tz}").unwrap();
+            out
+        }
+
+        for rule in [
+            "CST-8",
+            "IST-5:30",
+            "<+0545>-5:45",
+            "<-03>3",
+            "CET-1CEST,M3.5.0,M10.5.0/3",
+            "EST5EDT,M3.2.0,M11.1.0",
+            "XST3XDT2:30,J60/-1,300/26",
+            "<+1030>-10:30<+11>-11,M10.1.0,M4.1.0",
+            "ABC-0:00:30DEF,M1.1.1/-167:59:59,J365/0",
+        ] {
+            let tz = TimeZone::parse(rule).unwrap();
+            assert_eq!(format(&tz), rule);
+            assert_eq!(TimeZone::parse(&format(&tz)), Ok(tz));
+        }
+
+        // 默认值被省略
+        let tz = TimeZone::parse("CET-1:00CEST-2,M3.5.0/2,M10.5.0/3").unwrap();
+        assert_eq!(format(&tz), "CET-1CEST,M3.5.0,M10.5.0/3");
+
+        let longest = "<UTC+530>-24:59:59<UTC+630>-24:59:59,M12.5.6/-167:59:59,M12.5.6/-167:59:59";
+        assert_eq!(longest.len(), RULE_CAPACITY);
+        let tz = TimeZone::parse(longest).unwrap();
+        assert_eq!(format(&tz), longest);
+    }
+
+    #[test]
+    fn rule_dates() {
+        let last_sunday_march = RuleDate::MonthWeekDay {
+            month: 3,
+            week: 5,
+            weekday: 0,
+        };
+        assert_eq!(
+            last_sunday_march.date(2025),
+            NaiveDate::from_ymd_opt(2025, 3, 30)
+        );
+        assert_eq!(
+            last_sunday_march.date(2024),
+            NaiveDate::from_ymd_opt(2024, 3, 31)
+        );
+
+        let second_sunday_march = RuleDate::MonthWeekDay {
+            month: 3,
+            week: 2,
+            weekday: 0,
+        };
+        assert_eq!(
+            second_sunday_march.date(2025),
+            NaiveDate::from_ymd_opt(2025, 3, 9)
+        );
+
+        assert_eq!(
+            RuleDate::Julian1(60).date(2024),
+            NaiveDate::from_ymd_opt(2024, 3, 1)
+        );
+        assert_eq!(
+            RuleDate::Julian1(59).date(2024),
+            NaiveDate::from_ymd_opt(2024, 2, 28)
+        );
+        assert_eq!(
+            RuleDate::Julian0(59).date(2024),
+            NaiveDate::from_ymd_opt(2024, 2, 29)
+        );
+        assert_eq!(
+            RuleDate::Julian0(365).date(2024),
+            NaiveDate::from_ymd_opt(2024, 12, 31)
+        );
+    }
+
+    #[test]
+    fn central_europe_transitions() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+        // 2025-03-30 02:00 CET → 03:00 CEST（01:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 3, 30, 0, 59, 59)),
+            at(2025, 3, 30, 1, 59, 59)
+        );
+        assert_eq!(tz.abbreviation_at(at(2025, 3, 30, 0, 59, 59)), "CET");
+        assert_eq!(
+            tz.to_local(at(2025, 3, 30, 1, 0, 0)),
+            at(2025, 3, 30, 3, 0, 0)
+        );
+        assert_eq!(tz.abbreviation_at(at(2025, 3, 30, 1, 0, 0)), "CEST");
+
+        // 2025-10-26 03:00 CEST → 02:00 CET（01:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 10, 26, 0, 59, 59)),
+            at(2025, 10, 26, 2, 59, 59)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 10, 26, 1, 0, 0)),
+            at(2025, 10, 26, 2, 0, 0)
+        );
+
+        assert_eq!(
+            tz.to_local(at(2025, 1, 1, 0, 0, 0)),
+            at(2025, 1, 1, 1, 0, 0)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 7, 20, 10, 0, 0)),
+            at(2025, 7, 20, 12, 0, 0)
+        );
+    }
+
+    #[test]
+    fn us_eastern_transitions() {
+        let tz = TimeZone::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+
+        // 2025-03-09 02:00 EST → 03:00 EDT（07:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 3, 9, 6, 59, 59)),
+            at(2025, 3, 9, 1, 59, 59)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 3, 9, 7, 0, 0)),
+            at(2025, 3, 9, 3, 0, 0)
+        );
+
+        // 2025-11-02 02:00 EDT → 01:00 EST（06:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 11, 2, 5, 59, 59)),
+            at(2025, 11, 2, 1, 59, 59)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 11, 2, 6, 0, 0)),
+            at(2025, 11, 2, 1, 0, 0)
+        );
+
+        // UTC新年时当地仍在前一年
+        assert_eq!(
+            tz.to_local(at(2025, 1, 1, 3, 0, 0)),
+            at(2024, 12, 31, 22, 0, 0)
+        );
+    }
+
+    #[test]
+    fn southern_hemisphere_transitions() {
+        let tz = TimeZone::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+
+        // 2025-04-06 03:00 AEDT → 02:00 AEST（04-05 16:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 4, 5, 15, 59, 59)),
+            at(2025, 4, 6, 2, 59, 59)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 4, 5, 16, 0, 0)),
+            at(2025, 4, 6, 2, 0, 0)
+        );
+
+        // 2025-10-05 02:00 AEST → 03:00 AEDT（10-04 16:00 UTC）
+        assert_eq!(
+            tz.to_local(at(2025, 10, 4, 15, 59, 59)),
+            at(2025, 10, 5, 1, 59, 59)
+        );
+        assert_eq!(
+            tz.to_local(at(2025, 10, 4, 16, 0, 0)),
+            at(2025, 10, 5, 3, 0, 0)
+        );
+
+        // 跨年期间处于夏令时
+        assert!(tz.is_dst(at(2024, 12, 31, 14, 0, 0)));
+        assert!(tz.is_dst(at(2025, 1, 1, 0, 0, 0)));
+        assert!(!tz.is_dst(at(2025, 7, 1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn local_to_utc_round_trip() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        let mut utc = at(2025, 1, 1, 0, 30, 0);
+        while utc < at(2026, 1, 1, 0, 0, 0) {
+            let local = tz.to_local(utc);
+            let back = tz.to_utc(local);
+            // 重复的一小时按夏令时解释
+            if back != utc {
+                assert_eq!(back, utc - Duration::hours(1));
+                assert_eq!(tz.to_local(back), local);
+            }
+            utc += Duration::hours(1);
+        }
+    }
+
+    #[test]
+    fn local_to_utc_gap_and_overlap() {
+        let tz = TimeZone::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+        // 02:30 在3月30日不存在，按标准时间解释
+        assert_eq!(
+            tz.to_utc(at(2025, 3, 30, 2, 30, 0)),
+            at(2025, 3, 30, 1, 30, 0)
+        );
+        // 02:30 在10月26日出现两次，取夏令时
+        assert_eq!(
+            tz.to_utc(at(2025, 10, 26, 2, 30, 0)),
+            at(2025, 10, 26, 0, 30, 0)
+        );
+    }
+
+    #[test]
+    fn fixed_offset_round_trip() {
+        let tz = TimeZone::parse("CST-8").unwrap();
+        let utc = at(2025, 7, 20, 10, 0, 0);
+        assert_eq!(tz.to_local(utc), at(2025, 7, 20, 18, 0, 0));
+        assert_eq!(tz.to_utc(at(2025, 7, 20, 18, 0, 0)), utc);
+        assert_eq!(tz.abbreviation_at(utc), "CST");
+    }
+}
@@ -23,64 +23,190 @@
 //!      DT   -> PA9 (TIM1_CH2)
 //!      SW   -> PB15 (with pull-up)
 //!
+//!   Buzzer (optional, active high):
+//!      I/O  -> PA1
+//!
+//!   USB (on-board micro USB connector):
+//!      D-   -> PA11
+//!      D+   -> PA12 (1.5k pull-up on the board)
+//!
+//!   Backup Domain:
+//!      32.768kHz crystal -> PC14/PC15 (on-board LSE)
+//!      CR2032 coin cell  -> VBAT (keeps the RTC running across power cycles)
+//!
 //! Features:
-//! 1. Real-time clock with date and weekday display
+//! 1. Hardware RTC (LSE) clock with date and weekday display
 //! 2. Time adjustment interface with visual cursor
 //! 3. Rotary encoder for value modification
 //! 4. Button for field selection
 //! 5. Onboard LED heartbeat indicator
+//! 6. Recurring alarms with snooze (LED + buzzer output)
+//! 7. Time zone and DST rules (RTC keeps UTC, display shows local time)
+//! 8. Chinese lunar calendar with stem-branch year, zodiac and solar terms
+//! 9. Month grid view with ISO week numbers (button on the lunar screen to
+//!    enter/exit, encoder to scroll months)
+//! 10. Stopwatch with lap times and countdown timer (long-press the button to
+//!     switch modes), timed with `embassy_time::Instant`
+//! 11. USB CDC-ACM serial commands (`time get`, `time set`, `alarm list`, see
+//!     `main_cargo::app::command`) for setting the clock from a PC
+//! 12. Settings (alarms, time zone, 12/24-hour format, brightness) kept in the
+//!     last flash pages and restored at boot
 
 #![no_std]
 #![no_main]
 
-use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike, Weekday};
+use chrono::{Datelike, NaiveDate, Timelike, Weekday};
 use core::fmt::Write;
 use defmt_rtt as _; // Global logger
 use embassy_executor::Spawner;
+use embassy_futures::select::{Either, select};
 use embassy_stm32::{
     bind_interrupts,
     exti::ExtiInput,
+    flash::Flash,
     gpio::{Level, Output, Pull, Speed},
     i2c::{self, ErrorInterruptHandler, EventInterruptHandler},
     peripherals,
     time::Hertz,
     timer::qei::{Qei, QeiPin},
+    usb,
 };
 use embassy_sync::{
     blocking_mutex::raw::ThreadModeRawMutex,
     channel::{Channel, Receiver, Sender},
 };
-use embassy_time::{Ticker, Timer};
+use embassy_time::{Instant, Ticker, Timer};
+use embassy_usb::{
+    Builder, UsbDevice,
+    class::cdc_acm::{CdcAcmClass, State},
+    driver::EndpointError,
+};
 use embedded_graphics::{
-    mono_font::{MonoTextStyleBuilder, ascii::FONT_8X13, ascii::FONT_10X20},
+    mono_font::{
+        MonoTextStyle, MonoTextStyleBuilder,
+        ascii::{FONT_5X8, FONT_6X10, FONT_8X13, FONT_10X20},
+    },
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{Line, PrimitiveStyle},
+    primitives::{Line, PrimitiveStyle, Rectangle},
     text::{Baseline, Text},
 };
 use heapless::String;
+use main_cargo::app::{
+    alarm::{AlarmField, Repeat},
+    calendar::{CalendarApp, Mode, Screen},
+    cjk_font::FONT_CJK_12X12,
+    clock::EditField,
+    command::{Command, LineBuffer, respond_error},
+    lunar::{LunarDate, SolarTerm},
+    month_view::{DAYS_PER_WEEK, MAX_WEEKS},
+    timer::CountdownState,
+    timezone::{ABBREVIATION_CAPACITY, PRESETS},
+};
+use main_cargo::hardware::{
+    alarm_output::AlarmOutput,
+    board::clock_config,
+    config::{SETTINGS_OFFSET, SETTINGS_SIZE, Settings},
+    display::brightness_for,
+    flash_store::KvStore,
+    gpio_buzzer::GpioBuzzer,
+    gpio_led::GpioLed,
+    stm32_rtc::Stm32Rtc,
+    traits::{Led, TimeSource},
+};
 use panic_probe as _; // Panic handler
 use ssd1306::{I2CDisplayInterface, Ssd1306, prelude::*};
+use static_cell::StaticCell;
+
+/// USB full-speed bulk endpoint packet size
+const USB_PACKET_SIZE: u16 = 64;
+
+/// Maximum length of a serial command response
+const RESPONSE_CAPACITY: usize = 256;
 
-// Channel for sharing RTC data between tasks
-static RTC_CHANNEL: Channel<ThreadModeRawMutex, NaiveDateTime, 2> = Channel::new();
+type UsbDriver = usb::Driver<'static, peripherals::USB>;
+
+// Channel for sharing application state (clock, alarms, screen) between tasks
+static RTC_CHANNEL: Channel<ThreadModeRawMutex, CalendarApp, 2> = Channel::new();
+
+// Channel for alarm ringing state (drives LED/buzzer output)
+static ALARM_CHANNEL: Channel<ThreadModeRawMutex, bool, 1> = Channel::new();
 
 // Channel for rotary encoder delta values
 static ARE_CHANNEL: Channel<ThreadModeRawMutex, i32, 3> = Channel::new();
 
-// Channel for button press events (field selection)
-static KEY_CHANNEL: Channel<ThreadModeRawMutex, i32, 1> = Channel::new();
+// Channel for button press events, stamped with the instant the button went down
+static KEY_CHANNEL: Channel<ThreadModeRawMutex, (KeyPress, Instant), 1> = Channel::new();
+
+// Channel for commands received over USB serial
+static COMMAND_CHANNEL: Channel<ThreadModeRawMutex, Command, 1> = Channel::new();
+
+// Channel for command responses sent back over USB serial
+static RESPONSE_CHANNEL: Channel<ThreadModeRawMutex, String<RESPONSE_CAPACITY>, 1> = Channel::new();
+
+/// Serial command endpoints served by `rtc_update`
+struct SerialLink {
+    /// Parsed commands from `usb_serial`
+    commands: Receiver<'static, ThreadModeRawMutex, Command, 1>,
+    /// Responses back to `usb_serial`
+    responses: Sender<'static, ThreadModeRawMutex, String<RESPONSE_CAPACITY>, 1>,
+}
+
+/// Non-volatile state owned by `rtc_update`
+struct Storage {
+    /// Backup-domain RTC counting UTC
+    rtc: Stm32Rtc,
+    /// Settings store in the flash pages reserved by `memory.x`, `None` if unusable
+    settings: Option<KvStore<Flash<'static, embassy_stm32::mode::Blocking>>>,
+}
+
+/// Button press kinds reported by `key_update`
+#[derive(Clone, Copy)]
+enum KeyPress {
+    /// Released before the long-press threshold
+    Short,
+    /// Held for at least the long-press threshold
+    Long,
+}
 
 /// Main application entry point
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
-    // Initialize peripherals with default configuration
-    let p = embassy_stm32::init(Default::default());
+    // 72MHz system clock from the 8MHz HSE crystal, so that USB gets its
+    // 48MHz clock (PLL / 1.5)
+    let mut config = clock_config();
+    // Use the 32.768kHz LSE crystal as RTC clock source
+    config.rcc.ls = embassy_stm32::rcc::LsConfig::default_lse();
+
+    // Initialize peripherals
+    let mut p = embassy_stm32::init(config);
+
+    // Hardware RTC in the backup domain, counting UTC; falls back to
+    // 2025-07-20 10:00:00 UTC (18:00 Beijing time) if the backup domain lost power
+    let rtc = Stm32Rtc::new(
+        p.RTC,
+        NaiveDate::from_ymd_opt(2025, 7, 20)
+            .unwrap()
+            .and_hms_opt(10, 00, 00)
+            .unwrap(),
+    );
+
+    // Settings store in the last flash pages (formatted on first boot); without
+    // it the calendar keeps running on default settings that are not saved
+    let settings = match KvStore::new(Flash::new_blocking(p.FLASH), SETTINGS_OFFSET, SETTINGS_SIZE)
+    {
+        Ok(store) => Some(store),
+        Err(_) => {
+            defmt::warn!("Settings store unavailable, settings will not be saved");
+            None
+        }
+    };
 
-    // Bind I2C interrupt handlers
+    // Bind I2C and USB interrupt handlers
     bind_interrupts!(struct Irqs {
         I2C1_EV => EventInterruptHandler<peripherals::I2C1>;
         I2C1_ER => ErrorInterruptHandler<peripherals::I2C1>;
+        USB_LP_CAN1_RX0 => usb::InterruptHandler<peripherals::USB>;
     });
 
     // Configure I2C peripheral at 400kHz
@@ -101,12 +227,53 @@ async fn main(_spawner: Spawner) {
     // Configure button with external interrupt (pull-up configuration)
     let key_exti = ExtiInput::new(p.PB15, p.EXTI15, Pull::Up);
 
+    // The Blue Pill has a fixed pull-up on D+; pull D+ low briefly so the host
+    // re-enumerates the device after a firmware reset
+    {
+        let _dp = Output::new(&mut p.PA12, Level::Low, Speed::Low);
+        Timer::after_millis(10).await;
+    }
+
+    // USB CDC-ACM serial port (test VID/PID)
+    let driver = usb::Driver::new(p.USB, Irqs, p.PA12, p.PA11);
+    let mut usb_config = embassy_usb::Config::new(0xc0de, 0xcafe);
+    usb_config.manufacturer = Some("rust-embassy-stm32f1");
+    usb_config.product = Some("Calendar");
+    usb_config.max_power = 100;
+    usb_config.max_packet_size_0 = USB_PACKET_SIZE as u8;
+
+    // Descriptor buffers and class state must outlive the USB tasks
+    static CONFIG_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+    static BOS_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+    static CONTROL_BUF: StaticCell<[u8; 64]> = StaticCell::new();
+    static CDC_STATE: StaticCell<State<'static>> = StaticCell::new();
+
+    let mut builder = Builder::new(
+        driver,
+        usb_config,
+        CONFIG_DESCRIPTOR.init([0; 256]),
+        BOS_DESCRIPTOR.init([0; 256]),
+        &mut [], // no Microsoft OS descriptors
+        CONTROL_BUF.init([0; 64]),
+    );
+    let class = CdcAcmClass::new(&mut builder, CDC_STATE.init(State::new()), USB_PACKET_SIZE);
+    let usb_device = builder.build();
+
+    // Spawn USB device and serial command tasks
+    _spawner.spawn(usb_run(usb_device)).unwrap();
+    _spawner
+        .spawn(usb_serial(
+            class,
+            COMMAND_CHANNEL.sender(),
+            RESPONSE_CHANNEL.receiver(),
+        ))
+        .unwrap();
+
     // Spawn OLED display task
     _spawner
         .spawn(oled_display(
             i2c,
             RTC_CHANNEL.receiver(),
-            KEY_CHANNEL.receiver(),
             embassy_time::Duration::from_millis(100), // Refresh every 100ms
         ))
         .unwrap();
@@ -114,9 +281,15 @@ async fn main(_spawner: Spawner) {
     // Spawn RTC update task
     _spawner
         .spawn(rtc_update(
+            Storage { rtc, settings },
             RTC_CHANNEL.sender(),
+            ALARM_CHANNEL.sender(),
             KEY_CHANNEL.receiver(),
             ARE_CHANNEL.receiver(),
+            SerialLink {
+                commands: COMMAND_CHANNEL.receiver(),
+                responses: RESPONSE_CHANNEL.sender(),
+            },
             embassy_time::Duration::from_millis(30), // Update interval
         ))
         .unwrap();
@@ -139,15 +312,32 @@ async fn main(_spawner: Spawner) {
         ))
         .unwrap();
 
-    // Configure onboard LED (PC13) as heartbeat indicator
-    let mut led = Output::new(p.PC13, Level::High, Speed::Low);
-    let mut ticker = Ticker::every(embassy_time::Duration::from_millis(500));
+    // Onboard LED (PC13) as heartbeat / alarm indicator, buzzer on PA1
+    let mut output = AlarmOutput::new(
+        GpioLed::new(Output::new(p.PC13, Level::High, Speed::Low)),
+        Some(GpioBuzzer::new(Output::new(p.PA1, Level::Low, Speed::Low))),
+    );
+    let mut ticker = Ticker::every(embassy_time::Duration::from_millis(100));
+    let mut ringing = false;
+    let mut heartbeat_step = 0;
 
-    // Main heartbeat loop - blinks onboard LED
+    // Main output loop - alarm pattern while ringing, heartbeat otherwise
     loop {
-        led.set_low(); // LED on
-        ticker.next().await;
-        led.set_high(); // LED off
+        if let Ok(new_ringing) = ALARM_CHANNEL.try_receive() {
+            ringing = new_ringing;
+            output.silence();
+        }
+
+        if ringing {
+            output.ring_step();
+        } else {
+            // Toggle LED every 500ms
+            heartbeat_step = (heartbeat_step + 1) % 5;
+            if heartbeat_step == 0 {
+                output.led().toggle();
+            }
+        }
+
         ticker.next().await;
     }
 }
@@ -156,14 +346,14 @@ async fn main(_spawner: Spawner) {
 ///
 /// Responsibilities:
 /// 1. Manage SSD1306 display interface
-/// 2. Render date/time information
+/// 2. Render the active screen (clock, lunar calendar, month grid, alarm or
+///    time zone settings, stopwatch or countdown timer)
 /// 3. Handle setting mode cursor
-/// 4. Implement blinking cursor effect
+/// 4. Implement blinking cursor / alarm banner effect
 #[embassy_executor::task]
 async fn oled_display(
     i2c: i2c::I2c<'static, embassy_stm32::mode::Async>,
-    rtc_channel: Receiver<'static, ThreadModeRawMutex, NaiveDateTime, 2>,
-    key_channel: Receiver<'static, ThreadModeRawMutex, i32, 1>,
+    rtc_channel: Receiver<'static, ThreadModeRawMutex, CalendarApp, 2>,
     delay: embassy_time::Duration,
 ) {
     let mut ticker = Ticker::every(delay);
@@ -177,44 +367,23 @@ async fn oled_display(
     display.init().unwrap();
 
     // Configure text rendering styles
-    let year_month_day_style = MonoTextStyleBuilder::new()
+    let small_style = MonoTextStyleBuilder::new()
         .font(&FONT_8X13)
         .text_color(BinaryColor::On)
         .build();
 
-    let hour_minute_second_style = MonoTextStyleBuilder::new()
+    let large_style = MonoTextStyleBuilder::new()
         .font(&FONT_10X20)
         .text_color(BinaryColor::On)
         .build();
 
-    let weekday_style = MonoTextStyleBuilder::new()
-        .font(&FONT_8X13)
-        .text_color(BinaryColor::On)
-        .build();
-
     // Cursor state management
     let mut cursor_visible = false;
     let mut last_blink_time = embassy_time::Instant::now();
     const BLINK_INTERVAL: embassy_time::Duration = embassy_time::Duration::from_millis(500);
 
-    // Field positions for cursor rendering
-    const CURSOR_POSITIONS: [(Point, Point); 6] = [
-        // Year: (start, end)
-        (Point::new(24, 18), Point::new(24 + 4 * 8, 18)),
-        // Month
-        (Point::new(24 + 5 * 8, 18), Point::new(24 + 7 * 8, 18)),
-        // Day
-        (Point::new(24 + 8 * 8, 18), Point::new(24 + 10 * 8, 18)),
-        // Hour
-        (Point::new(24, 40), Point::new(24 + 2 * 10, 40)),
-        // Minute
-        (Point::new(24 + 3 * 10, 40), Point::new(24 + 5 * 10, 40)),
-        // Second
-        (Point::new(24 + 6 * 10, 40), Point::new(24 + 8 * 10, 40)),
-    ];
-
-    let mut now = rtc_channel.receive().await; // Initial time value
-    let mut set_pos = 0; // Current selected field (0 = no selection)
+    let mut app = rtc_channel.receive().await; // Initial application state
+    let mut brightness = None; // Brightness level applied to the display
 
     loop {
         display.clear_buffer();
@@ -225,158 +394,782 @@ async fn oled_display(
             last_blink_time = embassy_time::Instant::now();
         }
 
-        // Receive updated time if available
-        if let Ok(new_time) = rtc_channel.try_receive() {
-            now = new_time;
+        // Receive updated application state if available
+        if let Ok(new_app) = rtc_channel.try_receive() {
+            app = new_app;
         }
 
-        // Check for field selection changes
-        if let Ok(new_pos) = key_channel.try_peek() {
-            set_pos = new_pos;
+        // Apply brightness changes (restored settings or serial command)
+        if brightness != Some(app.brightness()) {
+            display
+                .set_brightness(brightness_for(app.brightness()))
+                .unwrap();
+            brightness = Some(app.brightness());
         }
 
-        // Draw cursor if in setting mode and blink state is visible
-        if cursor_visible && set_pos != 0 && set_pos <= 6 {
-            let (start, end) = CURSOR_POSITIONS[set_pos as usize - 1];
-            Line::new(start, end)
-                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
-                .draw(&mut display)
-                .unwrap();
+        match (app.mode(), app.screen()) {
+            (Mode::Stopwatch, _) => {
+                draw_stopwatch_screen(&mut display, &app, small_style, large_style)
+            }
+            (Mode::Timer, _) => {
+                draw_timer_screen(&mut display, &app, cursor_visible, small_style, large_style)
+            }
+            (Mode::Calendar, Screen::Clock) => {
+                draw_clock_screen(&mut display, &app, cursor_visible, small_style, large_style)
+            }
+            (Mode::Calendar, Screen::Lunar) => draw_lunar_screen(&mut display, &app, small_style),
+            (Mode::Calendar, Screen::Month) => draw_month_screen(&mut display, &app),
+            (Mode::Calendar, Screen::Alarm) => {
+                draw_alarm_screen(&mut display, &app, cursor_visible, small_style, large_style)
+            }
+            (Mode::Calendar, Screen::TimeZone) => {
+                draw_timezone_screen(&mut display, &app, cursor_visible, small_style, large_style)
+            }
         }
+        .unwrap();
 
-        // Render date (YYYY-MM-DD)
-        let mut date_buf: String<10> = String::new();
-        write!(
-            &mut date_buf,
-            "{:04}-{:02}-{:02}",
-            now.year(),
-            now.month(),
-            now.day()
+        // Update physical display
+        display.flush().unwrap();
+
+        // Wait for next render cycle
+        ticker.next().await;
+    }
+}
+
+/// Draw an underline cursor below a text field
+fn draw_cursor<D>(display: &mut D, start: Point, width: i32) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    Line::new(start, start + Point::new(width, 0))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+}
+
+/// Clock Screen
+///
+/// Layout: date (YYYY-MM-DD), time (HH:MM:SS, followed by AM/PM in 12-hour
+/// format), weekday or blinking alarm banner
+fn draw_clock_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    cursor_visible: bool,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+    large_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    // Field positions for cursor rendering (indexed by `EditField::index`)
+    const CURSOR_POSITIONS: [(Point, i32); EditField::ALL.len()] = [
+        // Year: (start, width)
+        (Point::new(24, 18), 4 * 8),
+        // Month
+        (Point::new(24 + 5 * 8, 18), 2 * 8),
+        // Day
+        (Point::new(24 + 8 * 8, 18), 2 * 8),
+        // Hour
+        (Point::new(24, 40), 2 * 10),
+        // Minute
+        (Point::new(24 + 3 * 10, 40), 2 * 10),
+        // Second
+        (Point::new(24 + 6 * 10, 40), 2 * 10),
+    ];
+
+    let clock = app.clock();
+    let now = clock.now();
+
+    // Draw cursor if in setting mode and blink state is visible
+    if cursor_visible && let Some(field) = clock.editing() {
+        let (start, width) = CURSOR_POSITIONS[field.index()];
+        draw_cursor(display, start, width)?;
+    }
+
+    // Render date (YYYY-MM-DD)
+    let mut date_buf: String<10> = String::new();
+    write!(
+        &mut date_buf,
+        "{:04}-{:02}-{:02}",
+        now.year(),
+        now.month(),
+        now.day()
+    )
+    .unwrap();
+    Text::with_baseline(&date_buf, Point::new(24, 4), small_style, Baseline::Top).draw(display)?;
+
+    // Render time (HH:MM:SS)
+    let (hour, marker) = app.hour_format().display_hour(now.hour());
+    let mut time_buf: String<8> = String::new();
+    write!(
+        &mut time_buf,
+        "{:02}:{:02}:{:02}",
+        hour,
+        now.minute(),
+        now.second()
+    )
+    .unwrap();
+    Text::with_baseline(&time_buf, Point::new(24, 21), large_style, Baseline::Top).draw(display)?;
+    if let Some(marker) = marker {
+        Text::with_baseline(marker, Point::new(106, 27), small_style, Baseline::Top)
+            .draw(display)?;
+    }
+
+    // Render weekday, or a blinking banner while an alarm is ringing (centered)
+    let mut banner_buf: String<9> = String::new();
+    let bottom_str = match app.alarms().ringing() {
+        Some(_) if !cursor_visible => "",
+        Some(index) => {
+            write!(&mut banner_buf, "ALARM {}", index + 1).unwrap();
+            banner_buf.as_str()
+        }
+        None => match now.weekday() {
+            Weekday::Mon => "Monday",
+            Weekday::Tue => "Tuesday",
+            Weekday::Wed => "Wednesday",
+            Weekday::Thu => "Thursday",
+            Weekday::Fri => "Friday",
+            Weekday::Sat => "Saturday",
+            Weekday::Sun => "Sunday",
+        },
+    };
+    let text_width = bottom_str.len() * 8;
+    let x_pos = (128 - text_width) / 2;
+    Text::with_baseline(
+        bottom_str,
+        Point::new(x_pos as i32, 46),
+        small_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    Ok(())
+}
+
+/// Lunar Calendar Screen
+///
+/// Layout:
+/// ```text
+///    2025-07-20
+///     乙巳蛇年
+///     六月廿六
+///   大暑 07-22
+/// ```
+/// Bottom line: today's solar term followed by "Today", or the next solar term
+/// and its date.
+fn draw_lunar_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let cjk_style = MonoTextStyle::new(&FONT_CJK_12X12, BinaryColor::On);
+    let date = app.clock().now().date();
+
+    // Horizontally centered text helper
+    let centered = |width: u32| (128 - width as i32).max(0) / 2;
+
+    // Render Gregorian date
+    let mut date_buf: String<10> = String::new();
+    write!(
+        &mut date_buf,
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        date.month(),
+        date.day()
+    )
+    .unwrap();
+    Text::with_baseline(&date_buf, Point::new(24, 0), small_style, Baseline::Top).draw(display)?;
+
+    let Some(lunar) = LunarDate::from_solar(date) else {
+        Text::with_baseline(
+            "Out of range",
+            Point::new(16, 26),
+            small_style,
+            Baseline::Top,
         )
-        .unwrap();
+        .draw(display)?;
+        return Ok(());
+    };
+
+    // Render stem-branch year and zodiac, e.g. "乙巳蛇年"
+    let mut year_buf: String<12> = String::new();
+    write!(
+        &mut year_buf,
+        "{}{}{}年",
+        lunar.stem(),
+        lunar.branch(),
+        lunar.zodiac()
+    )
+    .unwrap();
+    Text::with_baseline(
+        &year_buf,
+        Point::new(centered(4 * 12), 17),
+        cjk_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    // Render lunar month and day, e.g. "闰六月廿六"
+    let mut day_buf: String<15> = String::new();
+    if lunar.leap {
+        day_buf.push('闰').unwrap();
+    }
+    write!(&mut day_buf, "{}{}", lunar.month_name(), lunar.day_name()).unwrap();
+    let day_width = day_buf.chars().count() as u32 * 12;
+    Text::with_baseline(
+        &day_buf,
+        Point::new(centered(day_width), 33),
+        cjk_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    // Render today's or the next solar term
+    let mut term_buf: String<6> = String::new();
+    let term = match SolarTerm::on(date) {
+        Some(term) => {
+            term_buf.push_str("Today").unwrap();
+            Some(term)
+        }
+        None => SolarTerm::next_after(date).map(|(term, day)| {
+            write!(&mut term_buf, "{:02}-{:02}", day.month(), day.day()).unwrap();
+            term
+        }),
+    };
+    if let Some(term) = term {
+        let x = centered(2 * 12 + 8 + term_buf.len() as u32 * 8);
+        Text::with_baseline(term.name(), Point::new(x, 50), cjk_style, Baseline::Top)
+            .draw(display)?;
         Text::with_baseline(
-            &date_buf,
-            Point::new(24, 4),
-            year_month_day_style,
+            &term_buf,
+            Point::new(x + 32, 49),
+            small_style,
             Baseline::Top,
         )
-        .draw(&mut display)
-        .unwrap();
+        .draw(display)?;
+    }
+
+    Ok(())
+}
+
+/// Month Grid Screen
+///
+/// Layout (5x8 font, 8px rows, 16px columns, Monday first):
+/// ```text
+///          2025-07
+/// Wk Mo Tu We Th Fr Sa Su
+/// 27     1  2  3  4  5  6
+/// 28  7  8  9 10 11 12 13
+/// ...
+/// ```
+/// The left column holds ISO week numbers; today's cell is drawn inverted.
+fn draw_month_screen<D>(display: &mut D, app: &CalendarApp) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    const WEEKDAY_NAMES: [&str; DAYS_PER_WEEK] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+    const ROW_HEIGHT: i32 = 8;
+    const COLUMN_WIDTH: i32 = 16;
+    const GRID_TOP: i32 = 2 * ROW_HEIGHT;
 
-        // Render time (HH:MM:SS)
-        let mut time_buf: String<8> = String::new();
+    let view = app.month_view();
+    let today = app.clock().now().date();
+    let style = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+    let inverted_style = MonoTextStyle::new(&FONT_5X8, BinaryColor::Off);
+
+    // Render month title, e.g. "2025-07"
+    let mut title_buf: String<12> = String::new();
+    write!(&mut title_buf, "{:04}-{:02}", view.year(), view.month()).unwrap();
+    let title_x = (128 - title_buf.len() as i32 * 5) / 2;
+    Text::with_baseline(&title_buf, Point::new(title_x, 0), style, Baseline::Top).draw(display)?;
+
+    // Render weekday header, week number column is separated by a vertical line
+    Text::with_baseline("Wk", Point::new(2, ROW_HEIGHT), style, Baseline::Top).draw(display)?;
+    for (column, name) in WEEKDAY_NAMES.iter().enumerate() {
+        let x = COLUMN_WIDTH * (column as i32 + 1) + 3;
+        Text::with_baseline(name, Point::new(x, ROW_HEIGHT), style, Baseline::Top).draw(display)?;
+    }
+    Line::new(
+        Point::new(COLUMN_WIDTH - 2, ROW_HEIGHT),
+        Point::new(COLUMN_WIDTH - 2, 63),
+    )
+    .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+    .draw(display)?;
+
+    // Render one row per week
+    let mut number_buf: String<2> = String::new();
+    for week in 0..view.weeks().min(MAX_WEEKS) {
+        let y = GRID_TOP + week as i32 * ROW_HEIGHT;
+
+        if let Some(number) = view.week_number(week) {
+            number_buf.clear();
+            write!(&mut number_buf, "{number:>2}").unwrap();
+            Text::with_baseline(&number_buf, Point::new(2, y), style, Baseline::Top)
+                .draw(display)?;
+        }
+
+        for column in 0..DAYS_PER_WEEK {
+            let Some(date) = view.day_at(week, column) else {
+                continue;
+            };
+            let x = COLUMN_WIDTH * (column as i32 + 1);
+
+            // Highlight today with an inverted cell
+            let cell_style = if date == today {
+                Rectangle::new(Point::new(x + 1, y), Size::new(COLUMN_WIDTH as u32 - 2, 8))
+                    .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                    .draw(display)?;
+                inverted_style
+            } else {
+                style
+            };
+
+            number_buf.clear();
+            write!(&mut number_buf, "{:>2}", date.day()).unwrap();
+            Text::with_baseline(&number_buf, Point::new(x + 3, y), cell_style, Baseline::Top)
+                .draw(display)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Format a stopwatch/timer duration
+///
+/// Produces "MM:SS.cc" with centiseconds, or "MM:SS" without. From 100 minutes
+/// on the format switches to "HH:MM:SS" so it never grows wider.
+fn format_duration(duration: embassy_time::Duration, centiseconds: bool) -> String<8> {
+    let millis = duration.as_millis();
+    let secs = millis / 1000;
+    let mut buf = String::new();
+    if secs >= 100 * 60 {
         write!(
-            &mut time_buf,
+            &mut buf,
             "{:02}:{:02}:{:02}",
-            now.hour(),
-            now.minute(),
-            now.second()
+            (secs / 3600).min(99),
+            secs / 60 % 60,
+            secs % 60
         )
         .unwrap();
-        Text::with_baseline(
-            &time_buf,
-            Point::new(24, 21),
-            hour_minute_second_style,
-            Baseline::Top,
+    } else if centiseconds {
+        write!(
+            &mut buf,
+            "{:02}:{:02}.{:02}",
+            secs / 60,
+            secs % 60,
+            millis % 1000 / 10
         )
-        .draw(&mut display)
         .unwrap();
+    } else {
+        write!(&mut buf, "{:02}:{:02}", secs / 60, secs % 60).unwrap();
+    }
+    buf
+}
 
-        // Render weekday (centered)
-        let weekday_str = match now.weekday() {
-            Weekday::Mon => "Monday",
-            Weekday::Tue => "Tuesday",
-            Weekday::Wed => "Wednesday",
-            Weekday::Thu => "Thursday",
-            Weekday::Fri => "Friday",
-            Weekday::Sat => "Saturday",
-            Weekday::Sun => "Sunday",
-        };
-        let text_width = weekday_str.len() * 8;
-        let x_pos = (128 - text_width) / 2;
+/// Stopwatch Screen
+///
+/// Layout:
+/// ```text
+/// Stopwatch    RUN
+///  00:12.34
+/// L4 00:01.20
+/// L3 00:04.10
+/// L2 00:03.95
+/// ```
+/// The first lap row is the lap in progress, followed by the most recent
+/// recorded laps.
+fn draw_stopwatch_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+    large_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let lap_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let stopwatch = app.stopwatch();
+    let now = Instant::now();
+    let elapsed = stopwatch.elapsed(now);
+
+    // Render title and run state
+    let state = if stopwatch.is_running() {
+        "RUN"
+    } else {
+        "STOP"
+    };
+    Text::with_baseline("Stopwatch", Point::new(0, 0), small_style, Baseline::Top).draw(display)?;
+    Text::with_baseline(
+        state,
+        Point::new(128 - state.len() as i32 * 8, 0),
+        small_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    // Render total elapsed time
+    let total_buf = format_duration(elapsed, true);
+    let total_x = (128 - total_buf.len() as i32 * 10) / 2;
+    Text::with_baseline(
+        &total_buf,
+        Point::new(total_x, 14),
+        large_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    if elapsed.as_ticks() == 0 {
+        return Ok(());
+    }
+
+    // Render the lap in progress followed by recent laps
+    let current = (stopwatch.lap_count() + 1, stopwatch.current_lap(now));
+    let mut label_buf: String<4> = String::new();
+    for (row, (number, lap)) in core::iter::once(current)
+        .chain(stopwatch.recent_laps())
+        .take(3)
+        .enumerate()
+    {
+        let y = 36 + row as i32 * 9;
+        label_buf.clear();
+        write!(&mut label_buf, "L{}", number % 100).unwrap();
+        Text::with_baseline(&label_buf, Point::new(16, y), lap_style, Baseline::Top)
+            .draw(display)?;
         Text::with_baseline(
-            weekday_str,
-            Point::new(x_pos as i32, 46),
-            weekday_style,
+            &format_duration(lap, true),
+            Point::new(40, y),
+            lap_style,
             Baseline::Top,
         )
-        .draw(&mut display)
-        .unwrap();
+        .draw(display)?;
+    }
 
-        // Update physical display
-        display.flush().unwrap();
+    Ok(())
+}
 
-        // Wait for next render cycle
-        ticker.next().await;
+/// Countdown Timer Screen
+///
+/// Layout:
+/// ```text
+/// Timer      PAUSE
+///    04:59
+/// [#########     ]
+/// Set 05:00
+/// ```
+/// The remaining time blinks as "TIME UP" once expired; while idle it is the
+/// preset and carries a blinking cursor to show it can be adjusted.
+fn draw_timer_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    cursor_visible: bool,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+    large_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let countdown = app.countdown();
+    let state = countdown.state();
+    let preset = countdown.preset();
+    let remaining = countdown.remaining(Instant::now());
+
+    // Render title and state
+    let state_str = match state {
+        CountdownState::Idle => "SET",
+        CountdownState::Running => "RUN",
+        CountdownState::Paused => "PAUSE",
+        CountdownState::Expired => "DONE",
+    };
+    Text::with_baseline("Timer", Point::new(0, 0), small_style, Baseline::Top).draw(display)?;
+    Text::with_baseline(
+        state_str,
+        Point::new(128 - state_str.len() as i32 * 8, 0),
+        small_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    // Render remaining time (rounded up so "00:00" only shows on expiry),
+    // or a blinking banner once expired
+    let remaining_buf = match state {
+        CountdownState::Expired if !cursor_visible => String::new(),
+        CountdownState::Expired => String::try_from("TIME UP").unwrap(),
+        _ => format_duration(remaining + embassy_time::Duration::from_millis(999), false),
+    };
+    let remaining_x = (128 - remaining_buf.len() as i32 * 10) / 2;
+    Text::with_baseline(
+        &remaining_buf,
+        Point::new(remaining_x, 14),
+        large_style,
+        Baseline::Top,
+    )
+    .draw(display)?;
+
+    if cursor_visible && state == CountdownState::Idle {
+        draw_cursor(
+            display,
+            Point::new(remaining_x, 35),
+            remaining_buf.len() as i32 * 10,
+        )?;
+    }
+
+    // Render progress bar of the remaining time
+    Rectangle::new(Point::new(4, 39), Size::new(120, 8))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)?;
+    if preset.as_ticks() > 0 {
+        let width = (remaining.as_ticks() * 116 / preset.as_ticks()) as u32;
+        Rectangle::new(Point::new(6, 41), Size::new(width, 4))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)?;
     }
+
+    // Render preset
+    let mut preset_buf: String<12> = String::new();
+    write!(&mut preset_buf, "Set {}", format_duration(preset, false)).unwrap();
+    Text::with_baseline(&preset_buf, Point::new(0, 50), small_style, Baseline::Top)
+        .draw(display)?;
+
+    Ok(())
 }
 
-/// Software RTC Management Task
+/// Alarm Settings Screen
+///
+/// Layout:
+/// ```text
+/// Alarm 1       ON
+///       07:30
+/// MTWTF--  Wk  05m
+/// ```
+/// Bottom line: weekday mask ('-' = not selected), repeat mode (Wk = weekly,
+/// 1x = once) and snooze duration in minutes.
+fn draw_alarm_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    cursor_visible: bool,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+    large_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    const DAY_LETTERS: [char; 7] = ['M', 'T', 'W', 'T', 'F', 'S', 'S'];
+
+    let editor = app.alarm_editor();
+    let index = editor.selected();
+    let alarm = app.alarms().alarm(index);
+
+    // Draw cursor if editing and blink state is visible
+    if cursor_visible && let Some(field) = editor.editing() {
+        let (start, width) = match field {
+            AlarmField::Enabled => (Point::new(104, 15), 3 * 8),
+            AlarmField::Hour => (Point::new(39, 39), 2 * 10),
+            AlarmField::Minute => (Point::new(39 + 3 * 10, 39), 2 * 10),
+            AlarmField::Weekday(day) => (Point::new(day.num_days_from_monday() as i32 * 8, 60), 8),
+            AlarmField::Repeat => (Point::new(72, 60), 2 * 8),
+            AlarmField::Snooze => (Point::new(104, 60), 2 * 8),
+        };
+        draw_cursor(display, start, width)?;
+    }
+
+    // Render title and on/off state
+    let mut title_buf: String<8> = String::new();
+    write!(&mut title_buf, "Alarm {}", index + 1).unwrap();
+    Text::with_baseline(&title_buf, Point::new(0, 2), small_style, Baseline::Top).draw(display)?;
+    let state_str = if alarm.enabled { "ON" } else { "OFF" };
+    Text::with_baseline(state_str, Point::new(104, 2), small_style, Baseline::Top).draw(display)?;
+
+    // Render alarm time (HH:MM)
+    let mut time_buf: String<5> = String::new();
+    write!(
+        &mut time_buf,
+        "{:02}:{:02}",
+        alarm.time.hour(),
+        alarm.time.minute()
+    )
+    .unwrap();
+    Text::with_baseline(&time_buf, Point::new(39, 18), large_style, Baseline::Top).draw(display)?;
+
+    // Render weekday mask, repeat mode and snooze duration
+    let mut days_buf: String<7> = String::new();
+    let mut day = Weekday::Mon;
+    for letter in DAY_LETTERS {
+        let c = if alarm.weekdays.contains(day) {
+            letter
+        } else {
+            '-'
+        };
+        days_buf.push(c).unwrap();
+        day = day.succ();
+    }
+    Text::with_baseline(&days_buf, Point::new(0, 46), small_style, Baseline::Top).draw(display)?;
+
+    let repeat_str = match alarm.repeat {
+        Repeat::Weekly => "Wk",
+        Repeat::Once => "1x",
+    };
+    Text::with_baseline(repeat_str, Point::new(72, 46), small_style, Baseline::Top)
+        .draw(display)?;
+
+    let mut snooze_buf: String<3> = String::new();
+    write!(&mut snooze_buf, "{:02}m", alarm.snooze_minutes).unwrap();
+    Text::with_baseline(&snooze_buf, Point::new(104, 46), small_style, Baseline::Top)
+        .draw(display)?;
+
+    Ok(())
+}
+
+/// Time Zone Screen
+///
+/// Layout: title, preset name, current abbreviation and UTC offset
+fn draw_timezone_screen<D>(
+    display: &mut D,
+    app: &CalendarApp,
+    cursor_visible: bool,
+    small_style: MonoTextStyle<'static, BinaryColor>,
+    large_style: MonoTextStyle<'static, BinaryColor>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = BinaryColor>,
+{
+    let timezone = app.timezone();
+    let name = match app.timezone_preset() {
+        Some(index) => PRESETS[index].name,
+        None => "Custom",
+    };
+    let name_x = (128 - name.len() as i32 * 10).max(0) / 2;
+
+    // Draw cursor under the preset name while selecting
+    if cursor_visible && app.timezone_editing() {
+        draw_cursor(display, Point::new(name_x, 39), name.len() as i32 * 10)?;
+    }
+
+    Text::with_baseline("Time Zone", Point::new(0, 2), small_style, Baseline::Top).draw(display)?;
+    Text::with_baseline(name, Point::new(name_x, 18), large_style, Baseline::Top).draw(display)?;
+
+    // Render abbreviation and offset currently in effect, e.g. "CEST UTC+02:00"
+    let utc = app.utc();
+    let offset = timezone.offset_at(utc);
+    let sign = if offset < 0 { '-' } else { '+' };
+    // Longest abbreviation plus " UTC+hh:mm"
+    let mut offset_buf: String<{ ABBREVIATION_CAPACITY + 10 }> = String::new();
+    write!(
+        &mut offset_buf,
+        "{} UTC{}{:02}:{:02}",
+        timezone.abbreviation_at(utc),
+        sign,
+        offset.unsigned_abs() / 3600,
+        offset.unsigned_abs() % 3600 / 60
+    )
+    .unwrap();
+    Text::with_baseline(&offset_buf, Point::new(0, 46), small_style, Baseline::Top)
+        .draw(display)?;
+
+    Ok(())
+}
+
+/// RTC Management Task
 ///
 /// Responsibilities:
-/// 1. Maintain virtual real-time clock
-/// 2. Handle time adjustments from rotary encoder
-/// 3. Manage field selection states
+/// 1. Read current UTC time from the hardware RTC
+/// 2. Feed button/encoder events into the application state machine
+/// 3. Write edited time back to the RTC when leaving setting mode
+/// 4. Execute serial commands and reply with their response
+/// 5. Check alarms and the countdown timer, broadcast ringing state to the
+///    output loop
+/// 6. Restore settings from flash at startup and save them when they change
+/// 7. Broadcast application state changes to the display
 #[embassy_executor::task]
 async fn rtc_update(
-    rtc_sender: Sender<'static, ThreadModeRawMutex, NaiveDateTime, 2>,
-    key_receiver: Receiver<'static, ThreadModeRawMutex, i32, 1>,
+    mut storage: Storage,
+    rtc_sender: Sender<'static, ThreadModeRawMutex, CalendarApp, 2>,
+    alarm_sender: Sender<'static, ThreadModeRawMutex, bool, 1>,
+    key_receiver: Receiver<'static, ThreadModeRawMutex, (KeyPress, Instant), 1>,
     are_receiver: Receiver<'static, ThreadModeRawMutex, i32, 3>,
+    serial: SerialLink,
     delay: embassy_time::Duration,
 ) {
-    // Initialize to a specific date/time (2025-07-18 19:38:20)
-    let mut now = NaiveDate::from_ymd_opt(2025, 7, 20)
-        .unwrap()
-        .and_hms_opt(18, 00, 00)
-        .unwrap();
+    let mut app = CalendarApp::new(storage.rtc.now());
+
+    // Restore saved settings, falling back to defaults
+    let mut saved_settings = match storage.settings.as_mut().map(Settings::load) {
+        Some(Ok(settings)) => settings.unwrap_or_default(),
+        Some(Err(_)) => {
+            defmt::warn!("Failed to read settings");
+            Settings::default()
+        }
+        None => Settings::default(),
+    };
+    saved_settings.apply(&mut app);
 
     let mut ticker = Ticker::every(delay);
-    let mut set_pos: i32 = 0; // Current selected field
-    let mut prev_time = now; // For change detection
+    let mut prev_app = None; // For change detection
+    let mut prev_ringing = false;
 
     loop {
-        // Check for field selection changes
-        if let Ok(new_pos) = key_receiver.try_peek() {
-            set_pos = new_pos;
+        // Short press selects the next field (commit edited time on exit) or
+        // starts/stops the timers; long press switches modes
+        match key_receiver.try_receive() {
+            Ok((KeyPress::Short, pressed_at)) => {
+                if let Some(edited) = app.on_button(pressed_at) {
+                    storage.rtc.set_now(edited);
+                }
+            }
+            Ok((KeyPress::Long, _)) => app.on_long_press(),
+            Err(_) => {}
         }
 
-        // Apply rotary encoder adjustments based on selected field
-        if set_pos != 0 {
-            if let Ok(delta) = are_receiver.try_receive() {
-                now = match set_pos {
-                    1 => now
-                        .checked_add_signed(chrono::Duration::days(365 * delta as i64))
-                        .unwrap_or(now),
-                    2 => now
-                        .checked_add_signed(chrono::Duration::days(30 * delta as i64))
-                        .unwrap_or(now),
-                    3 => now
-                        .checked_add_signed(chrono::Duration::days(delta as i64))
-                        .unwrap_or(now),
-                    4 => now
-                        .checked_add_signed(chrono::Duration::hours(delta as i64))
-                        .unwrap_or(now),
-                    5 => now
-                        .checked_add_signed(chrono::Duration::minutes(delta as i64))
-                        .unwrap_or(now),
-                    6 => now
-                        .checked_add_signed(chrono::Duration::seconds(delta as i64))
-                        .unwrap_or(now),
-                    _ => now,
-                };
+        // Apply rotary encoder adjustments to the active screen
+        while let Ok(delta) = are_receiver.try_receive() {
+            app.on_encoder(delta, Instant::now());
+        }
+
+        // Execute serial commands; `time set` is written through to the RTC
+        if let Ok(command) = serial.commands.try_receive() {
+            if let Some(utc) = command.execute(&mut app) {
+                storage.rtc.set_now(utc);
+            }
+            let mut response = String::new();
+            if command.respond(&app, &mut response).is_err() {
+                defmt::warn!("Serial response truncated");
+            }
+            serial.responses.send(response).await;
+        }
+
+        // Follow the RTC (frozen while editing) and check alarms
+        for index in app.sync(storage.rtc.now()).iter() {
+            defmt::info!("Alarm {} ringing", index + 1);
+        }
+        if app.tick(Instant::now()) {
+            defmt::info!("Countdown expired");
+        }
+
+        // Save settings once the user has finished editing them
+        let settings = Settings::from_app(&app);
+        if settings != saved_settings && !app.is_editing() {
+            // Keep the old copy on failure so the save is retried next tick;
+            // without a store there is nothing to retry
+            match storage.settings.as_mut().map(|store| settings.save(store)) {
+                Some(Ok(())) | None => saved_settings = settings,
+                Some(Err(_)) => defmt::warn!("Failed to save settings"),
             }
-        } else {
-            // Normal time progression
-            now = now
-                .checked_add_signed(chrono::Duration::milliseconds(delay.as_millis() as i64))
-                .unwrap_or(now);
         }
 
-        // Broadcast time updates when changed
-        if prev_time != now {
+        // Broadcast ringing state when changed
+        let ringing = app.alarms().ringing().is_some() || app.countdown().is_expired();
+        if ringing != prev_ringing {
+            alarm_sender.clear();
+            alarm_sender.send(ringing).await;
+            prev_ringing = ringing;
+        }
+
+        // Broadcast application updates when changed
+        if prev_app != Some(app) {
             rtc_sender.clear();
-            rtc_sender.send(now).await;
-            prev_time = now;
+            rtc_sender.send(app).await;
+            prev_app = Some(app);
         }
 
         ticker.next().await;
@@ -435,19 +1228,21 @@ async fn are_update(
 ///
 /// Responsibilities:
 /// 1. Detect button presses with debouncing
-/// 2. Cycle through setting modes (year → month → day → hour → minute → second → normal)
-/// 3. Broadcast mode changes
+/// 2. Tell short presses from long presses
+/// 3. Broadcast press events stamped with the press instant (handled by
+///    `CalendarApp::on_button` / `CalendarApp::on_long_press`)
 #[embassy_executor::task]
 async fn key_update(
     mut button: ExtiInput<'static>,
-    key_sender: Sender<'static, ThreadModeRawMutex, i32, 1>,
+    key_sender: Sender<'static, ThreadModeRawMutex, (KeyPress, Instant), 1>,
     debounce_delay: embassy_time::Duration,
 ) {
-    let mut current_mode = 0; // 0 = normal, 1-6 = setting modes
+    const LONG_PRESS: embassy_time::Duration = embassy_time::Duration::from_millis(800);
 
     loop {
         // Wait for button press (falling edge)
         button.wait_for_falling_edge().await;
+        let pressed_at = Instant::now();
 
         // Apply debounce delay
         Timer::after(debounce_delay).await;
@@ -457,14 +1252,96 @@ async fn key_update(
             continue;
         }
 
-        // Cycle through modes (0 → 1 → 2 → 3 → 4 → 5 → 6 → 0)
-        current_mode = (current_mode + 1) % 7;
+        // Short press is reported on release, long press as soon as the
+        // threshold is reached while still held
+        match select(
+            button.wait_for_rising_edge(),
+            Timer::at(pressed_at + LONG_PRESS),
+        )
+        .await
+        {
+            Either::First(()) => key_sender.send((KeyPress::Short, pressed_at)).await,
+            Either::Second(()) => {
+                key_sender.send((KeyPress::Long, pressed_at)).await;
+
+                // Wait for button release
+                button.wait_for_rising_edge().await;
+            }
+        }
+    }
+}
+
+/// USB Device Task
+///
+/// Runs the USB stack (enumeration, control requests, suspend/resume)
+#[embassy_executor::task]
+async fn usb_run(mut device: UsbDevice<'static, UsbDriver>) {
+    device.run().await;
+}
+
+/// USB Serial Command Task
+///
+/// Responsibilities:
+/// 1. Wait for the host to open the CDC-ACM port
+/// 2. Split received bytes into command lines and parse them
+/// 3. Forward commands to `rtc_update` and send back its response
+/// 4. Reply to malformed commands directly
+#[embassy_executor::task]
+async fn usb_serial(
+    mut class: CdcAcmClass<'static, UsbDriver>,
+    command_sender: Sender<'static, ThreadModeRawMutex, Command, 1>,
+    response_receiver: Receiver<'static, ThreadModeRawMutex, String<RESPONSE_CAPACITY>, 1>,
+) {
+    loop {
+        class.wait_connection().await;
+        defmt::info!("USB serial connected");
+
+        // Serve commands until the port is closed or the cable is unplugged
+        let _ = serve_commands(&mut class, &command_sender, &response_receiver).await;
+        defmt::info!("USB serial disconnected");
+    }
+}
+
+/// Read command lines from the serial port and write back one response per line
+async fn serve_commands(
+    class: &mut CdcAcmClass<'static, UsbDriver>,
+    command_sender: &Sender<'static, ThreadModeRawMutex, Command, 1>,
+    response_receiver: &Receiver<'static, ThreadModeRawMutex, String<RESPONSE_CAPACITY>, 1>,
+) -> Result<(), EndpointError> {
+    let mut packet = [0; USB_PACKET_SIZE as usize];
+    let mut line_buffer = LineBuffer::new();
+
+    loop {
+        let len = class.read_packet(&mut packet).await?;
+
+        for &byte in &packet[..len] {
+            let Some(parsed) = line_buffer
+                .push(byte)
+                .map(|line| line.and_then(Command::parse))
+            else {
+                continue;
+            };
 
-        // Broadcast new mode
-        key_sender.clear();
-        key_sender.send(current_mode).await;
+            let response = match parsed {
+                Ok(command) => {
+                    command_sender.send(command).await;
+                    response_receiver.receive().await
+                }
+                Err(error) => {
+                    let mut response = String::new();
+                    respond_error(error, &mut response).ok();
+                    response
+                }
+            };
 
-        // Wait for button release
-        button.wait_for_rising_edge().await;
+            // Split into packets, ending with a zero-length packet if the last
+            // one is full so the host sees the end of the transfer
+            for chunk in response.as_bytes().chunks(USB_PACKET_SIZE as usize) {
+                class.write_packet(chunk).await?;
+            }
+            if response.len() % USB_PACKET_SIZE as usize == 0 {
+                class.write_packet(&[]).await?;
+            }
+        }
     }
 }
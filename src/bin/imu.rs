@@ -7,7 +7,8 @@
 //!==============================================================================================
 //!
 //! This firmware implements an IMU system using:
-//! - MPU6050 6-axis motion sensor via I2C2
+//! - MPU6050 6-axis motion sensor via I2C2 (async, DMA)
+//! - Optional HMC5883L/QMC5883L magnetometer on the MPU6050 aux bus or on I2C2
 //! - Madgwick filter for sensor fusion
 //! - SSD1306 OLED display (128x64) via I2C1
 //!
@@ -23,27 +24,60 @@
 //!      GND  -> GND
 //!      SDA  -> PB11 (I2C2)
 //!      SCL  -> PB10 (I2C2)
+//!      INT  -> PB12 (data ready, active high)
+//!
+//!   Magnetometer (optional) -> MPU6050 XDA/XCL, or PB11/PB10 (I2C2) alongside the MPU6050
+//!
+//!   Rotary encoder -> Blue Pill
+//!      CLK  -> PA8 (TIM1_CH1)
+//!      DT   -> PA9 (TIM1_CH2)
+//!      SW   -> PB15 (pull-up, active low)
 //!
 //! Features:
-//! 1. Real-time IMU data acquisition at 100Hz
-//! 2. Sensor calibration for offset compensation
+//! 1. Real-time IMU data acquisition at 100Hz, paced by the sensor's own sample clock
+//!    through its FIFO and data-ready interrupt
+//! 2. Sensor calibration stored in flash per board (keyed by the STM32 UID), gyro
+//!    offsets recalibrated automatically when nothing is stored
 //! 3. Madgwick filter for attitude estimation
-//! 4. Euler angle conversion (roll, pitch, yaw)
-//! 5. OLED display of orientation angles
+//! 4. Orientation output as Euler angles (ZYX or XYZ, safe at gimbal lock), quaternion,
+//!    rotation matrix, axis-angle or tilt-compensated heading
+//! 5. OLED display of the orientation, representation chosen with the rotary encoder
+//! 6. Display brightness restored from the settings stored in flash
+//! 7. Guided six-face accelerometer calibration (hold the key during boot)
+//! 8. Gyro bias temperature-drift calibration (press the key while running)
+//! 9. Gyro offset recalibration (hold the key for 2 seconds while running)
+//! 10. Sensor errors retried, I2C bus recovered and sensor re-initialized after repeated
+//!     failures; "SENSOR LOST" shown with error counters instead of a panic
+//! 11. Heading referenced to magnetic north when a magnetometer is found, corrected by
+//!     the configured magnetic declination
+//! 12. Magnetometer hard/soft-iron calibration (hold the key for 5 seconds while running,
+//!     then rotate the board in all directions)
+//! 13. Board mounting rotation (24 axis-aligned presets or any quaternion) applied before
+//!     filtering and stored with the calibration
+//! 14. World-frame linear acceleration with gravity removed, and short-term velocity and
+//!     displacement reset by zero-velocity updates whenever the board is still
+//! 15. Motion events (tap, double tap, shake, free fall, tilt, flip) published on a
+//!     PubSubChannel, shown briefly on the OLED and logged
 
 #![no_std] // 禁用标准库，适用于裸机嵌入式环境
 #![no_main] // 禁用标准main入口，使用自定义入口点
 
 use embassy_executor::Spawner; // Embassy异步任务调度器
+use embassy_futures::select::{Either, select};
 use embassy_stm32::{
     bind_interrupts,
+    exti::ExtiInput,
+    flash::Flash,
+    gpio::Pull,
     i2c::{self, ErrorInterruptHandler, EventInterruptHandler},
     peripherals,
     time::Hertz,
+    timer::qei::{Qei, QeiPin},
 };
 use embassy_sync::{
     blocking_mutex::raw::ThreadModeRawMutex,
     channel::{Channel, Receiver, Sender},
+    pubsub::PubSubChannel,
 };
 use {defmt_rtt as _, panic_probe as _}; // 日志记录和panic处理
 
@@ -60,70 +94,148 @@ use core::str::FromStr;
 use heapless::String;
 
 // 导入自定义的MPU6050姿态解算模块
-use main_cargo::hardware::mpu6050_madgwick_solver::Mpu6050MadgwickSolver;
+use main_cargo::app::imu::accel_calibration::{Face, SixFaceCalibration};
+use main_cargo::app::imu::gesture::{MotionEvent, MotionEventChannel, MotionEventSubscriber};
+use main_cargo::app::imu::health::{ErrorCounters, RetryPolicy, SensorHealth, SensorState};
+use main_cargo::app::imu::kinematics::Motion;
+use main_cargo::app::imu::magnetometer::EllipsoidFit;
+use main_cargo::app::imu::mounting::{Axis, Mounting};
+use main_cargo::app::imu::orientation::{EulerSequence, Orientation};
+use main_cargo::app::imu::temperature::{DriftFit, DriftOrder};
+use main_cargo::hardware::board::clock_config;
+use main_cargo::hardware::config::{SETTINGS_OFFSET, SETTINGS_SIZE, Settings, Uid};
+use main_cargo::hardware::display::brightness_for;
+use main_cargo::hardware::flash_store::KvStore;
+use main_cargo::hardware::imu_bus::{self, ImuBus, SensorDisplay, gyro_calibration, sensor_fault};
+use main_cargo::hardware::imu_error::ImuError;
+use main_cargo::hardware::mpu6050_async_solver::Mpu6050AsyncSolver;
+
+// 绑定I2C1（OLED）和I2C2（MPU6050）中断处理函数
+bind_interrupts!(struct Irqs {
+    I2C1_EV => EventInterruptHandler<peripherals::I2C1>;
+    I2C1_ER => ErrorInterruptHandler<peripherals::I2C1>;
+    I2C2_EV => EventInterruptHandler<peripherals::I2C2>;
+    I2C2_ER => ErrorInterruptHandler<peripherals::I2C2>;
+});
+
+// 旋转编码器每转过一格的计数
+const ENCODER_STEP: i32 = 4;
+
+// 显示数据通道（线程安全的单生产者单消费者通道）
+static IMU_CHANNEL: Channel<ThreadModeRawMutex, ImuDisplay, 1> = Channel::new();
+
+// 运动事件发布通道，订阅者为显示任务和日志任务
+static MOTION_EVENTS: MotionEventChannel<ThreadModeRawMutex> = PubSubChannel::new();
+
+// 检测到运动事件后在屏幕上显示的时间
+const EVENT_DISPLAY_TIME: embassy_time::Duration = embassy_time::Duration::from_secs(1);
+
+// 六面校准时每个面的采样次数（间隔10ms）
+const FACE_SAMPLES: u8 = 100;
+
+// 预热校准时每个样本的采样次数（间隔10ms，即每秒一个样本）
+const WARM_UP_SAMPLES: u8 = 100;
+
+// 预热校准最长时间（样本数），30分钟后自动结束
+const WARM_UP_MAX_SAMPLES: u16 = 1800;
+
+// 拟合二次温度模型所需的最小温度跨度（℃），不足时使用线性模型
+const QUADRATIC_MIN_SPAN: f32 = 8.0;
 
-// 欧拉角数据通道（线程安全的单生产者单消费者通道）
-static IMU_CHANNEL: Channel<ThreadModeRawMutex, EulerAngles, 1> = Channel::new();
+// 运行中按住按键超过该时间则重新校准陀螺仪零偏，短按进入预热校准
+const RECALIBRATE_HOLD: embassy_time::Duration = embassy_time::Duration::from_secs(2);
+
+// 运行中按住按键超过该时间则进行磁力计校准
+const MAG_CALIBRATION_HOLD: embassy_time::Duration = embassy_time::Duration::from_secs(5);
+
+// 磁力计校准的采样间隔
+const MAG_CALIBRATION_PERIOD: embassy_time::Duration = embassy_time::Duration::from_millis(50);
+
+// 磁力计校准最长时间（样本数），1分钟后自动结束
+const MAG_CALIBRATION_MAX_SAMPLES: u16 = 1200;
+
+// 当地磁偏角（度），东偏为正，例如北京约-7°
+const MAGNETIC_DECLINATION: f32 = 0.0;
+
+// 电路板的安装方向：传感器X轴和Z轴在设备中的指向（设备X轴朝前、Z轴朝上），
+// 例如竖直安装、传感器X轴朝上Z轴朝前时为Some((Axis::PosZ, Axis::PosX))。
+// 为None时使用闪存中保存的安装方向（没有保存过时与设备坐标轴一致）
+const MOUNTING: Option<(Axis, Axis)> = None;
+
+// 运动页面显示的数值上限，保证每行不超过13个字符
+const MOTION_DISPLAY_MAX: f32 = 99.99;
+
+// 等待数据就绪中断的超时时间（10个采样周期），超时说明传感器没有工作
+const DATA_READY_TIMEOUT: embassy_time::Duration = embassy_time::Duration::from_millis(100);
+
+// MPU6050姿态解算器，接在可恢复的I2C2总线上
+type Imu = imu_bus::Imu<Irqs>;
 
 /// 主入口函数
 ///
 /// Embassy执行器的主入口点，负责：
 /// 1. 配置系统时钟（HSE 8MHz + PLL倍频到72MHz）
-/// 2. 初始化I2C外设（OLED使用I2C1，MPU6050使用I2C2）
-/// 3. 启动传感器数据采集任务
-/// 4. 启动OLED显示任务
+/// 2. 从闪存读取设置（与万年历共用），设置存储随后交给传感器任务保存校准参数
+/// 3. 初始化I2C外设（OLED使用I2C1，MPU6050使用I2C2）
+/// 4. 启动传感器数据采集任务
+/// 5. 启动OLED显示任务和运动事件日志任务
 ///
 /// # 参数
 /// - `_spawner`: 任务生成器，用于创建异步任务
 #[embassy_executor::main]
 async fn main(_spawner: Spawner) {
     // 配置系统时钟（使用外部8MHz晶振，通过PLL倍频到72MHz）
-    let mut config = embassy_stm32::Config::default();
-    {
-        use embassy_stm32::rcc::*;
-        config.rcc.hse = Some(Hse {
-            freq: Hertz(8_000_000),
-            // 开发板使用外部振荡器
-            mode: HseMode::Oscillator,
-        });
-        config.rcc.pll = Some(Pll {
-            src: PllSource::HSE,   // PLL时钟源选择HSE
-            prediv: PllPreDiv::DIV1, // 预分频系数
-            mul: PllMul::MUL9,     // 倍频系数（8MHz * 9 = 72MHz）
-        });
-        config.rcc.sys = Sysclk::PLL1_P; // 系统时钟源选择PLL输出
-        config.rcc.ahb_pre = AHBPrescaler::DIV1; // AHB预分频（72MHz）
-        config.rcc.apb1_pre = APBPrescaler::DIV2; // APB1预分频（36MHz）
-        config.rcc.apb2_pre = APBPrescaler::DIV1; // APB2预分频（72MHz）
-    }
-    
+    let config = clock_config();
+
     // 初始化外设
     let p = embassy_stm32::init(config);
 
     // 初始化日志系统
     defmt::info!("系统启动!");
 
+    // 从闪存末尾的设置存储区读取设置，读取失败时使用默认设置；
+    // 存储区不可用时同样使用默认设置继续运行，校准参数不保存
+    let mut settings_store =
+        match KvStore::new(Flash::new_blocking(p.FLASH), SETTINGS_OFFSET, SETTINGS_SIZE) {
+            Ok(store) => Some(store),
+            Err(_) => {
+                defmt::warn!("设置存储不可用，校准参数将不会保存");
+                None
+            }
+        };
+    let settings = match settings_store.as_mut().map(Settings::load) {
+        Some(Ok(settings)) => settings.unwrap_or_default(),
+        Some(Err(_)) => {
+            defmt::warn!("设置读取失败");
+            Settings::default()
+        }
+        None => Settings::default(),
+    };
+
     // 配置I2C2接口（PB10: SCL, PB11: SDA）用于MPU6050
+    // 异步模式，DMA1通道4/5收发，等待传输时不占用CPU
     // 设置I2C时钟频率为400kHz
-    let imu_i2c =
-        i2c::I2c::new_blocking(p.I2C2, p.PB10, p.PB11, Hertz(400_000), Default::default());
+    let imu_bus = ImuBus::new(p.I2C2, p.PB10, p.PB11, Irqs, p.DMA1_CH4, p.DMA1_CH5);
+
+    // 编码器按键（PB15，上拉输入，按下为低电平），启动时按住进入六面校准
+    let key = ExtiInput::new(p.PB15, p.EXTI15, Pull::Up);
+
+    // MPU6050数据就绪中断（PB12，高电平有效，保持到读取FIFO）
+    let imu_int = ExtiInput::new(p.PB12, p.EXTI12, Pull::Down);
 
     // 创建MPU6050数据更新任务
-    // 设置采样周期为10ms (100Hz)
+    // 传感器采样率100Hz（采样周期10ms）
     _spawner
         .spawn(mpu6050_update(
-            imu_i2c,
+            imu_bus,
+            imu_int,
+            key,
+            settings_store,
             IMU_CHANNEL.sender(),
-            embassy_time::Duration::from_millis(10),
+            100,
         ))
         .unwrap();
 
-    // 绑定I2C1中断处理函数（用于OLED）
-    bind_interrupts!(struct Irqs {
-        I2C1_EV => EventInterruptHandler<peripherals::I2C1>;
-        I2C1_ER => ErrorInterruptHandler<peripherals::I2C1>;
-    });
-
     // 配置I2C1外设（PB6: SCL, PB7: SDA）用于OLED
     // 设置时钟频率为400kHz
     let oled_i2c = i2c::I2c::new(
@@ -137,15 +249,26 @@ async fn main(_spawner: Spawner) {
         Default::default(),
     );
 
+    // 旋转编码器（TIM1正交编码接口，PA8/PA9），用于切换姿态的表示方式
+    let encoder = Qei::new(p.TIM1, QeiPin::new_ch1(p.PA8), QeiPin::new_ch2(p.PA9));
+
     // 启动OLED显示任务（刷新周期100ms）
     _spawner
         .spawn(oled_display(
             oled_i2c,
+            encoder,
             IMU_CHANNEL.receiver(),
+            MOTION_EVENTS.subscriber().unwrap(),
+            settings.brightness,
             embassy_time::Duration::from_millis(100),
         ))
         .unwrap();
 
+    // 启动运动事件日志任务
+    _spawner
+        .spawn(motion_event_log(MOTION_EVENTS.subscriber().unwrap()))
+        .unwrap();
+
     // 主循环（保持系统运行）
     loop {
         embassy_time::Timer::after_secs(1000).await;
@@ -156,87 +279,430 @@ async fn main(_spawner: Spawner) {
 ///
 /// 此异步任务负责：
 /// 1. 初始化MPU6050传感器
-/// 2. 执行传感器校准（需保持设备静止3秒）
-/// 3. 定期采集传感器数据（100Hz）
-/// 4. 使用Madgwick滤波器进行姿态解算
-/// 5. 将四元数转换为欧拉角（度）
-/// 6. 通过通道发送姿态数据
+/// 2. 从闪存恢复本电路板的校准参数；没有保存过时执行陀螺仪零偏校准（需保持设备静止）
+/// 3. 按固件设置的安装方向将传感器读数转到设备坐标系
+/// 4. 启动时按住按键则进行六面校准，然后重新校准陀螺仪零偏
+/// 5. 运行中短按按键进入陀螺仪预热校准，按住2秒后松开重新校准陀螺仪零偏，
+///    按住5秒进行磁力计校准
+/// 6. 等待数据就绪中断，读出FIFO中排队的全部采样（100Hz）
+/// 7. 使用Madgwick滤波器按传感器采样周期逐个解算姿态，接有磁力计时以磁北为航向基准
+/// 8. 通过通道发送姿态（带磁偏角），由显示任务选择表示方式
+/// 9. 发布检测到的运动事件（敲击、摇晃、失重、倾斜、翻转）
+///
+/// 采样由传感器的采样时钟决定，显示任务较慢时采样在FIFO中排队，不会丢失。
+/// 每次校准后将校准参数写回闪存。初始化或读取失败时按重试策略处理（见[`sensor_fault`]）。
 ///
 /// # 参数
-/// - `i2c`: I2C总线实例（阻塞模式），用于与MPU6050通信
+/// - `bus`: MPU6050所在的I2C总线（异步模式），可恢复
+/// - `int`: MPU6050的INT引脚
+/// - `key`: 按键，用于启动和推进六面校准、启动和结束预热校准、重新校准零偏、
+///   启动和结束磁力计校准
+/// - `store`: 设置存储，以芯片唯一ID区分电路板保存校准参数；
+///   不可用时为`None`，校准参数不保存
 /// - `imu_sender`: 数据发送通道
-/// - `delay`: 采样周期时长（10ms）
+/// - `sample_rate`: 传感器采样率（Hz），须能整除陀螺仪输出频率
 #[embassy_executor::task]
 async fn mpu6050_update(
-    i2c: i2c::I2c<'static, embassy_stm32::mode::Blocking>,
-    imu_sender: Sender<'static, ThreadModeRawMutex, EulerAngles, 1>,
-    delay: embassy_time::Duration,
+    bus: ImuBus<Irqs>,
+    mut int: ExtiInput<'static>,
+    mut key: ExtiInput<'static>,
+    mut store: Option<KvStore<Flash<'static, embassy_stm32::mode::Blocking>>>,
+    imu_sender: Sender<'static, ThreadModeRawMutex, ImuDisplay, 1>,
+    sample_rate: u32,
 ) {
     // 创建MPU6050姿态解算器实例
-    // sample_period = 10ms / 1000 = 0.01秒 (100Hz)
+    // sample_period = 1 / 100Hz = 0.01秒，读取FIFO时按传感器实际的采样周期更新
     // beta = 0.1 (Madgwick滤波器增益系数)
-    let mut imu = Mpu6050MadgwickSolver::new(i2c, delay.as_millis() as f32 / 1000.0, 0.1);
+    // 也可以用with_filter选择其他滤波器，例如
+    // Mpu6050AsyncSolver::with_filter(bus, ComplementaryFilter::new(0.01, 0.02))
+    // 需要补偿陀螺仪零偏、抑制航向漂移时可使用扩展卡尔曼滤波器
+    // Mpu6050AsyncSolver::with_filter(bus, ExtendedKalmanFilter::new(0.01, EkfNoise::default()))
+    let mut imu = Mpu6050AsyncSolver::new(bus, 1.0 / sample_rate as f32, 0.1);
+    imu.set_declination(MAGNETIC_DECLINATION.to_radians());
 
-    // 初始化传感器 - 配置量程和数字滤波器
-    imu.init().unwrap();
+    // 初始化传感器 - 配置量程、数字滤波器和采样率
+    // 传感器采样率 = 陀螺仪输出频率 / (1 + 分频)，分频由解算器按低通滤波器模式计算，
+    // 无法得到该采样率时保持解算器默认的分频
+    let divider = match imu.sample_rate_divider_for(sample_rate) {
+        Some(divider) => divider,
+        None => {
+            defmt::error!("无法设置{}Hz采样率，使用默认采样率", sample_rate);
+            imu.sample_rate_divider()
+        }
+    };
+    // 失败时重试或恢复总线，传感器丢失时屏幕提示并定期重试，直到初始化成功
+    let mut health = SensorHealth::new(RetryPolicy::default());
+    while let Err(error) = start(&mut imu, divider).await {
+        sensor_fault(&mut imu, &mut health, error, &imu_sender).await;
+    }
+    health.record_success();
     defmt::info!("MPU6050初始化完成");
+    match imu.magnetometer() {
+        Some(magnetometer) => defmt::info!("检测到磁力计{}", magnetometer.chip.label()),
+        None => defmt::info!("未检测到磁力计，航向会随时间漂移"),
+    }
 
-    // 执行传感器校准（需保持设备静止水平放置3秒）
-    embassy_time::with_timeout(embassy_time::Duration::from_secs(3), async {
-        imu.calibration().await.unwrap();
-        defmt::info!("传感器校准完成");
-    })
-    .await
-    .unwrap();
+    // 恢复本电路板保存的校准参数
+    let uid: &Uid = embassy_stm32::uid::uid();
+    let loaded = match store.as_mut().map(|store| imu.load_calibration(store, uid)) {
+        Some(Ok(loaded)) => loaded,
+        Some(Err(_)) => {
+            defmt::warn!("校准参数读取失败");
+            false
+        }
+        None => false,
+    };
+    if loaded {
+        defmt::info!("已恢复保存的校准参数");
+    }
+
+    // 固件中指定了安装方向时以其为准，与保存的不同则写回闪存
+    if let Some((x, z)) = MOUNTING {
+        match Mounting::from_axes(x, z) {
+            Some(mounting) if mounting != *imu.mounting() => {
+                imu.set_mounting(mounting);
+                store_calibration(&imu, &mut store, uid);
+            }
+            Some(_) => {}
+            None => defmt::warn!("安装方向无效：两个轴不垂直"),
+        }
+    }
+    match imu.mounting().axes() {
+        Some((x, z)) => defmt::info!("安装方向 - 传感器X轴: {}, Z轴: {}", x.label(), z.label()),
+        None => defmt::info!("安装方向 - 任意角度"),
+    }
+
+    // 按住按键启动时进行六面校准，之后重新校准陀螺仪零偏；没有保存的校准参数时同样校准零偏
+    let six_face = key.is_low();
+    if six_face {
+        six_face_calibration(&mut imu, &mut key, &imu_sender).await;
+    }
+    if six_face || !loaded {
+        gyro_calibration(&mut imu, &imu_sender).await;
+        store_calibration(&imu, &mut store, uid);
+    }
 
     // 输出校准结果（加速度计和陀螺仪零偏）
-    let acc_offset = imu.get_accel_offset();
+    let acc_offset = *imu.get_accel_offset().as_g();
     defmt::info!(
-        "加速度零偏 - X: {}, Y: {}, Z: {}",
+        "加速度零偏(g) - X: {}, Y: {}, Z: {}",
         acc_offset.x,
         acc_offset.y,
         acc_offset.z
     );
 
-    let gyro_offset = imu.get_gyro_offset();
+    let gyro_offset = *imu.get_gyro_offset().as_rad_per_s();
     defmt::info!(
-        "陀螺仪零偏 - X: {}, Y: {}, Z: {}",
+        "陀螺仪零偏(rad/s) - X: {}, Y: {}, Z: {}",
         gyro_offset.x,
         gyro_offset.y,
         gyro_offset.z
     );
 
-    // 创建精确的定时采样器（10ms间隔）
-    let mut ticker = embassy_time::Ticker::every(delay);
+    // 由传感器按采样率写入FIFO并触发中断；失败时等待数据就绪超时，由主循环按重试策略处理
+    if imu.enable_fifo().await.is_err() {
+        defmt::warn!("FIFO使能失败");
+    }
 
     // 数据采集与解算主循环
     loop {
-        // 获取最新传感器数据
-        let data = imu.get_data().await.unwrap();
+        // 短按进入预热校准，按住2秒后松开重新校准陀螺仪零偏，按住5秒进行磁力计校准
+        if key.is_low() {
+            match embassy_time::with_timeout(RECALIBRATE_HOLD, key.wait_for_high()).await {
+                Ok(()) => warm_up_calibration(&mut imu, &mut key, &imu_sender).await,
+                Err(_) => {
+                    let remaining = MAG_CALIBRATION_HOLD - RECALIBRATE_HOLD;
+                    match embassy_time::with_timeout(remaining, key.wait_for_high()).await {
+                        Ok(()) => gyro_calibration(&mut imu, &imu_sender).await,
+                        Err(_) => mag_calibration(&mut imu, &mut key, &imu_sender).await,
+                    }
+                }
+            }
+            store_calibration(&imu, &mut store, uid);
+            // 零偏已改变，静止时估计的残余零偏不再适用
+            imu.reset_motion();
 
-        // 更新姿态解算（Madgwick滤波）
-        let quat = data.update().await.unwrap();
+            // 校准期间直接读取数据寄存器，丢弃FIFO中积压的采样
+            if imu.reset_fifo().await.is_err() {
+                defmt::warn!("FIFO复位失败");
+            }
+        }
 
-        // 将四元数转换为欧拉角（弧度）
-        let (roll, pitch, yaw) = quat.euler_angles();
+        // 等待数据就绪（INT引脚保持高电平直到读取），
+        // 然后读取FIFO中的全部采样并逐个更新姿态解算（Madgwick滤波）
+        let updated =
+            match embassy_time::with_timeout(DATA_READY_TIMEOUT, int.wait_for_high()).await {
+                Ok(()) => imu.update_from_fifo().await,
+                Err(_) => Err(ImuError::Timeout),
+            };
+        match updated {
+            Ok(0) => continue,
+            Ok(_) => health.record_success(),
+            Err(error) => {
+                sensor_fault(&mut imu, &mut health, error, &imu_sender).await;
+                continue;
+            }
+        }
+        imu.publish_motion_events(&MOTION_EVENTS);
 
-        // 构造欧拉角数据结构（弧度转角度）
-        let euler_angles = EulerAngles {
-            yaw: yaw.to_degrees() + 180_f32,     // 偏航角（度）
-            roll: roll.to_degrees(),   // 滚转角（度）
-            pitch: pitch.to_degrees(), // 俯仰角（度）
-        };
-        
         // 记录当前时间戳（用于性能分析）
         embassy_time::Instant::now().as_micros();
         
         // 发送姿态数据（先清空通道确保最新数据）
         imu_sender.clear();
-        imu_sender.send(euler_angles).await;
+        imu_sender
+            .send(ImuDisplay::Orientation(imu.orientation(), imu.motion()))
+            .await;
+    }
+}
 
-        // 等待下一个采样周期
-        ticker.next().await;
+/// 初始化传感器、检测磁力计并设置采样率
+///
+/// # 参数
+/// - `imu`: 姿态解算器
+/// - `divider`: 采样率分频
+async fn start(imu: &mut Imu, divider: u8) -> Result<(), ImuError<i2c::Error>> {
+    imu.init().await?;
+    imu.enable_magnetometer().await?;
+    imu.set_sample_rate_divider(divider).await?;
+    Ok(())
+}
+
+/// 将校准参数写回闪存，与已保存的相同时不写入
+///
+/// # 参数
+/// - `imu`: 姿态解算器
+/// - `store`: 设置存储，为`None`时不保存
+/// - `uid`: 芯片唯一ID
+fn store_calibration(
+    imu: &Imu,
+    store: &mut Option<KvStore<Flash<'static, embassy_stm32::mode::Blocking>>>,
+    uid: &Uid,
+) {
+    let Some(store) = store else {
+        return;
+    };
+    match imu.store_calibration(store, uid) {
+        Ok(()) => defmt::info!("校准参数已保存"),
+        Err(_) => defmt::warn!("校准参数保存失败"),
+    }
+}
+
+/// 磁力计校准过程
+///
+/// 屏幕提示转动电路板，每隔[`MAG_CALIBRATION_PERIOD`]记录一次未校准的磁场读数，
+/// 显示样本数；再次按下按键或达到最长时间后拟合椭球面求硬铁偏移和软铁修正并应用。
+/// 转动的方向覆盖不足时保留原有校准参数。
+///
+/// # 参数
+/// - `imu`: 姿态解算器
+/// - `key`: 按键，按下结束采集
+/// - `imu_sender`: 显示数据发送通道
+async fn mag_calibration(
+    imu: &mut Imu,
+    key: &mut ExtiInput<'static>,
+    imu_sender: &Sender<'static, ThreadModeRawMutex, ImuDisplay, 1>,
+) {
+    let mut fit = EllipsoidFit::new();
+    if imu.magnetometer().is_some() {
+        defmt::info!("开始磁力计校准");
+
+        // 等待松开启动校准的按键
+        key.wait_for_high().await;
+
+        let mut ticker = embassy_time::Ticker::every(MAG_CALIBRATION_PERIOD);
+        for _ in 0..MAG_CALIBRATION_MAX_SAMPLES {
+            if let Either::Second(()) = select(ticker.next(), key.wait_for_falling_edge()).await {
+                break;
+            }
+            match imu.get_data().await {
+                Ok(_) => fit.add(imu.raw_magnetic_field()),
+                Err(_) => defmt::warn!("磁力计校准读取失败"),
+            }
+            imu_sender.clear();
+            imu_sender
+                .send(ImuDisplay::MagCalibration {
+                    samples: fit.count(),
+                    hint: "ROTATE",
+                })
+                .await;
+        }
+    } else {
+        defmt::warn!("未检测到磁力计");
+    }
+
+    let hint = match fit.solve() {
+        Some(result) => {
+            imu.set_mag_calibration(result);
+            defmt::info!(
+                "磁力计校准完成 - {}个样本，硬铁偏移(Gs) X: {}, Y: {}, Z: {}",
+                fit.count(),
+                result.offset.x,
+                result.offset.y,
+                result.offset.z
+            );
+            "DONE"
+        }
+        None => {
+            defmt::warn!("磁力计校准求解失败");
+            "FAILED"
+        }
+    };
+    imu_sender.clear();
+    imu_sender
+        .send(ImuDisplay::MagCalibration {
+            samples: fit.count(),
+            hint,
+        })
+        .await;
+    embassy_time::Timer::after_secs(1).await;
+
+    // 等待松开结束校准的按键
+    key.wait_for_high().await;
+}
+
+/// 六面校准过程
+///
+/// 依次提示将电路板的各个面朝上放置，按下按键后静置采集1秒；
+/// 实际朝上的面与提示不符（如电路板倾斜）时提示后重新采集。
+/// 六个面全部完成后求解零偏、灵敏度和交叉耦合修正，并应用到解算器。
+///
+/// # 参数
+/// - `imu`: 姿态解算器
+/// - `key`: 按键，按下表示已放好
+/// - `imu_sender`: 显示数据发送通道
+async fn six_face_calibration(
+    imu: &mut Imu,
+    key: &mut ExtiInput<'static>,
+    imu_sender: &Sender<'static, ThreadModeRawMutex, ImuDisplay, 1>,
+) {
+    defmt::info!("开始六面校准");
+    let mut calibration = SixFaceCalibration::new();
+
+    // 等待松开启动时按住的按键
+    key.wait_for_high().await;
+
+    while let Some(face) = calibration.next_face() {
+        let step = calibration.recorded() + 1;
+        let prompt = |hint: &'static str| ImuDisplay::Calibration {
+            step,
+            label: face.label(),
+            hint,
+        };
+
+        // 提示放置，等待按键
+        imu_sender.clear();
+        imu_sender.send(prompt("PRESS KEY")).await;
+        key.wait_for_falling_edge().await;
+
+        // 等待按键引起的晃动平息后采集
+        imu_sender.clear();
+        imu_sender.send(prompt("HOLD STILL")).await;
+        embassy_time::Timer::after_millis(500).await;
+        let accel = match imu.average(FACE_SAMPLES).await {
+            Ok(mean) => mean.accel,
+            Err(_) => {
+                defmt::warn!("六面校准读取失败");
+                continue;
+            }
+        };
+
+        if Face::detect(&accel) == Some(face) {
+            calibration.record(face, accel);
+        } else {
+            imu_sender.clear();
+            imu_sender.send(prompt("WRONG FACE")).await;
+            embassy_time::Timer::after_secs(1).await;
+        }
+    }
+
+    let hint = match calibration.solve() {
+        Ok(result) => {
+            imu.set_accel_calibration(result);
+            defmt::info!(
+                "六面校准完成 - 零偏(g) X: {}, Y: {}, Z: {}",
+                result.bias.x,
+                result.bias.y,
+                result.bias.z
+            );
+            "DONE"
+        }
+        Err(_) => {
+            defmt::warn!("六面校准求解失败");
+            "FAILED"
+        }
+    };
+    imu_sender.clear();
+    imu_sender
+        .send(ImuDisplay::Calibration {
+            step: Face::ALL.len(),
+            label: "",
+            hint,
+        })
+        .await;
+    embassy_time::Timer::after_secs(1).await;
+}
+
+/// 陀螺仪预热校准过程
+///
+/// 设备保持静止，芯片从冷态逐渐升温。每秒记录一次陀螺仪平均读数和芯片温度，
+/// 屏幕显示当前温度和样本数；再次按下按键或达到最长时间后拟合零偏温度模型并应用。
+/// 温度跨度不足时保留原有模型。
+///
+/// # 参数
+/// - `imu`: 姿态解算器
+/// - `key`: 按键，按下结束采集
+/// - `imu_sender`: 显示数据发送通道
+async fn warm_up_calibration(
+    imu: &mut Imu,
+    key: &mut ExtiInput<'static>,
+    imu_sender: &Sender<'static, ThreadModeRawMutex, ImuDisplay, 1>,
+) {
+    defmt::info!("开始预热校准");
+    let mut fit = DriftFit::new();
+
+    // 等待松开启动校准的按键
+    key.wait_for_high().await;
+
+    for _ in 0..WARM_UP_MAX_SAMPLES {
+        match select(imu.average(WARM_UP_SAMPLES), key.wait_for_falling_edge()).await {
+            Either::First(Ok(mean)) => {
+                fit.add(mean.temperature, mean.gyro);
+                imu_sender.clear();
+                imu_sender
+                    .send(ImuDisplay::WarmUp {
+                        temperature: mean.temperature,
+                        samples: fit.count(),
+                    })
+                    .await;
+            }
+            Either::First(Err(_)) => defmt::warn!("预热校准读取失败"),
+            Either::Second(()) => break,
+        }
+    }
+
+    // 二次模型在温度跨度较大时才可靠
+    let order = match fit.temperature_span() >= QUADRATIC_MIN_SPAN {
+        true => DriftOrder::Quadratic,
+        false => DriftOrder::Linear,
+    };
+    match fit.solve(order) {
+        Some(model) => {
+            imu.set_gyro_drift_model(Some(model));
+            defmt::info!(
+                "预热校准完成 - {}个样本，温度跨度{}℃",
+                fit.count(),
+                fit.temperature_span()
+            );
+        }
+        None => defmt::warn!(
+            "温度跨度不足（{}℃），未更新温度模型",
+            fit.temperature_span()
+        ),
     }
+
+    // 等待松开结束校准的按键
+    key.wait_for_high().await;
 }
 
 /// OLED显示任务
@@ -244,18 +710,25 @@ async fn mpu6050_update(
 /// 此异步任务负责：
 /// 1. 初始化SSD1306 OLED显示屏
 /// 2. 配置文本渲染样式
-/// 3. 从通道获取欧拉角数据
-/// 4. 格式化并显示姿态数据（偏航角、滚转角、俯仰角）
-/// 5. 定期刷新显示（10Hz）
+/// 3. 从通道获取姿态数据
+/// 4. 按旋转编码器选择的表示方式（见[`Page`]）格式化并显示姿态
+/// 5. 检测到运动事件时代替姿态显示[`EVENT_DISPLAY_TIME`]
+/// 6. 定期刷新显示（10Hz）
 ///
 /// # 参数
 /// - `i2c`: I2C总线实例（异步模式），用于OLED通信
+/// - `encoder`: 旋转编码器，转动时切换姿态的表示方式
 /// - `imu_channel`: 数据接收通道
+/// - `events`: 运动事件订阅者
+/// - `brightness`: 屏幕亮度档位
 /// - `delay`: 显示刷新周期（100ms）
 #[embassy_executor::task]
 async fn oled_display(
     i2c: i2c::I2c<'static, embassy_stm32::mode::Async>,
-    imu_channel: Receiver<'static, ThreadModeRawMutex, EulerAngles, 1>,
+    encoder: Qei<'static, peripherals::TIM1>,
+    imu_channel: Receiver<'static, ThreadModeRawMutex, ImuDisplay, 1>,
+    mut events: MotionEventSubscriber<'static, ThreadModeRawMutex>,
+    brightness: u8,
     delay: embassy_time::Duration,
 ) {
     // 初始化显示接口和控制器（128x64分辨率，无旋转）
@@ -264,6 +737,7 @@ async fn oled_display(
         .into_buffered_graphics_mode();
 
     display.init().unwrap();
+    display.set_brightness(brightness_for(brightness)).unwrap();
 
     // 配置文本渲染样式（10x20 ASCII字体）
     let text_style = MonoTextStyleBuilder::new()
@@ -274,32 +748,113 @@ async fn oled_display(
     // 创建定时刷新器（100ms间隔）
     let mut ticker = embassy_time::Ticker::every(delay);
 
+    // 当前的表示方式，编码器每转一格切换一页
+    let mut page = 0;
+    let mut prev_count = encoder.count();
+    let mut accumulated = 0;
+
+    // 最近的运动事件及其到达时间
+    let mut last_event: Option<(MotionEvent, embassy_time::Instant)> = None;
+
     // 显示刷新主循环
     loop {
-        // 尝试获取最新的欧拉角数据
-        if let Ok(euler_angles) = imu_channel.try_peek() {
-            // 清空显示缓冲区
-            display.clear_buffer();
+        // 16位计数器按差值累计，溢出时差值仍然正确
+        let count = encoder.count();
+        accumulated -= count.wrapping_sub(prev_count) as i16 as i32;
+        prev_count = count;
+        if accumulated.abs() >= ENCODER_STEP {
+            let steps = accumulated / ENCODER_STEP;
+            page = (page as i32 + steps).rem_euclid(Page::ALL.len() as i32) as usize;
+            accumulated %= ENCODER_STEP;
+        }
 
-            // 格式化三个姿态角度的显示字符串
-            let text_yaw = format_euler(String::from_str("yaw  ").unwrap(), euler_angles.yaw);
-            let text_roll = format_euler(String::from_str("roll ").unwrap(), euler_angles.roll);
-            let text_pitch = format_euler(String::from_str("pitch").unwrap(), euler_angles.pitch);
+        // 只显示最新的运动事件，超过显示时间后回到姿态
+        while let Some(event) = events.try_next_message_pure() {
+            last_event = Some((event, embassy_time::Instant::now()));
+        }
+        let event = last_event
+            .filter(|(_, time)| time.elapsed() < EVENT_DISPLAY_TIME)
+            .map(|(event, _)| event);
 
-            // 在OLED上渲染偏航角（第一行）
-            Text::with_baseline(&text_yaw, Point::new(-1, 1), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
+        // 尝试获取最新的显示数据
+        if let Ok(message) = imu_channel.try_peek() {
+            // 清空显示缓冲区
+            display.clear_buffer();
 
-            // 在OLED上渲染滚转角（第二行）
-            Text::with_baseline(&text_roll, Point::new(-1, 22), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
+            let lines: [String<13>; 3] = match message {
+                // 运动事件、运动状态或按选择的表示方式格式化的姿态
+                ImuDisplay::Orientation(orientation, motion) => match (event, Page::ALL[page]) {
+                    (Some(event), _) => format_event(&event),
+                    (None, Page::Motion) => format_motion(&motion),
+                    (None, page) => format_orientation(page, &orientation),
+                },
+                // 预热校准进度：标题、芯片温度、样本数
+                ImuDisplay::WarmUp {
+                    temperature,
+                    samples,
+                } => {
+                    let mut text_temperature = String::new();
+                    write!(
+                        &mut text_temperature,
+                        "T {}.{}C",
+                        temperature as i32,
+                        ((temperature.abs() * 10_f32) as i32) % 10
+                    )
+                    .unwrap();
+                    let mut text_samples = String::new();
+                    write!(&mut text_samples, "N {}", samples).unwrap();
+                    [
+                        String::from_str("WARM UP").unwrap(),
+                        text_temperature,
+                        text_samples,
+                    ]
+                }
+                // 磁力计校准：标题、样本数、操作提示
+                ImuDisplay::MagCalibration { samples, hint } => {
+                    let mut text_samples = String::new();
+                    write!(&mut text_samples, "N {}", samples).unwrap();
+                    [
+                        String::from_str("CAL MAG").unwrap(),
+                        text_samples,
+                        String::from_str(hint).unwrap(),
+                    ]
+                }
+                // 陀螺仪零偏校准提示
+                ImuDisplay::GyroCalibration { hint } => [
+                    String::from_str("CAL GYRO").unwrap(),
+                    String::new(),
+                    String::from_str(hint).unwrap(),
+                ],
+                // 传感器故障：状态、错误次数、总线恢复次数
+                ImuDisplay::SensorFault { state, counters } => {
+                    let title = match state {
+                        SensorState::Lost => "SENSOR LOST",
+                        _ => "RECOVERING",
+                    };
+                    let mut errors = String::new();
+                    write!(&mut errors, "E {}", counters.errors).unwrap();
+                    let mut recoveries = String::new();
+                    write!(&mut recoveries, "R {}", counters.recoveries).unwrap();
+                    [String::from_str(title).unwrap(), errors, recoveries]
+                }
+                // 六面校准提示：进度、朝上的面、操作提示
+                ImuDisplay::Calibration { step, label, hint } => {
+                    let mut progress = String::new();
+                    write!(&mut progress, "CAL {}/{}", step, Face::ALL.len()).unwrap();
+                    [
+                        progress,
+                        String::from_str(label).unwrap(),
+                        String::from_str(hint).unwrap(),
+                    ]
+                }
+            };
 
-            // 在OLED上渲染俯仰角（第三行）
-            Text::with_baseline(&text_pitch, Point::new(-1, 43), text_style, Baseline::Top)
-                .draw(&mut display)
-                .unwrap();
+            // 在OLED上逐行渲染
+            for (line, y) in lines.iter().zip([1, 22, 43]) {
+                Text::with_baseline(line, Point::new(-1, y), text_style, Baseline::Top)
+                    .draw(&mut display)
+                    .unwrap();
+            }
 
             // 更新物理显示
             display.flush().unwrap();
@@ -310,18 +865,255 @@ async fn oled_display(
     }
 }
 
-/// 欧拉角数据结构
+/// 运动事件日志任务：记录订阅到的每个运动事件
 ///
-/// 表示三维空间中的物体方向：
-/// - yaw: 偏航角（绕Z轴旋转）
-/// - roll: 滚转角（绕X轴旋转）
-/// - pitch: 俯仰角（绕Y轴旋转）
-/// 所有角度单位为度（°）
+/// # 参数
+/// - `events`: 运动事件订阅者
+#[embassy_executor::task]
+async fn motion_event_log(mut events: MotionEventSubscriber<'static, ThreadModeRawMutex>) {
+    loop {
+        let event = events.next_message_pure().await;
+        defmt::info!("运动事件：{}", event.label());
+    }
+}
+
+/// 显示内容
 #[derive(Clone)]
-struct EulerAngles {
-    yaw: f32,
-    roll: f32,
-    pitch: f32,
+enum ImuDisplay {
+    /// 姿态和运动状态
+    Orientation(Orientation, Motion),
+    /// 预热校准进度
+    WarmUp {
+        /// 芯片温度（℃）
+        temperature: f32,
+        /// 已记录的样本数
+        samples: usize,
+    },
+    /// 磁力计校准进度
+    MagCalibration {
+        /// 已记录的样本数
+        samples: usize,
+        /// 操作提示或结果
+        hint: &'static str,
+    },
+    /// 陀螺仪零偏校准提示
+    GyroCalibration {
+        /// 操作提示或结果
+        hint: &'static str,
+    },
+    /// 传感器故障
+    SensorFault {
+        /// 恢复中或已丢失
+        state: SensorState,
+        /// 累计的错误计数
+        counters: ErrorCounters,
+    },
+    /// 六面校准提示
+    Calibration {
+        /// 当前步骤（从1开始）
+        step: usize,
+        /// 需要朝上的面
+        label: &'static str,
+        /// 操作提示
+        hint: &'static str,
+    },
+}
+
+impl SensorDisplay for ImuDisplay {
+    fn gyro_calibration(hint: &'static str) -> Self {
+        Self::GyroCalibration { hint }
+    }
+
+    fn sensor_fault(state: SensorState, counters: ErrorCounters) -> Self {
+        Self::SensorFault { state, counters }
+    }
+}
+
+/// 姿态的表示方式（显示页面）
+#[derive(Clone, Copy)]
+enum Page {
+    /// 欧拉角，ZYX顺序时偏航角显示为航向
+    Euler(EulerSequence),
+    /// 航向、倾斜角和方位
+    Heading,
+    /// 四元数
+    Quaternion,
+    /// 旋转矩阵
+    RotationMatrix,
+    /// 轴角
+    AxisAngle,
+    /// 线加速度、速度和位移
+    Motion,
+}
+
+impl Page {
+    /// 编码器依次切换的页面，第一页为启动时的默认页面
+    const ALL: [Page; 7] = [
+        Page::Euler(EulerSequence::Zyx),
+        Page::Euler(EulerSequence::Xyz),
+        Page::Heading,
+        Page::Quaternion,
+        Page::RotationMatrix,
+        Page::AxisAngle,
+        Page::Motion,
+    ];
+}
+
+/// 按表示方式格式化姿态的三行显示内容
+///
+/// 欧拉角处于万向节锁时第三个角固定为零，标签后加`*`提示。
+///
+/// # 参数
+/// - `page`: 表示方式
+/// - `orientation`: 姿态
+fn format_orientation(page: Page, orientation: &Orientation) -> [String<13>; 3] {
+    let label = |s: &str| String::<5>::from_str(s).unwrap();
+    match page {
+        Page::Euler(EulerSequence::Zyx) => {
+            let euler = orientation.euler(EulerSequence::Zyx);
+            let [_, pitch, roll] = euler.angles;
+            let roll_label = if euler.gimbal_lock { "roll*" } else { "roll " };
+            [
+                format_euler(label("yaw  "), orientation.heading().to_degrees()),
+                format_euler(label(roll_label), roll.to_degrees()),
+                format_euler(label("pitch"), pitch.to_degrees()),
+            ]
+        }
+        Page::Euler(sequence) => {
+            // 按转动顺序逐行显示，以所绕的轴为标签
+            let euler = orientation.euler(sequence);
+            let mut axes = sequence.label().chars();
+            core::array::from_fn(|index| {
+                let locked = index == 2 && euler.gimbal_lock;
+                let mut name: String<5> = String::new();
+                name.push(axes.next().unwrap()).unwrap();
+                name.push_str(if locked { "*   " } else { "    " }).unwrap();
+                format_euler(name, euler.angles[index].to_degrees())
+            })
+        }
+        Page::Heading => {
+            // 八个方位，每个占45°
+            const POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+            let heading = orientation.heading().to_degrees();
+            let point = POINTS[((heading + 22.5) / 45.0) as usize % POINTS.len()];
+            [
+                format_euler(label("head "), heading),
+                format_euler(label("tilt "), orientation.tilt().to_degrees()),
+                String::from_str(point).unwrap(),
+            ]
+        }
+        Page::Quaternion => {
+            let q = orientation.quaternion();
+            [
+                String::from_str("QUAT").unwrap(),
+                format_components(&[('w', q.w), ('x', q.i)]),
+                format_components(&[('y', q.j), ('z', q.k)]),
+            ]
+        }
+        Page::RotationMatrix => {
+            let matrix = orientation.rotation_matrix();
+            core::array::from_fn(|row| {
+                let mut line = String::new();
+                for value in matrix.matrix().row(row).iter() {
+                    write_fixed(&mut line, *value, 1);
+                }
+                line
+            })
+        }
+        Page::AxisAngle => {
+            let (axis, angle) = orientation.axis_angle();
+            [
+                format_euler(label("angle"), angle.to_degrees()),
+                format_components(&[('x', axis.x), ('y', axis.y)]),
+                format_components(&[('z', axis.z)]),
+            ]
+        }
+        // 运动状态由format_motion显示
+        Page::Motion => Default::default(),
+    }
+}
+
+/// 格式化运动事件的三行显示内容：事件名称和敲击方向或倾斜角
+///
+/// # 参数
+/// - `event`: 运动事件
+fn format_event(event: &MotionEvent) -> [String<13>; 3] {
+    let mut detail = String::new();
+    match event {
+        MotionEvent::Tap(axis) | MotionEvent::DoubleTap(axis) => {
+            detail.push_str(axis.label()).unwrap();
+        }
+        MotionEvent::Tilted { angle } => {
+            detail = format_euler(String::from_str("tilt ").unwrap(), angle.to_degrees());
+        }
+        _ => {}
+    }
+    [
+        String::from_str(event.label()).unwrap(),
+        detail,
+        String::new(),
+    ]
+}
+
+/// 格式化运动状态的三行显示内容，如"a+0.45 MOVE"、"v+0.12 m/s"、"d+0.35 m"
+///
+/// 线加速度、速度和位移均显示模长，超过[`MOTION_DISPLAY_MAX`]时截断。
+///
+/// # 参数
+/// - `motion`: 运动状态
+fn format_motion(motion: &Motion) -> [String<13>; 3] {
+    let state = if motion.still { " STILL" } else { " MOVE" };
+    let rows = [
+        ('a', motion.linear_acceleration.norm(), state),
+        ('v', motion.velocity.norm(), " m/s"),
+        ('d', motion.displacement.norm(), " m"),
+    ];
+    rows.map(|(name, value, unit)| {
+        let mut line = format_components(&[(name, value.min(MOTION_DISPLAY_MAX))]);
+        line.push_str(unit).unwrap();
+        line
+    })
+}
+
+/// 格式化若干带名称的分量，如"w+0.99 x-0.01"
+///
+/// # 参数
+/// - `components`: (名称, 数值)，最多两个
+fn format_components(components: &[(char, f32)]) -> String<13> {
+    let mut buf = String::new();
+    for (index, &(name, value)) in components.iter().enumerate() {
+        if index > 0 {
+            buf.push(' ').unwrap();
+        }
+        buf.push(name).unwrap();
+        write_fixed(&mut buf, value, 2);
+    }
+    buf
+}
+
+/// 追加带符号的定点小数，如"+0.98"
+///
+/// # 参数
+/// - `buf`: 显示字符串
+/// - `value`: 数值（绝对值小于10）
+/// - `decimals`: 小数位数
+fn write_fixed(buf: &mut String<13>, value: f32, decimals: u32) {
+    let scale = 10_i32.pow(decimals);
+    let scaled = (value.abs() * scale as f32 + 0.5) as i32;
+    let sign = if value.is_sign_negative() && scaled != 0 {
+        '-'
+    } else {
+        '+'
+    };
+    write!(
+        buf,
+        "{}{}.{:0width$}",
+        sign,
+        scaled / scale,
+        scaled % scale,
+        width = decimals as usize
+    )
+    .unwrap();
 }
 
 /// 格式化欧拉角显示字符串
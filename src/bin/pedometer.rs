@@ -0,0 +1,488 @@
+//! STM32 MPU6050 Pedometer with OLED Display
+//! =============================================================================================
+//!
+//! This firmware implements a step counter using:
+//! - MPU6050 6-axis motion sensor via I2C2 (async, DMA)
+//! - Band-pass filtered acceleration magnitude with adaptive peak detection
+//!   (`main_cargo::app::imu::pedometer`)
+//! - SSD1306 OLED display (128x64) via I2C1
+//!
+//! Hardware Connections:
+//!   OLED Display -> Blue Pill
+//!      GND  -> GND
+//!      VCC  -> 5V
+//!      SDA  -> PB7 (I2C1)
+//!      SCL  -> PB6 (I2C1)
+//!
+//!   MPU6050 Sensor -> Blue Pill
+//!      VCC  -> 3.3V
+//!      GND  -> GND
+//!      SDA  -> PB11 (I2C2)
+//!      SCL  -> PB10 (I2C2)
+//!      INT  -> PB12 (data ready, active high)
+//!
+//!   Key -> Blue Pill
+//!      SW   -> PB15 (pull-up, active low)
+//!
+//! Features:
+//! 1. Acceleration sampled at 100Hz through the MPU6050 FIFO and data-ready interrupt,
+//!    every sample fed to the step detector
+//! 2. Sensor calibration shared with the IMU firmware (stored in flash per board), gyro
+//!    offsets calibrated when nothing is stored
+//! 3. Step count, cadence (steps per minute) and distance from the step length shown on
+//!    the OLED; a few regular steps are needed before counting starts
+//! 4. Step length estimated from the configured body height
+//! 5. Press the key to reset the count and distance
+//! 6. Sensor errors retried, I2C bus recovered and sensor re-initialized after repeated
+//!    failures; "SENSOR LOST" shown with error counters instead of a panic
+
+#![no_std] // 禁用标准库，适用于裸机嵌入式环境
+#![no_main] // 禁用标准main入口，使用自定义入口点
+
+use embassy_executor::Spawner;
+use embassy_futures::select::{Either, select};
+use embassy_stm32::{
+    bind_interrupts,
+    exti::ExtiInput,
+    flash::Flash,
+    gpio::Pull,
+    i2c::{self, ErrorInterruptHandler, EventInterruptHandler},
+    peripherals,
+    time::Hertz,
+};
+use embassy_sync::{
+    blocking_mutex::raw::ThreadModeRawMutex,
+    channel::{Channel, Receiver, Sender},
+};
+use {defmt_rtt as _, panic_probe as _}; // 日志记录和panic处理
+
+use embedded_graphics::{
+    mono_font::{MonoTextStyleBuilder, ascii::FONT_10X20},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    text::{Baseline, Text},
+};
+use ssd1306::{I2CDisplayInterface, Ssd1306, prelude::*};
+
+use core::fmt::Write;
+use core::str::FromStr;
+use heapless::String;
+
+use main_cargo::app::imu::health::{ErrorCounters, RetryPolicy, SensorHealth, SensorState};
+use main_cargo::app::imu::pedometer::{ConfigError, Pedometer, PedometerConfig, Steps};
+use main_cargo::hardware::board::clock_config;
+use main_cargo::hardware::config::{SETTINGS_OFFSET, SETTINGS_SIZE, Settings, Uid};
+use main_cargo::hardware::display::brightness_for;
+use main_cargo::hardware::flash_store::KvStore;
+use main_cargo::hardware::imu_bus::{self, ImuBus, SensorDisplay, gyro_calibration, sensor_fault};
+use main_cargo::hardware::imu_error::ImuError;
+use main_cargo::hardware::mpu6050_async_solver::Mpu6050AsyncSolver;
+
+// 绑定I2C1（OLED）和I2C2（MPU6050）中断处理函数
+bind_interrupts!(struct Irqs {
+    I2C1_EV => EventInterruptHandler<peripherals::I2C1>;
+    I2C1_ER => ErrorInterruptHandler<peripherals::I2C1>;
+    I2C2_EV => EventInterruptHandler<peripherals::I2C2>;
+    I2C2_ER => ErrorInterruptHandler<peripherals::I2C2>;
+});
+
+// 身高（m），用于估计步长
+const BODY_HEIGHT: f32 = 1.70;
+
+// 传感器采样率（Hz），须能整除陀螺仪输出频率
+const SAMPLE_RATE: u32 = 100;
+
+// 等待数据就绪中断的超时时间（10个采样周期），超时说明传感器没有工作
+const DATA_READY_TIMEOUT: embassy_time::Duration = embassy_time::Duration::from_millis(100);
+
+// 按键消抖时间，按下后保持低电平超过该时间才清零
+const KEY_DEBOUNCE: embassy_time::Duration = embassy_time::Duration::from_millis(20);
+
+// MPU6050解算器，接在可恢复的I2C2总线上
+type Imu = imu_bus::Imu<Irqs>;
+
+// 显示数据通道（线程安全的单生产者单消费者通道）
+static DISPLAY_CHANNEL: Channel<ThreadModeRawMutex, PedometerDisplay, 1> = Channel::new();
+
+/// 主入口函数
+///
+/// Embassy执行器的主入口点，负责：
+/// 1. 配置系统时钟（HSE 8MHz + PLL倍频到72MHz）
+/// 2. 从闪存读取设置（与万年历共用），设置存储随后交给传感器任务读取校准参数
+/// 3. 初始化I2C外设（OLED使用I2C1，MPU6050使用I2C2）
+/// 4. 启动计步任务和OLED显示任务
+///
+/// # 参数
+/// - `_spawner`: 任务生成器，用于创建异步任务
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    // 配置系统时钟（使用外部8MHz晶振，通过PLL倍频到72MHz）
+    let config = clock_config();
+
+    // 初始化外设
+    let p = embassy_stm32::init(config);
+
+    defmt::info!("计步器启动!");
+
+    // 从闪存末尾的设置存储区读取设置，读取失败时使用默认设置；
+    // 存储区不可用时同样使用默认设置继续运行，校准参数不保存
+    let mut settings_store =
+        match KvStore::new(Flash::new_blocking(p.FLASH), SETTINGS_OFFSET, SETTINGS_SIZE) {
+            Ok(store) => Some(store),
+            Err(_) => {
+                defmt::warn!("设置存储不可用，校准参数将不会保存");
+                None
+            }
+        };
+    let settings = match settings_store.as_mut().map(Settings::load) {
+        Some(Ok(settings)) => settings.unwrap_or_default(),
+        Some(Err(_)) => {
+            defmt::warn!("设置读取失败");
+            Settings::default()
+        }
+        None => Settings::default(),
+    };
+
+    // 配置I2C2接口（PB10: SCL, PB11: SDA）用于MPU6050
+    // 异步模式，DMA1通道4/5收发，时钟频率400kHz，通信持续失败时可恢复总线
+    let imu_bus = ImuBus::new(p.I2C2, p.PB10, p.PB11, Irqs, p.DMA1_CH4, p.DMA1_CH5);
+
+    // 按键（PB15，上拉输入，按下为低电平），按下清零步数
+    let key = ExtiInput::new(p.PB15, p.EXTI15, Pull::Up);
+
+    // MPU6050数据就绪中断（PB12，高电平有效，保持到读取FIFO）
+    let imu_int = ExtiInput::new(p.PB12, p.EXTI12, Pull::Down);
+
+    _spawner
+        .spawn(step_counter(
+            imu_bus,
+            imu_int,
+            key,
+            settings_store,
+            DISPLAY_CHANNEL.sender(),
+        ))
+        .unwrap();
+
+    // 配置I2C1外设（PB6: SCL, PB7: SDA）用于OLED，时钟频率400kHz
+    let oled_i2c = i2c::I2c::new(
+        p.I2C1,
+        p.PB6,
+        p.PB7,
+        Irqs,
+        p.DMA1_CH6,
+        p.DMA1_CH7,
+        Hertz::khz(400),
+        Default::default(),
+    );
+
+    // 启动OLED显示任务（刷新周期200ms）
+    _spawner
+        .spawn(oled_display(
+            oled_i2c,
+            DISPLAY_CHANNEL.receiver(),
+            settings.brightness,
+            embassy_time::Duration::from_millis(200),
+        ))
+        .unwrap();
+
+    // 主循环（保持系统运行）
+    loop {
+        embassy_time::Timer::after_secs(1000).await;
+    }
+}
+
+/// 计步任务
+///
+/// 此异步任务负责：
+/// 1. 初始化MPU6050传感器，采样率100Hz
+/// 2. 从闪存恢复本电路板的校准参数（与姿态解算固件共用）；没有保存过时执行陀螺仪零偏校准
+/// 3. 等待数据就绪中断，读出FIFO中排队的全部采样，把每个校准后的加速度交给计步器
+/// 4. 步数、步频或距离变化时发送到显示任务
+/// 5. 按下按键时清零步数和距离
+///
+/// 初始化或读取失败时按重试策略处理（见[`sensor_fault`]）。
+///
+/// # 参数
+/// - `bus`: MPU6050所在的I2C总线（异步模式），可恢复
+/// - `int`: MPU6050的INT引脚
+/// - `key`: 按键，按下清零
+/// - `store`: 设置存储，以芯片唯一ID区分电路板读取和保存校准参数；
+///   不可用时为`None`，校准参数不保存
+/// - `sender`: 显示数据发送通道
+#[embassy_executor::task]
+async fn step_counter(
+    bus: ImuBus<Irqs>,
+    mut int: ExtiInput<'static>,
+    mut key: ExtiInput<'static>,
+    mut store: Option<KvStore<Flash<'static, embassy_stm32::mode::Blocking>>>,
+    sender: Sender<'static, ThreadModeRawMutex, PedometerDisplay, 1>,
+) {
+    // 计步只用校准后的加速度，与姿态无关；Madgwick滤波器参数与姿态解算固件相同
+    let mut imu = Mpu6050AsyncSolver::new(bus, 1.0 / SAMPLE_RATE as f32, 0.1);
+
+    // 传感器采样率 = 陀螺仪输出频率 / (1 + 分频)，分频由解算器按低通滤波器模式计算，
+    // 无法得到该采样率时保持解算器默认的分频
+    let divider = match imu.sample_rate_divider_for(SAMPLE_RATE) {
+        Some(divider) => divider,
+        None => {
+            defmt::error!("无法设置{}Hz采样率，使用默认采样率", SAMPLE_RATE);
+            imu.sample_rate_divider()
+        }
+    };
+
+    // 初始化传感器，失败时重试或重新初始化，传感器丢失时屏幕提示并定期重试
+    let mut health = SensorHealth::new(RetryPolicy::default());
+    while let Err(error) = start(&mut imu, divider).await {
+        sensor_fault(&mut imu, &mut health, error, &sender).await;
+    }
+    health.record_success();
+    defmt::info!("MPU6050初始化完成");
+
+    // 带通滤波器按传感器的采样周期设计；计步参数不适用于该采样率时屏幕提示并停止计步
+    let config = PedometerConfig::for_height(BODY_HEIGHT);
+    let mut pedometer = match Pedometer::new(config, imu.sample_period()) {
+        Ok(pedometer) => pedometer,
+        Err(error) => {
+            defmt::error!("计步参数错误: {}", error.label());
+            sender.send(PedometerDisplay::ConfigError(error)).await;
+            loop {
+                embassy_time::Timer::after_secs(1000).await;
+            }
+        }
+    };
+
+    // 恢复本电路板保存的校准参数，没有时校准陀螺仪零偏并保存
+    let uid: &Uid = embassy_stm32::uid::uid();
+    let loaded = match store.as_mut().map(|store| imu.load_calibration(store, uid)) {
+        Some(Ok(loaded)) => loaded,
+        Some(Err(_)) => {
+            defmt::warn!("校准参数读取失败");
+            false
+        }
+        None => false,
+    };
+    if loaded {
+        defmt::info!("已恢复保存的校准参数");
+    } else {
+        gyro_calibration(&mut imu, &sender).await;
+        if let Some(store) = store.as_mut() {
+            match imu.store_calibration(store, uid) {
+                Ok(()) => defmt::info!("校准参数已保存"),
+                Err(_) => defmt::warn!("校准参数保存失败"),
+            }
+        }
+    }
+
+    // 由传感器按采样率写入FIFO并触发中断；失败时等待数据就绪超时，由主循环按重试策略处理
+    if imu.enable_fifo().await.is_err() {
+        defmt::warn!("FIFO使能失败");
+    }
+
+    let mut shown = None;
+    loop {
+        // 等待数据就绪（INT引脚保持高电平直到读取），然后读取FIFO中的全部采样并逐个计步；
+        // 同时等待按键按下，按住按键时不影响计步
+        let data_ready = embassy_time::with_timeout(DATA_READY_TIMEOUT, int.wait_for_high());
+        let updated = match select(data_ready, key.wait_for_falling_edge()).await {
+            Either::First(Ok(())) => {
+                imu.update_from_fifo_with(|accel, _| pedometer.update(accel))
+                    .await
+            }
+            Either::First(Err(_)) => Err(ImuError::Timeout),
+            Either::Second(()) => {
+                // 消抖后仍为低电平才清零，并丢弃FIFO中清零之前的采样
+                embassy_time::Timer::after(KEY_DEBOUNCE).await;
+                if key.is_low() {
+                    pedometer.reset();
+                    defmt::info!("步数已清零");
+                    if imu.reset_fifo().await.is_err() {
+                        defmt::warn!("FIFO复位失败");
+                    }
+                    shown = None;
+                }
+                continue;
+            }
+        };
+        match updated {
+            Ok(0) => continue,
+            Ok(_) => health.record_success(),
+            Err(error) => {
+                sensor_fault(&mut imu, &mut health, error, &sender).await;
+                shown = None;
+                continue;
+            }
+        }
+
+        // 只在显示内容变化时发送
+        let steps = pedometer.steps();
+        if shown != Some(steps) {
+            if shown.is_some_and(|shown| shown.walking != steps.walking) {
+                let state = if steps.walking {
+                    "开始行走"
+                } else {
+                    "停止行走"
+                };
+                defmt::info!(
+                    "{} - 步数: {}, 距离: {}m",
+                    state,
+                    steps.count,
+                    steps.distance
+                );
+            }
+            shown = Some(steps);
+            sender.clear();
+            sender.send(PedometerDisplay::Steps(steps)).await;
+        }
+    }
+}
+
+/// 初始化传感器并设置采样率
+///
+/// # 参数
+/// - `imu`: 解算器
+/// - `divider`: 采样率分频
+async fn start(imu: &mut Imu, divider: u8) -> Result<(), ImuError<i2c::Error>> {
+    imu.init().await?;
+    imu.set_sample_rate_divider(divider).await?;
+    Ok(())
+}
+
+/// OLED显示任务
+///
+/// 从通道获取最新的显示内容，格式化为三行文本后定期刷新显示。
+///
+/// # 参数
+/// - `i2c`: I2C总线实例（异步模式），用于OLED通信
+/// - `receiver`: 显示数据接收通道
+/// - `brightness`: 屏幕亮度档位
+/// - `delay`: 显示刷新周期
+#[embassy_executor::task]
+async fn oled_display(
+    i2c: i2c::I2c<'static, embassy_stm32::mode::Async>,
+    receiver: Receiver<'static, ThreadModeRawMutex, PedometerDisplay, 1>,
+    brightness: u8,
+    delay: embassy_time::Duration,
+) {
+    // 初始化显示接口和控制器（128x64分辨率，无旋转）
+    let interface = I2CDisplayInterface::new(i2c);
+    let mut display = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+        .into_buffered_graphics_mode();
+
+    display.init().unwrap();
+    display.set_brightness(brightness_for(brightness)).unwrap();
+
+    // 配置文本渲染样式（10x20 ASCII字体）
+    let text_style = MonoTextStyleBuilder::new()
+        .font(&FONT_10X20)
+        .text_color(BinaryColor::On)
+        .build();
+
+    let mut ticker = embassy_time::Ticker::every(delay);
+    loop {
+        if let Ok(message) = receiver.try_peek() {
+            display.clear_buffer();
+
+            let lines: [String<12>; 3] = match message {
+                // 步数、步频、距离
+                PedometerDisplay::Steps(steps) => format_steps(&steps),
+                // 陀螺仪零偏校准提示
+                PedometerDisplay::GyroCalibration { hint } => [
+                    String::from_str("CAL GYRO").unwrap(),
+                    String::new(),
+                    String::from_str(hint).unwrap(),
+                ],
+                // 传感器故障：状态、错误次数、重新初始化次数
+                PedometerDisplay::SensorFault { state, counters } => {
+                    let title = match state {
+                        SensorState::Lost => "SENSOR LOST",
+                        _ => "RECOVERING",
+                    };
+                    let mut errors = String::new();
+                    write!(&mut errors, "E {}", counters.errors).unwrap();
+                    let mut recoveries = String::new();
+                    write!(&mut recoveries, "R {}", counters.recoveries).unwrap();
+                    [String::from_str(title).unwrap(), errors, recoveries]
+                }
+                // 计步参数错误：错误名称
+                PedometerDisplay::ConfigError(error) => [
+                    String::from_str("CONFIG ERROR").unwrap(),
+                    String::new(),
+                    String::from_str(error.label()).unwrap(),
+                ],
+            };
+
+            // 在OLED上逐行渲染
+            for (line, y) in lines.iter().zip([1, 22, 43]) {
+                Text::with_baseline(line, Point::new(-1, y), text_style, Baseline::Top)
+                    .draw(&mut display)
+                    .unwrap();
+            }
+            display.flush().unwrap();
+        }
+
+        ticker.next().await;
+    }
+}
+
+/// 显示内容
+#[derive(Clone)]
+enum PedometerDisplay {
+    /// 计步结果
+    Steps(Steps),
+    /// 陀螺仪零偏校准提示
+    GyroCalibration {
+        /// 操作提示或结果
+        hint: &'static str,
+    },
+    /// 传感器故障
+    SensorFault {
+        /// 传感器状态
+        state: SensorState,
+        /// 错误计数
+        counters: ErrorCounters,
+    },
+    /// 计步参数错误
+    ConfigError(ConfigError),
+}
+
+impl SensorDisplay for PedometerDisplay {
+    fn gyro_calibration(hint: &'static str) -> Self {
+        Self::GyroCalibration { hint }
+    }
+
+    fn sensor_fault(state: SensorState, counters: ErrorCounters) -> Self {
+        Self::SensorFault { state, counters }
+    }
+}
+
+/// 格式化计步结果的三行显示内容，如"1234 STEPS"、"112 SPM"、"0.86 km"
+///
+/// 距离不足1km时以米为单位显示整数。屏幕宽128像素，每行最多12个字符，
+/// 步数、步频和距离分别限制在99999步、999步/分钟和99999.99km以内。
+///
+/// # 参数
+/// - `steps`: 计步结果
+fn format_steps(steps: &Steps) -> [String<12>; 3] {
+    let mut count = String::new();
+    write!(&mut count, "{} STEPS", steps.count.min(99_999)).unwrap();
+
+    let mut cadence = String::new();
+    let spm = ((steps.cadence + 0.5) as u32).min(999);
+    write!(&mut cadence, "{} SPM", spm).unwrap();
+
+    let mut distance = String::new();
+    if steps.distance < 1000.0 {
+        write!(&mut distance, "{} m", steps.distance as u32).unwrap();
+    } else {
+        let decameters = ((steps.distance / 10.0 + 0.5) as u32).min(9_999_999);
+        write!(
+            &mut distance,
+            "{}.{:02} km",
+            decameters / 100,
+            decameters % 100
+        )
+        .unwrap();
+    }
+
+    [count, cadence, distance]
+}
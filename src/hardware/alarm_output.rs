@@ -0,0 +1,66 @@
+use super::traits::{Buzzer, Led};
+
+/// 响铃节奏：每步100ms，`true`表示LED点亮、蜂鸣器鸣叫（“嘀嘀——”循环）
+const RING_PATTERN: [bool; 10] = [
+    true, false, true, false, false, false, false, false, false, false,
+];
+
+/// 闹钟输出
+///
+/// 响铃时按固定节奏驱动LED和可选的蜂鸣器。
+///
+/// # 泛型参数
+/// - `L`: 实现[`Led`]的指示灯
+/// - `B`: 实现[`Buzzer`]的蜂鸣器
+pub struct AlarmOutput<L, B> {
+    led: L,
+    buzzer: Option<B>,
+    step: usize,
+}
+
+impl<L: Led, B: Buzzer> AlarmOutput<L, B> {
+    /// 创建闹钟输出
+    ///
+    /// # 参数
+    /// - `led`: 指示灯
+    /// - `buzzer`: 蜂鸣器，未连接时传入`None`
+    pub fn new(led: L, buzzer: Option<B>) -> Self {
+        Self {
+            led,
+            buzzer,
+            step: 0,
+        }
+    }
+
+    /// 响铃节奏前进一步，应每100ms调用一次
+    pub fn ring_step(&mut self) {
+        if RING_PATTERN[self.step] {
+            self.led.on();
+            if let Some(buzzer) = self.buzzer.as_mut() {
+                buzzer.on();
+            }
+        } else {
+            self.silence_outputs();
+        }
+
+        self.step = (self.step + 1) % RING_PATTERN.len();
+    }
+
+    /// 停止响铃，并将节奏复位到起点
+    pub fn silence(&mut self) {
+        self.silence_outputs();
+        self.step = 0;
+    }
+
+    /// 获取指示灯，用于非响铃时的其他指示（如心跳）
+    pub fn led(&mut self) -> &mut L {
+        &mut self.led
+    }
+
+    fn silence_outputs(&mut self) {
+        self.led.off();
+        if let Some(buzzer) = self.buzzer.as_mut() {
+            buzzer.off();
+        }
+    }
+}
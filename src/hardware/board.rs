@@ -0,0 +1,27 @@
+use embassy_stm32::Config;
+
+/// Blue Pill开发板的时钟配置
+///
+/// 外部8MHz晶振经PLL倍频到72MHz作为系统时钟，USB由PLL 1.5分频得到48MHz。
+/// AHB和APB2为72MHz，APB1最高36MHz，为二分频。
+pub fn clock_config() -> Config {
+    use embassy_stm32::rcc::*;
+    use embassy_stm32::time::Hertz;
+
+    let mut config = Config::default();
+    config.rcc.hse = Some(Hse {
+        freq: Hertz(8_000_000),
+        // 开发板使用外部振荡器
+        mode: HseMode::Oscillator,
+    });
+    config.rcc.pll = Some(Pll {
+        src: PllSource::HSE,     // PLL时钟源选择HSE
+        prediv: PllPreDiv::DIV1, // 预分频系数
+        mul: PllMul::MUL9,       // 倍频系数（8MHz * 9 = 72MHz）
+    });
+    config.rcc.sys = Sysclk::PLL1_P; // 系统时钟源选择PLL输出
+    config.rcc.ahb_pre = AHBPrescaler::DIV1; // AHB预分频（72MHz）
+    config.rcc.apb1_pre = APBPrescaler::DIV2; // APB1预分频（36MHz）
+    config.rcc.apb2_pre = APBPrescaler::DIV1; // APB2预分频（72MHz）
+    config
+}
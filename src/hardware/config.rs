@@ -1 +1,768 @@
+use super::flash_store::{Crc32, KvStore, MAX_VALUE_LEN, StoreError};
+use crate::app::alarm::{Alarm, Repeat, Weekdays};
+use crate::app::calendar::{ALARM_COUNT, BRIGHTNESS_LEVELS, CalendarApp};
+use crate::app::clock::HourFormat;
+use crate::app::imu::accel_calibration::AccelCalibration;
+use crate::app::imu::magnetometer::MagCalibration;
+use crate::app::imu::mounting::Mounting;
+use crate::app::imu::temperature::GyroDriftModel;
+use crate::app::imu::units::AngularRate;
+use crate::app::timezone::{PRESETS, RULE_CAPACITY, TimeZone};
+use chrono::{NaiveDateTime, NaiveTime, Timelike};
+use core::fmt::Write;
+use embedded_storage::nor_flash::NorFlash;
+use nalgebra::{Matrix3, Quaternion, UnitQuaternion, Vector3, Vector4};
 
+/// 设置存储区在闪存中的偏移（相对`0x0800_0000`）
+///
+/// 占用STM32F103C8最后4页（4KiB），须与`memory.x`中保留的`SETTINGS`区域一致。
+pub const SETTINGS_OFFSET: u32 = 60 * 1024;
+
+/// 设置存储区长度
+pub const SETTINGS_SIZE: u32 = 4 * 1024;
+
+/// 万年历设置在键值存储中的键
+pub const SETTINGS_KEY: u16 = 1;
+
+/// IMU校准参数在键值存储中的键
+pub const IMU_CALIBRATION_KEY: u16 = 2;
+
+/// 磁力计校准参数在键值存储中的键
+///
+/// 与IMU校准分开保存：合并后的记录超过单个值的长度上限，且两者分别校准。
+pub const MAG_CALIBRATION_KEY: u16 = 3;
+
+/// 传感器安装方向在键值存储中的键
+pub const MOUNTING_KEY: u16 = 4;
+
+/// 芯片唯一ID（STM32 UID寄存器，96位）
+pub type Uid = [u8; 12];
+
+/// 设置记录的格式版本
+const SETTINGS_VERSION: u8 = 2;
+
+/// 单个闹钟编码后的长度
+const ALARM_LEN: usize = 5;
+
+/// 时区规则在设置记录中的起始位置（闹钟之后）
+const TIMEZONE_RULE_OFFSET: usize = 4 + ALARM_COUNT * ALARM_LEN;
+
+/// 设置编码后的长度：时区规则以长度字节开头，不足部分补零
+const SETTINGS_LEN: usize = TIMEZONE_RULE_OFFSET + 1 + RULE_CAPACITY;
+
+/// 自定义时区（无对应预设）的编码
+const CUSTOM_TIMEZONE: u8 = 0xFF;
+
+/// IMU校准记录的格式版本
+const IMU_CALIBRATION_VERSION: u8 = 1;
+
+/// IMU校准记录中浮点数的个数：加速度计零偏3、修正矩阵9、陀螺仪零偏3、
+/// 零偏校准温度1、温度模型参考温度1、温度模型系数9
+const IMU_CALIBRATION_FLOATS: usize = 26;
+
+/// IMU校准编码后的长度
+const IMU_CALIBRATION_LEN: usize = calibration_len(IMU_CALIBRATION_FLOATS);
+
+/// 磁力计校准记录的格式版本
+const MAG_CALIBRATION_VERSION: u8 = 1;
+
+/// 磁力计校准记录中浮点数的个数：硬铁偏移3、软铁修正矩阵9
+const MAG_CALIBRATION_FLOATS: usize = 12;
+
+/// 磁力计校准编码后的长度
+const MAG_CALIBRATION_LEN: usize = calibration_len(MAG_CALIBRATION_FLOATS);
+
+/// 安装方向记录的格式版本
+const MOUNTING_VERSION: u8 = 1;
+
+/// 安装方向记录中浮点数的个数：安装旋转四元数4
+const MOUNTING_FLOATS: usize = 4;
+
+/// 安装方向编码后的长度
+const MOUNTING_LEN: usize = calibration_len(MOUNTING_FLOATS);
+
+/// 读取时允许的安装旋转四元数模长误差
+const MOUNTING_NORM_TOLERANCE: f32 = 1e-3;
+
+/// 校准记录中浮点数的起始位置（版本和UID之后）
+const CALIBRATION_FLOATS_OFFSET: usize = 1 + 12;
+
+/// 包含`floats`个浮点数的校准记录的长度：版本、UID、浮点数、CRC32
+const fn calibration_len(floats: usize) -> usize {
+    CALIBRATION_FLOATS_OFFSET + floats * 4 + 4
+}
+
+/// 掉电保存的设置
+///
+/// 两个固件启动时从闪存读取；万年历在设置状态结束后将变化写回闪存。
+/// 以版本号开头编码，版本不符或内容无效时使用默认设置。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// 小时显示格式
+    pub hour_format: HourFormat,
+    /// 屏幕亮度档位
+    pub brightness: u8,
+    /// 时区预设序号，`None`表示自定义时区
+    pub timezone_preset: Option<usize>,
+    /// 当前时区，自定义时区按此恢复
+    pub timezone: TimeZone,
+    /// 闹钟配置
+    pub alarms: [Alarm; ALARM_COUNT],
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::from_app(&CalendarApp::new(NaiveDateTime::default()))
+    }
+}
+
+impl Settings {
+    /// 读取应用当前的设置
+    pub fn from_app(app: &CalendarApp) -> Self {
+        Self {
+            hour_format: app.hour_format(),
+            brightness: app.brightness(),
+            timezone_preset: app.timezone_preset(),
+            timezone: *app.timezone(),
+            alarms: core::array::from_fn(|index| *app.alarms().alarm(index)),
+        }
+    }
+
+    /// 将设置应用到应用状态
+    pub fn apply(&self, app: &mut CalendarApp) {
+        app.set_hour_format(self.hour_format);
+        app.set_brightness(self.brightness);
+        // 预设以固件中的规则为准，自定义时区使用保存的规则
+        match self.timezone_preset {
+            Some(index) => {
+                if let Some(preset) = PRESETS.get(index)
+                    && let Ok(timezone) = TimeZone::parse(preset.rule)
+                {
+                    app.set_timezone(timezone, Some(index));
+                }
+            }
+            None => app.set_timezone(self.timezone, None),
+        }
+        for (index, alarm) in self.alarms.iter().enumerate() {
+            app.set_alarm(index, *alarm);
+        }
+    }
+
+    /// 从存储中读取设置
+    ///
+    /// # 返回值
+    /// 没有保存过设置或记录无效时返回`None`
+    pub fn load<F: NorFlash>(store: &mut KvStore<F>) -> Result<Option<Self>, StoreError<F::Error>> {
+        let mut buf = [0; MAX_VALUE_LEN];
+        let Some(len) = store.load(SETTINGS_KEY, &mut buf)? else {
+            return Ok(None);
+        };
+        Ok(Self::decode(&buf[..len]))
+    }
+
+    /// 将设置写入存储，与已保存的相同时不写入闪存
+    pub fn save<F: NorFlash>(&self, store: &mut KvStore<F>) -> Result<(), StoreError<F::Error>> {
+        store.store(SETTINGS_KEY, &self.encode())
+    }
+
+    /// 编码为字节序列
+    ///
+    /// 格式：版本、小时格式、亮度、时区预设，随后每个闹钟依次为
+    /// 时、分、星期掩码、标志（位0启用，位1每周重复）、贪睡分钟数，
+    /// 最后为时区的POSIX TZ规则字符串（长度字节加内容）。
+    pub fn encode(&self) -> [u8; SETTINGS_LEN] {
+        let mut bytes = [0; SETTINGS_LEN];
+        bytes[0] = SETTINGS_VERSION;
+        bytes[1] = match self.hour_format {
+            HourFormat::H24 => 0,
+            HourFormat::H12 => 1,
+        };
+        bytes[2] = self.brightness;
+        bytes[3] = match self.timezone_preset {
+            Some(index) if index < CUSTOM_TIMEZONE as usize => index as u8,
+            _ => CUSTOM_TIMEZONE,
+        };
+
+        for (alarm, chunk) in self
+            .alarms
+            .iter()
+            .zip(bytes[4..].chunks_exact_mut(ALARM_LEN))
+        {
+            let flags = alarm.enabled as u8 | ((alarm.repeat == Repeat::Weekly) as u8) << 1;
+            chunk.copy_from_slice(&[
+                alarm.time.hour() as u8,
+                alarm.time.minute() as u8,
+                alarm.weekdays.bits(),
+                flags,
+                alarm.snooze_minutes,
+            ]);
+        }
+
+        let mut rule = heapless::String::<RULE_CAPACITY>::new();
+        // 格式化结果不超过`RULE_CAPACITY`，不会失败
+        let _ = write!(rule, "{}", self.timezone);
+        bytes[TIMEZONE_RULE_OFFSET] = rule.len() as u8;
+        bytes[TIMEZONE_RULE_OFFSET + 1..][..rule.len()].copy_from_slice(rule.as_bytes());
+        bytes
+    }
+
+    /// 从字节序列解码，版本或长度不符、字段超出范围时返回`None`
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != SETTINGS_LEN || bytes[0] != SETTINGS_VERSION {
+            return None;
+        }
+
+        let hour_format = match bytes[1] {
+            0 => HourFormat::H24,
+            1 => HourFormat::H12,
+            _ => return None,
+        };
+        if bytes[2] >= BRIGHTNESS_LEVELS {
+            return None;
+        }
+        let timezone_preset = match bytes[3] {
+            CUSTOM_TIMEZONE => None,
+            index if (index as usize) < PRESETS.len() => Some(index as usize),
+            _ => return None,
+        };
+
+        let mut alarms = [Alarm::new(NaiveTime::MIN); ALARM_COUNT];
+        let alarm_bytes = &bytes[4..TIMEZONE_RULE_OFFSET];
+        for (alarm, chunk) in alarms.iter_mut().zip(alarm_bytes.chunks_exact(ALARM_LEN)) {
+            let [hour, minute, weekdays, flags, snooze_minutes] = chunk else {
+                return None;
+            };
+            if *weekdays > Weekdays::EVERY_DAY.bits() || *flags > 0b11 {
+                return None;
+            }
+            *alarm = Alarm {
+                time: NaiveTime::from_hms_opt(*hour as u32, *minute as u32, 0)?,
+                weekdays: Weekdays::from_bits(*weekdays),
+                repeat: match flags & 0b10 {
+                    0 => Repeat::Once,
+                    _ => Repeat::Weekly,
+                },
+                snooze_minutes: *snooze_minutes,
+                enabled: flags & 0b01 != 0,
+            };
+        }
+
+        let rule = &bytes[TIMEZONE_RULE_OFFSET + 1..];
+        let rule = rule.get(..bytes[TIMEZONE_RULE_OFFSET] as usize)?;
+        let timezone = TimeZone::parse(core::str::from_utf8(rule).ok()?).ok()?;
+
+        Some(Self {
+            hour_format,
+            brightness: bytes[2],
+            timezone_preset,
+            timezone,
+            alarms,
+        })
+    }
+}
+
+/// 掉电保存的IMU校准参数
+///
+/// 记录中包含芯片唯一ID，只有写入记录的同一块电路板才会读取，
+/// 闪存内容被复制到其他电路板时重新校准。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImuCalibration {
+    /// 加速度计六面校准参数
+    pub accel: AccelCalibration,
+    /// 陀螺仪零偏
+    pub gyro_offset: AngularRate,
+    /// 陀螺仪零偏校准时的芯片温度（℃）
+    pub gyro_offset_temperature: Option<f32>,
+    /// 陀螺仪零偏温度模型
+    pub gyro_drift: Option<GyroDriftModel>,
+}
+
+impl ImuCalibration {
+    /// 从存储中读取本电路板的校准参数
+    ///
+    /// # 返回值
+    /// 没有保存过、记录无效或属于其他电路板时返回`None`
+    pub fn load<F: NorFlash>(
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<Option<Self>, StoreError<F::Error>> {
+        let mut buf = [0; MAX_VALUE_LEN];
+        let Some(len) = store.load(IMU_CALIBRATION_KEY, &mut buf)? else {
+            return Ok(None);
+        };
+        Ok(Self::decode(&buf[..len], uid))
+    }
+
+    /// 将校准参数写入存储，与已保存的相同时不写入闪存
+    pub fn save<F: NorFlash>(
+        &self,
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<(), StoreError<F::Error>> {
+        store.store(IMU_CALIBRATION_KEY, &self.encode(uid))
+    }
+
+    /// 编码为字节序列
+    ///
+    /// 格式：版本、UID，随后为小端f32：加速度计零偏、修正矩阵（按列）、陀螺仪零偏、
+    /// 零偏校准温度、温度模型参考温度和系数（缺少的温度以NaN表示），最后为前面所有字节的CRC32。
+    pub fn encode(&self, uid: &Uid) -> [u8; IMU_CALIBRATION_LEN] {
+        let (reference, coefficients) = match self.gyro_drift {
+            Some(model) => (model.reference, model.coefficients),
+            None => (f32::NAN, [Vector3::zeros(); 3]),
+        };
+        let temperature = self.gyro_offset_temperature.unwrap_or(f32::NAN);
+        let floats = self
+            .accel
+            .bias
+            .iter()
+            .chain(self.accel.transform.iter())
+            .chain(self.gyro_offset.as_rad_per_s().iter())
+            .chain([&temperature, &reference])
+            .chain(coefficients.iter().flat_map(|c| c.iter()));
+
+        seal(IMU_CALIBRATION_VERSION, uid, floats.copied())
+    }
+
+    /// 从字节序列解码
+    ///
+    /// 版本、长度、CRC或UID不符，或数值无效时返回`None`
+    pub fn decode(bytes: &[u8], uid: &Uid) -> Option<Self> {
+        let floats: [f32; IMU_CALIBRATION_FLOATS] = unseal(bytes, IMU_CALIBRATION_VERSION, uid)?;
+        let [temperature, reference] = [floats[15], floats[16]];
+        // 只有两个温度允许为NaN（表示缺少）
+        if floats[..15]
+            .iter()
+            .chain(&floats[17..])
+            .any(|value| !value.is_finite())
+            || temperature.is_infinite()
+            || reference.is_infinite()
+        {
+            return None;
+        }
+
+        Some(Self {
+            accel: AccelCalibration {
+                bias: Vector3::from_column_slice(&floats[0..3]),
+                transform: Matrix3::from_column_slice(&floats[3..12]),
+            },
+            gyro_offset: AngularRate::from_rad_per_s(Vector3::from_column_slice(&floats[12..15])),
+            gyro_offset_temperature: (!temperature.is_nan()).then_some(temperature),
+            gyro_drift: (!reference.is_nan()).then(|| GyroDriftModel {
+                reference,
+                coefficients: [
+                    Vector3::from_column_slice(&floats[17..20]),
+                    Vector3::from_column_slice(&floats[20..23]),
+                    Vector3::from_column_slice(&floats[23..26]),
+                ],
+            }),
+        })
+    }
+}
+
+/// 磁力计校准参数的掉电保存，与[`ImuCalibration`]一样只由写入记录的电路板读取
+impl MagCalibration {
+    /// 从存储中读取本电路板的磁力计校准参数
+    ///
+    /// # 返回值
+    /// 没有保存过、记录无效或属于其他电路板时返回`None`
+    pub fn load<F: NorFlash>(
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<Option<Self>, StoreError<F::Error>> {
+        let mut buf = [0; MAX_VALUE_LEN];
+        let Some(len) = store.load(MAG_CALIBRATION_KEY, &mut buf)? else {
+            return Ok(None);
+        };
+        Ok(Self::decode(&buf[..len], uid))
+    }
+
+    /// 将磁力计校准参数写入存储，与已保存的相同时不写入闪存
+    pub fn save<F: NorFlash>(
+        &self,
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<(), StoreError<F::Error>> {
+        store.store(MAG_CALIBRATION_KEY, &self.encode(uid))
+    }
+
+    /// 编码为字节序列
+    ///
+    /// 格式：版本、UID，随后为小端f32：硬铁偏移、软铁修正矩阵（按列），最后为CRC32。
+    pub fn encode(&self, uid: &Uid) -> [u8; MAG_CALIBRATION_LEN] {
+        let floats = self.offset.iter().chain(self.transform.iter());
+        seal(MAG_CALIBRATION_VERSION, uid, floats.copied())
+    }
+
+    /// 从字节序列解码
+    ///
+    /// 版本、长度、CRC或UID不符，或数值无效时返回`None`
+    pub fn decode(bytes: &[u8], uid: &Uid) -> Option<Self> {
+        let floats: [f32; MAG_CALIBRATION_FLOATS] = unseal(bytes, MAG_CALIBRATION_VERSION, uid)?;
+        if floats.iter().any(|value| !value.is_finite()) {
+            return None;
+        }
+        Some(Self {
+            offset: Vector3::from_column_slice(&floats[0..3]),
+            transform: Matrix3::from_column_slice(&floats[3..12]),
+        })
+    }
+}
+
+/// 传感器安装方向的掉电保存，与[`ImuCalibration`]一样只由写入记录的电路板读取
+impl Mounting {
+    /// 从存储中读取本电路板的安装方向
+    ///
+    /// # 返回值
+    /// 没有保存过、记录无效或属于其他电路板时返回`None`
+    pub fn load<F: NorFlash>(
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<Option<Self>, StoreError<F::Error>> {
+        let mut buf = [0; MAX_VALUE_LEN];
+        let Some(len) = store.load(MOUNTING_KEY, &mut buf)? else {
+            return Ok(None);
+        };
+        Ok(Self::decode(&buf[..len], uid))
+    }
+
+    /// 将安装方向写入存储，与已保存的相同时不写入闪存
+    pub fn save<F: NorFlash>(
+        &self,
+        store: &mut KvStore<F>,
+        uid: &Uid,
+    ) -> Result<(), StoreError<F::Error>> {
+        store.store(MOUNTING_KEY, &self.encode(uid))
+    }
+
+    /// 编码为字节序列
+    ///
+    /// 格式：版本、UID，随后为小端f32：安装旋转四元数（i、j、k、w），最后为CRC32。
+    pub fn encode(&self, uid: &Uid) -> [u8; MOUNTING_LEN] {
+        let floats = self.quaternion().coords.iter();
+        seal(MOUNTING_VERSION, uid, floats.copied())
+    }
+
+    /// 从字节序列解码
+    ///
+    /// 版本、长度、CRC或UID不符，或不是单位四元数时返回`None`
+    pub fn decode(bytes: &[u8], uid: &Uid) -> Option<Self> {
+        let floats: [f32; MOUNTING_FLOATS] = unseal(bytes, MOUNTING_VERSION, uid)?;
+        let quat = Quaternion::from_vector(Vector4::from(floats));
+        if !quat.norm().is_finite() || (quat.norm() - 1.0).abs() > MOUNTING_NORM_TOLERANCE {
+            return None;
+        }
+        Some(Self::from_quaternion(UnitQuaternion::new_unchecked(quat)))
+    }
+}
+
+/// 编码校准记录：版本、UID、小端f32，最后为前面所有字节的CRC32
+///
+/// `N`须为[`calibration_len`]，多余的浮点数被忽略。
+fn seal<const N: usize>(version: u8, uid: &Uid, floats: impl Iterator<Item = f32>) -> [u8; N] {
+    let crc_offset = N - 4;
+    let mut bytes = [0; N];
+    bytes[0] = version;
+    bytes[1..CALIBRATION_FLOATS_OFFSET].copy_from_slice(uid);
+    for (value, chunk) in
+        floats.zip(bytes[CALIBRATION_FLOATS_OFFSET..crc_offset].chunks_exact_mut(4))
+    {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+
+    let crc = Crc32::new().update(&bytes[..crc_offset]).finish();
+    bytes[crc_offset..].copy_from_slice(&crc.to_le_bytes());
+    bytes
+}
+
+/// 解码校准记录中的`F`个浮点数
+///
+/// 长度、版本、UID或CRC不符时返回`None`；数值是否有效由调用方检查。
+fn unseal<const F: usize>(bytes: &[u8], version: u8, uid: &Uid) -> Option<[f32; F]> {
+    let crc_offset = CALIBRATION_FLOATS_OFFSET + F * 4;
+    if bytes.len() != calibration_len(F)
+        || bytes[0] != version
+        || bytes[1..CALIBRATION_FLOATS_OFFSET] != uid[..]
+    {
+        return None;
+    }
+    let crc = Crc32::new().update(&bytes[..crc_offset]).finish();
+    if bytes[crc_offset..] != crc.to_le_bytes() {
+        return None;
+    }
+
+    let mut floats = [0.0; F];
+    for (value, chunk) in floats
+        .iter_mut()
+        .zip(bytes[CALIBRATION_FLOATS_OFFSET..crc_offset].chunks_exact(4))
+    {
+        *value = f32::from_le_bytes(chunk.try_into().ok()?);
+    }
+    Some(floats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::mounting::Axis;
+    use crate::hardware::ram_flash::RamFlash;
+    use chrono::NaiveDate;
+
+    type Flash = RamFlash<4096, 2, 1024>;
+
+    fn settings() -> Settings {
+        let mut settings = Settings {
+            hour_format: HourFormat::H12,
+            brightness: 4,
+            timezone_preset: Some(3),
+            timezone: TimeZone::parse(PRESETS[3].rule).unwrap(),
+            ..Settings::default()
+        };
+        settings.alarms[1] = Alarm {
+            weekdays: Weekdays::WORKDAYS,
+            repeat: Repeat::Once,
+            snooze_minutes: 10,
+            ..Alarm::new(NaiveTime::from_hms_opt(6, 45, 0).unwrap())
+        };
+        settings
+    }
+
+    const UID: Uid = [
+        0x37, 0x00, 0x2C, 0x00, 0x0D, 0x51, 0x33, 0x32, 0x38, 0x33, 0x35, 0x30,
+    ];
+
+    fn imu_calibration() -> ImuCalibration {
+        ImuCalibration {
+            accel: AccelCalibration {
+                bias: Vector3::new(0.06, -0.025, 0.08),
+                transform: Matrix3::new(
+                    1.03, 0.012, -0.008, -0.006, 0.97, 0.015, 0.01, -0.004, 1.05,
+                ),
+            },
+            gyro_offset: AngularRate::from_rad_per_s(Vector3::new(0.11, -0.05, 0.006)),
+            gyro_offset_temperature: Some(27.5),
+            gyro_drift: Some(GyroDriftModel {
+                reference: 26.0,
+                coefficients: [
+                    Vector3::new(0.02, -0.015, 0.005),
+                    Vector3::new(0.0008, 0.0005, -0.0012),
+                    Vector3::zeros(),
+                ],
+            }),
+        }
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        let settings = settings();
+        assert_eq!(Settings::decode(&settings.encode()), Some(settings));
+
+        let default = Settings::default();
+        assert_eq!(Settings::decode(&default.encode()), Some(default));
+    }
+
+    #[test]
+    fn rejects_other_versions_and_invalid_fields() {
+        let bytes = settings().encode();
+        assert_eq!(Settings::decode(&bytes[..SETTINGS_LEN - 1]), None);
+
+        for (index, value) in [
+            (0, 1),
+            (1, 2),
+            (2, BRIGHTNESS_LEVELS),
+            (3, 200),
+            (4, 24),
+            (5, 60),
+        ] {
+            let mut corrupted = bytes;
+            corrupted[index] = value;
+            assert_eq!(Settings::decode(&corrupted), None, "{index}");
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_brightness() {
+        let bytes = settings().encode();
+        for level in BRIGHTNESS_LEVELS..=u8::MAX {
+            let mut corrupted = bytes;
+            corrupted[2] = level;
+            assert_eq!(Settings::decode(&corrupted), None, "{level}");
+        }
+    }
+
+    #[test]
+    fn custom_timezone_round_trips() {
+        let settings = Settings {
+            timezone_preset: None,
+            timezone: TimeZone::parse("<+1030>-10:30<+11>-11,M10.1.0,M4.1.0").unwrap(),
+            ..settings()
+        };
+        assert_eq!(Settings::decode(&settings.encode()), Some(settings));
+
+        let mut store = KvStore::new(Flash::new(), 0, SETTINGS_SIZE).unwrap();
+        settings.save(&mut store).unwrap();
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        let loaded = Settings::load(&mut store).unwrap().unwrap();
+        assert_eq!(loaded, settings);
+
+        let utc = NaiveDate::from_ymd_opt(2025, 1, 10)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let mut app = CalendarApp::new(utc);
+        loaded.apply(&mut app);
+        assert_eq!(app.timezone_preset(), None);
+        assert_eq!(app.timezone(), &settings.timezone);
+        // 豪勋爵岛夏令时UTC+11
+        assert_eq!(app.clock().now(), utc + chrono::Duration::hours(11));
+    }
+
+    #[test]
+    fn rejects_invalid_timezone_rule() {
+        let mut bytes = settings().encode();
+        bytes[TIMEZONE_RULE_OFFSET + 1] = b'?';
+        assert_eq!(Settings::decode(&bytes), None);
+
+        let mut bytes = settings().encode();
+        bytes[TIMEZONE_RULE_OFFSET] = RULE_CAPACITY as u8 + 1;
+        assert_eq!(Settings::decode(&bytes), None);
+    }
+
+    #[test]
+    fn settings_survive_remount() {
+        let mut store = KvStore::new(Flash::new(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(Settings::load(&mut store), Ok(None));
+
+        settings().save(&mut store).unwrap();
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(Settings::load(&mut store), Ok(Some(settings())));
+    }
+
+    #[test]
+    fn imu_calibration_round_trips() {
+        let calibration = imu_calibration();
+        assert_eq!(
+            ImuCalibration::decode(&calibration.encode(&UID), &UID),
+            Some(calibration)
+        );
+
+        // 没有零偏校准温度和温度模型
+        let default = ImuCalibration::default();
+        assert_eq!(
+            ImuCalibration::decode(&default.encode(&UID), &UID),
+            Some(default)
+        );
+    }
+
+    #[test]
+    fn imu_calibration_rejects_other_boards_and_corruption() {
+        let bytes = imu_calibration().encode(&UID);
+        assert_eq!(
+            ImuCalibration::decode(&bytes[..bytes.len() - 1], &UID),
+            None
+        );
+
+        let mut other = UID;
+        other[11] ^= 1;
+        assert_eq!(ImuCalibration::decode(&bytes, &other), None);
+
+        for index in [0, 20, 70, bytes.len() - 1] {
+            let mut corrupted = bytes;
+            corrupted[index] ^= 0x10;
+            assert_eq!(ImuCalibration::decode(&corrupted, &UID), None, "{index}");
+        }
+    }
+
+    #[test]
+    fn mag_calibration_round_trips() {
+        let calibration = MagCalibration {
+            offset: Vector3::new(0.12, -0.08, 0.2),
+            transform: Matrix3::new(1.1, 0.05, -0.03, 0.05, 0.9, 0.04, -0.03, 0.04, 1.02),
+        };
+        let bytes = calibration.encode(&UID);
+        assert!(bytes.len() <= MAX_VALUE_LEN);
+        assert_eq!(MagCalibration::decode(&bytes, &UID), Some(calibration));
+        assert_eq!(MagCalibration::decode(&bytes, &[0; 12]), None);
+
+        // 与IMU校准记录长度、版本不同，不会互相误读
+        assert_eq!(
+            MagCalibration::decode(&imu_calibration().encode(&UID), &UID),
+            None
+        );
+        let mut corrupted = bytes;
+        corrupted[30] ^= 0x01;
+        assert_eq!(MagCalibration::decode(&corrupted, &UID), None);
+
+        let mut store = KvStore::new(Flash::new(), 0, SETTINGS_SIZE).unwrap();
+        imu_calibration().save(&mut store, &UID).unwrap();
+        assert_eq!(MagCalibration::load(&mut store, &UID), Ok(None));
+        calibration.save(&mut store, &UID).unwrap();
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(
+            MagCalibration::load(&mut store, &UID),
+            Ok(Some(calibration))
+        );
+        assert_eq!(
+            ImuCalibration::load(&mut store, &UID),
+            Ok(Some(imu_calibration()))
+        );
+    }
+
+    #[test]
+    fn mounting_round_trips() {
+        let mountings = Mounting::presets()
+            .into_iter()
+            .chain([Mounting::from_quaternion(
+                UnitQuaternion::from_euler_angles(0.4, -1.1, 2.3),
+            )]);
+        for mounting in mountings {
+            assert_eq!(
+                Mounting::decode(&mounting.encode(&UID), &UID),
+                Some(mounting)
+            );
+        }
+
+        // 不是单位四元数
+        let zero = seal::<MOUNTING_LEN>(MOUNTING_VERSION, &UID, [0.0; 4].into_iter());
+        assert_eq!(Mounting::decode(&zero, &UID), None);
+
+        let mounting = Mounting::from_axes(Axis::NegY, Axis::PosX).unwrap();
+        let mut store = KvStore::new(Flash::new(), 0, SETTINGS_SIZE).unwrap();
+        imu_calibration().save(&mut store, &UID).unwrap();
+        assert_eq!(Mounting::load(&mut store, &UID), Ok(None));
+        mounting.save(&mut store, &UID).unwrap();
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(Mounting::load(&mut store, &UID), Ok(Some(mounting)));
+        assert_eq!(Mounting::load(&mut store, &[0; 12]), Ok(None));
+    }
+
+    #[test]
+    fn imu_calibration_survives_remount() {
+        let mut store = KvStore::new(Flash::new(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(ImuCalibration::load(&mut store, &UID), Ok(None));
+
+        settings().save(&mut store).unwrap();
+        imu_calibration().save(&mut store, &UID).unwrap();
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        assert_eq!(
+            ImuCalibration::load(&mut store, &UID),
+            Ok(Some(imu_calibration()))
+        );
+        assert_eq!(Settings::load(&mut store), Ok(Some(settings())));
+    }
+
+    #[test]
+    fn apply_restores_app_state() {
+        let utc = NaiveDate::from_ymd_opt(2025, 7, 20)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        let mut app = CalendarApp::new(utc);
+        settings().apply(&mut app);
+
+        assert_eq!(Settings::from_app(&app), settings());
+        assert_eq!(app.timezone_preset(), Some(3));
+        // 柏林夏令时UTC+2
+        assert_eq!(app.clock().now(), utc + chrono::Duration::hours(2));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::app::calendar::BRIGHTNESS_LEVELS;
+use ssd1306::prelude::Brightness;
+
+/// 各亮度档位对应的SSD1306屏幕亮度，三个固件共用
+pub const BRIGHTNESS: [Brightness; BRIGHTNESS_LEVELS as usize] = [
+    Brightness::DIMMEST,
+    Brightness::DIM,
+    Brightness::NORMAL,
+    Brightness::BRIGHT,
+    Brightness::BRIGHTEST,
+];
+
+/// 亮度档位`level`对应的屏幕亮度，超出范围时取最亮一档
+///
+/// 与`CalendarApp::set_brightness`一致，设置记录损坏时也不会越界。
+pub fn brightness_for(level: u8) -> Brightness {
+    BRIGHTNESS[level.min(BRIGHTNESS_LEVELS - 1) as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_out_of_range_levels() {
+        assert_eq!(brightness_for(0), Brightness::DIMMEST);
+        assert_eq!(brightness_for(BRIGHTNESS_LEVELS - 1), Brightness::BRIGHTEST);
+        assert_eq!(brightness_for(BRIGHTNESS_LEVELS), Brightness::BRIGHTEST);
+        assert_eq!(brightness_for(u8::MAX), Brightness::BRIGHTEST);
+    }
+}
@@ -0,0 +1,588 @@
+//! 闪存键值存储
+//!
+//! 在若干擦除页组成的闪存区域中以追加日志的方式保存键值记录，适用于设置、
+//! 校准参数等写入不频繁的小块数据。
+//!
+//! 区域平分为两个存储体（bank），任一时刻只有一个存储体处于活动状态：
+//! ```text
+//! 存储体: [头部: 魔数 | 代数 | CRC][记录][记录]...[擦除状态0xFF]
+//! 记录:   [键 u16][值长度 u16][CRC32][值][填充0xFF]
+//! ```
+//! - 修改某个键时只在活动存储体末尾追加新记录，读取时以最后一条有效记录为准，
+//!   值长度为0的记录表示删除
+//! - 活动存储体写满后进行整理：擦除另一个存储体，复制每个键的最新记录，
+//!   最后写入代数加一的头部使其生效。两个存储体轮流擦除，实现磨损均衡
+//! - 掉电保护：记录和头部均带CRC，写到一半的记录会被跳过；整理过程中掉电时
+//!   新存储体尚无有效头部，重启后仍使用原存储体
+
+use embedded_storage::nor_flash::NorFlash;
+
+/// 值的最大长度（字节）
+pub const MAX_VALUE_LEN: usize = 128;
+
+/// 支持的最大写入粒度（字节）
+pub const MAX_WRITE_SIZE: usize = 16;
+
+/// 存储体头部魔数，低16位为格式版本
+const MAGIC: u32 = 0x4B56_0001;
+
+/// 存储体头部长度（未对齐）
+const BANK_HEADER_LEN: usize = 12;
+
+/// 记录头部长度
+const RECORD_HEADER_LEN: usize = 8;
+
+/// 擦除状态的键，不能作为有效键使用
+const ERASED_KEY: u16 = 0xFFFF;
+
+/// 记录缓冲区长度，可容纳最长的记录及填充
+const RECORD_BUF_LEN: usize = RECORD_HEADER_LEN + MAX_VALUE_LEN + MAX_WRITE_SIZE;
+
+/// 存储错误
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StoreError<E> {
+    /// 底层闪存读写错误
+    Flash(E),
+    /// 区域未按擦除页对齐、不足两页，或闪存读写粒度不受支持
+    InvalidRegion,
+    /// 键为保留值`0xFFFF`
+    InvalidKey,
+    /// 值超过[`MAX_VALUE_LEN`]
+    ValueTooLarge,
+    /// 读取缓冲区小于存储的值
+    BufferTooSmall,
+    /// 整理后仍没有足够空间
+    Full,
+}
+
+impl<E> From<E> for StoreError<E> {
+    fn from(error: E) -> Self {
+        StoreError::Flash(error)
+    }
+}
+
+/// CRC-32（IEEE 802.3，与zlib相同）增量计算
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32(u32);
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crc32 {
+    /// 开始新的计算
+    pub const fn new() -> Self {
+        Self(0xFFFF_FFFF)
+    }
+
+    /// 追加数据
+    pub fn update(mut self, data: &[u8]) -> Self {
+        for &byte in data {
+            self.0 ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+        self
+    }
+
+    /// 计算结果
+    pub fn finish(self) -> u32 {
+        !self.0
+    }
+}
+
+/// 存储体中某个位置的内容
+enum Slot {
+    /// 擦除状态，日志结束
+    End,
+    /// 有效记录
+    Valid { key: u16, len: usize, next: u32 },
+    /// 校验失败的记录（如写入时掉电），可跳过
+    Corrupt { next: u32 },
+    /// 记录头部损坏，无法确定后续记录的位置
+    Broken,
+}
+
+/// 闪存键值存储
+///
+/// # 泛型参数
+/// - `F`: 实现[`NorFlash`]的闪存，要求`READ_SIZE`为1、`WRITE_SIZE`不超过
+///   [`MAX_WRITE_SIZE`]
+pub struct KvStore<F> {
+    flash: F,
+    /// 区域起始偏移
+    start: u32,
+    /// 单个存储体的长度
+    bank_size: u32,
+    /// 活动存储体序号（0或1）
+    active: u32,
+    /// 活动存储体的代数，每次整理加一
+    generation: u32,
+    /// 活动存储体中第一个空闲位置（相对存储体起始）
+    free: u32,
+}
+
+impl<F: NorFlash> KvStore<F> {
+    /// 挂载存储区域，区域中没有有效数据时自动格式化
+    ///
+    /// # 参数
+    /// - `flash`: 闪存
+    /// - `start`: 区域起始偏移，须按擦除页对齐
+    /// - `size`: 区域长度，须为擦除页长度的偶数倍
+    pub fn new(flash: F, start: u32, size: u32) -> Result<Self, StoreError<F::Error>> {
+        let erase_size = F::ERASE_SIZE as u32;
+        let valid = F::READ_SIZE == 1
+            && F::WRITE_SIZE <= MAX_WRITE_SIZE
+            && start % erase_size == 0
+            && size % (2 * erase_size) == 0
+            && size > 0
+            && (start + size) as usize <= flash.capacity();
+        if !valid {
+            return Err(StoreError::InvalidRegion);
+        }
+
+        let mut store = Self {
+            flash,
+            start,
+            bank_size: size / 2,
+            active: 0,
+            generation: 0,
+            free: 0,
+        };
+
+        match (store.read_header(0)?, store.read_header(1)?) {
+            (Some(a), Some(b)) if b > a => (store.active, store.generation) = (1, b),
+            (Some(a), _) => (store.active, store.generation) = (0, a),
+            (None, Some(b)) => (store.active, store.generation) = (1, b),
+            (None, None) => store.format(0, 1)?,
+        }
+        store.free = store.scan_free()?;
+
+        Ok(store)
+    }
+
+    /// 释放存储，取回闪存
+    pub fn release(self) -> F {
+        self.flash
+    }
+
+    /// 读取键对应的值
+    ///
+    /// # 返回值
+    /// 值的长度；键不存在或已删除时返回`None`
+    pub fn load(
+        &mut self,
+        key: u16,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>, StoreError<F::Error>> {
+        let Some((offset, len)) = self.find(key)? else {
+            return Ok(None);
+        };
+        if len > buf.len() {
+            return Err(StoreError::BufferTooSmall);
+        }
+
+        let address = self.bank_start(self.active) + offset + RECORD_HEADER_LEN as u32;
+        self.flash.read(address, &mut buf[..len])?;
+        Ok(Some(len))
+    }
+
+    /// 写入键值，值与当前存储的相同时不写入闪存
+    ///
+    /// 空值等同于删除。
+    pub fn store(&mut self, key: u16, value: &[u8]) -> Result<(), StoreError<F::Error>> {
+        if key == ERASED_KEY {
+            return Err(StoreError::InvalidKey);
+        }
+        if value.len() > MAX_VALUE_LEN {
+            return Err(StoreError::ValueTooLarge);
+        }
+
+        let mut current = [0; MAX_VALUE_LEN];
+        match self.load(key, &mut current)? {
+            Some(len) if current[..len] == *value => return Ok(()),
+            None if value.is_empty() => return Ok(()),
+            _ => {}
+        }
+
+        let size = self.record_size(value.len());
+        if self.free + size > self.bank_size {
+            self.compact()?;
+            if self.free + size > self.bank_size {
+                return Err(StoreError::Full);
+            }
+        }
+
+        let mut buf = [0xFF; RECORD_BUF_LEN];
+        buf[0..2].copy_from_slice(&key.to_le_bytes());
+        buf[2..4].copy_from_slice(&(value.len() as u16).to_le_bytes());
+        let crc = Crc32::new().update(&buf[0..4]).update(value).finish();
+        buf[4..8].copy_from_slice(&crc.to_le_bytes());
+        buf[RECORD_HEADER_LEN..RECORD_HEADER_LEN + value.len()].copy_from_slice(value);
+
+        let address = self.bank_start(self.active) + self.free;
+        // 先前进写入位置：写入失败时该位置可能已被部分编程，不能再次使用
+        self.free += size;
+        self.flash.write(address, &buf[..size as usize])?;
+        Ok(())
+    }
+
+    /// 删除键
+    pub fn remove(&mut self, key: u16) -> Result<(), StoreError<F::Error>> {
+        self.store(key, &[])
+    }
+
+    /// 活动存储体剩余的空闲字节数
+    pub fn free_space(&self) -> u32 {
+        self.bank_size - self.free
+    }
+
+    /// 整理：将每个键的最新记录复制到另一个存储体并切换过去
+    pub fn compact(&mut self) -> Result<(), StoreError<F::Error>> {
+        let source = self.active;
+        let target = 1 - source;
+        let target_start = self.bank_start(target);
+        self.flash
+            .erase(target_start, target_start + self.bank_size)?;
+
+        let mut buf = [0; RECORD_BUF_LEN];
+        let mut offset = self.data_start();
+        let mut dst = self.data_start();
+        loop {
+            let next = match self.slot(source, offset)? {
+                Slot::Valid { key, len, next } => {
+                    if len > 0 && !self.superseded(source, key, next)? {
+                        let size = (next - offset) as usize;
+                        let address = self.bank_start(source) + offset;
+                        self.flash.read(address, &mut buf[..size])?;
+                        self.flash.write(target_start + dst, &buf[..size])?;
+                        dst += size as u32;
+                    }
+                    next
+                }
+                Slot::Corrupt { next } => next,
+                Slot::End | Slot::Broken => break,
+            };
+            offset = next;
+        }
+
+        // 头部最后写入，此前掉电时原存储体仍然有效
+        self.write_header(target, self.generation + 1)?;
+        self.active = target;
+        self.generation += 1;
+        self.free = dst;
+        Ok(())
+    }
+
+    /// 擦除存储体并写入头部，使其成为活动存储体
+    fn format(&mut self, bank: u32, generation: u32) -> Result<(), StoreError<F::Error>> {
+        let start = self.bank_start(bank);
+        self.flash.erase(start, start + self.bank_size)?;
+        self.write_header(bank, generation)?;
+        self.active = bank;
+        self.generation = generation;
+        Ok(())
+    }
+
+    fn bank_start(&self, bank: u32) -> u32 {
+        self.start + bank * self.bank_size
+    }
+
+    /// 第一条记录的位置（相对存储体起始）
+    fn data_start(&self) -> u32 {
+        BANK_HEADER_LEN.next_multiple_of(F::WRITE_SIZE) as u32
+    }
+
+    /// 记录按写入粒度对齐后的长度
+    fn record_size(&self, len: usize) -> u32 {
+        (RECORD_HEADER_LEN + len).next_multiple_of(F::WRITE_SIZE) as u32
+    }
+
+    /// 读取存储体头部，返回有效头部的代数
+    fn read_header(&mut self, bank: u32) -> Result<Option<u32>, StoreError<F::Error>> {
+        let mut header = [0; BANK_HEADER_LEN];
+        self.flash.read(self.bank_start(bank), &mut header)?;
+
+        let word =
+            |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+        let valid = word(0) == MAGIC && word(8) == Crc32::new().update(&header[..8]).finish();
+        Ok(valid.then(|| word(4)))
+    }
+
+    fn write_header(&mut self, bank: u32, generation: u32) -> Result<(), StoreError<F::Error>> {
+        let mut header = [0xFF; BANK_HEADER_LEN + MAX_WRITE_SIZE];
+        header[0..4].copy_from_slice(&MAGIC.to_le_bytes());
+        header[4..8].copy_from_slice(&generation.to_le_bytes());
+        let crc = Crc32::new().update(&header[..8]).finish();
+        header[8..12].copy_from_slice(&crc.to_le_bytes());
+
+        let len = self.data_start() as usize;
+        self.flash.write(self.bank_start(bank), &header[..len])?;
+        Ok(())
+    }
+
+    /// 解析存储体中指定位置的记录
+    fn slot(&mut self, bank: u32, offset: u32) -> Result<Slot, StoreError<F::Error>> {
+        if offset + RECORD_HEADER_LEN as u32 > self.bank_size {
+            return Ok(Slot::End);
+        }
+
+        let address = self.bank_start(bank) + offset;
+        let mut buf = [0; RECORD_HEADER_LEN + MAX_VALUE_LEN];
+        self.flash.read(address, &mut buf[..RECORD_HEADER_LEN])?;
+        if buf[..RECORD_HEADER_LEN].iter().all(|&byte| byte == 0xFF) {
+            return Ok(Slot::End);
+        }
+
+        let key = u16::from_le_bytes([buf[0], buf[1]]);
+        let len = u16::from_le_bytes([buf[2], buf[3]]) as usize;
+        let next = offset + self.record_size(len);
+        if key == ERASED_KEY || len > MAX_VALUE_LEN || next > self.bank_size {
+            return Ok(Slot::Broken);
+        }
+
+        let crc = u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]);
+        let (header, value) = buf.split_at_mut(RECORD_HEADER_LEN);
+        self.flash
+            .read(address + RECORD_HEADER_LEN as u32, &mut value[..len])?;
+        if Crc32::new()
+            .update(&header[0..4])
+            .update(&value[..len])
+            .finish()
+            != crc
+        {
+            return Ok(Slot::Corrupt { next });
+        }
+
+        Ok(Slot::Valid { key, len, next })
+    }
+
+    /// 找到空闲位置；记录头部损坏时视为已写满，下次写入时整理
+    fn scan_free(&mut self) -> Result<u32, StoreError<F::Error>> {
+        let mut offset = self.data_start();
+        loop {
+            offset = match self.slot(self.active, offset)? {
+                Slot::Valid { next, .. } | Slot::Corrupt { next } => next,
+                Slot::End => return Ok(offset),
+                Slot::Broken => return Ok(self.bank_size),
+            };
+        }
+    }
+
+    /// 在活动存储体中查找键的最新记录
+    ///
+    /// # 返回值
+    /// 记录位置和值长度；键不存在或最新记录为删除标记时返回`None`
+    fn find(&mut self, key: u16) -> Result<Option<(u32, usize)>, StoreError<F::Error>> {
+        let mut found = None;
+        let mut offset = self.data_start();
+        loop {
+            offset = match self.slot(self.active, offset)? {
+                Slot::Valid { key: k, len, next } => {
+                    if k == key {
+                        found = Some((offset, len));
+                    }
+                    next
+                }
+                Slot::Corrupt { next } => next,
+                Slot::End | Slot::Broken => break,
+            };
+        }
+        Ok(found.filter(|&(_, len)| len > 0))
+    }
+
+    /// 从`offset`开始是否还有同一个键的有效记录
+    fn superseded(
+        &mut self,
+        bank: u32,
+        key: u16,
+        mut offset: u32,
+    ) -> Result<bool, StoreError<F::Error>> {
+        loop {
+            offset = match self.slot(bank, offset)? {
+                Slot::Valid { key: k, .. } if k == key => return Ok(true),
+                Slot::Valid { next, .. } | Slot::Corrupt { next } => next,
+                Slot::End | Slot::Broken => return Ok(false),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::ram_flash::RamFlash;
+
+    /// 4页×256字节，每个存储体2页
+    type Flash = RamFlash<1024, 2, 256>;
+
+    fn mount(flash: Flash) -> KvStore<Flash> {
+        KvStore::new(flash, 0, 1024).unwrap()
+    }
+
+    fn load(store: &mut KvStore<Flash>, key: u16) -> Option<heapless::Vec<u8, MAX_VALUE_LEN>> {
+        let mut buf = [0; MAX_VALUE_LEN];
+        let len = store.load(key, &mut buf).unwrap()?;
+        Some(heapless::Vec::from_slice(&buf[..len]).unwrap())
+    }
+
+    #[test]
+    fn crc32_matches_reference_value() {
+        assert_eq!(Crc32::new().update(b"123456789").finish(), 0xCBF4_3926);
+        assert_eq!(
+            Crc32::new().update(b"1234").update(b"56789").finish(),
+            0xCBF4_3926
+        );
+    }
+
+    #[test]
+    fn stores_and_reloads_values() {
+        let mut store = mount(Flash::new());
+        assert_eq!(load(&mut store, 1), None);
+
+        store.store(1, b"hello").unwrap();
+        store.store(2, b"world").unwrap();
+        store.store(1, b"hi").unwrap();
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&b"hi"[..]));
+
+        // 重新挂载后数据仍在
+        let mut store = mount(store.release());
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&b"hi"[..]));
+        assert_eq!(load(&mut store, 2).as_deref(), Some(&b"world"[..]));
+
+        store.remove(2).unwrap();
+        let mut store = mount(store.release());
+        assert_eq!(load(&mut store, 2), None);
+    }
+
+    #[test]
+    fn rejects_invalid_arguments() {
+        assert!(matches!(
+            KvStore::new(Flash::new(), 128, 512),
+            Err(StoreError::InvalidRegion)
+        ));
+        assert!(matches!(
+            KvStore::new(Flash::new(), 0, 256),
+            Err(StoreError::InvalidRegion)
+        ));
+
+        let mut store = mount(Flash::new());
+        assert_eq!(store.store(ERASED_KEY, b"x"), Err(StoreError::InvalidKey));
+        assert_eq!(
+            store.store(1, &[0; MAX_VALUE_LEN + 1]),
+            Err(StoreError::ValueTooLarge)
+        );
+
+        store.store(1, b"hello").unwrap();
+        let mut buf = [0; 4];
+        assert_eq!(store.load(1, &mut buf), Err(StoreError::BufferTooSmall));
+    }
+
+    #[test]
+    fn unchanged_values_are_not_rewritten() {
+        let mut store = mount(Flash::new());
+        store.store(1, b"value").unwrap();
+        let free = store.free_space();
+        store.store(1, b"value").unwrap();
+        store.remove(3).unwrap();
+        assert_eq!(store.free_space(), free);
+    }
+
+    #[test]
+    fn compaction_keeps_latest_values_and_levels_wear() {
+        let mut store = mount(Flash::new());
+        store.store(7, b"constant").unwrap();
+        for i in 0..200u8 {
+            store.store(1, &[i; 20]).unwrap();
+            store.store(2, &[i]).unwrap();
+        }
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&[199; 20][..]));
+        assert_eq!(load(&mut store, 2).as_deref(), Some(&[199][..]));
+        assert_eq!(load(&mut store, 7).as_deref(), Some(&b"constant"[..]));
+
+        let flash = store.release();
+        let erases: [u32; 4] = core::array::from_fn(|page| flash.erase_count(page));
+        assert!(erases.iter().all(|&count| count > 5), "{erases:?}");
+        assert!(erases[0].abs_diff(erases[2]) <= 1, "{erases:?}");
+
+        let mut store = mount(flash);
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&[199; 20][..]));
+    }
+
+    #[test]
+    fn reports_full_when_live_data_does_not_fit() {
+        let mut store = mount(Flash::new());
+        for key in 0..3 {
+            store.store(key, &[key as u8; MAX_VALUE_LEN]).unwrap();
+        }
+        assert_eq!(store.store(3, &[0; MAX_VALUE_LEN]), Err(StoreError::Full));
+        for key in 0..3 {
+            assert_eq!(
+                load(&mut store, key).as_deref(),
+                Some(&[key as u8; MAX_VALUE_LEN][..])
+            );
+        }
+    }
+
+    #[test]
+    fn torn_record_write_keeps_previous_value() {
+        // 预算不足以写完值时记录无效
+        for budget in 0..RECORD_HEADER_LEN + b"newer".len() {
+            let mut store = mount(Flash::new());
+            store.store(1, b"old").unwrap();
+
+            let mut flash = store.release();
+            flash.fail_after(budget);
+            let mut store = mount(flash);
+            assert!(store.store(1, b"newer").is_err());
+
+            let mut flash = store.release();
+            flash.fail_after(usize::MAX);
+            let mut store = mount(flash);
+            assert_eq!(
+                load(&mut store, 1).as_deref(),
+                Some(&b"old"[..]),
+                "{budget}"
+            );
+
+            // 掉电后仍可继续写入
+            store.store(1, b"newest").unwrap();
+            let mut store = mount(store.release());
+            assert_eq!(
+                load(&mut store, 1).as_deref(),
+                Some(&b"newest"[..]),
+                "{budget}"
+            );
+        }
+    }
+
+    #[test]
+    fn interrupted_compaction_keeps_old_bank() {
+        let mut store = mount(Flash::new());
+        for i in 0..10u8 {
+            store.store(1, &[i; 40]).unwrap();
+        }
+        store.store(2, b"other").unwrap();
+
+        // 整理时复制的数据未写完即掉电
+        let mut flash = store.release();
+        flash.fail_after(30);
+        let mut store = mount(flash);
+        assert!(store.compact().is_err());
+
+        let mut flash = store.release();
+        flash.fail_after(usize::MAX);
+        let mut store = mount(flash);
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&[9; 40][..]));
+        assert_eq!(load(&mut store, 2).as_deref(), Some(&b"other"[..]));
+
+        store.compact().unwrap();
+        let mut store = mount(store.release());
+        assert_eq!(load(&mut store, 1).as_deref(), Some(&[9; 40][..]));
+        assert_eq!(load(&mut store, 2).as_deref(), Some(&b"other"[..]));
+    }
+}
@@ -0,0 +1,262 @@
+//! MPU6050所在的I2C2总线和传感器任务共用的处理过程
+//!
+//! 姿态解算固件和计步器固件都把MPU6050接在I2C2（PB10: SCL, PB11: SDA）上，
+//! 用[`ImuBus`]支持总线恢复，出错时由[`sensor_fault`]按重试策略重试、恢复总线并重新初始化，
+//! 需要时用[`gyro_calibration`]校准陀螺仪零偏。显示内容由各固件通过[`SensorDisplay`]提供。
+
+use super::imu_error::ImuError;
+use super::mpu6050_async_solver::Mpu6050AsyncSolver;
+use super::traits::BusRecovery;
+use crate::app::imu::health::{Action, ErrorCounters, SensorHealth, SensorState};
+use embassy_stm32::gpio::{Level, OutputOpenDrain, Speed};
+use embassy_stm32::i2c::{self, ErrorInterruptHandler, EventInterruptHandler};
+use embassy_stm32::interrupt::typelevel::{Binding, I2C2_ER, I2C2_EV};
+use embassy_stm32::peripherals;
+use embassy_stm32::time::Hertz;
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Sender;
+use embassy_time::{Duration, Timer};
+
+/// I2C2的时钟频率
+const BUS_FREQUENCY: Hertz = Hertz::khz(400);
+
+/// 总线恢复时SCL时钟的半周期
+const BUS_RECOVERY_HALF_PERIOD: Duration = Duration::from_micros(10);
+
+/// 传感器丢失后每次尝试恢复的间隔
+const SENSOR_LOST_RETRY: Duration = Duration::from_secs(2);
+
+/// 陀螺仪零偏校准超时时间（正常约1秒）
+const GYRO_CALIBRATION_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 接在[`ImuBus`]上的异步解算器
+pub type Imu<IRQ> = Mpu6050AsyncSolver<ImuBus<IRQ>>;
+
+/// I2C2的中断绑定，由固件的`bind_interrupts!`生成
+pub trait ImuBusIrqs:
+    Binding<I2C2_EV, EventInterruptHandler<peripherals::I2C2>>
+    + Binding<I2C2_ER, ErrorInterruptHandler<peripherals::I2C2>>
+    + Copy
+    + 'static
+{
+}
+
+impl<T> ImuBusIrqs for T where
+    T: Binding<I2C2_EV, EventInterruptHandler<peripherals::I2C2>>
+        + Binding<I2C2_ER, ErrorInterruptHandler<peripherals::I2C2>>
+        + Copy
+        + 'static
+{
+}
+
+/// MPU6050所在的I2C2总线（异步模式，DMA1通道4/5，400kHz）
+///
+/// 传感器在传输中途复位时可能一直拉低SDA。恢复时释放I2C2外设，将PB10/PB11作为开漏输出，
+/// 在SCL上输出时钟直到传感器释放SDA并发送STOP，然后重新创建I2C2实例。
+pub struct ImuBus<IRQ> {
+    /// I2C2实例，只在恢复过程中为空
+    i2c: Option<i2c::I2c<'static, embassy_stm32::mode::Async>>,
+    /// 重新创建实例时使用的中断绑定
+    irqs: IRQ,
+}
+
+impl<IRQ: ImuBusIrqs> ImuBus<IRQ> {
+    /// 创建总线，取得I2C2及其引脚和DMA通道的所有权
+    ///
+    /// # 参数
+    /// - `i2c`: I2C2外设
+    /// - `scl`: PB10
+    /// - `sda`: PB11
+    /// - `irqs`: I2C2的中断绑定
+    /// - `tx_dma`: DMA1通道4
+    /// - `rx_dma`: DMA1通道5
+    pub fn new(
+        i2c: peripherals::I2C2,
+        scl: peripherals::PB10,
+        sda: peripherals::PB11,
+        irqs: IRQ,
+        tx_dma: peripherals::DMA1_CH4,
+        rx_dma: peripherals::DMA1_CH5,
+    ) -> Self {
+        let i2c = i2c::I2c::new(
+            i2c,
+            scl,
+            sda,
+            irqs,
+            tx_dma,
+            rx_dma,
+            BUS_FREQUENCY,
+            Default::default(),
+        );
+        Self {
+            i2c: Some(i2c),
+            irqs,
+        }
+    }
+}
+
+impl<IRQ> embedded_hal::i2c::ErrorType for ImuBus<IRQ> {
+    type Error = i2c::Error;
+}
+
+impl<IRQ> embedded_hal_async::i2c::I2c for ImuBus<IRQ> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [embedded_hal::i2c::Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match &mut self.i2c {
+            Some(i2c) => embedded_hal_async::i2c::I2c::transaction(i2c, address, operations).await,
+            None => Err(i2c::Error::Bus),
+        }
+    }
+}
+
+impl<IRQ: ImuBusIrqs> BusRecovery for ImuBus<IRQ> {
+    fn recover_bus(&mut self) {
+        // 释放I2C2外设，引脚交还GPIO
+        self.i2c = None;
+
+        // SAFETY: 原I2C2实例已释放，这些外设在创建时交给了ImuBus，只由它使用
+        let (scl, sda) = unsafe { (peripherals::PB10::steal(), peripherals::PB11::steal()) };
+        {
+            let mut scl = OutputOpenDrain::new(scl, Level::High, Speed::Low);
+            let mut sda = OutputOpenDrain::new(sda, Level::High, Speed::Low);
+
+            // 传感器每个时钟移出一位，最多9个时钟后释放SDA
+            for _ in 0..9 {
+                if sda.is_high() {
+                    break;
+                }
+                scl.set_low();
+                embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+                scl.set_high();
+                embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+            }
+
+            // STOP：SCL为高电平时SDA由低变高
+            scl.set_low();
+            embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+            sda.set_low();
+            embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+            scl.set_high();
+            embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+            sda.set_high();
+            embassy_time::block_for(BUS_RECOVERY_HALF_PERIOD);
+        }
+
+        // 重新创建I2C2实例（复位I2C2外设，清除卡住的BUSY状态）
+        // SAFETY: 同上，GPIO实例已在上面的作用域结束时释放
+        self.i2c = Some(unsafe {
+            i2c::I2c::new(
+                peripherals::I2C2::steal(),
+                peripherals::PB10::steal(),
+                peripherals::PB11::steal(),
+                self.irqs,
+                peripherals::DMA1_CH4::steal(),
+                peripherals::DMA1_CH5::steal(),
+                BUS_FREQUENCY,
+                Default::default(),
+            )
+        });
+    }
+}
+
+/// 传感器任务发给显示任务的内容
+pub trait SensorDisplay {
+    /// 陀螺仪零偏校准提示，`hint`为操作提示或结果
+    fn gyro_calibration(hint: &'static str) -> Self;
+
+    /// 传感器故障：恢复中或已丢失，以及累计的错误计数
+    fn sensor_fault(state: SensorState, counters: ErrorCounters) -> Self;
+}
+
+/// 传感器出错时的处理过程
+///
+/// 按重试策略：重试前清空FIFO中可能错位的采样；连续失败后恢复总线、重新初始化传感器
+/// 并使能FIFO，屏幕显示恢复中和错误计数；多次恢复失败后显示传感器丢失，
+/// 每隔[`SENSOR_LOST_RETRY`]再尝试恢复。校准参数、姿态和采样率不受影响。
+///
+/// # 参数
+/// - `imu`: 解算器
+/// - `health`: 传感器错误跟踪
+/// - `error`: 本次的错误
+/// - `sender`: 显示数据发送通道
+pub async fn sensor_fault<IRQ: ImuBusIrqs, M: RawMutex, T: SensorDisplay, const N: usize>(
+    imu: &mut Imu<IRQ>,
+    health: &mut SensorHealth,
+    error: ImuError<i2c::Error>,
+    sender: &Sender<'_, M, T, N>,
+) {
+    match error {
+        ImuError::Sensor(_) => defmt::warn!("传感器通信失败"),
+        ImuError::Filter(_) => defmt::warn!("传感器数据无效（加速度为零）"),
+        ImuError::Timeout => defmt::warn!("等待数据就绪超时"),
+    }
+
+    let action = health.record_error();
+    if action == Action::Retry {
+        if imu.reset_fifo().await.is_err() {
+            defmt::warn!("FIFO复位失败");
+        }
+        return;
+    }
+
+    sender.clear();
+    sender
+        .send(T::sensor_fault(health.state(), health.counters()))
+        .await;
+    if action == Action::Lost {
+        Timer::after(SENSOR_LOST_RETRY).await;
+    }
+
+    defmt::warn!("恢复I2C总线并重新初始化传感器");
+    let recovered = match imu.recover().await {
+        Ok(()) => imu.enable_fifo().await,
+        Err(error) => Err(error),
+    };
+    if recovered.is_err() {
+        defmt::warn!("传感器恢复失败");
+    }
+}
+
+/// 陀螺仪零偏校准过程
+///
+/// 屏幕提示保持静止，等待按键引起的晃动平息后采集1秒求零偏。
+/// 读取失败或超时时保留原有零偏。
+///
+/// # 参数
+/// - `imu`: 解算器
+/// - `sender`: 显示数据发送通道
+pub async fn gyro_calibration<IRQ: ImuBusIrqs, M: RawMutex, T: SensorDisplay, const N: usize>(
+    imu: &mut Imu<IRQ>,
+    sender: &Sender<'_, M, T, N>,
+) {
+    sender.clear();
+    sender.send(T::gyro_calibration("HOLD STILL")).await;
+    Timer::after_millis(500).await;
+
+    let hint = match embassy_time::with_timeout(GYRO_CALIBRATION_TIMEOUT, imu.calibration()).await {
+        Ok(Ok(())) => {
+            let gyro_offset = *imu.get_gyro_offset().as_rad_per_s();
+            defmt::info!(
+                "陀螺仪零偏校准完成(rad/s) - X: {}, Y: {}, Z: {}",
+                gyro_offset.x,
+                gyro_offset.y,
+                gyro_offset.z
+            );
+            "DONE"
+        }
+        Ok(Err(_)) => {
+            defmt::warn!("陀螺仪零偏校准读取失败");
+            "FAILED"
+        }
+        Err(_) => {
+            defmt::warn!("陀螺仪零偏校准超时");
+            "FAILED"
+        }
+    };
+    sender.clear();
+    sender.send(T::gyro_calibration(hint)).await;
+    Timer::after_secs(1).await;
+}
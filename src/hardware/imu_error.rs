@@ -0,0 +1,30 @@
+//! 姿态解算流程的错误类型
+
+use ahrs::AhrsError;
+use mpu6050::Mpu6050Error;
+
+/// 姿态解算流程中的错误
+///
+/// 统一传感器通信和姿态滤波的错误，调用方可以用`?`传递，再按重试策略
+/// （[`SensorHealth`](crate::app::imu::health::SensorHealth)）决定重试、恢复总线还是放弃。
+#[derive(Debug)]
+pub enum ImuError<E> {
+    /// 传感器通信错误：I2C错误（如从机无应答），或芯片ID不符
+    Sensor(Mpu6050Error<E>),
+    /// 姿态滤波错误，通常是加速度全为零：传感器复位后处于睡眠状态，数据寄存器为零
+    Filter(AhrsError),
+    /// 等待数据就绪超时：传感器没有按采样率产生数据
+    Timeout,
+}
+
+impl<E> From<Mpu6050Error<E>> for ImuError<E> {
+    fn from(error: Mpu6050Error<E>) -> Self {
+        ImuError::Sensor(error)
+    }
+}
+
+impl<E> From<AhrsError> for ImuError<E> {
+    fn from(error: AhrsError) -> Self {
+        ImuError::Filter(error)
+    }
+}
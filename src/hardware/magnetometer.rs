@@ -0,0 +1,131 @@
+//! HMC5883L/QMC5883L三轴磁力计
+//!
+//! 两种芯片常见于GY-271、GY-87等模块，引脚兼容但地址、寄存器和数据格式不同。
+//! 磁力计可以接在MPU6050的辅助I2C总线（XDA/XCL）上，由MPU6050的I2C主机按采样率
+//! 读取数据并与加速度计、陀螺仪数据一起写入FIFO；也可以与MPU6050接在同一条I2C总线上，
+//! 由解算器直接读取（仅异步解算器支持）。
+//!
+//! 磁力计的坐标轴须与MPU6050一致，模块方向不同时需在安装时对齐。
+
+use crate::app::imu::units::MagneticField;
+use nalgebra::Vector3;
+
+/// 数据寄存器的字节数：三个轴各16位
+pub const MAG_DATA_LEN: usize = 6;
+
+/// 磁力计芯片型号
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MagnetometerChip {
+    /// Honeywell HMC5883L，量程±1.3Gs
+    Hmc5883l,
+    /// QST QMC5883L，量程±8Gs
+    Qmc5883l,
+}
+
+impl MagnetometerChip {
+    /// 自动检测时依次尝试的型号
+    pub const ALL: [Self; 2] = [Self::Hmc5883l, Self::Qmc5883l];
+
+    /// I2C地址
+    pub const fn address(self) -> u8 {
+        match self {
+            Self::Hmc5883l => 0x1E,
+            Self::Qmc5883l => 0x0D,
+        }
+    }
+
+    /// 识别寄存器及其期望值：HMC5883L识别寄存器A为`'H'`，QMC5883L芯片ID为0xFF
+    pub const fn id(self) -> (u8, u8) {
+        match self {
+            Self::Hmc5883l => (0x0A, b'H'),
+            Self::Qmc5883l => (0x0D, 0xFF),
+        }
+    }
+
+    /// 初始化时依次写入的(寄存器, 值)
+    ///
+    /// - HMC5883L：8次平均、75Hz输出（CRA=0x78），±1.3Gs（CRB=0x20），连续测量（MODE=0）
+    /// - QMC5883L：置位/复位周期（0x0B=0x01），512过采样、±8Gs、200Hz、连续测量（CTRL1=0x1D）
+    pub const fn config(self) -> &'static [(u8, u8)] {
+        match self {
+            Self::Hmc5883l => &[(0x00, 0x78), (0x01, 0x20), (0x02, 0x00)],
+            Self::Qmc5883l => &[(0x0B, 0x01), (0x09, 0x1D)],
+        }
+    }
+
+    /// 数据寄存器起始地址，之后连续[`MAG_DATA_LEN`]字节
+    pub const fn data_register(self) -> u8 {
+        match self {
+            Self::Hmc5883l => 0x03,
+            Self::Qmc5883l => 0x00,
+        }
+    }
+
+    /// 灵敏度（LSB/Gs），取自数据手册
+    pub const fn sensitivity(self) -> f32 {
+        match self {
+            Self::Hmc5883l => 1090.0,
+            Self::Qmc5883l => 3000.0,
+        }
+    }
+
+    /// 换算数据寄存器的内容
+    ///
+    /// HMC5883L高字节在前，顺序为X、Z、Y；QMC5883L低字节在前，顺序为X、Y、Z
+    pub fn parse(self, buf: &[u8; MAG_DATA_LEN]) -> MagneticField {
+        let raw = match self {
+            Self::Hmc5883l => {
+                let word = |index: usize| i16::from_be_bytes([buf[index * 2], buf[index * 2 + 1]]);
+                Vector3::new(word(0), word(2), word(1))
+            }
+            Self::Qmc5883l => {
+                let word = |index: usize| i16::from_le_bytes([buf[index * 2], buf[index * 2 + 1]]);
+                Vector3::new(word(0), word(1), word(2))
+            }
+        };
+        MagneticField::from_raw(raw, self.sensitivity())
+    }
+
+    /// 显示用名称
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hmc5883l => "HMC5883L",
+            Self::Qmc5883l => "QMC5883L",
+        }
+    }
+}
+
+/// 磁力计的连接方式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MagnetometerLink {
+    /// 接在MPU6050的辅助I2C总线上，由MPU6050读取
+    Aux,
+    /// 与MPU6050接在同一条I2C总线上，由解算器直接读取
+    Direct,
+}
+
+/// 检测到的磁力计
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Magnetometer {
+    /// 芯片型号
+    pub chip: MagnetometerChip,
+    /// 连接方式
+    pub link: MagnetometerLink,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_chip_byte_order() {
+        // X=1090、Y=-545、Z=2180
+        let hmc = MagnetometerChip::Hmc5883l.parse(&[0x04, 0x42, 0x08, 0x84, 0xFD, 0xDF]);
+        assert!((hmc.as_gauss() - Vector3::new(1.0, -0.5, 2.0)).amax() < 1e-6);
+
+        // X=3000、Y=-1500、Z=6000
+        let qmc = MagnetometerChip::Qmc5883l.parse(&[0xB8, 0x0B, 0x24, 0xFA, 0x70, 0x17]);
+        assert!((qmc.as_gauss() - Vector3::new(1.0, -0.5, 2.0)).amax() < 1e-6);
+        assert!((qmc.as_microtesla().x - 100.0).abs() < 1e-4);
+    }
+}
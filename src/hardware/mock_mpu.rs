@@ -0,0 +1,285 @@
+//! 主机端测试用的MPU6050模拟器
+
+use super::magnetometer::{MAG_DATA_LEN, MagnetometerChip, MagnetometerLink};
+use super::mpu6050_madgwick_solver::{
+    FIFO_COUNTH, FIFO_EN, FIFO_EN_SLV0, FIFO_R_W, I2C_MST_STATUS, I2C_SLV_EN, I2C_SLV_READ,
+    I2C_SLV0_ADDR, I2C_SLV0_CTRL, I2C_SLV0_REG, I2C_SLV4_ADDR, I2C_SLV4_CTRL, I2C_SLV4_DI,
+    I2C_SLV4_DO, I2C_SLV4_DONE, I2C_SLV4_NACK, I2C_SLV4_REG, INT_STATUS, MAX_SAMPLE_LEN,
+    SAMPLE_LEN, USER_CTRL, USER_CTRL_FIFO_RESET, USER_CTRL_I2C_MST_EN,
+};
+use crate::app::imu::units::{AccelRange, GyroRange};
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use nalgebra::Vector3;
+
+/// 陀螺仪配置寄存器GYRO_CONFIG
+const GYRO_CONFIG: usize = 0x1B;
+/// 加速度计配置寄存器ACCEL_CONFIG
+const ACCEL_CONFIG: usize = 0x1C;
+/// 数据寄存器起始地址ACCEL_XOUT_H
+const ACCEL_XOUT_H: usize = 0x3B;
+/// 辅助从机读取数据的起始地址EXT_SENS_DATA_00
+const EXT_SENS_DATA_00: usize = 0x49;
+/// 芯片ID寄存器WHO_AM_I
+const WHO_AM_I: usize = 0x75;
+/// MPU6050的I2C地址
+const ADDRESS: u8 = 0x68;
+
+/// 按寄存器模拟的磁力计，输出给定的磁场
+pub struct MockMag {
+    /// 芯片型号
+    pub chip: MagnetometerChip,
+    /// 连接方式
+    pub link: MagnetometerLink,
+    /// 磁场（Gs）
+    pub field: Vector3<f32>,
+    /// 寄存器
+    pub registers: [u8; 16],
+}
+
+impl MockMag {
+    /// 识别寄存器已就绪的磁力计
+    pub fn new(chip: MagnetometerChip, link: MagnetometerLink, field: Vector3<f32>) -> Self {
+        let mut mock = Self {
+            chip,
+            link,
+            field,
+            registers: [0; 16],
+        };
+        let (register, id) = chip.id();
+        mock.registers[register as usize] = id;
+        mock
+    }
+
+    /// 按芯片的数据格式生成数据寄存器内容
+    fn data(&self) -> [u8; MAG_DATA_LEN] {
+        let raw = self
+            .field
+            .map(|v| (v * self.chip.sensitivity()).round() as i16);
+        let mut data = [0; MAG_DATA_LEN];
+        match self.chip {
+            MagnetometerChip::Hmc5883l => {
+                for (word, chunk) in [raw.x, raw.z, raw.y].iter().zip(data.chunks_exact_mut(2)) {
+                    chunk.copy_from_slice(&word.to_be_bytes());
+                }
+            }
+            MagnetometerChip::Qmc5883l => {
+                for (word, chunk) in raw.iter().zip(data.chunks_exact_mut(2)) {
+                    chunk.copy_from_slice(&word.to_le_bytes());
+                }
+            }
+        }
+        data
+    }
+
+    /// 读一个寄存器
+    fn read(&mut self, register: usize) -> u8 {
+        let start = self.chip.data_register() as usize;
+        let data = self.data();
+        self.registers[start..start + MAG_DATA_LEN].copy_from_slice(&data);
+        self.registers[register]
+    }
+
+    /// 与MPU6050接在同一条总线上时按I2C传输读写寄存器
+    fn transaction(&mut self, operations: &mut [Operation<'_>]) {
+        let mut pointer = 0;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    pointer = bytes[0] as usize;
+                    for (offset, byte) in bytes[1..].iter().enumerate() {
+                        self.registers[pointer + offset] = *byte;
+                    }
+                }
+                Operation::Read(buf) => {
+                    for (offset, byte) in buf.iter_mut().enumerate() {
+                        *byte = self.read(pointer + offset);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 按寄存器模拟的MPU6050，按配置的量程输出给定的加速度和角速度
+pub struct MockMpu {
+    /// 寄存器
+    pub registers: [u8; 128],
+    /// 加速度（g）
+    pub accel: Vector3<f32>,
+    /// 角速度（°/s）
+    pub gyro: Vector3<f32>,
+    /// 芯片温度（℃）
+    pub temperature: f32,
+    /// FIFO中排队的采样数
+    pub fifo: usize,
+    /// 读取FIFO数据的传输次数
+    pub fifo_reads: usize,
+    /// 磁力计
+    pub mag: Option<MockMag>,
+}
+
+impl MockMpu {
+    /// 静止在25℃、FIFO为空的传感器
+    pub fn new(accel: Vector3<f32>, gyro: Vector3<f32>) -> Self {
+        let mut mock = Self {
+            registers: [0; 128],
+            accel,
+            gyro,
+            temperature: 25.0,
+            fifo: 0,
+            fifo_reads: 0,
+            mag: None,
+        };
+        mock.registers[WHO_AM_I] = 0x68;
+        mock
+    }
+
+    /// 按当前量程生成数据寄存器内容
+    fn sample(&mut self) {
+        let gyro_range = GyroRange::ALL[(self.registers[GYRO_CONFIG] >> 3 & 0b11) as usize];
+        let accel_range = AccelRange::ALL[(self.registers[ACCEL_CONFIG] >> 3 & 0b11) as usize];
+        let accel = self.accel * accel_range.sensitivity();
+        let gyro = self.gyro * gyro_range.sensitivity();
+        let temperature = (self.temperature - 36.53) * 340.0;
+        let words = [
+            accel.x,
+            accel.y,
+            accel.z,
+            temperature,
+            gyro.x,
+            gyro.y,
+            gyro.z,
+        ];
+        for (index, word) in words.iter().enumerate() {
+            let bytes = (word.round() as i16).to_be_bytes();
+            let address = ACCEL_XOUT_H + index * 2;
+            self.registers[address..address + 2].copy_from_slice(&bytes);
+        }
+
+        // I2C主机使能时，从机0从辅助总线上的磁力计读取数据
+        let master = self.registers[USER_CTRL as usize] & USER_CTRL_I2C_MST_EN != 0;
+        let slave = self.registers[I2C_SLV0_CTRL as usize] & I2C_SLV_EN != 0;
+        let address = self.registers[I2C_SLV0_ADDR as usize] & !I2C_SLV_READ;
+        let register = self.registers[I2C_SLV0_REG as usize] as usize;
+        if master
+            && slave
+            && let Some(mag) = self.aux_mag(address)
+        {
+            let data = core::array::from_fn::<u8, MAG_DATA_LEN, _>(|i| mag.read(register + i));
+            self.registers[EXT_SENS_DATA_00..EXT_SENS_DATA_00 + MAG_DATA_LEN]
+                .copy_from_slice(&data);
+        }
+    }
+
+    /// 辅助总线上地址为`address`的磁力计
+    fn aux_mag(&mut self, address: u8) -> Option<&mut MockMag> {
+        self.mag
+            .as_mut()
+            .filter(|mag| mag.link == MagnetometerLink::Aux && mag.chip.address() == address)
+    }
+
+    /// 从机4的一次传输：立即完成，辅助总线上没有该地址的从机时无应答
+    fn slave4_transfer(&mut self) {
+        let address = self.registers[I2C_SLV4_ADDR as usize];
+        let register = self.registers[I2C_SLV4_REG as usize] as usize;
+        let value = self.registers[I2C_SLV4_DO as usize];
+        let status = match self.aux_mag(address & !I2C_SLV_READ) {
+            Some(mag) if address & I2C_SLV_READ != 0 => {
+                let byte = mag.read(register);
+                self.registers[I2C_SLV4_DI as usize] = byte;
+                I2C_SLV4_DONE
+            }
+            Some(mag) => {
+                mag.registers[register] = value;
+                I2C_SLV4_DONE
+            }
+            None => I2C_SLV4_DONE | I2C_SLV4_NACK,
+        };
+        self.registers[I2C_MST_STATUS as usize] = status;
+        self.registers[I2C_SLV4_CTRL as usize] &= !I2C_SLV_EN;
+    }
+
+    /// FIFO中每个采样的字节数
+    fn sample_len(&self) -> usize {
+        if self.registers[FIFO_EN as usize] & FIFO_EN_SLV0 != 0 {
+            MAX_SAMPLE_LEN
+        } else {
+            SAMPLE_LEN
+        }
+    }
+}
+
+impl ErrorType for MockMpu {
+    type Error = ErrorKind;
+}
+
+impl I2c for MockMpu {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != ADDRESS {
+            return match &mut self.mag {
+                Some(mag)
+                    if mag.link == MagnetometerLink::Direct && mag.chip.address() == address =>
+                {
+                    mag.transaction(operations);
+                    Ok(())
+                }
+                _ => Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)),
+            };
+        }
+
+        let mut pointer = 0;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    pointer = bytes[0] as usize;
+                    for (offset, byte) in bytes[1..].iter().enumerate() {
+                        self.registers[pointer + offset] = *byte;
+                    }
+                    let reset = bytes.get(1).is_some_and(|b| b & USER_CTRL_FIFO_RESET != 0);
+                    if pointer == USER_CTRL as usize && reset {
+                        self.fifo = 0;
+                    }
+                    let start = bytes.get(1).is_some_and(|b| b & I2C_SLV_EN != 0);
+                    if pointer == I2C_SLV4_CTRL as usize && start {
+                        self.slave4_transfer();
+                    }
+                }
+                Operation::Read(buf) if pointer == FIFO_COUNTH as usize => {
+                    let len = self.fifo * self.sample_len();
+                    buf.copy_from_slice(&(len as u16).to_be_bytes());
+                }
+                Operation::Read(buf) if pointer == FIFO_R_W as usize => {
+                    self.fifo_reads += 1;
+                    let len = self.sample_len();
+                    for chunk in buf.chunks_mut(len) {
+                        self.sample();
+                        chunk.copy_from_slice(&self.registers[ACCEL_XOUT_H..ACCEL_XOUT_H + len]);
+                        self.fifo -= 1;
+                    }
+                }
+                Operation::Read(buf) => {
+                    self.sample();
+                    buf.copy_from_slice(&self.registers[pointer..pointer + buf.len()]);
+                    // 读取后清除中断状态
+                    self.registers[INT_STATUS as usize] = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for MockMpu {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        I2c::transaction(self, address, operations)
+    }
+}
@@ -1,5 +1,27 @@
+pub mod alarm_output;
+#[cfg(target_os = "none")]
+pub mod board;
 pub mod config;
+pub mod display;
+pub mod flash_store;
+#[cfg(target_os = "none")]
 pub mod gpio_button;
+#[cfg(target_os = "none")]
+pub mod gpio_buzzer;
+#[cfg(target_os = "none")]
 pub mod gpio_led;
+#[cfg(all(target_os = "none", feature = "async"))]
+pub mod imu_bus;
+pub mod imu_error;
+pub mod magnetometer;
+#[cfg(test)]
+mod mock_mpu;
+#[cfg(feature = "async")]
+pub mod mpu6050_async_solver;
 pub mod mpu6050_madgwick_solver;
+pub mod ram_flash;
+pub mod software_clock;
+pub mod solver_core;
+#[cfg(target_os = "none")]
+pub mod stm32_rtc;
 pub mod traits;
@@ -0,0 +1,606 @@
+use super::imu_error::ImuError;
+use super::magnetometer::{MAG_DATA_LEN, Magnetometer, MagnetometerChip, MagnetometerLink};
+use super::mpu6050_madgwick_solver::{
+    ACCEL_XOUT_H, AUX_POLL_TIMES, CONFIG, FIFO_BURST_SAMPLES, FIFO_COUNTH, FIFO_EN, FIFO_R_W,
+    I2C_MST_CTRL, I2C_MST_CTRL_400KHZ, I2C_MST_STATUS, I2C_SLV_EN, I2C_SLV_READ, I2C_SLV0_ADDR,
+    I2C_SLV0_CTRL, I2C_SLV0_REG, I2C_SLV4_ADDR, I2C_SLV4_CTRL, I2C_SLV4_DI, I2C_SLV4_DO,
+    I2C_SLV4_DONE, I2C_SLV4_NACK, I2C_SLV4_REG, INT_DATA_READY, INT_ENABLE, INT_FIFO_OVERFLOW,
+    INT_LATCHED, INT_PIN_CFG, INT_STATUS, MAX_SAMPLE_LEN, Reading, SMPLRT_DIV, USER_CTRL,
+    USER_CTRL_FIFO_EN, USER_CTRL_FIFO_RESET, USER_CTRL_I2C_MST_EN,
+};
+use super::solver_core::SolverCore;
+use super::traits::BusRecovery;
+use crate::app::imu::filter::{Madgwick, OrientationFilter};
+use crate::app::imu::units::{AccelRange, Acceleration, AngularRate, GyroRange, MagneticField};
+use core::ops::{Deref, DerefMut};
+use embassy_time::{Ticker, Timer};
+use embedded_hal_async::i2c::{Error, ErrorKind, I2c};
+use mpu6050::Mpu6050Error;
+
+/// MPU6050的I2C地址（AD0接地）
+const ADDRESS: u8 = 0x68;
+/// 陀螺仪配置寄存器GYRO_CONFIG
+const GYRO_CONFIG: u8 = 0x1B;
+/// 加速度计配置寄存器ACCEL_CONFIG
+const ACCEL_CONFIG: u8 = 0x1C;
+/// 电源管理寄存器PWR_MGMT_1
+const PWR_MGMT_1: u8 = 0x6B;
+/// 芯片ID寄存器WHO_AM_I
+const WHO_AM_I: u8 = 0x75;
+/// WHO_AM_I的正确值
+const CHIP_ID: u8 = 0x68;
+
+/// PWR_MGMT_1：复位全部寄存器
+const PWR_MGMT_1_RESET: u8 = 0b1000_0000;
+/// PWR_MGMT_1：退出睡眠，使用X轴陀螺仪的PLL作为时钟
+const PWR_MGMT_1_CLOCK_PLL_X: u8 = 0b0000_0001;
+/// GYRO_CONFIG/ACCEL_CONFIG中量程字段（位4:3）
+const FULL_SCALE_MASK: u8 = 0b0001_1000;
+/// ACCEL_CONFIG中高通滤波器字段（位2:0）
+const ACCEL_HPF_MASK: u8 = 0b0000_0111;
+/// 加速度计高通滤波器5Hz
+const ACCEL_HPF_5HZ: u8 = 0b0000_0001;
+
+/// MPU6050姿态解算器的异步版本
+///
+/// 与[`Mpu6050MadgwickSolver`](super::mpu6050_madgwick_solver::Mpu6050MadgwickSolver)
+/// 的接口相同，但通过`embedded_hal_async::i2c::I2c`访问传感器，所有读写寄存器的方法都是
+/// 异步的：等待I2C传输（如DMA）完成时让出执行器，不阻塞其他任务。
+/// 寄存器直接读写，不经过阻塞的`mpu6050`驱动，错误类型仍为[`Mpu6050Error`]。
+/// 与总线无关的部分同样由[`SolverCore`]实现。
+///
+/// 磁力计除接在MPU6050的辅助总线上外，也可以与MPU6050接在同一条I2C总线上，
+/// 此时每次读取数据寄存器或FIFO时由解算器直接读取一次磁场。
+///
+/// # 泛型参数
+/// - `I2C`: 实现`embedded_hal_async::i2c::I2c`接口的类型，用于与MPU6050通信
+/// - `F`: 姿态滤波器类型
+pub struct Mpu6050AsyncSolver<I2C, F = Madgwick<f32>> {
+    /// I2C总线实例
+    i2c: I2C,
+    /// 与总线无关的状态
+    core: SolverCore<F>,
+}
+
+impl<I2C, E> Mpu6050AsyncSolver<I2C>
+where
+    I2C: I2c<Error = E>,
+    E: Error,
+{
+    /// 创建使用Madgwick滤波器的异步姿态解算器实例
+    ///
+    /// # 参数
+    /// - `i2c`: 异步I2C总线实例
+    /// - `sample_period`: 采样周期（秒），即滤波器更新频率的倒数
+    /// - `beta`: Madgwick滤波器增益系数，控制收敛速度和稳定性
+    pub fn new(i2c: I2C, sample_period: f32, beta: f32) -> Self {
+        Self::with_filter(i2c, Madgwick::new(sample_period, beta))
+    }
+}
+
+impl<I2C, E, F> Mpu6050AsyncSolver<I2C, F>
+where
+    I2C: I2c<Error = E>,
+    E: Error,
+    F: OrientationFilter,
+{
+    /// 创建使用指定滤波器的异步姿态解算器实例
+    ///
+    /// # 参数
+    /// - `i2c`: 异步I2C总线实例
+    /// - `filter`: 姿态滤波器，采样周期须与调用[`Self::update`]的周期一致
+    pub fn with_filter(i2c: I2C, filter: F) -> Self {
+        Self {
+            i2c,
+            core: SolverCore::new(filter),
+        }
+    }
+
+    /// 初始化MPU6050传感器
+    ///
+    /// 复位并唤醒传感器、检查芯片ID，然后依次配置量程、数字低通滤波器、
+    /// 采样率和加速度计高通滤波器（5Hz），之前检测到磁力计时重新配置，步骤与阻塞版本相同。
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 初始化成功
+    /// - `Err(Mpu6050Error<E>)`: I2C错误，或芯片ID不符（`InvalidChipId`）
+    pub async fn init(&mut self) -> Result<(), Mpu6050Error<E>> {
+        // 复位后等待寄存器恢复默认值，再退出睡眠
+        self.write_byte(PWR_MGMT_1, PWR_MGMT_1_RESET).await?;
+        Timer::after_millis(100).await;
+        self.write_byte(PWR_MGMT_1, PWR_MGMT_1_CLOCK_PLL_X).await?;
+
+        let id = self.read_byte(WHO_AM_I).await?;
+        if id != CHIP_ID {
+            return Err(Mpu6050Error::InvalidChipId(id));
+        }
+
+        self.set_gyro_range(self.core.gyro_range).await?;
+        self.set_accel_range(self.core.accel_range).await?;
+        self.set_dlpf_mode(self.core.dlpf_mode).await?;
+        self.set_sample_rate_divider(self.core.sample_rate_divider)
+            .await?;
+        self.modify_byte(ACCEL_CONFIG, ACCEL_HPF_MASK, ACCEL_HPF_5HZ)
+            .await?;
+
+        // 复位清除了I2C主机配置，磁力计也可能随总线一起掉电
+        if self.core.magnetometer.is_some() {
+            self.enable_magnetometer().await?;
+        }
+        Ok(())
+    }
+
+    /// 检测并配置磁力计
+    ///
+    /// 先使能MPU6050的I2C主机，在辅助总线上依次尝试[`MagnetometerChip::ALL`]中的型号，
+    /// 识别后由I2C主机在每个采样周期读取磁场；辅助总线上没有时关闭I2C主机，
+    /// 再在MPU6050所在的I2C总线上查找。须在[`Self::init`]之后、[`Self::enable_fifo`]之前调用。
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Magnetometer))`: 检测到的磁力计及其连接方式
+    /// - `Ok(None)`: 没有磁力计应答，只使用陀螺仪和加速度计
+    /// - `Err(Mpu6050Error<E>)`: I2C通信错误（磁力计无应答除外）
+    pub async fn enable_magnetometer(&mut self) -> Result<Option<Magnetometer>, Mpu6050Error<E>> {
+        self.core.magnetometer = None;
+        self.write_byte(I2C_MST_CTRL, I2C_MST_CTRL_400KHZ).await?;
+        self.write_byte(USER_CTRL, USER_CTRL_I2C_MST_EN).await?;
+        for chip in MagnetometerChip::ALL {
+            if self.configure_aux(chip).await? {
+                return Ok(self.found(chip, MagnetometerLink::Aux));
+            }
+        }
+        self.write_byte(USER_CTRL, 0).await?;
+
+        for chip in MagnetometerChip::ALL {
+            if self.configure_direct(chip).await? {
+                return Ok(self.found(chip, MagnetometerLink::Direct));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 陀螺仪零偏校准：采集100次数据（间隔10ms），以平均值作为零偏并记录芯片温度
+    ///
+    /// # 注意
+    /// 校准时需保持传感器静止，不要求水平放置
+    pub async fn calibration(&mut self) -> Result<(), Mpu6050Error<E>> {
+        // 校准采样次数（100次）
+        const TIMES: u8 = 100;
+
+        let mean = self.average(TIMES).await?;
+        self.core.gyro_offset = mean.gyro;
+        self.core.gyro_offset_temperature = Some(mean.temperature);
+
+        Ok(())
+    }
+
+    /// 以10ms间隔采集`times`次数据，返回未校准数据的平均值
+    pub async fn average(&mut self, times: u8) -> Result<Reading, Mpu6050Error<E>> {
+        let mut accel_sum = Acceleration::zeros();
+        let mut gyro_sum = AngularRate::zeros();
+        let mut mag_sum = MagneticField::zeros();
+        let mut temperature_sum = 0.0;
+
+        let mut ticker = Ticker::every(embassy_time::Duration::from_millis(10));
+        for _ in 0..times {
+            let reading = self.read().await?;
+            accel_sum = accel_sum + reading.accel;
+            gyro_sum = gyro_sum + reading.gyro;
+            mag_sum = mag_sum + reading.mag;
+            temperature_sum += reading.temperature;
+
+            ticker.next().await;
+        }
+
+        let count = times.max(1) as f32;
+        Ok(Reading {
+            accel: accel_sum / count,
+            gyro: gyro_sum / count,
+            temperature: temperature_sum / count,
+            mag: mag_sum / count,
+        })
+    }
+
+    /// 读取最新的加速度计、陀螺仪和温度数据，按当前量程换算后存储
+    ///
+    /// # 返回值
+    /// - `Ok(&mut Self)`: 成功获取数据，返回自身可变引用
+    /// - `Err(Mpu6050Error<E>)`: 数据读取过程中发生的错误
+    pub async fn get_data(&mut self) -> Result<&mut Self, Mpu6050Error<E>> {
+        self.core.raw = self.read().await?;
+
+        Ok(self)
+    }
+
+    /// 使能FIFO和数据就绪中断，FIFO先被清空
+    ///
+    /// INT引脚高电平有效，保持到读取任意寄存器；变为高电平后调用[`Self::update_from_fifo`]。
+    pub async fn enable_fifo(&mut self) -> Result<(), Mpu6050Error<E>> {
+        self.write_byte(INT_PIN_CFG, INT_LATCHED).await?;
+        self.write_byte(INT_ENABLE, INT_DATA_READY).await?;
+        self.write_byte(FIFO_EN, self.fifo_sources()).await?;
+        self.reset_fifo().await
+    }
+
+    /// 清空FIFO，丢弃排队的采样
+    pub async fn reset_fifo(&mut self) -> Result<(), Mpu6050Error<E>> {
+        let i2c_master = self.i2c_master();
+        self.write_byte(USER_CTRL, USER_CTRL_FIFO_RESET | i2c_master)
+            .await?;
+        self.write_byte(USER_CTRL, USER_CTRL_FIFO_EN | i2c_master)
+            .await
+    }
+
+    /// 读取FIFO中排队的全部采样，以传感器的采样周期依次更新姿态
+    ///
+    /// 每次传输连续读出至多[`FIFO_BURST_SAMPLES`]个采样。FIFO溢出时清空FIFO并返回0；
+    /// 滤波器拒绝某个采样时停止处理并返回错误。
+    /// 磁力计与MPU6050接在同一条总线上时，本次读出的采样共用先读取的一次磁场。
+    ///
+    /// # 返回值
+    /// - `Ok(usize)`: 处理的采样数
+    /// - `Err(ImuError<E>)`: 读取过程中发生的错误，或滤波器拒绝了采样
+    pub async fn update_from_fifo(&mut self) -> Result<usize, ImuError<E>> {
+        self.update_from_fifo_with(|_, _| {}).await
+    }
+
+    /// 与[`Self::update_from_fifo`]相同，并把每个采样校准后的加速度和角速度交给`on_sample`
+    ///
+    /// 用于需要完整采样序列的应用层算法，如计步器。采样周期为[`Self::sample_period`]。
+    pub async fn update_from_fifo_with(
+        &mut self,
+        mut on_sample: impl FnMut(&Acceleration, &AngularRate),
+    ) -> Result<usize, ImuError<E>> {
+        // 读取中断状态，同时清除INT引脚电平
+        let status = self.read_byte(INT_STATUS).await?;
+        if status & INT_FIFO_OVERFLOW != 0 {
+            self.reset_fifo().await?;
+            return Ok(0);
+        }
+
+        let mut count = [0; 2];
+        self.read_bytes(FIFO_COUNTH, &mut count).await?;
+        let len = self.sample_len();
+        let samples = u16::from_be_bytes(count) as usize / len;
+
+        if let Some(chip) = self.direct_magnetometer()
+            && samples > 0
+        {
+            self.core.raw.mag = self.read_direct(chip).await?;
+        }
+
+        self.core.filter.set_sample_period(self.sample_period());
+        let mut remaining = samples;
+        while remaining > 0 {
+            let burst = remaining.min(FIFO_BURST_SAMPLES);
+            let mut buf = [0; FIFO_BURST_SAMPLES * MAX_SAMPLE_LEN];
+            let buf = &mut buf[..burst * len];
+            self.read_bytes(FIFO_R_W, buf).await?;
+            for sample in buf.chunks_exact(len) {
+                self.update_sample(sample)?;
+                on_sample(&self.acceleration(), &self.angular_rate());
+            }
+            remaining -= burst;
+        }
+
+        Ok(samples)
+    }
+
+    /// 恢复总线并重新初始化传感器
+    ///
+    /// 用于传感器在传输中途复位、拉住SDA等通信持续失败的情况。校准参数和姿态保持不变，
+    /// 量程等配置由[`Self::init`]重新写入；使用FIFO时需再次调用[`Self::enable_fifo`]。
+    pub async fn recover(&mut self) -> Result<(), Mpu6050Error<E>>
+    where
+        I2C: BusRecovery,
+    {
+        self.i2c.recover_bus();
+        self.init().await
+    }
+
+    /// 设置采样率分频，采样率 = 陀螺仪输出频率 / (1 + `divider`)
+    pub async fn set_sample_rate_divider(&mut self, divider: u8) -> Result<(), Mpu6050Error<E>> {
+        self.write_byte(SMPLRT_DIV, divider).await?;
+        self.core.sample_rate_divider = divider;
+        Ok(())
+    }
+
+    /// 设置陀螺仪量程，立即写入传感器，之后的读数按新量程换算
+    pub async fn set_gyro_range(&mut self, range: GyroRange) -> Result<(), Mpu6050Error<E>> {
+        let index = GyroRange::ALL.iter().position(|r| *r == range).unwrap_or(0);
+        self.modify_byte(GYRO_CONFIG, FULL_SCALE_MASK, (index as u8) << 3)
+            .await?;
+        self.core.gyro_range = range;
+        Ok(())
+    }
+
+    /// 设置加速度计量程，立即写入传感器，之后的读数按新量程换算
+    pub async fn set_accel_range(&mut self, range: AccelRange) -> Result<(), Mpu6050Error<E>> {
+        let index = AccelRange::ALL
+            .iter()
+            .position(|r| *r == range)
+            .unwrap_or(0);
+        self.modify_byte(ACCEL_CONFIG, FULL_SCALE_MASK, (index as u8) << 3)
+            .await?;
+        self.core.accel_range = range;
+        Ok(())
+    }
+
+    /// 设置数字低通滤波器(DLPF)模式（0-6），各模式带宽见阻塞版本
+    pub async fn set_dlpf_mode(&mut self, dlpf_cfg: u8) -> Result<(), Mpu6050Error<E>> {
+        let value = dlpf_cfg & 0x07;
+        self.write_byte(CONFIG, value).await?;
+        self.core.dlpf_mode = value;
+        Ok(())
+    }
+
+    /// 读取一次加速度计、陀螺仪、温度和磁力计数据，按当前量程换算
+    async fn read(&mut self) -> Result<Reading, Mpu6050Error<E>> {
+        let len = self.sample_len();
+        let mut buf = [0; MAX_SAMPLE_LEN];
+        self.read_bytes(ACCEL_XOUT_H, &mut buf[..len]).await?;
+        let mut reading = self.parse(&buf[..len]);
+        if let Some(chip) = self.direct_magnetometer() {
+            reading.mag = self.read_direct(chip).await?;
+        }
+        Ok(reading)
+    }
+
+    /// 记录检测到的磁力计
+    fn found(&mut self, chip: MagnetometerChip, link: MagnetometerLink) -> Option<Magnetometer> {
+        self.core.magnetometer = Some(Magnetometer { chip, link });
+        self.core.magnetometer
+    }
+
+    /// 与MPU6050接在同一条总线上的磁力计
+    fn direct_magnetometer(&self) -> Option<MagnetometerChip> {
+        match self.core.magnetometer {
+            Some(Magnetometer {
+                chip,
+                link: MagnetometerLink::Direct,
+            }) => Some(chip),
+            _ => None,
+        }
+    }
+
+    /// 识别并配置辅助总线上的磁力计，成功后由从机0在每个采样周期读取其数据寄存器
+    async fn configure_aux(&mut self, chip: MagnetometerChip) -> Result<bool, Mpu6050Error<E>> {
+        let (register, id) = chip.id();
+        if self.aux_read(chip.address(), register).await? != Some(id) {
+            return Ok(false);
+        }
+        for &(register, value) in chip.config() {
+            if !self.aux_write(chip.address(), register, value).await? {
+                return Ok(false);
+            }
+        }
+
+        self.write_byte(I2C_SLV0_ADDR, I2C_SLV_READ | chip.address())
+            .await?;
+        self.write_byte(I2C_SLV0_REG, chip.data_register()).await?;
+        self.write_byte(I2C_SLV0_CTRL, I2C_SLV_EN | MAG_DATA_LEN as u8)
+            .await?;
+        Ok(true)
+    }
+
+    /// 通过辅助从机4读一个寄存器，从机无应答时返回`None`
+    async fn aux_read(&mut self, address: u8, register: u8) -> Result<Option<u8>, Mpu6050Error<E>> {
+        self.write_byte(I2C_SLV4_ADDR, I2C_SLV_READ | address)
+            .await?;
+        self.write_byte(I2C_SLV4_REG, register).await?;
+        self.write_byte(I2C_SLV4_CTRL, I2C_SLV_EN).await?;
+        if !self.aux_wait().await? {
+            return Ok(None);
+        }
+        self.read_byte(I2C_SLV4_DI).await.map(Some)
+    }
+
+    /// 通过辅助从机4写一个寄存器，返回从机是否应答
+    async fn aux_write(
+        &mut self,
+        address: u8,
+        register: u8,
+        value: u8,
+    ) -> Result<bool, Mpu6050Error<E>> {
+        self.write_byte(I2C_SLV4_ADDR, address).await?;
+        self.write_byte(I2C_SLV4_REG, register).await?;
+        self.write_byte(I2C_SLV4_DO, value).await?;
+        self.write_byte(I2C_SLV4_CTRL, I2C_SLV_EN).await?;
+        self.aux_wait().await
+    }
+
+    /// 等待从机4传输完成，返回从机是否应答；超时视为无应答
+    async fn aux_wait(&mut self) -> Result<bool, Mpu6050Error<E>> {
+        for _ in 0..AUX_POLL_TIMES {
+            let status = self.read_byte(I2C_MST_STATUS).await?;
+            if status & I2C_SLV4_DONE != 0 {
+                return Ok(status & I2C_SLV4_NACK == 0);
+            }
+            Timer::after_millis(1).await;
+        }
+        Ok(false)
+    }
+
+    /// 识别并配置MPU6050所在总线上的磁力计
+    ///
+    /// # 返回值
+    /// 磁力计应答且识别寄存器正确时返回`true`；无应答不视为错误
+    async fn configure_direct(&mut self, chip: MagnetometerChip) -> Result<bool, Mpu6050Error<E>> {
+        let (register, id) = chip.id();
+        let mut buf = [0];
+        match self
+            .i2c
+            .write_read(chip.address(), &[register], &mut buf)
+            .await
+        {
+            Ok(()) if buf[0] == id => {}
+            Ok(()) => return Ok(false),
+            Err(error) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => return Ok(false),
+            Err(error) => return Err(Mpu6050Error::I2c(error)),
+        }
+
+        for &(register, value) in chip.config() {
+            self.i2c
+                .write(chip.address(), &[register, value])
+                .await
+                .map_err(Mpu6050Error::I2c)?;
+        }
+        Ok(true)
+    }
+
+    /// 直接读取MPU6050所在总线上磁力计的数据寄存器
+    async fn read_direct(
+        &mut self,
+        chip: MagnetometerChip,
+    ) -> Result<MagneticField, Mpu6050Error<E>> {
+        let mut buf = [0; MAG_DATA_LEN];
+        self.i2c
+            .write_read(chip.address(), &[chip.data_register()], &mut buf)
+            .await
+            .map_err(Mpu6050Error::I2c)?;
+        Ok(chip.parse(&buf))
+    }
+
+    /// 写一个寄存器
+    async fn write_byte(&mut self, register: u8, value: u8) -> Result<(), Mpu6050Error<E>> {
+        self.i2c
+            .write(ADDRESS, &[register, value])
+            .await
+            .map_err(Mpu6050Error::I2c)
+    }
+
+    /// 读一个寄存器
+    async fn read_byte(&mut self, register: u8) -> Result<u8, Mpu6050Error<E>> {
+        let mut buf = [0];
+        self.read_bytes(register, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    /// 从`register`起连续读取寄存器
+    async fn read_bytes(&mut self, register: u8, buf: &mut [u8]) -> Result<(), Mpu6050Error<E>> {
+        self.i2c
+            .write_read(ADDRESS, &[register], buf)
+            .await
+            .map_err(Mpu6050Error::I2c)
+    }
+
+    /// 修改寄存器中`mask`对应的位，其余位保持不变
+    async fn modify_byte(
+        &mut self,
+        register: u8,
+        mask: u8,
+        value: u8,
+    ) -> Result<(), Mpu6050Error<E>> {
+        let byte = self.read_byte(register).await?;
+        self.write_byte(register, (byte & !mask) | (value & mask))
+            .await
+    }
+}
+
+impl<I2C, F> Deref for Mpu6050AsyncSolver<I2C, F> {
+    type Target = SolverCore<F>;
+
+    fn deref(&self) -> &SolverCore<F> {
+        &self.core
+    }
+}
+
+impl<I2C, F> DerefMut for Mpu6050AsyncSolver<I2C, F> {
+    fn deref_mut(&mut self) -> &mut SolverCore<F> {
+        &mut self.core
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::filter::ComplementaryFilter;
+    use crate::app::imu::magnetometer::MagCalibration;
+    use crate::hardware::mock_mpu::{MockMag, MockMpu};
+    use crate::hardware::mpu6050_madgwick_solver::FIFO_EN_SENSORS;
+    use embassy_futures::block_on;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn readings_are_independent_of_range() {
+        let mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 90.0));
+        let mut solver = Mpu6050AsyncSolver::new(mock, 0.01, 0.1);
+
+        for (gyro_range, accel_range) in GyroRange::ALL.into_iter().zip(AccelRange::ALL) {
+            block_on(solver.set_gyro_range(gyro_range)).unwrap();
+            block_on(solver.set_accel_range(accel_range)).unwrap();
+            block_on(solver.get_data()).unwrap();
+
+            assert!((solver.acceleration().as_g() - Vector3::z()).amax() < 1e-3);
+            let rate = solver.angular_rate().as_rad_per_s().z;
+            assert!((rate - 90f32.to_radians()).abs() < 1e-3, "{gyro_range:?}");
+        }
+    }
+
+    #[test]
+    fn range_changes_keep_high_pass_filter() {
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        mock.registers[ACCEL_CONFIG as usize] = ACCEL_HPF_5HZ;
+        let mut solver = Mpu6050AsyncSolver::new(mock, 0.01, 0.1);
+
+        block_on(solver.set_accel_range(AccelRange::G16)).unwrap();
+        let config = block_on(solver.read_byte(ACCEL_CONFIG)).unwrap();
+        assert_eq!(config, 0b0001_1000 | ACCEL_HPF_5HZ);
+    }
+
+    #[test]
+    fn integrates_every_fifo_sample_at_sensor_period() {
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 90.0));
+        mock.fifo = 50;
+        let filter = ComplementaryFilter::new(0.05, 0.02);
+        let mut solver = Mpu6050AsyncSolver::with_filter(mock, filter);
+        block_on(solver.set_gyro_range(GyroRange::D250)).unwrap();
+
+        assert_eq!(block_on(solver.update_from_fifo()).unwrap(), 50);
+        assert_eq!(solver.i2c.fifo_reads, 50usize.div_ceil(FIFO_BURST_SAMPLES));
+        let (_, _, yaw) = solver.quaternion().euler_angles();
+        assert!(
+            (yaw.to_degrees() - 45.0).abs() < 0.5,
+            "{}",
+            yaw.to_degrees()
+        );
+
+        block_on(solver.enable_fifo()).unwrap();
+        assert_eq!(block_on(solver.update_from_fifo()).unwrap(), 0);
+    }
+
+    #[test]
+    fn reads_magnetometer_on_the_same_bus() {
+        let field = Vector3::new(0.3, 0.0, -0.4);
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        mock.mag = Some(MockMag::new(
+            MagnetometerChip::Hmc5883l,
+            MagnetometerLink::Direct,
+            field,
+        ));
+        mock.fifo = 5;
+        let mut solver = Mpu6050AsyncSolver::new(mock, 0.01, 0.1);
+
+        let magnetometer = block_on(solver.enable_magnetometer()).unwrap();
+        assert_eq!(
+            magnetometer,
+            Some(Magnetometer {
+                chip: MagnetometerChip::Hmc5883l,
+                link: MagnetometerLink::Direct
+            })
+        );
+        // 辅助总线上没有磁力计，I2C主机保持关闭，FIFO采样不含磁力计数据
+        assert_eq!(block_on(solver.read_byte(USER_CTRL)).unwrap(), 0);
+        assert_eq!(solver.fifo_sources(), FIFO_EN_SENSORS);
+
+        assert_eq!(block_on(solver.update_from_fifo()).unwrap(), 5);
+        assert!((solver.raw_magnetic_field().as_gauss() - field).amax() < 1e-3);
+
+        solver.set_mag_calibration(MagCalibration {
+            offset: field,
+            ..MagCalibration::default()
+        });
+        block_on(solver.get_data()).unwrap();
+        assert!(solver.magnetic_field().as_gauss().amax() < 1e-3);
+    }
+}
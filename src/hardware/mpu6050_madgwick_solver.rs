@@ -1,37 +1,153 @@
-use ahrs::{Ahrs, AhrsError, Madgwick};
+use super::imu_error::ImuError;
+use super::magnetometer::{MAG_DATA_LEN, Magnetometer, MagnetometerChip, MagnetometerLink};
+use super::solver_core::SolverCore;
+use crate::app::imu::filter::{Madgwick, OrientationFilter};
+use crate::app::imu::units::{AccelRange, Acceleration, AngularRate, GyroRange, MagneticField};
+use core::ops::{Deref, DerefMut};
 use embassy_time::Ticker;
+use embedded_hal::delay::DelayNs;
 use embedded_hal::i2c::I2c;
 use mpu6050::{Mpu6050, Mpu6050Error, device};
-use nalgebra::{UnitQuaternion, Vector3};
 
-/// MPU6050传感器结合Madgwick滤波算法的姿态解算器
+/// 采样率分频寄存器SMPLRT_DIV
+pub(crate) const SMPLRT_DIV: u8 = 0x19;
+/// 配置寄存器CONFIG（数字低通滤波器）
+pub(crate) const CONFIG: u8 = 0x1A;
+/// FIFO数据选择寄存器FIFO_EN
+pub(crate) const FIFO_EN: u8 = 0x23;
+/// I2C主机控制寄存器I2C_MST_CTRL
+pub(crate) const I2C_MST_CTRL: u8 = 0x24;
+/// 辅助从机0地址寄存器I2C_SLV0_ADDR
+pub(crate) const I2C_SLV0_ADDR: u8 = 0x25;
+/// 辅助从机0寄存器地址I2C_SLV0_REG
+pub(crate) const I2C_SLV0_REG: u8 = 0x26;
+/// 辅助从机0控制寄存器I2C_SLV0_CTRL
+pub(crate) const I2C_SLV0_CTRL: u8 = 0x27;
+/// 辅助从机4地址寄存器I2C_SLV4_ADDR
+pub(crate) const I2C_SLV4_ADDR: u8 = 0x31;
+/// 辅助从机4寄存器地址I2C_SLV4_REG
+pub(crate) const I2C_SLV4_REG: u8 = 0x32;
+/// 辅助从机4写入数据寄存器I2C_SLV4_DO
+pub(crate) const I2C_SLV4_DO: u8 = 0x33;
+/// 辅助从机4控制寄存器I2C_SLV4_CTRL
+pub(crate) const I2C_SLV4_CTRL: u8 = 0x34;
+/// 辅助从机4读出数据寄存器I2C_SLV4_DI
+pub(crate) const I2C_SLV4_DI: u8 = 0x35;
+/// I2C主机状态寄存器I2C_MST_STATUS
+pub(crate) const I2C_MST_STATUS: u8 = 0x36;
+/// 中断引脚配置寄存器INT_PIN_CFG
+pub(crate) const INT_PIN_CFG: u8 = 0x37;
+/// 中断使能寄存器INT_ENABLE
+pub(crate) const INT_ENABLE: u8 = 0x38;
+/// 中断状态寄存器INT_STATUS
+pub(crate) const INT_STATUS: u8 = 0x3A;
+/// 数据寄存器起始地址ACCEL_XOUT_H
+pub(crate) const ACCEL_XOUT_H: u8 = 0x3B;
+/// 用户控制寄存器USER_CTRL
+pub(crate) const USER_CTRL: u8 = 0x6A;
+/// FIFO字节数寄存器FIFO_COUNTH（高字节在前）
+pub(crate) const FIFO_COUNTH: u8 = 0x72;
+/// FIFO读写寄存器FIFO_R_W
+pub(crate) const FIFO_R_W: u8 = 0x74;
+
+/// FIFO_EN：写入温度、陀螺仪XYZ和加速度计数据
+pub(crate) const FIFO_EN_SENSORS: u8 = 0b1111_1000;
+/// FIFO_EN：写入辅助从机0读取的数据
+pub(crate) const FIFO_EN_SLV0: u8 = 0b0000_0001;
+/// I2C_MST_CTRL：等待辅助从机数据读完再触发数据就绪中断（WAIT_FOR_ES），400kHz
+pub(crate) const I2C_MST_CTRL_400KHZ: u8 = 0b0100_1101;
+/// I2C_SLVx_CTRL：使能从机；从机4完成一次传输后自动清除
+pub(crate) const I2C_SLV_EN: u8 = 0b1000_0000;
+/// I2C_SLVx_ADDR：读从机
+pub(crate) const I2C_SLV_READ: u8 = 0b1000_0000;
+/// I2C_MST_STATUS：从机4传输完成
+pub(crate) const I2C_SLV4_DONE: u8 = 0b0100_0000;
+/// I2C_MST_STATUS：从机4无应答
+pub(crate) const I2C_SLV4_NACK: u8 = 0b0001_0000;
+/// INT_PIN_CFG：中断电平保持到读取任意寄存器为止（LATCH_INT_EN | INT_RD_CLEAR）
+pub(crate) const INT_LATCHED: u8 = 0b0011_0000;
+/// INT_ENABLE/INT_STATUS：数据就绪
+pub(crate) const INT_DATA_READY: u8 = 0b0000_0001;
+/// INT_STATUS：FIFO溢出
+pub(crate) const INT_FIFO_OVERFLOW: u8 = 0b0001_0000;
+/// USER_CTRL：使能FIFO
+pub(crate) const USER_CTRL_FIFO_EN: u8 = 0b0100_0000;
+/// USER_CTRL：使能辅助总线的I2C主机
+pub(crate) const USER_CTRL_I2C_MST_EN: u8 = 0b0010_0000;
+/// USER_CTRL：复位FIFO
+pub(crate) const USER_CTRL_FIFO_RESET: u8 = 0b0000_0100;
+
+/// 每个采样的字节数：加速度XYZ、温度、角速度XYZ，与数据寄存器顺序相同
+pub(crate) const SAMPLE_LEN: usize = 14;
+
+/// 辅助总线上有磁力计时每个采样的字节数，磁力计数据在最后
+pub(crate) const MAX_SAMPLE_LEN: usize = SAMPLE_LEN + MAG_DATA_LEN;
+
+/// 一次突发读取FIFO的最多采样数，排队更多时分多次读取
+pub(crate) const FIFO_BURST_SAMPLES: usize = 8;
+
+/// 辅助总线单次读写等待完成的最多次数，每次间隔1ms；从机4每个采样周期传输一次
+pub(crate) const AUX_POLL_TIMES: u8 = 20;
+
+/// MPU6050传感器结合姿态滤波算法的姿态解算器
 ///
-/// 本结构体封装了MPU6050传感器的操作和Madgwick滤波算法，
+/// 本结构体封装了MPU6050传感器的操作和姿态滤波算法，
 /// 提供完整的姿态解算解决方案。包含传感器初始化、校准、
-/// 数据采集和姿态解算功能。
+/// 数据采集和姿态解算功能。默认使用Madgwick滤波器，
+/// 也可以通过[`Self::with_filter`]选择其他[`OrientationFilter`]实现。
+///
+/// 原始读数按当前量程换算为物理单位（加速度g、角速度rad/s），
+/// 校准参数同样以物理单位保存，修改量程后仍然有效。
+/// 设置了陀螺仪零偏温度模型时，按芯片温度补偿零偏校准后的零偏变化。
+/// 除定时读取数据寄存器（[`Self::get_data`]）外，也可以让传感器按自身的采样时钟
+/// 将数据写入FIFO并在数据就绪时拉高INT引脚，由[`Self::update_from_fifo`]一次读出全部
+/// 排队的采样，以传感器的采样周期更新滤波器，任务调度延迟不会改变积分周期，也不会丢失采样。
+///
+/// 校准参数可以通过[`Self::store_calibration`]保存到闪存，下次启动时用
+/// [`Self::load_calibration`]恢复；未校准时零偏为零、不做灵敏度修正。
+///
+/// MPU6050的辅助I2C总线上接有磁力计时，[`Self::enable_magnetometer`]使MPU6050按采样率
+/// 读取磁场，姿态滤波随之改用[`OrientationFilter::update_marg`]，航向以磁北为基准，
+/// 再按磁偏角（[`Self::set_declination`]）换算为真北航向（[`Self::heading`]）。
+///
+/// 电路板不按设备的坐标轴安装时，用[`Self::set_mounting`]设置安装方向：校准后的读数
+/// 先转到设备坐标系再送入滤波器，得到的是设备的姿态。安装方向随校准参数一起保存。
+///
+/// 每次更新姿态后，用新的姿态去掉加速度中的重力，得到地球坐标系中的线加速度，
+/// 并积分出速度和位移（[`Self::motion`]）；检测到静止时速度清零，误差只在每段运动内累积。
+/// 每个采样同时送入运动事件检测，检测到的敲击、摇晃、翻转等事件用
+/// [`Self::publish_motion_events`]发布。
+///
+/// 这些与总线无关的部分由[`SolverCore`]实现，解算器通过`Deref`直接提供其方法。
 ///
 /// # 泛型参数
 /// - `I2C`: 实现`embedded_hal::i2c::I2c`接口的类型，用于与MPU6050通信
-pub struct Mpu6050MadgwickSolver<I2C> {
+/// - `F`: 姿态滤波器类型
+pub struct Mpu6050MadgwickSolver<I2C, F = Madgwick<f32>> {
     /// MPU6050传感器实例
     mpu: Mpu6050<I2C>,
-    /// Madgwick滤波器实例
-    filter: Madgwick<f32>,
-    /// 原始加速度计数据（未校准）
-    accel_raw: Vector3<f32>,
-    /// 加速度计零偏校准值
-    accel_offset: Vector3<f32>,
-    /// 原始陀螺仪数据（未校准）
-    gyro_raw: Vector3<f32>,
-    /// 陀螺仪零偏校准值
-    gyro_offset: Vector3<f32>,
+    /// 与总线无关的状态
+    core: SolverCore<F>,
+}
+
+/// 一次采集的传感器数据
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Reading {
+    /// 加速度
+    pub accel: Acceleration,
+    /// 角速度
+    pub gyro: AngularRate,
+    /// 芯片温度（℃）
+    pub temperature: f32,
+    /// 磁场，没有磁力计时为零
+    pub mag: MagneticField,
 }
 
 impl<I2C, E> Mpu6050MadgwickSolver<I2C>
 where
     I2C: I2c<Error = E>,
 {
-    /// 创建新的MPU6050姿态解算器实例
+    /// 创建使用Madgwick滤波器的MPU6050姿态解算器实例
     ///
     /// # 参数
     /// - `i2c`: I2C总线实例
@@ -41,13 +157,27 @@ where
     /// # 返回值
     /// 初始化后的姿态解算器实例
     pub fn new(i2c: I2C, sample_period: f32, beta: f32) -> Self {
+        Self::with_filter(i2c, Madgwick::new(sample_period, beta))
+    }
+}
+
+impl<I2C, E, F> Mpu6050MadgwickSolver<I2C, F>
+where
+    I2C: I2c<Error = E>,
+    F: OrientationFilter,
+{
+    /// 创建使用指定滤波器的MPU6050姿态解算器实例
+    ///
+    /// # 参数
+    /// - `i2c`: I2C总线实例
+    /// - `filter`: 姿态滤波器，采样周期须与调用[`Self::update`]的周期一致
+    ///
+    /// # 返回值
+    /// 初始化后的姿态解算器实例
+    pub fn with_filter(i2c: I2C, filter: F) -> Self {
         Self {
             mpu: Mpu6050::new(i2c),
-            filter: Madgwick::new(sample_period, beta),
-            accel_raw: Vector3::zeros(),
-            accel_offset: Vector3::new(0.059909668, -0.022489013, 0.07658446),
-            gyro_raw: Vector3::zeros(),
-            gyro_offset: Vector3::new(0.11233792, -0.052522425, 0.006111393),
+            core: SolverCore::new(filter),
         }
     }
 
@@ -55,10 +185,12 @@ where
     ///
     /// 执行以下初始化步骤：
     /// 1. 唤醒传感器并重置配置
-    /// 2. 设置陀螺仪量程为±500°/s
-    /// 3. 设置加速度计量程为±4g
+    /// 2. 设置陀螺仪量程（默认±500°/s）
+    /// 3. 设置加速度计量程（默认±4g）
     /// 4. 配置数字低通滤波器为模式2（加速度计94Hz/陀螺仪98Hz）
-    /// 5. 配置加速度计高通滤波器为5Hz
+    /// 5. 配置采样率（默认100Hz）
+    /// 6. 配置加速度计高通滤波器为5Hz
+    /// 7. 之前检测到磁力计时，重新配置辅助总线和磁力计
     ///
     /// # 返回值
     /// - `Ok(())`: 初始化成功
@@ -69,124 +201,338 @@ where
         // 唤醒传感器并应用默认配置
         self.mpu.init(&mut delay)?;
 
-        // 设置陀螺仪量程（±500°/s）
-        self.mpu.set_gyro_range(device::GyroRange::D500)?;
-
-        // 设置加速度计量程（±4g）
-        self.mpu.set_accel_range(device::AccelRange::G4)?;
+        // 设置量程（默认±500°/s、±4g）
+        self.set_gyro_range(self.core.gyro_range)?;
+        self.set_accel_range(self.core.accel_range)?;
 
         // 设置数字低通滤波器 - 针对100Hz积分频率
         // 模式2：加速度计94Hz/陀螺仪98Hz
-        self.set_dlpf_mode(2)?;
+        self.set_dlpf_mode(self.core.dlpf_mode)?;
+
+        // 设置传感器采样率（默认1kHz / (1 + 9) = 100Hz），用于FIFO采样
+        self.set_sample_rate_divider(self.core.sample_rate_divider)?;
 
         // 设置加速度计高通滤波器 - 5Hz适合姿态解算
         // 滤除低频噪声，保留有效运动信号
         self.mpu.set_accel_hpf(device::ACCEL_HPF::_5)?;
 
+        // 复位清除了I2C主机配置
+        if self.core.magnetometer.is_some() {
+            self.enable_magnetometer()?;
+        }
+
         Ok(())
     }
 
-    /// 传感器校准方法
+    /// 检测并配置MPU6050辅助I2C总线上的磁力计
+    ///
+    /// 使能MPU6050的I2C主机，依次尝试[`MagnetometerChip::ALL`]中的型号，识别后写入配置，
+    /// 并让I2C主机在每个采样周期读取磁力计数据。之后的读数和FIFO采样都包含磁场。
+    /// 须在[`Self::init`]之后、[`Self::enable_fifo`]之前调用。
+    ///
+    /// # 返回值
+    /// - `Ok(Some(Magnetometer))`: 检测到的磁力计
+    /// - `Ok(None)`: 没有磁力计应答，I2C主机被关闭，只使用陀螺仪和加速度计
+    /// - `Err(Mpu6050Error<E>)`: 与MPU6050通信时发生的错误
+    pub fn enable_magnetometer(&mut self) -> Result<Option<Magnetometer>, Mpu6050Error<E>> {
+        self.core.magnetometer = None;
+        self.mpu.write_byte(I2C_MST_CTRL, I2C_MST_CTRL_400KHZ)?;
+        self.mpu.write_byte(USER_CTRL, USER_CTRL_I2C_MST_EN)?;
+
+        for chip in MagnetometerChip::ALL {
+            if self.configure_aux(chip)? {
+                self.core.magnetometer = Some(Magnetometer {
+                    chip,
+                    link: MagnetometerLink::Aux,
+                });
+                return Ok(self.core.magnetometer);
+            }
+        }
+
+        self.mpu.write_byte(USER_CTRL, 0)?;
+        Ok(None)
+    }
+
+    /// 陀螺仪零偏校准方法
     ///
     /// 执行以下校准步骤：
     /// 1. 采集100次传感器数据（间隔10ms）
-    /// 2. 计算加速度计和陀螺仪的平均值作为零偏
-    /// 3. 针对加速度计Z轴减去1g（重力加速度）
+    /// 2. 计算陀螺仪的平均值作为零偏，并记录此时的芯片温度
+    ///
+    /// 加速度计需要通过六面校准（见[`Self::average`]和
+    /// [`SixFaceCalibration`](crate::app::imu::accel_calibration::SixFaceCalibration)）
+    /// 求得零偏和灵敏度，结果用[`Self::set_accel_calibration`]设置。
     ///
     /// # 注意
-    /// 校准时需保持传感器静止且水平放置
+    /// 校准时需保持传感器静止，不要求水平放置
     ///
     /// # 返回值
     /// - `Ok(())`: 校准成功
     /// - `Err(Mpu6050Error<E>)`: 校准过程中发生的错误
     pub async fn calibration(&mut self) -> Result<(), Mpu6050Error<E>> {
-        // 初始化累加器
-        let mut accel_sum = Vector3::zeros();
-        let mut gyro_sum = Vector3::zeros();
-
         // 校准采样次数（100次）
         const TIMES: u8 = 100;
 
+        // 计算陀螺仪零偏（平均值）
+        let mean = self.average(TIMES).await?;
+        self.core.gyro_offset = mean.gyro;
+        self.core.gyro_offset_temperature = Some(mean.temperature);
+
+        Ok(())
+    }
+
+    /// 以10ms间隔采集`times`次数据，返回未校准数据的平均值
+    ///
+    /// 用于零偏校准、六面校准中每个面的读数和预热校准中各温度下的零偏。
+    ///
+    /// # 返回值
+    /// - `Ok(Reading)`: 平均值
+    /// - `Err(Mpu6050Error<E>)`: 采集过程中发生的错误
+    pub async fn average(&mut self, times: u8) -> Result<Reading, Mpu6050Error<E>> {
+        // 初始化累加器
+        let mut accel_sum = Acceleration::zeros();
+        let mut gyro_sum = AngularRate::zeros();
+        let mut mag_sum = MagneticField::zeros();
+        let mut temperature_sum = 0.0;
+
         // 创建10ms间隔的定时器
         let delay = embassy_time::Duration::from_millis(10);
         let mut ticker = Ticker::every(delay);
 
         // 循环采集数据
-        for _ in 0..TIMES {
-            // 累加加速度计原始数据（转换为f32）
-            accel_sum += self.mpu.get_acc()?.map(|v| v as f32);
-
-            // 累加陀螺仪原始数据（转换为f32）
-            gyro_sum += self.mpu.get_gyro()?.map(|v| v as f32);
+        for _ in 0..times {
+            // 累加换算后的传感器数据
+            let reading = self.read()?;
+            accel_sum = accel_sum + reading.accel;
+            gyro_sum = gyro_sum + reading.gyro;
+            mag_sum = mag_sum + reading.mag;
+            temperature_sum += reading.temperature;
 
             // 等待下一个采样点
             ticker.next().await;
         }
 
-        // 计算加速度计零偏（平均值）
-        self.accel_offset = accel_sum / TIMES as f32;
-
-        // 针对重力加速度修正Z轴（减去1g）
-        // 假设传感器Z轴向上时受+1g重力
-        self.accel_offset.z -= 1.0_f32;
-
-        // 计算陀螺仪零偏（平均值）
-        self.gyro_offset = gyro_sum / TIMES as f32;
-
-        Ok(())
+        let count = times.max(1) as f32;
+        Ok(Reading {
+            accel: accel_sum / count,
+            gyro: gyro_sum / count,
+            temperature: temperature_sum / count,
+            mag: mag_sum / count,
+        })
     }
 
     /// 获取传感器最新数据
     ///
-    /// 从MPU6050读取最新的加速度计和陀螺仪数据，
-    /// 并将原始数据转换为f32格式存储
+    /// 从MPU6050读取最新的加速度计、陀螺仪和温度数据，
+    /// 并按当前量程换算为物理单位存储
     ///
     /// # 返回值
     /// - `Ok(&mut Self)`: 成功获取数据，返回自身可变引用
     /// - `Err(Mpu6050Error<E>)`: 数据读取过程中发生的错误
     pub async fn get_data(&mut self) -> Result<&mut Self, Mpu6050Error<E>> {
-        // 读取加速度计数据并转换为f32
-        self.accel_raw = self.mpu.get_acc()?.map(|v| v as f32);
-
-        // 读取陀螺仪数据并转换为f32
-        self.gyro_raw = self.mpu.get_gyro()?.map(|v| v as f32);
+        self.core.raw = self.read()?;
 
         Ok(self)
     }
 
-    /// 更新姿态解算结果
+    /// 使能FIFO和数据就绪中断
+    ///
+    /// 传感器每个采样周期将加速度、温度和角速度（以及辅助总线上的磁场）写入FIFO，并拉高INT引脚
+    /// （高电平有效，保持到读取任意寄存器）。之后等待INT引脚变为高电平，
+    /// 再调用[`Self::update_from_fifo`]。FIFO先被清空。
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 配置成功
+    /// - `Err(Mpu6050Error<E>)`: 配置过程中发生的错误
+    pub fn enable_fifo(&mut self) -> Result<(), Mpu6050Error<E>> {
+        self.mpu.write_byte(INT_PIN_CFG, INT_LATCHED)?;
+        self.mpu.write_byte(INT_ENABLE, INT_DATA_READY)?;
+        self.mpu.write_byte(FIFO_EN, self.fifo_sources())?;
+        self.reset_fifo()
+    }
+
+    /// 清空FIFO，丢弃排队的采样
+    ///
+    /// 长时间不读取FIFO（如校准时直接读取数据寄存器）后调用，避免FIFO溢出。
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 清空成功
+    /// - `Err(Mpu6050Error<E>)`: 配置过程中发生的错误
+    pub fn reset_fifo(&mut self) -> Result<(), Mpu6050Error<E>> {
+        let i2c_master = self.i2c_master();
+        self.mpu
+            .write_byte(USER_CTRL, USER_CTRL_FIFO_RESET | i2c_master)?;
+        self.mpu
+            .write_byte(USER_CTRL, USER_CTRL_FIFO_EN | i2c_master)
+    }
+
+    /// 读取FIFO中排队的全部采样，依次更新姿态
     ///
-    /// 使用最新采集的传感器数据和校准参数，
-    /// 通过Madgwick算法更新姿态四元数
+    /// 每次传输连续读出至多[`FIFO_BURST_SAMPLES`]个采样，每个采样都以传感器的采样周期
+    /// （[`Self::sample_period`]）积分。FIFO溢出时排队的数据已错位，清空FIFO后返回0。
+    /// 滤波器拒绝某个采样（加速度全为零，通常是传感器已复位）时停止处理并返回错误，
+    /// 同一次传输中读出的其余采样被丢弃，重试前应调用[`Self::reset_fifo`]。
     ///
     /// # 返回值
-    /// - `Ok(&UnitQuaternion<f32>)`: 成功更新，返回姿态四元数引用
-    /// - `Err(AhrsError)`: 姿态解算过程中发生的错误
-    pub async fn update(&mut self) -> Result<&UnitQuaternion<f32>, AhrsError> {
-        // 应用校准参数：陀螺仪数据减去零偏
-        let calibrated_gyro = self.gyro_raw - self.gyro_offset;
+    /// - `Ok(usize)`: 处理的采样数，最新的采样可通过[`Self::acceleration`]等读取
+    /// - `Err(ImuError<E>)`: 读取过程中发生的错误，或滤波器拒绝了采样
+    pub fn update_from_fifo(&mut self) -> Result<usize, ImuError<E>> {
+        self.update_from_fifo_with(|_, _| {})
+    }
+
+    /// 与[`Self::update_from_fifo`]相同，并把每个采样校准后的加速度和角速度交给`on_sample`
+    ///
+    /// 用于需要完整采样序列的应用层算法，如计步器。采样周期为[`Self::sample_period`]。
+    pub fn update_from_fifo_with(
+        &mut self,
+        mut on_sample: impl FnMut(&Acceleration, &AngularRate),
+    ) -> Result<usize, ImuError<E>> {
+        // 读取中断状态，同时清除INT引脚电平
+        let status = self.mpu.read_byte(INT_STATUS)?;
+        if status & INT_FIFO_OVERFLOW != 0 {
+            self.reset_fifo()?;
+            return Ok(0);
+        }
 
-        // 应用校准参数：加速度计数据减去零偏
-        let calibrated_accel = self.accel_raw - self.accel_offset;
+        let mut count = [0; 2];
+        self.mpu.read_bytes(FIFO_COUNTH, &mut count)?;
+        let len = self.sample_len();
+        let samples = u16::from_be_bytes(count) as usize / len;
 
-        // 更新Madgwick滤波器
-        self.filter.update_imu(&calibrated_gyro, &calibrated_accel)
+        self.core.filter.set_sample_period(self.sample_period());
+        let mut remaining = samples;
+        while remaining > 0 {
+            let burst = remaining.min(FIFO_BURST_SAMPLES);
+            let mut buf = [0; FIFO_BURST_SAMPLES * MAX_SAMPLE_LEN];
+            let buf = &mut buf[..burst * len];
+            self.mpu.read_bytes(FIFO_R_W, buf)?;
+            for sample in buf.chunks_exact(len) {
+                self.update_sample(sample)?;
+                on_sample(&self.acceleration(), &self.angular_rate());
+            }
+            remaining -= burst;
+        }
+
+        Ok(samples)
+    }
+
+    /// 设置采样率分频
+    ///
+    /// 采样率 = 陀螺仪输出频率 / (1 + `divider`)，例如低通滤波器开启时9对应100Hz。
+    /// 在[`Self::init`]之前调用时，初始化会使用该分频。
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 配置成功
+    /// - `Err(Mpu6050Error<E>)`: 配置过程中发生的错误
+    pub fn set_sample_rate_divider(&mut self, divider: u8) -> Result<(), Mpu6050Error<E>> {
+        self.mpu.write_byte(SMPLRT_DIV, divider)?;
+        self.core.sample_rate_divider = divider;
+        Ok(())
+    }
+
+    /// 设置陀螺仪量程
+    ///
+    /// 立即写入传感器，之后的读数按新量程换算。
+    /// 在[`Self::init`]之前调用时，初始化会使用该量程。
+    ///
+    /// # 返回值
+    /// - `Ok(())`: 配置成功
+    /// - `Err(Mpu6050Error<E>)`: 配置过程中发生的错误
+    pub fn set_gyro_range(&mut self, range: GyroRange) -> Result<(), Mpu6050Error<E>> {
+        self.mpu.set_gyro_range(match range {
+            GyroRange::D250 => device::GyroRange::D250,
+            GyroRange::D500 => device::GyroRange::D500,
+            GyroRange::D1000 => device::GyroRange::D1000,
+            GyroRange::D2000 => device::GyroRange::D2000,
+        })?;
+        self.core.gyro_range = range;
+        Ok(())
     }
 
-    /// 获取加速度计零偏校准值
+    /// 设置加速度计量程
+    ///
+    /// 立即写入传感器，之后的读数按新量程换算。
+    /// 在[`Self::init`]之前调用时，初始化会使用该量程。
     ///
     /// # 返回值
-    /// 加速度计的零偏校准向量
-    pub fn get_accel_offset(&mut self) -> Vector3<f32> {
-        self.accel_offset
+    /// - `Ok(())`: 配置成功
+    /// - `Err(Mpu6050Error<E>)`: 配置过程中发生的错误
+    pub fn set_accel_range(&mut self, range: AccelRange) -> Result<(), Mpu6050Error<E>> {
+        self.mpu.set_accel_range(match range {
+            AccelRange::G2 => device::AccelRange::G2,
+            AccelRange::G4 => device::AccelRange::G4,
+            AccelRange::G8 => device::AccelRange::G8,
+            AccelRange::G16 => device::AccelRange::G16,
+        })?;
+        self.core.accel_range = range;
+        Ok(())
+    }
+
+    /// 读取一次加速度计、陀螺仪和温度数据，按当前量程换算
+    ///
+    /// 一次连续读取加速度计、温度和陀螺仪的14个数据寄存器（有磁力计时连同其后的
+    /// 磁力计数据），不依赖驱动内部记录的灵敏度。
+    fn read(&mut self) -> Result<Reading, Mpu6050Error<E>> {
+        let len = self.sample_len();
+        let mut buf = [0; MAX_SAMPLE_LEN];
+        self.mpu.read_bytes(ACCEL_XOUT_H, &mut buf[..len])?;
+        Ok(self.parse(&buf[..len]))
     }
 
-    /// 获取陀螺仪零偏校准值
+    /// 识别并配置辅助总线上的磁力计，成功后由从机0在每个采样周期读取其数据寄存器
     ///
     /// # 返回值
-    /// 陀螺仪的零偏校准向量
-    pub fn get_gyro_offset(&mut self) -> Vector3<f32> {
-        self.gyro_offset
+    /// 磁力计应答且识别寄存器正确时返回`true`
+    fn configure_aux(&mut self, chip: MagnetometerChip) -> Result<bool, Mpu6050Error<E>> {
+        let (register, id) = chip.id();
+        if self.aux_read(chip.address(), register)? != Some(id) {
+            return Ok(false);
+        }
+        for &(register, value) in chip.config() {
+            if !self.aux_write(chip.address(), register, value)? {
+                return Ok(false);
+            }
+        }
+
+        self.mpu
+            .write_byte(I2C_SLV0_ADDR, I2C_SLV_READ | chip.address())?;
+        self.mpu.write_byte(I2C_SLV0_REG, chip.data_register())?;
+        self.mpu
+            .write_byte(I2C_SLV0_CTRL, I2C_SLV_EN | MAG_DATA_LEN as u8)?;
+        Ok(true)
+    }
+
+    /// 通过辅助从机4读一个寄存器，从机无应答时返回`None`
+    fn aux_read(&mut self, address: u8, register: u8) -> Result<Option<u8>, Mpu6050Error<E>> {
+        self.mpu.write_byte(I2C_SLV4_ADDR, I2C_SLV_READ | address)?;
+        self.mpu.write_byte(I2C_SLV4_REG, register)?;
+        self.mpu.write_byte(I2C_SLV4_CTRL, I2C_SLV_EN)?;
+        if !self.aux_wait()? {
+            return Ok(None);
+        }
+        self.mpu.read_byte(I2C_SLV4_DI).map(Some)
+    }
+
+    /// 通过辅助从机4写一个寄存器，返回从机是否应答
+    fn aux_write(&mut self, address: u8, register: u8, value: u8) -> Result<bool, Mpu6050Error<E>> {
+        self.mpu.write_byte(I2C_SLV4_ADDR, address)?;
+        self.mpu.write_byte(I2C_SLV4_REG, register)?;
+        self.mpu.write_byte(I2C_SLV4_DO, value)?;
+        self.mpu.write_byte(I2C_SLV4_CTRL, I2C_SLV_EN)?;
+        self.aux_wait()
+    }
+
+    /// 等待从机4传输完成，返回从机是否应答；超时视为无应答
+    fn aux_wait(&mut self) -> Result<bool, Mpu6050Error<E>> {
+        let mut delay = embassy_time::Delay;
+        for _ in 0..AUX_POLL_TIMES {
+            let status = self.mpu.read_byte(I2C_MST_STATUS)?;
+            if status & I2C_SLV4_DONE != 0 {
+                return Ok(status & I2C_SLV4_NACK == 0);
+            }
+            delay.delay_ms(1);
+        }
+        Ok(false)
     }
 
     /// 设置数字低通滤波器(DLPF)模式
@@ -215,8 +561,388 @@ where
         let value = dlpf_cfg & 0x07;
 
         // 写入CONFIG寄存器(地址0x1A)
-        self.mpu.write_byte(0x1A, value)?;
+        self.mpu.write_byte(CONFIG, value)?;
+        self.core.dlpf_mode = value;
 
         Ok(())
     }
 }
+
+impl<I2C, F> Deref for Mpu6050MadgwickSolver<I2C, F> {
+    type Target = SolverCore<F>;
+
+    fn deref(&self) -> &SolverCore<F> {
+        &self.core
+    }
+}
+
+impl<I2C, F> DerefMut for Mpu6050MadgwickSolver<I2C, F> {
+    fn deref_mut(&mut self) -> &mut SolverCore<F> {
+        &mut self.core
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::imu::accel_calibration::AccelCalibration;
+    use crate::app::imu::filter::ComplementaryFilter;
+    use crate::app::imu::kinematics::Motion;
+    use crate::app::imu::magnetometer::MagCalibration;
+    use crate::app::imu::mounting::{Axis, Mounting};
+    use crate::app::imu::temperature::GyroDriftModel;
+    use crate::hardware::config::{ImuCalibration, SETTINGS_SIZE, Settings, Uid};
+    use crate::hardware::flash_store::KvStore;
+    use crate::hardware::mock_mpu::{MockMag, MockMpu};
+    use crate::hardware::ram_flash::RamFlash;
+    use nalgebra::{Matrix3, UnitQuaternion, Vector3};
+
+    /// 北半球中纬度的地磁场（Gs），地球坐标系X轴指向磁北
+    const EARTH_FIELD: Vector3<f32> = Vector3::new(0.3, 0.0, -0.4);
+
+    #[test]
+    fn readings_are_independent_of_range() {
+        // 静止水平放置（1g），同时绕Z轴以90°/s转动
+        let mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 90.0));
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+
+        for (gyro_range, accel_range) in GyroRange::ALL.into_iter().zip(AccelRange::ALL) {
+            solver.set_gyro_range(gyro_range).unwrap();
+            solver.set_accel_range(accel_range).unwrap();
+            assert_eq!(solver.gyro_range(), gyro_range);
+            assert_eq!(solver.accel_range(), accel_range);
+
+            let Reading { accel, gyro, .. } = solver.read().unwrap();
+            assert!(
+                (accel.as_g() - Vector3::z()).amax() < 1e-3,
+                "{accel_range:?}"
+            );
+            assert!(
+                (gyro.as_rad_per_s().z - 90f32.to_radians()).abs() < 1e-3,
+                "{gyro_range:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn applies_accel_calibration() {
+        // Z轴灵敏度偏高5%、X轴零偏0.1g
+        let mock = MockMpu::new(Vector3::new(0.1, 0.0, 1.05), Vector3::zeros());
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        solver.set_accel_range(AccelRange::G4).unwrap();
+        solver.raw = solver.read().unwrap();
+
+        let mut calibration = AccelCalibration::from_bias(Vector3::new(0.1, 0.0, 0.0));
+        calibration.transform[(2, 2)] = 1.0 / 1.05;
+        solver.set_accel_calibration(calibration);
+        assert!((solver.acceleration().as_g() - Vector3::z()).amax() < 1e-3);
+        assert_eq!(solver.get_accel_offset().as_g().x, 0.1);
+    }
+
+    #[test]
+    fn compensates_gyro_drift_with_temperature() {
+        // 零偏每升温1℃增加0.05°/s，25℃时为1°/s
+        let drift = GyroDriftModel {
+            reference: 25.0,
+            coefficients: [
+                Vector3::repeat(1f32.to_radians()),
+                Vector3::repeat(0.05f32.to_radians()),
+                Vector3::zeros(),
+            ],
+        };
+        let mut mock = MockMpu::new(Vector3::z(), drift.bias(35.0).as_deg_per_s());
+        mock.temperature = 35.0;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        solver.set_gyro_range(GyroRange::D250).unwrap();
+        solver.gyro_offset = drift.bias(25.0);
+        solver.gyro_offset_temperature = Some(25.0);
+        solver.raw = solver.read().unwrap();
+        assert!((solver.temperature() - 35.0).abs() < 0.01);
+
+        // 没有温度模型时只减去25℃的零偏，残留0.5°/s
+        let residual = solver.angular_rate().as_deg_per_s();
+        assert!((residual - Vector3::repeat(0.5)).amax() < 0.02);
+
+        solver.set_gyro_drift_model(Some(drift));
+        assert!(solver.angular_rate().as_deg_per_s().amax() < 0.02);
+    }
+
+    #[test]
+    fn calibration_survives_remount() {
+        const UID: Uid = [7; 12];
+        let mut store = KvStore::new(RamFlash::<4096, 2, 1024>::new(), 0, SETTINGS_SIZE).unwrap();
+        Settings::default().save(&mut store).unwrap();
+
+        let mut solver =
+            Mpu6050MadgwickSolver::new(MockMpu::new(Vector3::z(), Vector3::zeros()), 0.01, 0.1);
+        assert_eq!(solver.load_calibration(&mut store, &UID), Ok(false));
+        assert_eq!(solver.imu_calibration(), ImuCalibration::default());
+
+        solver.set_accel_calibration(AccelCalibration::from_bias(Vector3::new(0.06, -0.02, 0.08)));
+        solver.gyro_offset = AngularRate::from_rad_per_s(Vector3::new(0.11, -0.05, 0.006));
+        solver.gyro_offset_temperature = Some(27.0);
+        let mag = MagCalibration {
+            offset: Vector3::new(0.1, -0.05, 0.2),
+            transform: Matrix3::from_diagonal_element(1.1),
+        };
+        solver.set_mag_calibration(mag);
+        let mounting = Mounting::from_axes(Axis::NegY, Axis::PosZ).unwrap();
+        solver.set_mounting(mounting);
+        solver.store_calibration(&mut store, &UID).unwrap();
+        let saved = solver.imu_calibration();
+
+        let mut store = KvStore::new(store.release(), 0, SETTINGS_SIZE).unwrap();
+        let mut solver =
+            Mpu6050MadgwickSolver::new(MockMpu::new(Vector3::z(), Vector3::zeros()), 0.01, 0.1);
+        assert_eq!(solver.load_calibration(&mut store, &[8; 12]), Ok(false));
+        assert_eq!(solver.load_calibration(&mut store, &UID), Ok(true));
+        assert_eq!(solver.imu_calibration(), saved);
+        assert_eq!(*solver.mag_calibration(), mag);
+        assert_eq!(*solver.mounting(), mounting);
+        assert_eq!(Settings::load(&mut store), Ok(Some(Settings::default())));
+    }
+
+    #[test]
+    fn mounting_gives_identical_attitude_on_rotated_sensor() {
+        // 设备倾斜放置并绕自身Z轴转动，电路板竖直安装：传感器X轴朝上、Z轴朝前
+        let attitude = UnitQuaternion::from_euler_angles(0.3, -0.2, 0.0);
+        let accel = attitude.inverse() * Vector3::z();
+        let gyro = Vector3::new(0.0, 0.0, 45.0);
+        let mounting = Mounting::from_axes(Axis::PosZ, Axis::PosX).unwrap();
+        let to_sensor = mounting.quaternion().inverse();
+
+        let mut reference = MockMpu::new(accel, gyro);
+        reference.fifo = 200;
+        let mut reference =
+            Mpu6050MadgwickSolver::with_filter(reference, ComplementaryFilter::new(0.01, 0.02));
+        let mut mounted = MockMpu::new(to_sensor * accel, to_sensor * gyro);
+        mounted.fifo = 200;
+        let mut mounted =
+            Mpu6050MadgwickSolver::with_filter(mounted, ComplementaryFilter::new(0.01, 0.02));
+        mounted.set_mounting(mounting);
+
+        assert_eq!(reference.update_from_fifo().unwrap(), 200);
+        assert_eq!(mounted.update_from_fifo().unwrap(), 200);
+        let difference = reference.acceleration() - mounted.acceleration();
+        assert!(difference.as_g().amax() < 1e-3);
+        let difference = reference.angular_rate() - mounted.angular_rate();
+        assert!(difference.as_deg_per_s().amax() < 0.05);
+
+        let error = reference.quaternion().angle_to(mounted.quaternion());
+        assert!(error.to_degrees() < 0.05, "{}°", error.to_degrees());
+    }
+
+    #[test]
+    fn sample_period_follows_divider_and_dlpf() {
+        let mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        assert!((solver.sample_period() - 0.01).abs() < 1e-6);
+
+        solver.set_sample_rate_divider(4).unwrap();
+        assert!((solver.sample_period() - 0.005).abs() < 1e-6);
+        solver.set_dlpf_mode(0).unwrap();
+        assert!((solver.sample_period() - 0.000625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_rate_divider_follows_dlpf() {
+        let mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        assert_eq!(solver.sample_rate_divider_for(100), Some(9));
+        assert_eq!(solver.sample_rate_divider_for(1000), Some(0));
+        // 不能整除、分频超过255或采样率为零
+        assert_eq!(solver.sample_rate_divider_for(300), None);
+        assert_eq!(solver.sample_rate_divider_for(2), None);
+        assert_eq!(solver.sample_rate_divider_for(0), None);
+
+        solver.set_dlpf_mode(0).unwrap();
+        let divider = solver.sample_rate_divider_for(100).unwrap();
+        assert_eq!(divider, 79);
+        solver.set_sample_rate_divider(divider).unwrap();
+        assert!((solver.sample_period() - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn integrates_every_fifo_sample_at_sensor_period() {
+        // 绕Z轴以90°/s转动，FIFO中排队0.5秒的采样
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 90.0));
+        mock.fifo = 50;
+        // 构造时的周期与传感器采样周期（10ms）不同，应以传感器为准
+        let mut solver =
+            Mpu6050MadgwickSolver::with_filter(&mut mock, ComplementaryFilter::new(0.05, 0.02));
+        solver.set_gyro_range(GyroRange::D250).unwrap();
+
+        assert_eq!(solver.update_from_fifo().unwrap(), 50);
+        assert_eq!(solver.update_from_fifo().unwrap(), 0);
+        let (_, _, yaw) = solver.quaternion().euler_angles();
+        assert!(
+            (yaw.to_degrees() - 45.0).abs() < 0.5,
+            "{}",
+            yaw.to_degrees()
+        );
+
+        // 每次传输读出多个采样
+        assert_eq!(mock.fifo_reads, 50usize.div_ceil(FIFO_BURST_SAMPLES));
+    }
+
+    #[test]
+    fn passes_every_fifo_sample_to_the_caller() {
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 90.0));
+        mock.fifo = 20;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        solver.set_accel_range(AccelRange::G4).unwrap();
+        solver.set_gyro_range(GyroRange::D250).unwrap();
+
+        let mut count = 0;
+        let updated = solver.update_from_fifo_with(|accel, gyro| {
+            assert!((accel.as_g() - Vector3::z()).norm() < 1e-3);
+            assert!((gyro.as_deg_per_s().z - 90.0).abs() < 0.1);
+            count += 1;
+        });
+        assert_eq!(updated.unwrap(), 20);
+        assert_eq!(count, 20);
+    }
+
+    #[test]
+    fn removes_gravity_and_detects_stillness() {
+        // 倾斜静止放置，FIFO中排队3秒的采样
+        let attitude = UnitQuaternion::from_euler_angles(0.3, -0.2, 0.0);
+        let mut mock = MockMpu::new(attitude.inverse() * Vector3::z(), Vector3::zeros());
+        mock.fifo = 300;
+        let mut solver =
+            Mpu6050MadgwickSolver::with_filter(mock, ComplementaryFilter::new(0.01, 0.1));
+        solver.set_accel_range(AccelRange::G2).unwrap();
+        solver.set_gyro_range(GyroRange::D250).unwrap();
+
+        assert_eq!(solver.update_from_fifo().unwrap(), 300);
+        let motion = solver.motion();
+        assert!(motion.still);
+        assert_eq!(motion.velocity, Vector3::zeros());
+        assert!(motion.linear_acceleration.amax() < 0.05, "{motion:?}");
+
+        solver.reset_motion();
+        assert_eq!(solver.motion(), Motion::default());
+
+        // 原地转动时不是静止，一直在积分
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::new(0.0, 0.0, 30.0));
+        mock.fifo = 100;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        assert_eq!(solver.update_from_fifo().unwrap(), 100);
+        let motion = solver.motion();
+        assert!(!motion.still);
+        assert!((motion.moving_time - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn zeroed_sample_stops_fifo_update() {
+        // 传感器复位后处于睡眠状态，数据全为零
+        let mut mock = MockMpu::new(Vector3::zeros(), Vector3::zeros());
+        mock.fifo = 5;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+
+        let error = solver.update_from_fifo().unwrap_err();
+        assert!(matches!(error, ImuError::Filter(_)), "{error:?}");
+        assert_eq!(*solver.quaternion(), UnitQuaternion::identity());
+
+        solver.reset_fifo().unwrap();
+        assert_eq!(solver.update_from_fifo().unwrap(), 0);
+    }
+
+    #[test]
+    fn fifo_overflow_discards_queued_samples() {
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        mock.fifo = 73;
+        mock.registers[INT_STATUS as usize] = INT_FIFO_OVERFLOW | INT_DATA_READY;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+
+        assert_eq!(solver.update_from_fifo().unwrap(), 0);
+        let mut count = [0xFF; 2];
+        solver.mpu.read_bytes(FIFO_COUNTH, &mut count).unwrap();
+        assert_eq!(count, [0, 0]);
+    }
+
+    #[test]
+    fn reads_magnetometer_through_aux_bus() {
+        for chip in MagnetometerChip::ALL {
+            let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+            mock.mag = Some(MockMag::new(chip, MagnetometerLink::Aux, EARTH_FIELD));
+            mock.fifo = 10;
+            let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+
+            let magnetometer = solver.enable_magnetometer().unwrap();
+            assert_eq!(
+                magnetometer,
+                Some(Magnetometer {
+                    chip,
+                    link: MagnetometerLink::Aux
+                })
+            );
+
+            // 每个FIFO采样连同磁力计数据共20字节
+            let sources = solver.fifo_sources();
+            assert_eq!(sources, FIFO_EN_SENSORS | FIFO_EN_SLV0);
+            solver.mpu.write_byte(FIFO_EN, sources).unwrap();
+            assert_eq!(solver.update_from_fifo().unwrap(), 10);
+            let field = solver.magnetic_field();
+            assert!((field.as_gauss() - EARTH_FIELD).amax() < 1e-3, "{chip:?}");
+
+            solver.enable_fifo().unwrap();
+            assert_eq!(
+                solver.mpu.read_byte(USER_CTRL).unwrap(),
+                USER_CTRL_FIFO_EN | USER_CTRL_I2C_MST_EN
+            );
+            assert_eq!(solver.update_from_fifo().unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn missing_magnetometer_disables_i2c_master() {
+        let mut solver =
+            Mpu6050MadgwickSolver::new(MockMpu::new(Vector3::z(), Vector3::zeros()), 0.01, 0.1);
+
+        assert_eq!(solver.enable_magnetometer().unwrap(), None);
+        assert_eq!(solver.mpu.read_byte(USER_CTRL).unwrap(), 0);
+        solver.enable_fifo().unwrap();
+        assert_eq!(solver.mpu.read_byte(FIFO_EN).unwrap(), FIFO_EN_SENSORS);
+        assert_eq!(solver.magnetic_field(), MagneticField::zeros());
+    }
+
+    #[test]
+    fn heading_follows_magnetic_north_and_declination() {
+        // 水平放置，机头指向磁北以东30°（绕Z轴转-30°）
+        let attitude = UnitQuaternion::from_euler_angles(0.0, 0.0, -30f32.to_radians());
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        mock.mag = Some(MockMag::new(
+            MagnetometerChip::Qmc5883l,
+            MagnetometerLink::Aux,
+            attitude.inverse() * EARTH_FIELD,
+        ));
+        mock.fifo = 600;
+        let mut solver =
+            Mpu6050MadgwickSolver::with_filter(mock, ComplementaryFilter::new(0.01, 0.05));
+        solver.enable_magnetometer().unwrap();
+        solver
+            .mpu
+            .write_byte(FIFO_EN, FIFO_EN_SENSORS | FIFO_EN_SLV0)
+            .unwrap();
+        solver.set_declination(-7f32.to_radians());
+
+        assert_eq!(solver.update_from_fifo().unwrap(), 600);
+        assert!(solver.quaternion().angle_to(&attitude).to_degrees() < 0.5);
+        let heading = solver.heading().to_degrees();
+        assert!((heading - 23.0).abs() < 0.5, "{heading}°");
+    }
+
+    #[test]
+    fn enable_fifo_configures_interrupt() {
+        let mut mock = MockMpu::new(Vector3::z(), Vector3::zeros());
+        mock.fifo = 10;
+        let mut solver = Mpu6050MadgwickSolver::new(mock, 0.01, 0.1);
+        solver.enable_fifo().unwrap();
+
+        assert_eq!(solver.mpu.read_byte(FIFO_EN).unwrap(), FIFO_EN_SENSORS);
+        assert_eq!(solver.mpu.read_byte(INT_ENABLE).unwrap(), INT_DATA_READY);
+        assert_eq!(solver.mpu.read_byte(USER_CTRL).unwrap(), USER_CTRL_FIFO_EN);
+        assert_eq!(solver.update_from_fifo().unwrap(), 0);
+    }
+}
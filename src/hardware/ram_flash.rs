@@ -0,0 +1,119 @@
+use embedded_storage::nor_flash::{
+    ErrorType, NorFlash, NorFlashErrorKind, ReadNorFlash, check_erase, check_read, check_write,
+};
+
+/// 支持统计擦除次数的最大页数
+const MAX_PAGES: usize = 64;
+
+/// 内存模拟闪存
+///
+/// 在主机端测试中代替片上闪存，按NOR闪存的规则工作：擦除后所有字节为`0xFF`，
+/// 与STM32F1相同，只能向处于擦除状态的位置写入。
+/// 还可以模拟写入过程中掉电，并统计每页的擦除次数。
+///
+/// # 泛型参数
+/// - `SIZE`: 容量（字节）
+/// - `WRITE`: 写入粒度（字节）
+/// - `ERASE`: 擦除页长度（字节），页数不超过64
+pub struct RamFlash<const SIZE: usize, const WRITE: usize, const ERASE: usize> {
+    data: [u8; SIZE],
+    erases: [u32; MAX_PAGES],
+    /// 模拟掉电前还能写入的字节数
+    write_budget: usize,
+}
+
+impl<const SIZE: usize, const WRITE: usize, const ERASE: usize> Default
+    for RamFlash<SIZE, WRITE, ERASE>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize, const WRITE: usize, const ERASE: usize> RamFlash<SIZE, WRITE, ERASE> {
+    /// 创建处于擦除状态的闪存
+    pub fn new() -> Self {
+        assert!(SIZE % ERASE == 0 && SIZE / ERASE <= MAX_PAGES);
+        Self {
+            data: [0xFF; SIZE],
+            erases: [0; MAX_PAGES],
+            write_budget: usize::MAX,
+        }
+    }
+
+    /// 闪存内容
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// 指定页的擦除次数
+    pub fn erase_count(&self, page: usize) -> u32 {
+        self.erases[page]
+    }
+
+    /// 模拟掉电：再写入`bytes`字节后，之后的写入只完成一部分并返回错误
+    ///
+    /// 传入`usize::MAX`恢复正常。
+    pub fn fail_after(&mut self, bytes: usize) {
+        self.write_budget = bytes;
+    }
+}
+
+impl<const SIZE: usize, const WRITE: usize, const ERASE: usize> ErrorType
+    for RamFlash<SIZE, WRITE, ERASE>
+{
+    type Error = NorFlashErrorKind;
+}
+
+impl<const SIZE: usize, const WRITE: usize, const ERASE: usize> ReadNorFlash
+    for RamFlash<SIZE, WRITE, ERASE>
+{
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        check_read(self, offset, bytes.len())?;
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        SIZE
+    }
+}
+
+impl<const SIZE: usize, const WRITE: usize, const ERASE: usize> NorFlash
+    for RamFlash<SIZE, WRITE, ERASE>
+{
+    const WRITE_SIZE: usize = WRITE;
+    const ERASE_SIZE: usize = ERASE;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        check_erase(self, from, to)?;
+        let (from, to) = (from as usize, to as usize);
+        self.data[from..to].fill(0xFF);
+        for page in from / ERASE..to / ERASE {
+            self.erases[page] += 1;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        check_write(self, offset, bytes.len())?;
+        let offset = offset as usize;
+        let target = &mut self.data[offset..offset + bytes.len()];
+        if target.iter().any(|&byte| byte != 0xFF) {
+            return Err(NorFlashErrorKind::Other);
+        }
+
+        let written = bytes.len().min(self.write_budget);
+        target[..written].copy_from_slice(&bytes[..written]);
+        if self.write_budget != usize::MAX {
+            self.write_budget -= written;
+        }
+        match written == bytes.len() {
+            true => Ok(()),
+            false => Err(NorFlashErrorKind::Other),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use super::traits::TimeSource;
+use chrono::NaiveDateTime;
+use embassy_time::Instant;
+
+/// 软件时钟
+///
+/// 以设置时刻为基准，加上embassy系统时钟经过的时间得到当前时间。
+/// 不依赖额外硬件，但掉电或复位后时间会丢失，精度取决于系统时钟源。
+pub struct SoftwareClock {
+    /// 最近一次设置的时间
+    base: NaiveDateTime,
+    /// 设置时刻对应的系统时间
+    since: Instant,
+}
+
+impl SoftwareClock {
+    /// 创建软件时钟，从`now`开始走时
+    pub fn new(now: NaiveDateTime) -> Self {
+        Self::started_at(now, Instant::now())
+    }
+
+    /// 创建软件时钟，系统时间为`instant`时的时间为`now`
+    pub fn started_at(now: NaiveDateTime, instant: Instant) -> Self {
+        Self {
+            base: now,
+            since: instant,
+        }
+    }
+
+    /// 系统时间为`instant`时的当前时间，早于设置时刻时返回设置的时间
+    pub fn now_at(&self, instant: Instant) -> NaiveDateTime {
+        let elapsed = instant.saturating_duration_since(self.since);
+        self.base
+            .checked_add_signed(chrono::Duration::microseconds(elapsed.as_micros() as i64))
+            .unwrap_or(self.base)
+    }
+
+    /// 在系统时间为`instant`时将时间设置为`now`
+    pub fn set_at(&mut self, now: NaiveDateTime, instant: Instant) {
+        self.base = now;
+        self.since = instant;
+    }
+}
+
+impl TimeSource for SoftwareClock {
+    fn now(&mut self) -> NaiveDateTime {
+        self.now_at(Instant::now())
+    }
+
+    fn set_now(&mut self, now: NaiveDateTime) {
+        self.set_at(now, Instant::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn datetime(hour: u32, min: u32, sec: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 7, 20)
+            .unwrap()
+            .and_hms_opt(hour, min, sec)
+            .unwrap()
+    }
+
+    #[test]
+    fn advances_with_system_time() {
+        let clock = SoftwareClock::started_at(datetime(18, 0, 0), Instant::from_secs(5));
+        assert_eq!(clock.now_at(Instant::from_secs(5)), datetime(18, 0, 0));
+        assert_eq!(clock.now_at(Instant::from_secs(65)), datetime(18, 1, 0));
+        assert_eq!(
+            clock.now_at(Instant::from_secs(5 + 86_400)),
+            datetime(18, 0, 0) + chrono::Duration::days(1)
+        );
+    }
+
+    #[test]
+    fn setting_the_time_restarts_from_the_new_base() {
+        let mut clock = SoftwareClock::started_at(datetime(18, 0, 0), Instant::from_secs(0));
+        clock.set_at(datetime(9, 30, 0), Instant::from_secs(100));
+        assert_eq!(clock.now_at(Instant::from_secs(100)), datetime(9, 30, 0));
+        assert_eq!(clock.now_at(Instant::from_secs(130)), datetime(9, 30, 30));
+    }
+
+    #[test]
+    fn earlier_system_time_reads_the_base() {
+        let clock = SoftwareClock::started_at(datetime(18, 0, 0), Instant::from_secs(10));
+        assert_eq!(clock.now_at(Instant::from_secs(3)), datetime(18, 0, 0));
+    }
+}
@@ -0,0 +1,491 @@
+//! 与传感器总线无关的姿态解算状态
+//!
+//! 阻塞的[`Mpu6050MadgwickSolver`](super::mpu6050_madgwick_solver::Mpu6050MadgwickSolver)
+//! 和异步的`Mpu6050AsyncSolver`只有读写寄存器的方式不同，二者各持有一个[`SolverCore`]，
+//! 读数换算、校准参数、姿态滤波、运动估计和运动事件检测都由它完成；
+//! 解算器通过`Deref`直接提供这些方法。
+
+use super::config::{ImuCalibration, Uid};
+use super::flash_store::{KvStore, StoreError};
+use super::magnetometer::{MAG_DATA_LEN, Magnetometer, MagnetometerLink};
+use super::mpu6050_madgwick_solver::{
+    FIFO_EN_SENSORS, FIFO_EN_SLV0, MAX_SAMPLE_LEN, Reading, SAMPLE_LEN, USER_CTRL_I2C_MST_EN,
+};
+use crate::app::imu::accel_calibration::AccelCalibration;
+use crate::app::imu::filter::OrientationFilter;
+use crate::app::imu::gesture::{GestureConfig, GestureDetector, MotionEvent, MotionEventChannel};
+use crate::app::imu::kinematics::{Kinematics, Motion, StillnessConfig};
+use crate::app::imu::magnetometer::MagCalibration;
+use crate::app::imu::mounting::Mounting;
+use crate::app::imu::orientation::Orientation;
+use crate::app::imu::temperature::{GyroDriftModel, temperature_from_raw};
+use crate::app::imu::units::{AccelRange, Acceleration, AngularRate, GyroRange, MagneticField};
+use ahrs::AhrsError;
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embedded_storage::nor_flash::NorFlash;
+use nalgebra::{UnitQuaternion, Vector3};
+
+/// 姿态解算器中与总线无关的部分
+///
+/// 保存传感器配置、最近一次读数、校准参数和各算法的状态。
+/// 字段由解算器在读写寄存器后直接更新。
+///
+/// # 泛型参数
+/// - `F`: 姿态滤波器类型
+pub struct SolverCore<F> {
+    /// 姿态滤波器实例
+    pub(crate) filter: F,
+    /// 陀螺仪量程
+    pub(crate) gyro_range: GyroRange,
+    /// 加速度计量程
+    pub(crate) accel_range: AccelRange,
+    /// 数字低通滤波器模式
+    pub(crate) dlpf_mode: u8,
+    /// 采样率分频，采样率 = 陀螺仪输出频率 / (1 + 分频)
+    pub(crate) sample_rate_divider: u8,
+    /// 最近一次采集的数据（未校准）
+    pub(crate) raw: Reading,
+    /// 加速度计校准参数
+    pub(crate) accel_calibration: AccelCalibration,
+    /// 陀螺仪零偏校准值
+    pub(crate) gyro_offset: AngularRate,
+    /// 陀螺仪零偏校准时的芯片温度（℃），未校准时为`None`
+    pub(crate) gyro_offset_temperature: Option<f32>,
+    /// 陀螺仪零偏温度模型
+    pub(crate) gyro_drift: Option<GyroDriftModel>,
+    /// 检测到的磁力计
+    pub(crate) magnetometer: Option<Magnetometer>,
+    /// 磁力计校准参数
+    pub(crate) mag_calibration: MagCalibration,
+    /// 磁偏角（rad），磁北在真北以东为正
+    pub(crate) declination: f32,
+    /// 传感器安装方向
+    pub(crate) mounting: Mounting,
+    /// 线加速度、速度和位移估计
+    pub(crate) kinematics: Kinematics,
+    /// 运动事件检测
+    pub(crate) gestures: GestureDetector,
+}
+
+impl<F: OrientationFilter> SolverCore<F> {
+    /// 以默认配置（±500°/s、±4g、低通滤波器模式2、100Hz采样）创建，未校准
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter,
+            gyro_range: GyroRange::default(),
+            accel_range: AccelRange::default(),
+            dlpf_mode: 2,
+            sample_rate_divider: 9,
+            raw: Reading::default(),
+            accel_calibration: AccelCalibration::default(),
+            gyro_offset: AngularRate::zeros(),
+            gyro_offset_temperature: None,
+            gyro_drift: None,
+            magnetometer: None,
+            mag_calibration: MagCalibration::default(),
+            declination: 0.0,
+            mounting: Mounting::default(),
+            kinematics: Kinematics::default(),
+            gestures: GestureDetector::default(),
+        }
+    }
+
+    /// 更新姿态解算结果
+    ///
+    /// 使用最新采集的传感器数据和校准参数，
+    /// 通过姿态滤波器更新姿态四元数
+    ///
+    /// # 返回值
+    /// - `Ok(&UnitQuaternion<f32>)`: 成功更新，返回姿态四元数引用
+    /// - `Err(AhrsError)`: 姿态解算过程中发生的错误
+    pub async fn update(&mut self) -> Result<&UnitQuaternion<f32>, AhrsError> {
+        self.fuse()?;
+        Ok(self.filter.quaternion())
+    }
+
+    /// 当前姿态
+    ///
+    /// 有磁力计时地球坐标系的X轴指向磁北，否则航向以启动时的朝向为零。
+    pub fn quaternion(&self) -> &UnitQuaternion<f32> {
+        self.filter.quaternion()
+    }
+
+    /// 当前姿态及其各种表示（旋转矩阵、欧拉角、轴角、航向），已带上磁偏角
+    pub fn orientation(&self) -> Orientation {
+        Orientation::new(*self.filter.quaternion()).with_declination(self.declination)
+    }
+
+    /// 航向角（rad，0-2π）：机体X轴的水平投影从真北顺时针转过的角度
+    ///
+    /// 由磁航向加上磁偏角得到，已补偿倾斜（见[`Orientation::heading`]）。
+    /// 没有磁力计时航向随陀螺仪零偏漂移，只适合看相对转动。
+    pub fn heading(&self) -> f32 {
+        self.orientation().heading()
+    }
+
+    /// 磁偏角（rad），磁北在真北以东为正
+    pub fn declination(&self) -> f32 {
+        self.declination
+    }
+
+    /// 设置当地的磁偏角（rad），磁北在真北以东为正，例如北京约为-7°
+    pub fn set_declination(&mut self, declination: f32) {
+        self.declination = declination;
+    }
+
+    /// 检测到的磁力计，没有时为`None`
+    pub fn magnetometer(&self) -> Option<Magnetometer> {
+        self.magnetometer
+    }
+
+    /// 最近一次采集的磁场（已校准，设备坐标系），没有磁力计时为零
+    pub fn magnetic_field(&self) -> MagneticField {
+        let field = self.mag_calibration.apply(self.raw.mag);
+        MagneticField::from_gauss(self.mounting.apply(field.as_gauss()))
+    }
+
+    /// 最近一次采集的磁场（未校准，传感器坐标系），用于磁力计校准
+    pub fn raw_magnetic_field(&self) -> MagneticField {
+        self.raw.mag
+    }
+
+    /// 磁力计校准参数
+    pub fn mag_calibration(&self) -> &MagCalibration {
+        &self.mag_calibration
+    }
+
+    /// 设置磁力计校准参数，通常为椭球拟合的结果
+    pub fn set_mag_calibration(&mut self, calibration: MagCalibration) {
+        self.mag_calibration = calibration;
+    }
+
+    /// 最近一次采集的加速度（已校准，设备坐标系）
+    pub fn acceleration(&self) -> Acceleration {
+        let accel = self.accel_calibration.apply(self.raw.accel);
+        Acceleration::from_g(self.mounting.apply(accel.as_g()))
+    }
+
+    /// 最近一次采集的角速度（已减去零偏和零偏的温度漂移，设备坐标系）
+    pub fn angular_rate(&self) -> AngularRate {
+        let rate = self.raw.gyro - self.gyro_bias(self.raw.temperature);
+        AngularRate::from_rad_per_s(self.mounting.apply(rate.as_rad_per_s()))
+    }
+
+    /// 传感器安装方向
+    pub fn mounting(&self) -> &Mounting {
+        &self.mounting
+    }
+
+    /// 设置传感器安装方向
+    ///
+    /// 零偏等校准参数都在传感器坐标系中，修改安装方向后仍然有效；
+    /// 姿态滤波器从当前状态继续收敛到新的设备姿态。
+    pub fn set_mounting(&mut self, mounting: Mounting) {
+        self.mounting = mounting;
+    }
+
+    /// 地球坐标系中的线加速度、速度和位移
+    ///
+    /// 每个采样以[`Self::sample_period`]积分，直接调用[`Self::update`]时
+    /// 调用周期须与之一致。速度和位移只在几秒内可信，见[`Motion::moving_time`]。
+    pub fn motion(&self) -> Motion {
+        *self.kinematics.motion()
+    }
+
+    /// 静止检测阈值
+    pub fn stillness_config(&self) -> &StillnessConfig {
+        self.kinematics.stillness_config()
+    }
+
+    /// 设置静止检测阈值，同时清零速度和位移
+    pub fn set_stillness_config(&mut self, config: StillnessConfig) {
+        self.kinematics = Kinematics::new(config);
+    }
+
+    /// 清零速度、位移和静止时估计的残余零偏
+    ///
+    /// 校准零偏或修改安装方向后，之前估计的残余零偏不再适用。
+    pub fn reset_motion(&mut self) {
+        self.kinematics.reset();
+    }
+
+    /// 运动事件检测的阈值
+    pub fn gesture_config(&self) -> &GestureConfig {
+        self.gestures.config()
+    }
+
+    /// 设置运动事件检测的阈值，重新开始检测
+    pub fn set_gesture_config(&mut self, config: GestureConfig) {
+        self.gestures = GestureDetector::new(config);
+    }
+
+    /// 取出最早的一个未发布的运动事件
+    pub fn next_motion_event(&mut self) -> Option<MotionEvent> {
+        self.gestures.next_event()
+    }
+
+    /// 将未发布的运动事件全部发布到通道，返回发布的事件数
+    ///
+    /// 每次读取FIFO后调用；订阅者来不及处理时丢弃其最早的事件，不阻塞采样。
+    pub fn publish_motion_events<M: RawMutex>(&mut self, channel: &MotionEventChannel<M>) -> usize {
+        self.gestures.publish(channel)
+    }
+
+    /// 最近一次采集的芯片温度（℃）
+    pub fn temperature(&self) -> f32 {
+        self.raw.temperature
+    }
+
+    /// 指定温度下的陀螺仪零偏
+    ///
+    /// 以零偏校准值为基准，有温度模型时加上从校准时温度到`temperature`的零偏变化。
+    pub fn gyro_bias(&self, temperature: f32) -> AngularRate {
+        match (self.gyro_drift, self.gyro_offset_temperature) {
+            (Some(model), Some(reference)) => {
+                self.gyro_offset + model.drift(reference, temperature)
+            }
+            _ => self.gyro_offset,
+        }
+    }
+
+    /// 陀螺仪零偏温度模型
+    pub fn gyro_drift_model(&self) -> Option<&GyroDriftModel> {
+        self.gyro_drift.as_ref()
+    }
+
+    /// 设置陀螺仪零偏温度模型，通常为预热校准的拟合结果，`None`表示不补偿
+    pub fn set_gyro_drift_model(&mut self, model: Option<GyroDriftModel>) {
+        self.gyro_drift = model;
+    }
+
+    /// 获取加速度计零偏校准值
+    ///
+    /// # 返回值
+    /// 加速度计的零偏校准值
+    pub fn get_accel_offset(&mut self) -> Acceleration {
+        Acceleration::from_g(self.accel_calibration.bias)
+    }
+
+    /// 加速度计校准参数
+    pub fn accel_calibration(&self) -> &AccelCalibration {
+        &self.accel_calibration
+    }
+
+    /// 设置加速度计校准参数，通常为六面校准的结果
+    pub fn set_accel_calibration(&mut self, calibration: AccelCalibration) {
+        self.accel_calibration = calibration;
+    }
+
+    /// 获取陀螺仪零偏校准值
+    ///
+    /// # 返回值
+    /// 陀螺仪的零偏校准值
+    pub fn get_gyro_offset(&mut self) -> AngularRate {
+        self.gyro_offset
+    }
+
+    /// 全部校准参数：加速度计校准、陀螺仪零偏及其温度模型
+    pub fn imu_calibration(&self) -> ImuCalibration {
+        ImuCalibration {
+            accel: self.accel_calibration,
+            gyro_offset: self.gyro_offset,
+            gyro_offset_temperature: self.gyro_offset_temperature,
+            gyro_drift: self.gyro_drift,
+        }
+    }
+
+    /// 替换全部校准参数
+    pub fn set_imu_calibration(&mut self, calibration: ImuCalibration) {
+        self.accel_calibration = calibration.accel;
+        self.gyro_offset = calibration.gyro_offset;
+        self.gyro_offset_temperature = calibration.gyro_offset_temperature;
+        self.gyro_drift = calibration.gyro_drift;
+    }
+
+    /// 从存储中恢复本电路板保存的校准参数，包括磁力计校准参数和安装方向
+    ///
+    /// # 参数
+    /// - `store`: 设置存储
+    /// - `uid`: 芯片唯一ID，与保存时不同则不恢复
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 已恢复
+    /// - `Ok(false)`: 没有本电路板的有效记录，校准参数保持不变
+    /// - `Err(StoreError)`: 读取闪存失败
+    pub fn load_calibration<S: NorFlash>(
+        &mut self,
+        store: &mut KvStore<S>,
+        uid: &Uid,
+    ) -> Result<bool, StoreError<S::Error>> {
+        if let Some(calibration) = MagCalibration::load(store, uid)? {
+            self.mag_calibration = calibration;
+        }
+        if let Some(mounting) = Mounting::load(store, uid)? {
+            self.mounting = mounting;
+        }
+        let calibration = ImuCalibration::load(store, uid)?;
+        if let Some(calibration) = calibration {
+            self.set_imu_calibration(calibration);
+        }
+        Ok(calibration.is_some())
+    }
+
+    /// 将当前校准参数保存到存储，与已保存的相同时不写入闪存
+    ///
+    /// # 参数
+    /// - `store`: 设置存储
+    /// - `uid`: 芯片唯一ID
+    pub fn store_calibration<S: NorFlash>(
+        &self,
+        store: &mut KvStore<S>,
+        uid: &Uid,
+    ) -> Result<(), StoreError<S::Error>> {
+        self.imu_calibration().save(store, uid)?;
+        self.mag_calibration.save(store, uid)?;
+        self.mounting.save(store, uid)
+    }
+
+    /// 传感器采样周期（秒），由数字低通滤波器模式和采样率分频决定
+    pub fn sample_period(&self) -> f32 {
+        (1.0 + self.sample_rate_divider as f32) / self.gyro_output_rate() as f32
+    }
+
+    /// 陀螺仪输出频率（Hz）：低通滤波器关闭（模式0、7）时为8kHz，否则为1kHz
+    pub fn gyro_output_rate(&self) -> u32 {
+        match self.dlpf_mode {
+            0 | 7 => 8000,
+            _ => 1000,
+        }
+    }
+
+    /// 当前的采样率分频
+    pub fn sample_rate_divider(&self) -> u8 {
+        self.sample_rate_divider
+    }
+
+    /// 按当前低通滤波器模式得到采样率`sample_rate`（Hz）所需的采样率分频
+    ///
+    /// 分频 = 陀螺仪输出频率 / 采样率 - 1。采样率不能整除陀螺仪输出频率，
+    /// 或分频超出寄存器范围（0-255）时返回`None`。
+    pub fn sample_rate_divider_for(&self, sample_rate: u32) -> Option<u8> {
+        let output_rate = self.gyro_output_rate();
+        if sample_rate == 0 || output_rate % sample_rate != 0 {
+            return None;
+        }
+        u8::try_from(output_rate / sample_rate - 1).ok()
+    }
+
+    /// 当前陀螺仪量程
+    pub fn gyro_range(&self) -> GyroRange {
+        self.gyro_range
+    }
+
+    /// 当前加速度计量程
+    pub fn accel_range(&self) -> AccelRange {
+        self.accel_range
+    }
+
+    /// 磁力计是否接在辅助总线上，由MPU6050的I2C主机读取
+    pub(crate) fn aux_magnetometer(&self) -> bool {
+        matches!(
+            self.magnetometer,
+            Some(Magnetometer {
+                link: MagnetometerLink::Aux,
+                ..
+            })
+        )
+    }
+
+    /// 每个采样的字节数，辅助总线上有磁力计时包含磁力计数据
+    pub(crate) fn sample_len(&self) -> usize {
+        if self.aux_magnetometer() {
+            MAX_SAMPLE_LEN
+        } else {
+            SAMPLE_LEN
+        }
+    }
+
+    /// USER_CTRL中I2C主机的使能位，辅助总线上有磁力计时保持使能
+    pub(crate) fn i2c_master(&self) -> u8 {
+        if self.aux_magnetometer() {
+            USER_CTRL_I2C_MST_EN
+        } else {
+            0
+        }
+    }
+
+    /// 写入FIFO的数据
+    pub(crate) fn fifo_sources(&self) -> u8 {
+        if self.aux_magnetometer() {
+            FIFO_EN_SENSORS | FIFO_EN_SLV0
+        } else {
+            FIFO_EN_SENSORS
+        }
+    }
+
+    /// 按当前量程换算一个采样
+    ///
+    /// 数据寄存器和FIFO中依次为加速度XYZ、温度、角速度XYZ，高字节在前，
+    /// 之后是辅助总线上磁力计的数据。没有磁力计数据时沿用上一次的磁场。
+    pub(crate) fn parse(&self, buf: &[u8]) -> Reading {
+        let word = |index: usize| i16::from_be_bytes([buf[index * 2], buf[index * 2 + 1]]);
+
+        let accel = Vector3::new(word(0), word(1), word(2));
+        let gyro = Vector3::new(word(4), word(5), word(6));
+        let mag = match (self.magnetometer, buf.get(SAMPLE_LEN..MAX_SAMPLE_LEN)) {
+            (Some(magnetometer), Some(data)) => {
+                let mut bytes = [0; MAG_DATA_LEN];
+                bytes.copy_from_slice(data);
+                magnetometer.chip.parse(&bytes)
+            }
+            _ => self.raw.mag,
+        };
+        Reading {
+            accel: Acceleration::from_raw(accel, self.accel_range),
+            gyro: AngularRate::from_raw(gyro, self.gyro_range),
+            temperature: temperature_from_raw(word(3)),
+            mag,
+        }
+    }
+
+    /// 换算FIFO中的一个采样并更新姿态
+    ///
+    /// 加速度为零时滤波器保持原姿态并返回错误
+    pub(crate) fn update_sample(&mut self, buf: &[u8]) -> Result<(), AhrsError> {
+        self.raw = self.parse(buf);
+        self.fuse()
+    }
+
+    /// 以最近一次采集的数据更新姿态滤波器，再以新的姿态更新速度和位移，
+    /// 并检测运动事件
+    fn fuse(&mut self) -> Result<(), AhrsError> {
+        let gyro = self.angular_rate();
+        let accel = self.acceleration();
+        self.update_filter(&gyro, &accel)?;
+        let sample_period = self.sample_period();
+        self.kinematics
+            .update(self.filter.quaternion(), &accel, &gyro, sample_period);
+        self.gestures.update(&accel, &gyro, sample_period);
+        Ok(())
+    }
+
+    /// 更新姿态滤波器（陀螺仪rad/s，加速度计g）
+    ///
+    /// 有磁力计时融合校准后的磁场修正航向；磁场为零（如磁力计尚未输出数据）时
+    /// 本次只使用陀螺仪和加速度计。
+    fn update_filter(&mut self, gyro: &AngularRate, accel: &Acceleration) -> Result<(), AhrsError> {
+        if self.magnetometer.is_some() {
+            let mag = self.magnetic_field();
+            match self
+                .filter
+                .update_marg(gyro.as_rad_per_s(), accel.as_g(), mag.as_gauss())
+            {
+                Err(AhrsError::MagnetometerNormZero) => {}
+                result => return result.map(|_| ()),
+            }
+        }
+        self.filter
+            .update_imu(gyro.as_rad_per_s(), accel.as_g())
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,112 @@
+use super::traits::TimeSource;
+use chrono::{DateTime, NaiveDateTime};
+use embassy_stm32::{pac, peripherals};
+
+/// LSE晶振频率（Hz）
+const LSE_FREQUENCY: u32 = 32_768;
+
+/// STM32F1备份域RTC时间源
+///
+/// STM32F1的RTC是一个由LSE（32.768kHz）驱动的32位秒计数器，
+/// 本驱动将计数值解释为自1970-01-01 00:00:00起的秒数。
+/// 备份域由VBAT引脚供电，接上纽扣电池后掉电和复位均不会丢失时间。
+///
+/// # 注意
+/// - 需要在`embassy_stm32::Config`中将RTC时钟源配置为LSE：
+///   `config.rcc.ls = LsConfig::default_lse();`
+/// - 备份域掉电后计数器从0开始，此时读到的时间早于`fallback`，
+///   驱动会以`fallback`重新初始化计数器和预分频
+pub struct Stm32Rtc {
+    _rtc: peripherals::RTC,
+}
+
+impl Stm32Rtc {
+    /// 创建RTC时间源
+    ///
+    /// # 参数
+    /// - `rtc`: RTC外设
+    /// - `fallback`: 备份域掉电（计数器无效）时使用的初始时间
+    pub fn new(rtc: peripherals::RTC, fallback: NaiveDateTime) -> Self {
+        // 使能电源和备份接口时钟，并解除备份域写保护
+        pac::RCC.apb1enr().modify(|w| {
+            w.set_pwren(true);
+            w.set_bkpen(true);
+        });
+        pac::PWR.cr().modify(|w| w.set_dbp(true));
+
+        let mut this = Self { _rtc: rtc };
+
+        // 等待RTC寄存器与APB1总线同步
+        pac::RTC.crl().modify(|w| w.set_rsf(false));
+        while !pac::RTC.crl().read().rsf() {}
+
+        if this.now() < fallback {
+            this.set_now(fallback);
+        }
+
+        this
+    }
+
+    /// 进入配置模式执行寄存器写入
+    ///
+    /// 按参考手册要求：等待上次写操作完成 → 置位CNF → 写寄存器 → 清除CNF → 等待写入完成
+    fn configure(&mut self, f: impl FnOnce()) {
+        while !pac::RTC.crl().read().rtoff() {}
+        pac::RTC.crl().modify(|w| w.set_cnf(true));
+        f();
+        pac::RTC.crl().modify(|w| w.set_cnf(false));
+        while !pac::RTC.crl().read().rtoff() {}
+    }
+
+    /// 写入预分频装载值：32768分频得到1Hz秒脉冲，须在配置模式下调用
+    ///
+    /// 写入后预分频计数器（DIV）从装载值重新开始，当前秒从零开始计时。
+    fn load_prescaler() {
+        let prescaler = LSE_FREQUENCY - 1;
+        pac::RTC
+            .prlh()
+            .write(|w| w.set_prlh((prescaler >> 16) as u8));
+        pac::RTC.prll().write(|w| w.set_prll(prescaler as u16));
+    }
+
+    /// 读取32位秒计数器和当前秒内经过的LSE周期数
+    fn read_counter(&self) -> (u32, u32) {
+        loop {
+            let high = pac::RTC.cnth().read().cnth();
+            let low = pac::RTC.cntl().read().cntl();
+            let div = ((pac::RTC.divh().read().divh() as u32) << 16)
+                | pac::RTC.divl().read().divl() as u32;
+
+            // 读取期间低16位发生进位时重新读取
+            if pac::RTC.cnth().read().cnth() == high {
+                let counter = ((high as u32) << 16) | low as u32;
+                return (counter, (LSE_FREQUENCY - 1).saturating_sub(div));
+            }
+        }
+    }
+}
+
+impl TimeSource for Stm32Rtc {
+    fn now(&mut self) -> NaiveDateTime {
+        let (seconds, ticks) = self.read_counter();
+        let nanos = (ticks as u64 * 1_000_000_000 / LSE_FREQUENCY as u64) as u32;
+        DateTime::from_timestamp(seconds as i64, nanos)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+
+    /// 设置当前时间
+    ///
+    /// 同时重新装载预分频，设置后的第一秒是完整的一秒，而不是上一秒剩余的部分。
+    fn set_now(&mut self, now: NaiveDateTime) {
+        // 计数器只能表示1970年至2106年之间的时间
+        let seconds = now.and_utc().timestamp().clamp(0, u32::MAX as i64) as u32;
+        self.configure(|| {
+            Self::load_prescaler();
+            pac::RTC
+                .cnth()
+                .write(|w| w.set_cnth((seconds >> 16) as u16));
+            pac::RTC.cntl().write(|w| w.set_cntl(seconds as u16));
+        });
+    }
+}
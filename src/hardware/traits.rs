@@ -7,3 +7,25 @@ pub trait Led {
 pub trait Button {
     fn is_pressed(&self) -> bool;
 }
+
+pub trait Buzzer {
+    fn on(&mut self);
+    fn off(&mut self);
+}
+
+/// 时间源：为万年历提供当前时间，并允许校准
+pub trait TimeSource {
+    /// 获取当前时间
+    fn now(&mut self) -> chrono::NaiveDateTime;
+    /// 设置当前时间
+    fn set_now(&mut self, now: chrono::NaiveDateTime);
+}
+
+/// 可恢复的总线
+///
+/// 从机在传输中途复位时可能一直拉低SDA，总线外设无法再发出START。
+/// 恢复时释放总线外设，在SCL上输出时钟直到从机释放SDA，发送STOP后重新初始化外设。
+pub trait BusRecovery {
+    /// 将总线恢复到空闲状态
+    fn recover_bus(&mut self);
+}
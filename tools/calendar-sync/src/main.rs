@@ -0,0 +1,136 @@
+//! Calendar Clock Sync Tool
+//! =============================================================================================
+//!
+//! Host-side companion of the `calendar` firmware. Talks to the board over its
+//! USB CDC-ACM serial port and sets the calendar clock from the PC clock.
+//!
+//! Usage:
+//!   calendar-sync [--port <PORT>]            set the board clock to the PC clock (sent as UTC)
+//!   calendar-sync [--port <PORT>] <COMMAND>  send any command, e.g. `time get` or `alarm list`
+//!
+//! Without `--port` the first serial port with the firmware's USB VID/PID is used.
+//! From the repository root: `cargo sync-time` or `cargo sync-time alarm list`.
+
+use chrono::{DateTime, Timelike, Utc};
+use serialport::{ClearBuffer, SerialPortType};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// USB VID/PID of the calendar firmware
+const USB_VID: u16 = 0xc0de;
+const USB_PID: u16 = 0xcafe;
+
+/// Maximum time to wait for a response line
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1).peekable();
+    let port_name = match args.next_if(|arg| arg == "--port") {
+        Some(_) => args.next().ok_or("--port needs a port name")?,
+        None => find_port()?,
+    };
+    let command = args.collect::<Vec<_>>().join(" ");
+
+    let mut port = serialport::new(&port_name, 115_200)
+        .timeout(TIMEOUT)
+        .open()
+        .map_err(|error| format!("cannot open {port_name}: {error}"))?;
+    port.clear(ClearBuffer::Input)?;
+
+    if command.is_empty() {
+        // The RTC counts whole seconds, so send the time right at a second boundary
+        let now = Utc::now();
+        std::thread::sleep(Duration::from_nanos(
+            1_000_000_000 - u64::from(now.nanosecond() % 1_000_000_000),
+        ));
+        let command = set_command(Utc::now());
+        port.write_all(command.as_bytes())?;
+        for line in read_response(BufReader::new(&mut port))? {
+            println!("board clock set to {line}");
+        }
+    } else {
+        port.write_all(format!("{command}\r\n").as_bytes())?;
+        for line in read_response(BufReader::new(&mut port))? {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the first serial port exposed by the calendar firmware
+fn find_port() -> Result<String, Box<dyn Error>> {
+    serialport::available_ports()?
+        .into_iter()
+        .find(|port| {
+            matches!(&port.port_type, SerialPortType::UsbPort(usb)
+                if usb.vid == USB_VID && usb.pid == USB_PID)
+        })
+        .map(|port| port.port_name)
+        .ok_or_else(|| "calendar board not found, pass --port <PORT>".into())
+}
+
+/// Build the `time set` command line for `now`, truncated to whole seconds
+fn set_command(now: DateTime<Utc>) -> String {
+    format!("time set {}Z\r\n", now.format("%Y-%m-%dT%H:%M:%S"))
+}
+
+/// Read response lines up to the closing `OK`, or fail on `ERR <reason>`
+fn read_response(reader: impl BufRead) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end();
+        if line == "OK" {
+            return Ok(lines);
+        }
+        if let Some(reason) = line.strip_prefix("ERR ") {
+            return Err(format!("board replied: {reason}").into());
+        }
+        if !line.is_empty() {
+            lines.push(line.to_owned());
+        }
+    }
+    Err("serial port closed before the response ended".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn set_command_sends_utc_whole_seconds() {
+        let now = Utc.with_ymd_and_hms(2025, 7, 20, 10, 0, 5).unwrap()
+            + chrono::Duration::milliseconds(999);
+        assert_eq!(set_command(now), "time set 2025-07-20T10:00:05Z\r\n");
+    }
+
+    #[test]
+    fn reads_response_until_result_line() {
+        let input = b"2025-07-20T18:00:00+08:00 CST\r\nOK\r\nleftover\r\n";
+        assert_eq!(
+            read_response(&input[..]).unwrap(),
+            ["2025-07-20T18:00:00+08:00 CST"]
+        );
+
+        let error = read_response(&b"ERR unknown command, try help\r\n"[..]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "board replied: unknown command, try help"
+        );
+        assert!(read_response(&b"partial"[..]).is_err());
+    }
+}
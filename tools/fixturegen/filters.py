@@ -0,0 +1,24 @@
+"""Synthetic traces for the orientation filter tests (src/app/imu/filter.rs).
+
+Run with `python3 tools/fixturegen/filters.py`; writes tests/data/synthetic/imu_*.csv.
+"""
+
+from imu import Recorder
+
+SCRIPT = "filters.py"
+
+# Resting on a wedge.
+r = Recorder(11, roll=30, pitch=-20)
+r.hold(20)
+r.write("imu_tilt_rest.csv", SCRIPT,
+        "Board resting on a 30° roll / -20° pitch wedge for 20 s",
+        [("still", 20), ("roll", 30), ("pitch", -20)])
+
+# Rolled a quarter turn about X.
+r = Recorder(12)
+r.hold(2)
+r.rotate_by((1, 0, 0), 90, 1.0)
+r.hold(10)
+r.write("imu_turn_x.csv", SCRIPT,
+        "Flat for 2 s, rolled +90° about X in about 1 s, then held for 10 s",
+        [("still", 2), ("settled", 3), ("roll", 90), ("pitch", 0)])
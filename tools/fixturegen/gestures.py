@@ -0,0 +1,74 @@
+"""Synthetic traces for the motion-event tests (src/app/imu/gesture.rs).
+
+Run with `python3 tools/fixturegen/gestures.py`; writes tests/data/synthetic/gesture_*.csv.
+"""
+
+import math
+
+from imu import Recorder
+
+SCRIPT = "gestures.py"
+
+# Linear acceleration profile of a knock (g), decaying with some ringing.
+TAP = [2.0, 1.2, -0.8, 0.3, -0.1, 0.05]
+
+
+def tap(r, direction, scale=1.0):
+    """A knock along `direction` that also jolts the board slightly about its horizontal axes."""
+    for k, v in enumerate(TAP):
+        jolt = 6.0 * v if k < 3 else 0.0
+        r.step((jolt, -0.5 * jolt, 0), tuple(c * v * scale for c in direction))
+
+
+# Taps on the top face: a single tap, then a double tap.
+r = Recorder(seed=11)
+r.hold(2.0)
+tap(r, (0, 0, -1))
+r.hold(1.5)
+tap(r, (0, 0, -1), 1.1)
+r.hold(0.25)
+tap(r, (0, 0, -1), 0.9)
+r.hold(1.5)
+r.write("gesture_tap.csv", SCRIPT,
+        "Lying flat; single tap on the top face, then a double tap on the top face.",
+        [("events", "TAP Z-, DOUBLE TAP Z-")])
+
+# Shaking along X for about two seconds, with some wobble on Y and Z.
+r = Recorder(seed=12)
+r.hold(2.0)
+n = 200
+for k in range(n):
+    t = k * r.dt
+    env = math.sin(math.pi * k / n) ** 0.5
+    f = 4.0 + 0.5 * math.sin(2 * math.pi * 0.5 * t)
+    a = 2.2 * env * math.sin(2 * math.pi * f * t)
+    rate = (0, 0, 40.0 * env * math.cos(2 * math.pi * f * t))
+    r.step(rate, (a, 0.3 * a, 0.2 * env * math.sin(4 * math.pi * f * t)))
+r.hold(1.5)
+r.write("gesture_shake.csv", SCRIPT,
+        "Lying flat; shaken back and forth along X at about 4 Hz for 2 s.",
+        [("events", "SHAKE, SHAKE, SHAKE")])
+
+# Dropped from about 45 cm while tumbling slowly, caught in the hand.
+r = Recorder(seed=13)
+r.hold(2.0)
+for _ in range(30):
+    r.step((35.0, -20.0, 10.0), (0, 0, -1.0))
+for v in [2.5, 1.8, 1.0, 0.6, 0.3, 0.1, -0.1, 0.05]:
+    r.step((0, 0, 0), (0, 0, v))
+r.rotate_by((0.8, -0.45, 0.25), -18.0, 1.0)
+r.hold(1.5)
+r.write("gesture_free_fall.csv", SCRIPT,
+        "Held flat, dropped for 0.3 s while tumbling slowly, caught and levelled again.",
+        [("events", "FREE FALL")])
+
+# Turned face down about Y, left there, then turned back face up.
+r = Recorder(seed=14)
+r.hold(2.0)
+r.rotate_by((0, 1, 0), 180.0, 1.2)
+r.hold(2.0)
+r.rotate_by((0, 1, 0), -180.0, 1.2)
+r.hold(2.0)
+r.write("gesture_flip.csv", SCRIPT,
+        "Lying flat; turned face down about Y, left there, then turned back face up.",
+        [("events", "TILT, FACE DOWN, LEVEL, FACE UP")])
@@ -0,0 +1,114 @@
+"""Motion model that writes synthetic MPU6050 traces in the tests/data CSV format.
+
+The traces stand in for bench captures until real ones are recorded with
+examples/record_imu.rs. Each trace is scripted as a sequence of rotations and
+linear accelerations; the model adds a constant bias and Gaussian noise close
+to a typical MPU6050 and quantizes to raw counts at ±4 g / ±500 °/s.
+Random seeds are fixed so that regenerating a file reproduces it exactly.
+"""
+
+import math
+import os
+import random
+
+ACCEL_LSB = 8192.0  # ±4 g
+GYRO_LSB = 65.5  # ±500 °/s
+
+OUT = os.path.normpath(os.path.join(os.path.dirname(__file__), "..", "..", "tests", "data", "synthetic"))
+
+
+def qmul(a, b):
+    w1, x1, y1, z1 = a
+    w2, x2, y2, z2 = b
+    return (
+        w1 * w2 - x1 * x2 - y1 * y2 - z1 * z2,
+        w1 * x2 + x1 * w2 + y1 * z2 - z1 * y2,
+        w1 * y2 - x1 * z2 + y1 * w2 + z1 * x2,
+        w1 * z2 + x1 * y2 - y1 * x2 + z1 * w2,
+    )
+
+
+def qnorm(q):
+    n = math.sqrt(sum(c * c for c in q))
+    return tuple(c / n for c in q)
+
+
+def qconj(q):
+    return (q[0], -q[1], -q[2], -q[3])
+
+
+def rotate(q, v):
+    return qmul(qmul(q, (0.0,) + tuple(v)), qconj(q))[1:]
+
+
+def axis_angle(v):
+    a = math.sqrt(sum(c * c for c in v))
+    if a < 1e-12:
+        return (1.0, 0.0, 0.0, 0.0)
+    s = math.sin(a / 2) / a
+    return (math.cos(a / 2), v[0] * s, v[1] * s, v[2] * s)
+
+
+def from_euler(roll, pitch, yaw):
+    qx = axis_angle((math.radians(roll), 0, 0))
+    qy = axis_angle((0, math.radians(pitch), 0))
+    qz = axis_angle((0, 0, math.radians(yaw)))
+    return qmul(qz, qmul(qy, qx))
+
+
+def clip(v):
+    return max(-32768, min(32767, int(round(v))))
+
+
+class Recorder:
+    """Scripted board motion, sampled at `rate` Hz starting from the given attitude (degrees)."""
+
+    def __init__(self, seed, roll=0.0, pitch=0.0, yaw=0.0, rate=100,
+                 accel_bias=(0.004, -0.003, 0.006), gyro_bias=(0.6, -0.4, 0.25),
+                 accel_noise=0.0035, gyro_noise=0.05):
+        self.rng = random.Random(seed)
+        self.q = from_euler(roll, pitch, yaw)
+        self.dt = 1.0 / rate
+        self.rate = rate
+        self.accel_bias = accel_bias
+        self.gyro_bias = gyro_bias
+        self.accel_noise = accel_noise
+        self.gyro_noise = gyro_noise
+        self.rows = []
+
+    def step(self, rate_dps=(0, 0, 0), linear=(0, 0, 0)):
+        """One sample: rate in the board frame (°/s), linear acceleration in the earth frame (g)."""
+        r = tuple(math.radians(c) * self.dt for c in rate_dps)
+        self.q = qnorm(qmul(self.q, axis_angle(r)))
+        specific_force = (linear[0], linear[1], 1.0 + linear[2])
+        a = rotate(qconj(self.q), specific_force)
+        n = self.rng.gauss
+        accel = [clip((a[i] + self.accel_bias[i] + n(0, self.accel_noise)) * ACCEL_LSB) for i in range(3)]
+        gyro = [clip((rate_dps[i] + self.gyro_bias[i] + n(0, self.gyro_noise)) * GYRO_LSB) for i in range(3)]
+        self.rows.append(accel + gyro)
+
+    def hold(self, seconds, linear=(0, 0, 0)):
+        for _ in range(int(round(seconds * self.rate))):
+            self.step((0, 0, 0), linear)
+
+    def rotate_by(self, axis, degrees, seconds):
+        """Smooth rotation about a board axis with a raised-cosine rate profile."""
+        n = int(round(seconds * self.rate))
+        for k in range(n):
+            w = degrees / seconds * (1 - math.cos(2 * math.pi * (k + 0.5) / n))
+            self.step(tuple(w * c for c in axis))
+
+    def write(self, name, script, description, meta):
+        """Write tests/data/synthetic/<name>, labelled with the script that generated it."""
+        os.makedirs(OUT, exist_ok=True)
+        with open(os.path.join(OUT, name), "w") as f:
+            f.write(f"# {description}\n")
+            f.write("# Synthetic trace from a motion model with MPU6050-like noise and bias, not a\n")
+            f.write("# sensor capture; replace with a bench capture from examples/record_imu.rs.\n")
+            f.write(f"# source: synthetic, tools/fixturegen/{script}\n")
+            f.write(f"# rate: {self.rate}\n# accel_range: 4\n# gyro_range: 500\n")
+            for key, value in meta:
+                f.write(f"# {key}: {value}\n")
+            f.write("ax,ay,az,gx,gy,gz\n")
+            for row in self.rows:
+                f.write(",".join(str(c) for c in row) + "\n")
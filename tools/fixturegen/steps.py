@@ -0,0 +1,76 @@
+"""Synthetic traces for the step counter tests (src/app/imu/pedometer.rs).
+
+Run with `python3 tools/fixturegen/steps.py`; writes tests/data/synthetic/steps_*.csv.
+The `steps` value in each file is the number of steps the script generates.
+"""
+
+import math
+import random
+
+from imu import Recorder
+
+SCRIPT = "steps.py"
+
+
+def gait(r, rng, cadence, steps, amplitude, impact=0.4):
+    """Each step with its own duration and strength; vertical, fore-aft and sway."""
+    for s in range(steps):
+        period = 60.0 / (cadence * rng.uniform(0.96, 1.04))
+        amp = amplitude * rng.uniform(0.85, 1.15)
+        # first and last steps are softer
+        if s in (0, steps - 1):
+            amp *= 0.7
+        n = int(round(period * r.rate))
+        for k in range(n):
+            a = 2 * math.pi * (k + 0.5) / n
+            vertical = math.sin(a) + impact * math.sin(2 * a) + 0.1 * math.sin(3 * a + 0.5)
+            forward = 0.3 * math.sin(a + 1.2)
+            lateral = 0.2 * math.sin((a + 2 * math.pi * (s % 2)) / 2)
+            sway = 15.0 * math.cos((a + 2 * math.pi * (s % 2)) / 2)
+            r.step((3.0 * math.sin(a), -4.0 * math.cos(a), sway),
+                   (amp * forward, amp * lateral, amp * vertical))
+
+
+# Walking with the board in a trouser pocket.
+rng = random.Random(21)
+r = Recorder(seed=21, roll=75.0, pitch=-10.0, yaw=40.0)
+r.hold(3.0)
+gait(r, rng, 105.0, 60, 0.3)
+r.hold(3.0)
+r.write("steps_walking.csv", SCRIPT,
+        "Phone-sized board in a trouser pocket; stood still, walked 60 steps, stood still.",
+        [("steps", 60)])
+
+# Running with the board strapped to the upper arm.
+rng = random.Random(22)
+r = Recorder(seed=22, roll=-20.0, pitch=65.0, yaw=-110.0)
+r.hold(3.0)
+gait(r, rng, 165.0, 80, 1.1, impact=0.6)
+r.hold(3.0)
+r.write("steps_running.csv", SCRIPT,
+        "Board strapped to the upper arm; stood still, ran 80 steps, stood still.",
+        [("steps", 80)])
+
+# Handling without walking: picked up, fidgeted with, tapped on, turned over, set down.
+rng = random.Random(23)
+r = Recorder(seed=23)
+r.hold(3.0)
+r.rotate_by((1, 0, 0), 60.0, 1.5)
+for k in range(400):
+    t = k * r.dt
+    # small hand tremor and fidgeting
+    r.step((8 * math.sin(2 * math.pi * 0.7 * t), 5 * math.sin(2 * math.pi * 1.1 * t), 0),
+           (0.02 * math.sin(2 * math.pi * 6 * t), 0.03 * math.sin(2 * math.pi * 0.9 * t),
+            0.04 * math.sin(2 * math.pi * 1.3 * t)))
+for _ in range(6):
+    for v in [0.9, -0.4, 0.15]:
+        r.step((0, 0, 0), (0, 0, -v))
+    r.hold(rng.uniform(0.3, 0.7))
+r.rotate_by((0, 1, 0), 180.0, 1.0)
+r.hold(2.0)
+r.rotate_by((0, 1, 0), -180.0, 1.0)
+r.rotate_by((1, 0, 0), -60.0, 1.5)
+r.hold(3.0)
+r.write("steps_idle.csv", SCRIPT,
+        "Handled while standing: picked up, fidgeted with, tapped, turned over and put down again.",
+        [("steps", 0)])